@@ -0,0 +1,55 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use lle::{Action, Agent, World};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+/// Random available actions, the way rollouts pick them.
+fn random_actions(world: &World, rng: &mut StdRng) -> Vec<Action> {
+    world
+        .available_actions()
+        .iter()
+        .map(|actions| actions[rng.random_range(0..actions.len())])
+        .collect()
+}
+
+/// Random rollouts on the levels of LLE, resetting the world when an episode is over.
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    for level in 1..=6 {
+        let mut world = World::get_level(level).unwrap();
+        world.reset();
+        let mut rng = StdRng::seed_from_u64(0);
+        group.bench_function(BenchmarkId::from_parameter(level), |b| {
+            b.iter(|| {
+                let actions = random_actions(&world, &mut rng);
+                world.step(&actions).unwrap();
+                let agents = world.agents();
+                if agents.iter().any(Agent::is_dead) || agents.iter().all(Agent::has_arrived) {
+                    world.reset();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Going back to a snapshot after a step, the way tree searches explore a state.
+fn restore(c: &mut Criterion) {
+    let mut group = c.benchmark_group("restore");
+    for level in 1..=6 {
+        let mut world = World::get_level(level).unwrap();
+        world.reset();
+        let snapshot = world.snapshot();
+        let mut rng = StdRng::seed_from_u64(0);
+        group.bench_function(BenchmarkId::from_parameter(level), |b| {
+            b.iter(|| {
+                let actions = random_actions(&world, &mut rng);
+                world.step(&actions).unwrap();
+                world.restore(&snapshot).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, step, restore);
+criterion_main!(benches);
@@ -16,6 +16,11 @@ fn numeric_png_files(dir: &str) -> Vec<PathBuf> {
         })
         .collect();
     files.sort_by_key(|(index, _)| *index);
+    // Sprite 0 is the base from which the sprites of higher agent ids are generated.
+    assert!(
+        !files.is_empty(),
+        "at least one numbered sprite is required in {dir}"
+    );
     for (expected, (actual, path)) in files.iter().enumerate() {
         assert_eq!(
             expected,
@@ -30,13 +35,6 @@ fn numeric_png_files(dir: &str) -> Vec<PathBuf> {
         .collect()
 }
 
-fn numeric_sprite_upper_bound(files: &[PathBuf]) -> usize {
-    files
-        .len()
-        .checked_sub(1)
-        .expect("at least one numbered sprite is required")
-}
-
 fn fallback_file(dir: &str) -> PathBuf {
     fs::canonicalize(Path::new(dir).join("n.png")).unwrap()
 }
@@ -62,12 +60,7 @@ fn include_bytes_const(name: &str, file: &Path) -> String {
 
 fn agent_files() -> String {
     let dir = format!("{RESOURCES}/agents");
-    let files = numeric_png_files(&dir);
-    let mut res = format!(
-        "pub const MAX_NUMBERED_AGENT_SPRITE_ID: usize = {};\n",
-        numeric_sprite_upper_bound(&files)
-    );
-    res.push_str(&include_bytes_slice("AGENT_BYTES", &files));
+    let mut res = include_bytes_slice("AGENT_BYTES", &numeric_png_files(&dir));
     res.push_str(&include_bytes_const(
         "AGENT_FALLBACK_BYTES",
         &fallback_file(&dir),
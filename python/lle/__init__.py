@@ -197,7 +197,7 @@ from .generator import generate, GeneratorBuilder
 from .observations import ObservationType
 from .solver import solve
 from .types import AgentId, LaserId, Position
-from .world import Action, EventType, World, WorldEvent, WorldState
+from .world import Action, DistanceMap, EventType, World, WorldEvent, WorldSnapshot, WorldState
 from .characterization import is_cooperative, characterize, is_asymmetric, is_sequential, is_convergent, is_divergent
 from . import tiles, exceptions, world, agent, env, generator, characterization, solver, observations
 
@@ -224,6 +224,8 @@ __all__ = [
     "Agent",
     "World",
     "WorldState",
+    "WorldSnapshot",
+    "DistanceMap",
     "Action",
     "EventType",
     "WorldEvent",
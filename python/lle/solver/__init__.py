@@ -1,11 +1,20 @@
 from __future__ import annotations
 
-from .clauses import SolveMode
+from .clauses import GemObjective, SolveMode
 from .solver import (
     Solver,
     solve,
     solve_model,
 )
-from .types import SolveModeLiteral
+from .types import AtMostOneEncodingLiteral, ProjectionLiteral, SolveModeLiteral
 
-__all__ = ["Solver", "solve", "solve_model", "SolveMode", "SolveModeLiteral"]
+__all__ = [
+    "Solver",
+    "solve",
+    "solve_model",
+    "SolveMode",
+    "GemObjective",
+    "SolveModeLiteral",
+    "ProjectionLiteral",
+    "AtMostOneEncodingLiteral",
+]
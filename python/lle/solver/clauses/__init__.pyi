@@ -6,6 +6,7 @@ from lle import world
 import typing
 __all__ = [
     "ClauseGenerator",
+    "GemObjective",
     "SolveMode",
 ]
 
@@ -50,11 +51,58 @@ class ClauseGenerator:
         r"""
         The number of SAT variables allocated so far by this generator.
         """
-    def __new__(cls, world: world.World, t_max: builtins.int) -> ClauseGenerator:
+    def __new__(cls, world: world.World, t_max: builtins.int, symmetry_breaking: builtins.bool = False, position_encoding: typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary'] = "auto", overlap_encoding: typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary'] = "pairwise") -> ClauseGenerator:
         r"""
         Build a clause generator for the given `world`, considering plans of length up to `t_max`.
+        
+        With `symmetry_breaking=True`, the formula also forbids plans that wait before moving to
+        a position only this agent can reach, since the same plan with the wait one step later is
+        equally valid. Satisfiability is unchanged, but the SAT solver explores fewer equivalent
+        plans.
+        
+        `position_encoding` and `overlap_encoding` select the at-most-one encodings of "one position
+        per agent" and "one agent per tile": `"auto"` (pairwise up to five variables, sequential
+        beyond), `"pairwise"`, `"sequential"`, `"commander"`, `"product"` or `"binary"`. They only
+        change the size of the formula, which [`Self::encoding_stats`] reports.
+        
+        # Raises:
+        - `ValueError`: if an encoding is unknown.
+        """
+    def with_starts(self, starts: typing.Sequence[tuple[builtins.int, builtins.int]]) -> ClauseGenerator:
+        r"""
+        A clause generator for the same world, horizon and options when the agents start from
+        `starts`, one `(i, j)` position per agent, such as one of `World.start_assignments()`.
+        
+        The world geometry is computed once and shared with this generator, so checking a world
+        from every start assignment only pays for what depends on the starts.
+        
+        # Raises:
+        - `ValueError`: if `starts` does not give one walkable position per agent, or if two agents
+          share a position.
         """
-    def generate(self, t: builtins.int, mode: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str | SolveMode | None = None, collect_gems: builtins.bool = True) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int]]:
+    @staticmethod
+    def from_state(world: world.World, state: world.WorldState, t_max: builtins.int, symmetry_breaking: builtins.bool = False, position_encoding: typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary'] = "auto", overlap_encoding: typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary'] = "pairwise") -> ClauseGenerator:
+        r"""
+        Build a clause generator that plans from `state` instead of the start positions of `world`,
+        to replan in the middle of an episode. The other parameters are those of the constructor.
+        
+        The agents start where `state` puts them, and those on an exit stay there. The gems that
+        `state` marks as collected are removed from every gem objective: `GemObjective.all()`,
+        `subset` and `by_agent` no longer require them, and they count towards `at_least` and
+        `count_gems`.
+        
+        # Raises:
+        - `ValueError`: if `state` is not a valid state of `world`, if one of its agents is dead, or
+          if an encoding is unknown.
+        """
+    def encoding_stats(self) -> builtins.dict[builtins.str, tuple[builtins.int, builtins.int]]:
+        r"""
+        The clauses and variables of each world rule, over every step generated so far, as
+        `{family: (clauses, variables)}`. The families are the movement rules
+        (`"exactly_one_position"`, `"no_overlap"`, ...) and `"lasers"`. A variable counts for the
+        first family that uses it.
+        """
+    def generate(self, t: builtins.int, mode: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None = None, collect_gems: builtins.bool | GemObjective = True) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int]]:
         r"""
         Generate the complete formula for horizon `t`: every clause and assumption needed to solve
         the problem, from step 0.
@@ -62,8 +110,14 @@ class ClauseGenerator:
         # Parameters
         - `mode` accepts either a `SolveMode` instance or its canonical string (`"standard"`,
         `"no-cooperation"`, `"no-asymmetric"`, `"no-mutual"`, `"no-fully-coupled"`, `"no-sequence[-N]"`,
-        `"no-interdependence[-N]"`, `"no-convergence[-N]"`, `"no-divergence[-N]"`)
-        - `collect_gems` adds gem-collection clauses to the objective.
+        `"no-interdependence[-N]"`, `"no-convergence[-N]"`, `"no-divergence[-N]"`, and the
+        `"require-*"` counterparts). Several modes can be combined, either as a sequence or joined
+        with `+` (`"no-mutual+no-divergence-3"`): the plan must then satisfy all of them. The same
+        syntax adds targeted cooperation constraints, which are assumed: `"forbid-help-H-B"`,
+        `"require-help-H-B"`, `"forbid-helper-H"` and `"forbid-laser-L"`, with agent IDs `H` and
+        `B` and laser ID `L` (see [`Self::constraint_clauses`]).
+        - `collect_gems` is a `GemObjective` saying which gems the objective requires, or a `bool`
+        for all of them (`True`) or none (`False`).
         
         The result depends only on the arguments: it does not read or affect any stream started
         with [`Self::start_delta_stream`]. Its output must not be mixed into a solver that is also
@@ -77,9 +131,30 @@ class ClauseGenerator:
         - `(clauses, assumptions)` ready to be fed to a SAT solver.
         
         # Raises:
-        - `ValueError`: if `mode` is invalid or its parameter is meaningless.
+        - `ValueError`: if `mode` is invalid, its parameter is meaningless, a constraint names an
+            agent or a laser the world does not have, or `collect_gems` names a tile without a gem
+            or an agent the world does not have.
         """
-    def start_delta_stream(self, mode: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str | SolveMode | None = None, collect_gems: builtins.bool = True) -> None:
+    def generate_explained(self, t: builtins.int, mode: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None = None, collect_gems: builtins.bool | GemObjective = True) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int], builtins.dict[builtins.int, builtins.str]]:
+        r"""
+        Generate the formula of [`Self::generate`] with every restriction behind a selector literal,
+        to explain why it is unsatisfiable.
+        
+        Each selector switches on one reason: the goal of one agent, one gem or the gem count of
+        `GemObjective.at_least`, one help pair
+        forbidden by `"no-cooperation"`, one pattern of `"no-sequence-N"` or
+        `"no-interdependence-N"`, or a mode or cooperation constraint as a whole. The selectors are
+        part of the assumptions. When the SAT solver fails under them, the reasons of the selectors
+        in its assumption core (e.g. `Minisat22.get_core()`) explain the failure.
+        
+        # Returns
+        - `(clauses, assumptions, reasons)`, where `reasons` maps each selector to a human-readable
+            description.
+        
+        # Raises:
+        - `ValueError`: see [`Self::generate`].
+        """
+    def start_delta_stream(self, mode: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None = None, collect_gems: builtins.bool | GemObjective = True) -> None:
         r"""
         Start a new incremental stream fixed to `mode` and `collect_gems`, replacing any stream
         started previously.
@@ -95,7 +170,58 @@ class ClauseGenerator:
         whole stream: to change either, start a new stream (and, in practice, a new solver).
         
         # Raises:
-          - `ValueError`: if `mode` is invalid or its parameter is meaningless.
+          - `ValueError`: see [`Self::generate`].
+        """
+    def count_help_events(self, model: typing.Sequence[builtins.int], t: builtins.int) -> builtins.int:
+        r"""
+        The number of help events through horizon `t` in a SAT model of one of this generator's
+        formulas, whatever its mode. This is the quantity bounded by `SolveMode.max_help_events`.
+        """
+    def count_gems(self, model: typing.Sequence[builtins.int], t: builtins.int) -> builtins.int:
+        r"""
+        The number of gems collected before horizon `t` in a SAT model of one of this generator's
+        formulas, whatever its objective. This is the quantity maximised by `Solver.max_gems`.
+        """
+    def help_edges(self, model: typing.Sequence[builtins.int], t: builtins.int) -> builtins.list[tuple[builtins.int, builtins.int, builtins.int]]:
+        r"""
+        The help events `(helper, beneficiary, t)` through horizon `t` in a SAT model of one of this
+        generator's formulas, whatever its mode, sorted by time step.
+        """
+    def projection_clauses(self, t: builtins.int, projection: typing.Literal['positions', 'help-edges', 'pairwise-help']) -> builtins.list[builtins.list[builtins.int]]:
+        r"""
+        Clauses defining the variables of `projection` through horizon `t`, to add to a formula of
+        this generator before enumerating its solutions with [`Self::blocking_clause`].
+        
+        `projection` is `"positions"` (every distinct plan), `"help-edges"` (every distinct set of
+        help events) or `"pairwise-help"` (every distinct set of helper-beneficiary pairs).
+        
+        # Raises:
+        - `ValueError`: if `projection` is unknown.
+        """
+    def blocking_clause(self, model: typing.Sequence[builtins.int], t: builtins.int, projection: typing.Literal['positions', 'help-edges', 'pairwise-help']) -> builtins.list[builtins.int]:
+        r"""
+        The clause ruling out every solution with the same `projection` as `model` through horizon
+        `t`. Adding it to the SAT solver after each model enumerates the distinct solutions.
+        
+        An empty clause means that every model has the same projection: there is nothing left to
+        enumerate.
+        
+        # Raises:
+        - `ValueError`: if `projection` is unknown.
+        """
+    def constraint_clauses(self, constraint: builtins.str, t: builtins.int) -> builtins.list[builtins.list[builtins.int]]:
+        r"""
+        Clauses asserting a cooperation constraint through horizon `t`, to add permanently to a
+        formula of this generator instead of assuming the constraint through `mode`.
+        
+        `constraint` is one of `"forbid-help-H-B"` (agent `B` never stands in a beam of agent `H`),
+        `"require-help-H-B"` (it does at least once), `"forbid-helper-H"` (nobody stands in a beam
+        of `H`) and `"forbid-laser-L"` (nobody but its owner stands in the beam of laser `L`).
+        Asserting a forbidding constraint is equivalent to assuming it, but an asserted required
+        help event must happen by `t` at every longer horizon solved with the same SAT solver.
+        
+        # Raises:
+        - `ValueError`: if `constraint` is malformed or does not fit the world.
         """
     def advance_delta_stream(self, t: builtins.int) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int]]:
         r"""
@@ -125,13 +251,16 @@ class ClauseGenerator:
         - `ValueError`: if no stream is active, or `t` is smaller than the horizon of the
             previous call.
         """
-    def objective(self, t: builtins.int, collect_gems: builtins.bool = False) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int]]:
+    def objective(self, t: builtins.int, collect_gems: builtins.bool | GemObjective = False) -> tuple[builtins.list[builtins.list[builtins.int]], builtins.list[builtins.int]]:
         r"""
         Generate only the objective clauses for horizon `t`.
         
         # Returns
         `(clauses, [])`. Useful for callers that manage the SAT solver directly and want to
         append the objective separately.
+        
+        # Raises:
+        - `ValueError`: if `collect_gems` does not fit the world (see [`Self::generate`]).
         """
     def decode_plan(self, model: typing.Sequence[builtins.int], t_end: builtins.int) -> builtins.list[builtins.list[world.Action]]:
         r"""
@@ -142,6 +271,56 @@ class ClauseGenerator:
         - `ValueError`: if the model does not encode a coherent sequence of moves.
         """
 
+@typing.final
+class GemObjective:
+    r"""
+    What the objective of a `ClauseGenerator` formula requires about gems, on top of every agent
+    reaching an exit. A gem is collected when an agent stands on its tile before the horizon.
+    
+    Every `collect_gems` parameter accepts one of these, or a `bool` meaning `GemObjective.all()`
+    (`True`) or `GemObjective.none()` (`False`):
+    
+    - `none()` — gems are ignored.
+    - `all()` — every gem is collected.
+    - `at_least(k)` — at least `k` gems are collected, whichever they are (see `Solver.max_gems`).
+    - `subset([(i, j), ...])` — the gems on these tiles are collected.
+    - `by_agent({(i, j): agent, ...})` — the gem on each tile is collected by the given agent.
+    
+    ```python
+    from lle.solver.clauses import ClauseGenerator, GemObjective
+    
+    clauses, assumptions = gen.generate(12, collect_gems=GemObjective.at_least(2))
+    ```
+    """
+    def __eq__(self, other: builtins.object, /) -> builtins.bool: ...
+    def __hash__(self) -> builtins.int: ...
+    @staticmethod
+    def none() -> GemObjective:
+        r"""
+        Gems are ignored.
+        """
+    @staticmethod
+    def all() -> GemObjective:
+        r"""
+        Every gem is collected.
+        """
+    @staticmethod
+    def at_least(k: builtins.int) -> GemObjective:
+        r"""
+        At least `k` gems are collected, whichever they are.
+        """
+    @staticmethod
+    def subset(positions: typing.Sequence[tuple[builtins.int, builtins.int]]) -> GemObjective:
+        r"""
+        The gems on the `(i, j)` tiles of `positions` are collected.
+        """
+    @staticmethod
+    def by_agent(collectors: typing.Mapping[tuple[builtins.int, builtins.int], builtins.int]) -> GemObjective:
+        r"""
+        The gem on each `(i, j)` tile of `collectors` is collected by the agent it maps to.
+        """
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class SolveMode:
     r"""
@@ -168,6 +347,15 @@ class SolveMode:
     - `no_divergence(k=2)` — rules out plans where one helper helps at least `k` distinct
       beneficiaries. This is the outgoing dual of `no_convergence`.
     
+    Each `require_*` factory is the positive counterpart of a `no_*` one: every plan must exhibit
+    the profile at least once (`require_cooperation()`, `require_asymmetric()`,
+    `require_mutual()`, `require_sequence(length=2)`, `require_interdependence(order=2)`,
+    `require_convergence(k=2)`, `require_divergence(k=2)`). A profile that cannot occur makes the
+    query unsatisfiable.
+    
+    `max_help_events(k)` and `exact_help_events(k)` bound the number of help events instead, to
+    study minimal cooperation (see `Solver.min_help_events`).
+    
     ```python
     from lle.solver.clauses import ClauseGenerator, SolveMode
     from lle import World
@@ -179,6 +367,11 @@ class SolveMode:
     ```
     """
     @property
+    def is_requirement(self) -> builtins.bool:
+        r"""
+        Whether the mode requires a cooperation profile instead of forbidding one.
+        """
+    @property
     def value(self) -> builtins.str:
         r"""
         The canonical string representation, inverse of `from_str` (e.g. `"no-sequence-3"`).
@@ -235,13 +428,62 @@ class SolveMode:
         Forbid any helper from helping at least `k` distinct beneficiaries. `k` must be `>= 2`.
         """
     @staticmethod
-    def from_str(value: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str) -> SolveMode:
+    def require_cooperation() -> SolveMode:
+        r"""
+        Require at least one help event: some agent enters a laser span that its owner blocks.
+        """
+    @staticmethod
+    def require_asymmetric() -> SolveMode:
+        r"""
+        Require an agent that helps someone without ever being helped by another agent.
+        """
+    @staticmethod
+    def require_mutual() -> SolveMode:
+        r"""
+        Require two agents that each help the other.
+        
+        Equivalent to `SolveMode::require_interdependence(2)`.
+        """
+    @staticmethod
+    def require_sequence(length: builtins.int = 2) -> SolveMode:
+        r"""
+        Require a non-decreasing-time temporal sequence of `length` help edges or more. `length` must be `>= 2`.
+        """
+    @staticmethod
+    def require_interdependence(order: builtins.int = 2) -> SolveMode:
+        r"""
+        Require a temporal closed trail with exactly `order` distinct agents. `order` must be `>= 2`.
+        """
+    @staticmethod
+    def require_convergence(k: builtins.int = 2) -> SolveMode:
+        r"""
+        Require a beneficiary that receives help from at least `k` distinct helpers. `k` must be `>= 2`.
+        """
+    @staticmethod
+    def require_divergence(k: builtins.int = 2) -> SolveMode:
+        r"""
+        Require a helper that helps at least `k` distinct beneficiaries. `k` must be `>= 2`.
+        """
+    @staticmethod
+    def max_help_events(k: builtins.int) -> SolveMode:
+        r"""
+        Allow at most `k` help events, counting every `(helper, beneficiary, t)` triple once. `k`
+        must be `>= 0`.
+        """
+    @staticmethod
+    def exact_help_events(k: builtins.int) -> SolveMode:
+        r"""
+        Require exactly `k` help events, counted as in `max_help_events`. `k` must be `>= 0`.
+        """
+    @staticmethod
+    def from_str(value: typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str) -> SolveMode:
         r"""
         Parse a canonical string (e.g. `"standard"`, `"no-sequence-3"`, `"no-divergence-3"`).
         
         `"no-sequence"`, `"no-interdependence"`, `"no-convergence"`, and `"no-divergence"` accept a
-        `"-n"` suffix for their parameter. Their bare forms are aliases for the corresponding
-        `"-2"` forms.
+        `"-n"` suffix for their parameter, and so do their `"require-*"` counterparts. Their bare
+        forms are aliases for the corresponding `"-2"` forms. `"max-help-events-k"` and
+        `"exact-help-events-k"` require their parameter.
         """
     def __str__(self) -> builtins.str: ...
     def __repr__(self) -> builtins.str: ...
@@ -1,23 +1,30 @@
 """Incremental SAT solver that builds constraints incrementally for time-bounded solving."""
 
 import random
+from collections.abc import Iterator, Sequence
 from typing import Literal
 
 from pysat.solvers import Minisat22  # pyright: ignore[reportMissingTypeStubs]
 
-from ..world import Action, World
-from .clauses import ClauseGenerator, SolveMode
-from .types import SolveModeLiteral
+from ..world import Action, World, WorldState
+from .clauses import ClauseGenerator, GemObjective, SolveMode
+from .types import AtMostOneEncodingLiteral, ProjectionLiteral, SolveModeLiteral
 
 
 def _default_t_max(world: World) -> int:
     return (world.width * world.height) // 2
 
 
-def _parse_mode(mode: SolveModeLiteral | str | SolveMode) -> SolveMode:
-    if isinstance(mode, str):
-        return SolveMode.from_str(mode)
-    return mode
+ModeArg = SolveModeLiteral | str | SolveMode | Sequence[SolveModeLiteral | str | SolveMode]
+"""One solve mode, or a conjunction of modes and cooperation constraints (such as
+`"forbid-help-0-1"`) given as a sequence or a `+`-separated string."""
+
+
+def _parse_mode(mode: ModeArg) -> SolveMode | str | list[SolveMode | str]:
+    """Normalize `mode` for the clause generator, which parses and validates the strings."""
+    if isinstance(mode, (SolveMode, str)):
+        return mode
+    return list(mode)
 
 
 class Solver:
@@ -31,21 +38,67 @@ class Solver:
     t_max: int
     generator: ClauseGenerator
 
-    def __init__(self, world: World, t_max: int | Literal["auto"] = "auto") -> None:
+    def __init__(
+        self,
+        world: World,
+        t_max: int | Literal["auto"] = "auto",
+        *,
+        symmetry_breaking: bool = False,
+        position_encoding: AtMostOneEncodingLiteral = "auto",
+        overlap_encoding: AtMostOneEncodingLiteral = "pairwise",
+        state: WorldState | None = None,
+    ) -> None:
+        """`symmetry_breaking` adds clauses that rule out plans only differing by when agents wait
+        on positions no other agent can reach. It never changes which horizons are solvable.
+
+        `position_encoding` and `overlap_encoding` select the at-most-one encodings of the movement
+        clauses (see `ClauseGenerator`). They only change the size of the formula.
+
+        With a `state`, such as `world.get_state()` in the middle of an episode, plans start from
+        that state instead of the start positions, and the gems it has collected no longer count
+        as objectives (see `ClauseGenerator.from_state`)."""
         self.world = world
         self.t_max = _default_t_max(world) if t_max == "auto" else t_max
-        self.generator = ClauseGenerator(world, self.t_max)
+        if state is None:
+            self.generator = ClauseGenerator(
+                world,
+                self.t_max,
+                symmetry_breaking=symmetry_breaking,
+                position_encoding=position_encoding,
+                overlap_encoding=overlap_encoding,
+            )
+        else:
+            self.generator = ClauseGenerator.from_state(
+                world,
+                state,
+                self.t_max,
+                symmetry_breaking=symmetry_breaking,
+                position_encoding=position_encoding,
+                overlap_encoding=overlap_encoding,
+            )
 
     @property
     def solution_lower_bound(self) -> int:
         return self.generator.solution_lower_bound
 
+    def _resolve_path_length(self, path_length: int | Literal["auto"]) -> int:
+        """Replace `"auto"` with `t_max` and check that `path_length` is within `0..=t_max`."""
+        if path_length == "auto":
+            return self.t_max
+        if path_length < 0:
+            raise ValueError(f"path_length must be non-negative, got {path_length}.")
+        if path_length > self.t_max:
+            raise ValueError(
+                f"path_length={path_length} exceeds this solver's t_max={self.t_max}. Construct a new Solver with a larger t_max."
+            )
+        return path_length
+
     def solve(
         self,
         path_length: int | Literal["auto"] = "auto",
         *,
-        mode: SolveModeLiteral | str | SolveMode = "standard",
-        collect_gems: bool = False,
+        mode: ModeArg = "standard",
+        collect_gems: bool | GemObjective = False,
         shuffle: bool = False,
     ) -> list[tuple[Action, ...]] | None:
         """Find a plan with the requested length.
@@ -54,15 +107,7 @@ class Solver:
         When it is `"auto"` (the default), this solver's construction-time
         `t_max` is used. A requested length cannot exceed `t_max`.
         """
-        if path_length == "auto":
-            path_length = self.t_max
-        elif path_length < 0:
-            raise ValueError(f"path_length must be non-negative, got {path_length}.")
-        elif path_length > self.t_max:
-            raise ValueError(
-                f"path_length={path_length} exceeds this solver's t_max={self.t_max}. Construct a new Solver with a larger t_max."
-            )
-
+        path_length = self._resolve_path_length(path_length)
         if path_length < self.solution_lower_bound:
             return None
 
@@ -76,12 +121,190 @@ class Solver:
             return None
         return _to_plan(self.generator.decode_plan(model, path_length))
 
+    def min_help_events(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+        collect_gems: bool | GemObjective = False,
+    ) -> tuple[int, list[tuple[Action, ...]]] | None:
+        """Find a plan with the requested length and as few help events as possible.
+
+        Returns the number of help events of the plan along with the plan itself, or `None` when
+        no plan satisfies `mode` at this length. Each round solves again with
+        `SolveMode.max_help_events` set just below the best plan so far, until no plan with fewer
+        help events exists. `path_length` is handled as in `solve`.
+        """
+        path_length = self._resolve_path_length(path_length)
+        if path_length < self.solution_lower_bound:
+            return None
+
+        parsed_mode = _parse_mode(mode)
+        base = parsed_mode if isinstance(parsed_mode, list) else [parsed_mode]
+        clauses, assumptions = self.generator.generate(path_length, mode=base, collect_gems=collect_gems)
+        best_model = solve_model(clauses, assumptions=assumptions)
+        if best_model is None:
+            return None
+        best = self.generator.count_help_events(best_model, path_length)
+        while best > 0:
+            bounded = [*base, SolveMode.max_help_events(best - 1)]
+            clauses, assumptions = self.generator.generate(path_length, mode=bounded, collect_gems=collect_gems)
+            model = solve_model(clauses, assumptions=assumptions)
+            if model is None:
+                break
+            best_model = model
+            best = self.generator.count_help_events(model, path_length)
+        return best, _to_plan(self.generator.decode_plan(best_model, path_length))
+
+    def max_gems(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+    ) -> tuple[int, list[tuple[Action, ...]]] | None:
+        """Find a plan with the requested length that collects as many gems as possible.
+
+        Returns the number of gems collected by the plan along with the plan itself, or `None` when
+        no plan satisfies `mode` at this length. Each round solves again with
+        `GemObjective.at_least` set just above the best plan so far, until no plan collects more
+        gems. `path_length` is handled as in `solve`.
+        """
+        path_length = self._resolve_path_length(path_length)
+        if path_length < self.solution_lower_bound:
+            return None
+
+        parsed_mode = _parse_mode(mode)
+        clauses, assumptions = self.generator.generate(path_length, mode=parsed_mode, collect_gems=False)
+        best_model = solve_model(clauses, assumptions=assumptions)
+        if best_model is None:
+            return None
+        best = self.generator.count_gems(best_model, path_length)
+        while best < self.world.n_gems:
+            objective = GemObjective.at_least(best + 1)
+            clauses, assumptions = self.generator.generate(path_length, mode=parsed_mode, collect_gems=objective)
+            model = solve_model(clauses, assumptions=assumptions)
+            if model is None:
+                break
+            best_model = model
+            best = self.generator.count_gems(model, path_length)
+        return best, _to_plan(self.generator.decode_plan(best_model, path_length))
+
+    def enumerate_plans(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+        projection: ProjectionLiteral = "positions",
+        limit: int = 1000,
+        collect_gems: bool | GemObjective = False,
+    ) -> Iterator[list[tuple[Action, ...]]]:
+        """Yield plans with the requested length that are pairwise distinct under `projection`.
+
+        With `"positions"`, every plan differs from the others (waits that the encoding prunes as
+        useless are not enumerated). With `"help-edges"` or `"pairwise-help"`, each plan stands for
+        one cooperation structure the map admits under `mode`: a distinct set of help events
+        `(helper, beneficiary, t)`, or of pairs `(helper, beneficiary)`. Each plan found adds a
+        clause ruling out its projection, until no plan is left or `limit` plans were yielded.
+        `path_length` is handled as in `solve`.
+        """
+        path_length = self._resolve_path_length(path_length)
+        if path_length < self.solution_lower_bound:
+            return
+        clauses, assumptions = self.generator.generate(path_length, mode=_parse_mode(mode), collect_gems=collect_gems)
+        clauses += self.generator.projection_clauses(path_length, projection)
+        with Minisat22(bootstrap_with=clauses) as sat_solver:
+            for _ in range(limit):
+                if not sat_solver.solve(assumptions=assumptions):  # pyright: ignore[reportUnknownMemberType]
+                    return
+                model: list[int] | None = sat_solver.get_model()  # pyright: ignore[reportUnknownVariableType]
+                assert model is not None
+                yield _to_plan(self.generator.decode_plan(model, path_length))
+                blocking = self.generator.blocking_clause(model, path_length, projection)
+                if not blocking:
+                    return
+                sat_solver.add_clause(blocking)  # pyright: ignore[reportUnknownMemberType]
+
+    def explain_unsat(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+        collect_gems: bool | GemObjective = False,
+    ) -> list[str] | None:
+        """Explain why no plan with the requested length satisfies `mode`.
+
+        Returns `None` when a plan exists. Otherwise, returns the human-readable reasons that are
+        jointly responsible, such as the goal of an agent, a help pair forbidden by
+        `"no-cooperation"`, a forbidden sequence pattern or a cooperation constraint: together they
+        rule out every plan, but a plan exists without any one of them. `path_length` is handled as
+        in `solve`.
+        """
+        path_length = self._resolve_path_length(path_length)
+        clauses, assumptions, reasons = self.generator.generate_explained(
+            path_length, mode=_parse_mode(mode), collect_gems=collect_gems
+        )
+        with Minisat22(bootstrap_with=clauses) as sat_solver:
+            if sat_solver.solve(assumptions=assumptions):  # pyright: ignore[reportUnknownMemberType]
+                return None
+            core: list[int] = sat_solver.get_core() or []  # pyright: ignore[reportUnknownVariableType, reportUnknownMemberType]
+            # Shrink the core: drop every literal without which the formula stays unsatisfiable.
+            for literal in list(core):
+                candidate = [other for other in core if other != literal]
+                if not sat_solver.solve(assumptions=candidate):  # pyright: ignore[reportUnknownMemberType]
+                    core = candidate
+        return [reasons[literal] for literal in assumptions if literal in core and literal in reasons]
+
+    def unsolvable_starts(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+        collect_gems: bool | GemObjective = False,
+    ) -> list[list[tuple[int, int]]]:
+        """List the start assignments of `world.start_assignments()` from which no plan with the
+        requested length satisfies `mode`.
+
+        Worlds with several possible starts per agent are solved for the assignment sampled at the
+        last reset only; this checks every assignment that a reset can sample. The world geometry
+        is shared between them (see `ClauseGenerator.with_starts`). `path_length` is handled as in
+        `solve`.
+        """
+        return list(self._unsolvable_starts(path_length, mode, collect_gems))
+
+    def solvable_from_every_start(
+        self,
+        path_length: int | Literal["auto"] = "auto",
+        *,
+        mode: ModeArg = "standard",
+        collect_gems: bool | GemObjective = False,
+    ) -> bool:
+        """Whether a plan with the requested length satisfies `mode` from every start assignment of
+        the world. Stops at the first unsolvable one (see `unsolvable_starts`)."""
+        return next(self._unsolvable_starts(path_length, mode, collect_gems), None) is None
+
+    def _unsolvable_starts(
+        self,
+        path_length: int | Literal["auto"],
+        mode: ModeArg,
+        collect_gems: bool | GemObjective,
+    ) -> Iterator[list[tuple[int, int]]]:
+        path_length = self._resolve_path_length(path_length)
+        parsed_mode = _parse_mode(mode)
+        for starts in self.world.start_assignments():
+            generator = self.generator.with_starts(starts)
+            if path_length < generator.solution_lower_bound:
+                yield starts
+                continue
+            clauses, assumptions = generator.generate(path_length, mode=parsed_mode, collect_gems=collect_gems)
+            if solve_model(clauses, assumptions=assumptions) is None:
+                yield starts
+
     def find_shortest(
         self,
-        mode: SolveModeLiteral | str | SolveMode = "standard",
+        mode: ModeArg = "standard",
         *,
         t_min: int | None = None,
-        collect_gems: bool = False,
+        collect_gems: bool | GemObjective = False,
         shuffle: bool = False,
     ) -> list[tuple[Action, ...]] | None:
         """Find the shortest plan from `t_min` through this solver's `t_max`.
@@ -126,8 +349,8 @@ def solve(
     /,
     *,
     path_length: int | Literal["auto"] = "auto",
-    mode: SolveModeLiteral | str | SolveMode = "standard",
-    collect_gems: bool = False,
+    mode: ModeArg = "standard",
+    collect_gems: bool | GemObjective = False,
     shuffle: bool = False,
 ) -> list[tuple[Action, ...]] | None:
     """Solve `world` with a fixed solver horizon and requested path length.
@@ -10,4 +10,15 @@ SolveModeLiteral = Literal[
     "no-convergence",
     "no-divergence",
     "no-interdependence",
+    "require-cooperation",
+    "require-asymmetric",
+    "require-mutual",
+    "require-sequence",
+    "require-convergence",
+    "require-divergence",
+    "require-interdependence",
 ]
+
+ProjectionLiteral = Literal["positions", "help-edges", "pairwise-help"]
+
+AtMostOneEncodingLiteral = Literal["auto", "pairwise", "sequential", "commander", "product", "binary"]
@@ -13,13 +13,46 @@ import typing
 from . import rendering
 __all__ = [
     "Action",
+    "DistanceMap",
     "EventType",
     "World",
     "WorldEvent",
+    "WorldSnapshot",
     "WorldState",
     "rendering",
 ]
 
+@typing.final
+class DistanceMap:
+    r"""
+    Breadth-first distances over the tiles of a world, computed with `World.distance_map`.
+    
+    An agent that reaches an exit can no longer move: exits are dead ends.
+    ```python
+    world = World("S0 . X")
+    distances = world.distance_map()
+    assert distances.distance((0, 0)) == 2
+    assert distances.path((0, 0)) == [(0, 0), (0, 1), (0, 2)]
+    ```
+    """
+    @property
+    def distances(self) -> builtins.list[builtins.list[typing.Optional[builtins.int]]]:
+        r"""
+        The distance of every tile, row by row, with `None` for the tiles that can not be reached.
+        """
+    def distance(self, position: tuple[builtins.int, builtins.int]) -> typing.Optional[builtins.int]:
+        r"""
+        The distance of `position`, or `None` if it can not be reached or is out of the world.
+        """
+    def path(self, position: tuple[builtins.int, builtins.int]) -> typing.Optional[builtins.list[tuple[builtins.int, builtins.int]]]:
+        r"""
+        A shortest path between the origin and `position`, in walking order and with both ends
+        included: from the origin to `position`, or from `position` to its nearest exit when the
+        distances are measured to the exits.
+        
+        Returns `None` if `position` can not be reached.
+        """
+
 class World:
     r"""
     The `World` represents the environment in which the agents evolve.
@@ -82,9 +115,51 @@ class World:
     def world_string(self) -> builtins.str:
         r"""
         Compute the world configuration string from the `World`.
-        If possible, the string is returned in "plain text" format.
-        Otherwise, it is returned in toml format.
+        If possible, the string is returned in "plain text" format (text v1, or text v2 when the
+        level has metadata or several start positions per agent).
+        Otherwise, it is returned in a compact toml format.
+        """
+    @property
+    def conflict_rule(self) -> typing.Literal['no-following', 'allow-following', 'priority']:
+        r"""
+        How `step` resolves agents that get in each other's way: `no-following` (default),
+        `allow-following` or `priority`.
+        
+        Setting the rule recomputes the available actions of the agents. The rule is not part of
+        the level: a world loaded from a world string or a TOML file always has the default rule,
+        and `world_string` does not record it.
         """
+    @conflict_rule.setter
+    def conflict_rule(self, value: typing.Literal['no-following', 'allow-following', 'priority']) -> None: ...
+    @property
+    def slip_probability(self) -> builtins.float:
+        r"""
+        The probability that the action of an agent slips at each step and is replaced by one of
+        its available actions (possibly `STAY` or the chosen action itself), drawn uniformly. A
+        slip that changes the action is reported by an `AGENT_SLIPPED` event.
+        The default, 0, keeps the world deterministic.
+        """
+    @slip_probability.setter
+    def slip_probability(self, value: builtins.float) -> None: ...
+    @property
+    def help_events(self) -> builtins.bool:
+        r"""
+        Whether `step` also reports, after the other events, the lasers that agents start or stop to
+        block (`LASER_BLOCKED` and `LASER_UNBLOCKED`) and then the help in the state it reaches
+        (`HELP`): an alive agent standing on an enabled beam of the colour of another agent, who
+        must therefore block it. Disabled by default.
+        
+        ```python
+        world = World("L0E . .\n. S0 S1\nX X .")
+        world.help_events = True
+        world.reset()
+        events = world.step([Action.NORTH, Action.NORTH])
+        assert [e.event_type for e in events] == [EventType.LASER_BLOCKED, EventType.HELP]
+        assert (events[1].agent_id, events[1].beneficiary) == (0, 1)
+        ```
+        """
+    @help_events.setter
+    def help_events(self, value: builtins.bool) -> None: ...
     @property
     def image_dimensions(self) -> tuple[builtins.int, builtins.int]:
         r"""
@@ -130,6 +205,11 @@ class World:
         r"""
         The number of different laser colours in the world.
         """
+    @property
+    def history_len(self) -> builtins.int:
+        r"""
+        The number of snapshots that `undo()` can go back to.
+        """
     def __init__(self, map_str: builtins.str) -> None:
         r"""
         Constructs a World from a string.
@@ -224,6 +304,11 @@ class World:
         assert all(not laser.is_on for laser in world.lasers)
         ```
         """
+    def start_assignments(self) -> builtins.list[builtins.list[tuple[builtins.int, builtins.int]]]:
+        r"""
+        Every start configuration that `reset` can sample: one of the `random_start_pos` of each
+        agent, with no two agents on the same position.
+        """
     def seed(self, seed_value: builtins.int) -> None: ...
     def step(self, action: Action | typing.Sequence[Action]) -> builtins.list[WorldEvent]:
         r"""
@@ -254,10 +339,33 @@ class World:
         assert all(e.event_type == EventType.AGENT_EXIT for e in events)
         ```
         """
+    def transitions(self, action: Action | typing.Sequence[Action]) -> builtins.list[tuple[WorldState, builtins.float]]:
+        r"""
+        The distribution P(s' | s, a) of the states reached when the agents take the given actions
+        from the current state s, accounting for the slip probability.
+        
+        Returns:
+            A list of (state, probability) pairs in which each reachable state appears once.
+            The world is left in its current state.
+        
+        Raises:
+            `InvalidActionError` if an agent takes an action that is not available.
+            `ValueError` if the number of actions is different from the number of agents
+        
+        Example:
+        ```python
+        world = World("S0 . X")
+        world.slip_probability = 0.2
+        transitions = world.transitions([Action.EAST])
+        assert len(transitions) == 2
+        assert abs(sum(p for _, p in transitions) - 1.0) < 1e-9
+        ```
+        """
     def reset(self) -> None:
         r"""
         Reset the world to its original state.
         This should be done directly after creating the world.
+        This also clears the history of `push_snapshot()`.
         """
     def available_actions(self) -> builtins.list[builtins.list[Action]]:
         r"""
@@ -297,7 +405,8 @@ class World:
         """
     def set_state(self, state: WorldState) -> builtins.list[WorldEvent]:
         r"""
-        Force the world to a given state
+        Force the world to a given state. If it succeeds, this also clears the history of
+        `push_snapshot()`.
         Args:
             state: The state to set the world to.
         Returns:
@@ -309,6 +418,72 @@ class World:
         r"""
         Return the current state of the world.
         """
+    def snapshot(self) -> WorldSnapshot:
+        r"""
+        Take a snapshot of the full state of the world, see `WorldSnapshot`.
+        """
+    def restore(self, snapshot: WorldSnapshot) -> None:
+        r"""
+        Put the world back in the state of a snapshot taken with `snapshot()` on this world.
+        
+        Raises:
+            `ValueError` if the snapshot was taken in a world with a different layout.
+        """
+    def distance_map(self, origin: typing.Optional[tuple[builtins.int, builtins.int]] = None, *, lasers_as_walls: typing.Sequence[builtins.int] = [], ignore_voids: builtins.bool = False, agents_as_obstacles: builtins.bool = False) -> DistanceMap:
+        r"""
+        Breadth-first distances in the current state of the world, from `origin` to every tile, or
+        from every tile to the nearest exit if `origin` is `None`. Walls and laser sources are never
+        walked on and, unless `ignore_voids` is set, neither are voids.
+        
+        Args:
+            origin: The position to measure the distances from. If it can not be walked on, it only
+                reaches itself, at distance 0.
+            lasers_as_walls: The colours of the beams to walk around, where they are currently on.
+            ignore_voids: Whether to walk on voids as on floor tiles.
+            agents_as_obstacles: Whether to walk around the alive agents, except at `origin`.
+        
+        Raises:
+            `IndexError`: if `origin` is out of bounds.
+        
+        Example:
+        ```python
+        world = World("S0 . .\nL1E . .\nX X S1")
+        world.reset()
+        assert world.distance_map((0, 0)).distance((2, 1)) == 3
+        assert world.distance_map((0, 0), lasers_as_walls=[1]).distance((2, 1)) is None
+        ```
+        """
+    def push_snapshot(self) -> None:
+        r"""
+        Record a snapshot of the world that the next `undo()` goes back to, e.g. before a step.
+        This discards the states that could be redone.
+        
+        Example:
+        ```python
+        world = World("S0 G X")
+        world.push_snapshot()
+        world.step([Action.EAST])
+        assert world.undo()
+        assert world.agents_positions == [(0, 0)]
+        assert world.redo()
+        assert world.agents_positions == [(0, 1)]
+        ```
+        """
+    def undo(self) -> builtins.bool:
+        r"""
+        Go back to the last snapshot recorded by `push_snapshot()` and remove it from the history.
+        The current state can then be restored with `redo()`.
+        
+        Returns:
+            Whether there was a snapshot to go back to. If not, the world is left untouched.
+        """
+    def redo(self) -> builtins.bool:
+        r"""
+        Go back to the state left by the last `undo()`.
+        
+        Returns:
+            Whether there was a state to redo. If not, the world is left untouched.
+        """
     def __deepcopy__(self, _memo: dict) -> World:
         r"""
         Returns a deep copy of the object.
@@ -329,13 +504,16 @@ class World:
         It required "default arguments" to be provided to the __new__ method
         before replacing them by the actual values in __setstate__.
         """
-    def __getstate__(self) -> tuple[builtins.str, WorldState]:
+    def __getstate__(self) -> tuple[builtins.str, WorldState, builtins.str, builtins.float, builtins.bool]:
         r"""
         Enable serialisation with pickle
         """
-    def __setstate__(self, state: tuple[builtins.str, WorldState]) -> None:
+    def __setstate__(self, state: tuple[builtins.str, WorldState, builtins.str, builtins.float, builtins.bool] | tuple[builtins.str, WorldState]) -> None:
         r"""
-        Enable deserialisation with pickle
+        Enable deserialisation with pickle.
+        
+        Pickles made before the runtime settings were recorded only hold the world string and the
+        state: the world then gets the default settings.
         """
     def __repr__(self) -> builtins.str: ...
 
@@ -344,11 +522,48 @@ class WorldEvent:
     @property
     def event_type(self) -> EventType: ...
     @property
-    def agent_id(self) -> builtins.int: ...
-    def __new__(cls, event_type: EventType, agent_id: builtins.int) -> WorldEvent: ...
+    def agent_id(self) -> builtins.int:
+        r"""
+        The agent concerned by the event, which is the helper for `HELP` events and the agent that
+        blocks the laser for `LASER_BLOCKED` and `LASER_UNBLOCKED` events.
+        """
+    @property
+    def beneficiary(self) -> typing.Optional[builtins.int]:
+        r"""
+        The agent that is helped, for `HELP` events.
+        """
+    @property
+    def laser_id(self) -> typing.Optional[builtins.int]:
+        r"""
+        The laser concerned by `HELP`, `LASER_BLOCKED` and `LASER_UNBLOCKED` events.
+        """
+    def __new__(cls, event_type: EventType, agent_id: builtins.int, beneficiary: typing.Optional[builtins.int] = None, laser_id: typing.Optional[builtins.int] = None) -> WorldEvent: ...
     def __str__(self) -> builtins.str: ...
     def __repr__(self) -> builtins.str: ...
 
+@typing.final
+class WorldSnapshot:
+    r"""
+    A snapshot of the full state of a `World` (agents, gems, laser beams and available actions),
+    taken with `World.snapshot()` and put back with `World.restore(snapshot)`.
+    
+    Restoring a snapshot is much faster than `World.set_state`, which makes it suited to tree
+    search. The conflict rule, the slip probability and the random generator of the world are
+    not part of the snapshot.
+    ```python
+    world = World("S0 G X")
+    snapshot = world.snapshot()
+    world.step([Action.EAST])
+    world.restore(snapshot)
+    assert world.get_state() == snapshot.state
+    ```
+    """
+    @property
+    def state(self) -> WorldState:
+        r"""
+        The state of the world when the snapshot was taken.
+        """
+
 class WorldState:
     r"""
     A state in the `World` is defined by:
@@ -479,6 +694,10 @@ class EventType(enum.Enum):
     AGENT_EXIT = ...
     GEM_COLLECTED = ...
     AGENT_DIED = ...
+    AGENT_SLIPPED = ...
+    HELP = ...
+    LASER_BLOCKED = ...
+    LASER_UNBLOCKED = ...
 
     def __repr__(self) -> builtins.str: ...
     def __hash__(self) -> builtins.int: ...
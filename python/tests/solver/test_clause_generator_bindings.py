@@ -1,6 +1,6 @@
 import pytest
-from lle import Action, World
-from lle.solver.clauses import ClauseGenerator, SolveMode
+from lle import Action, World, WorldState
+from lle.solver.clauses import ClauseGenerator, GemObjective, SolveMode
 from pysat.solvers import Minisat22
 
 COOPERATION_DELTA_WORLD = "L0E .  .  X\nL1E .  .  X\nL2E .  .  X\nS0  S1 S2 X"
@@ -228,3 +228,148 @@ def test_full_generation_does_not_affect_delta_streams():
 
     repeated, _ = generator.advance_delta_stream(2)
     assert repeated == []
+
+
+@pytest.mark.parametrize("level", [1, 6])
+def test_symmetry_breaking_keeps_satisfiability(level: int):
+    """Symmetry breaking only removes equivalent plans, never a whole horizon."""
+    world = World.level(level)
+    plain = ClauseGenerator(world, 24)
+    symmetric = ClauseGenerator(world, 24, symmetry_breaking=True)
+    for t in range(plain.solution_lower_bound, 25):
+        expected = _formula_is_sat(*plain.generate(t, collect_gems=False))
+        assert _formula_is_sat(*symmetric.generate(t, collect_gems=False)) == expected
+        if expected:
+            break
+
+
+OPTIONAL_HELP_WORLD = "S0 . . X\nL0E . . .\nS1 . . X"
+
+
+@pytest.mark.parametrize(
+    "mode",
+    [
+        "no-cooperation+require-cooperation",
+        ["no-cooperation", "require-cooperation"],
+        [SolveMode.no_cooperation(), SolveMode.require_cooperation()],
+    ],
+)
+def test_combined_modes_must_all_hold(mode):
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    for single in ("no-cooperation", "require-cooperation"):
+        assert _formula_is_sat(*gen.generate(8, mode=single, collect_gems=False))
+    assert not _formula_is_sat(*gen.generate(8, mode=mode, collect_gems=False))
+    gen.start_delta_stream(mode=mode, collect_gems=False)
+    assert not _formula_is_sat(*gen.advance_delta_stream(8))
+
+
+def test_combined_modes_reject_invalid_entries():
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    for bad in ("no-cooperation+bogus", ["no-cooperation", 3]):
+        with pytest.raises(ValueError):
+            gen.generate(8, mode=bad)
+
+
+def test_cooperation_constraints_can_be_assumed_or_asserted():
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    assert _formula_is_sat(*gen.generate(8, mode="require-help-0-1", collect_gems=False))
+    assert not _formula_is_sat(*gen.generate(8, mode="require-help-0-1+forbid-laser-0", collect_gems=False))
+    assert not _formula_is_sat(*gen.generate(8, mode=["require-help-0-1", "forbid-laser-0"], collect_gems=False))
+    clauses, assumptions = gen.generate(8, mode="require-help-0-1", collect_gems=False)
+    clauses += gen.constraint_clauses("forbid-helper-0", 8)
+    assert not _formula_is_sat(clauses, assumptions)
+
+
+@pytest.mark.parametrize("constraint", ["forbid-help-0-0", "forbid-help-0-2", "forbid-laser-1", "forbid-help-0"])
+def test_cooperation_constraints_must_fit_the_world(constraint: str):
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    with pytest.raises(ValueError):
+        gen.generate(8, mode=constraint)
+    with pytest.raises(ValueError):
+        gen.constraint_clauses(constraint, 8)
+
+
+@pytest.mark.parametrize("mode", ["standard", "no-cooperation", "no-sequence-2", "require-help-0-1"])
+def test_explained_formulas_select_every_reason(mode: str):
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    clauses, assumptions, reasons = gen.generate_explained(8, mode=mode, collect_gems=False)
+    assert set(reasons) <= set(assumptions)
+    assert "agent 0 must reach an exit" in reasons.values()
+    assert _formula_is_sat(clauses, assumptions) == _formula_is_sat(*gen.generate(8, mode=mode, collect_gems=False))
+
+
+def test_help_edges_enumeration_lists_distinct_structures():
+    gen = ClauseGenerator(World(OPTIONAL_HELP_WORLD), t_max=8)
+    clauses, assumptions = gen.generate(8, collect_gems=False)
+    clauses += gen.projection_clauses(8, "help-edges")
+    structures = []
+    with Minisat22(bootstrap_with=clauses) as solver:
+        while len(structures) < 1000 and solver.solve(assumptions=assumptions):
+            model = solver.get_model()
+            structures.append(tuple(gen.help_edges(model, 8)))
+            solver.add_clause(gen.blocking_clause(model, 8, "help-edges"))
+    assert len(set(structures)) == len(structures) > 2
+    assert () in structures
+    with pytest.raises(ValueError):
+        gen.projection_clauses(8, "help_edges")
+
+
+def test_gem_objectives_compare_by_value():
+    assert GemObjective.at_least(2) == GemObjective.at_least(2)
+    assert GemObjective.by_agent({(0, 1): 0}) != GemObjective.by_agent({(0, 1): 1})
+    assert repr(GemObjective.subset([(0, 0), (0, 2)])) == "GemObjective.subset([(0, 0), (0, 2)])"
+
+
+def test_count_gems_reads_the_collected_gems_of_a_model():
+    gen = ClauseGenerator(World("G S0 G\n. X ."), t_max=5)
+    clauses, assumptions = gen.generate(5, collect_gems=GemObjective.at_least(2))
+    with Minisat22(bootstrap_with=clauses) as solver:
+        assert solver.solve(assumptions=assumptions)
+        assert gen.count_gems(solver.get_model(), 5) == 2
+
+
+def test_explained_gem_objectives_name_their_reasons():
+    gen = ClauseGenerator(World("S0 G . S1\nX . . X"), t_max=3)
+    objective = GemObjective.by_agent({(0, 1): 1})
+    _, _, reasons = gen.generate_explained(3, collect_gems=objective)
+    assert "agent 1 must collect the gem at (0, 1)" in reasons.values()
+    with pytest.raises(ValueError, match="only has 2 agents"):
+        gen.generate(3, collect_gems=GemObjective.by_agent({(0, 1): 2}))
+
+
+@pytest.mark.parametrize("encoding", ["auto", "pairwise", "sequential", "commander", "product", "binary"])
+def test_every_encoding_solves_the_same_horizons(encoding: str):
+    world = World.level(2)
+    default = ClauseGenerator(world, t_max=12)
+    selected = ClauseGenerator(world, t_max=12, position_encoding=encoding, overlap_encoding=encoding)
+    for t in range(default.solution_lower_bound, 13):
+        expected = _formula_is_sat(*default.generate(t, collect_gems=False))
+        assert _formula_is_sat(*selected.generate(t, collect_gems=False)) == expected
+
+
+def test_encoding_stats_compare_encodings():
+    def position_stats(encoding: str) -> tuple[int, int]:
+        gen = ClauseGenerator(World.level(6), t_max=24, position_encoding=encoding)
+        gen.generate(24, collect_gems=False)
+        return gen.encoding_stats()["exactly_one_position"]
+
+    pairwise_clauses, pairwise_variables = position_stats("pairwise")
+    sequential_clauses, sequential_variables = position_stats("sequential")
+    assert pairwise_clauses > sequential_clauses
+    assert pairwise_variables < sequential_variables
+    with pytest.raises(ValueError, match="Unknown at-most-one encoding"):
+        ClauseGenerator(World.level(6), t_max=24, position_encoding="ladder")
+
+
+def test_from_state_plans_from_the_given_positions():
+    world = World("S0 G . S1\nX . . X")
+    state = WorldState([(1, 0), (0, 3)], [False])
+    gen = ClauseGenerator.from_state(world, state, t_max=5)
+    assert not _formula_is_sat(*gen.generate(4, collect_gems=True))
+    clauses, assumptions = gen.generate(5, collect_gems=True)
+    with Minisat22(bootstrap_with=clauses) as solver:
+        assert solver.solve(assumptions=assumptions)
+        plan = gen.decode_plan(solver.get_model(), 5)
+    assert all(actions[0] == Action.STAY for actions in plan)
+    with pytest.raises(ValueError, match="is dead"):
+        ClauseGenerator.from_state(world, WorldState([(1, 0), (0, 3)], [False], [True, False]), t_max=5)
@@ -127,3 +127,57 @@ def test_solve_accepts_the_divergence_mode_as_a_string():
 
     world = World("S0 . X")
     assert solve(world, 4, mode="no-divergence-3") is not None
+
+
+def test_require_factories_and_parser_round_trip():
+    """Required modes render canonical strings that parse back to the same mode."""
+    modes = [
+        SolveMode.require_cooperation(),
+        SolveMode.require_asymmetric(),
+        SolveMode.require_mutual(),
+        SolveMode.require_sequence(3),
+        SolveMode.require_interdependence(3),
+        SolveMode.require_convergence(),
+        SolveMode.require_divergence(3),
+    ]
+    for mode in modes:
+        assert mode.is_requirement
+        assert SolveMode.from_str(mode.value) == mode
+    assert SolveMode.require_mutual() == SolveMode.require_interdependence(2)
+    assert not SolveMode.no_cooperation().is_requirement
+
+
+@pytest.mark.parametrize("n", [-1, 0, 1])
+def test_require_factories_reject_parameters_below_two(n: int):
+    for factory in (
+        SolveMode.require_sequence,
+        SolveMode.require_interdependence,
+        SolveMode.require_convergence,
+        SolveMode.require_divergence,
+    ):
+        with pytest.raises(ValueError):
+            factory(n)
+
+
+def test_solve_requires_a_profile():
+    """A required profile that the layout rules out makes the query unsatisfiable."""
+    from lle import World, solve
+
+    world = World("S0 . X")
+    assert solve(world, 4, mode="standard") is not None
+    assert solve(world, 4, mode="require-cooperation") is None
+
+
+def test_help_event_factories_and_parser_round_trip():
+    for mode in (SolveMode.max_help_events(0), SolveMode.max_help_events(3), SolveMode.exact_help_events(2)):
+        assert SolveMode.from_str(mode.value) == mode
+    assert SolveMode.max_help_events(3).value == "max-help-events-3"
+    assert SolveMode.exact_help_events(1).is_requirement
+
+
+@pytest.mark.parametrize("bad", ["max-help-events", "max-help-events--1", "exact-help-events-x"])
+def test_help_event_modes_require_a_count(bad: str):
+    with pytest.raises(ValueError):
+        SolveMode.from_str(bad)
+    with pytest.raises(ValueError):
+        SolveMode.max_help_events(-1)
@@ -1,7 +1,7 @@
 import lle
 import pytest
 from lle import Action, World
-from lle.solver import Solver
+from lle.solver import GemObjective, SolveMode, Solver
 
 from ..world_layouts import (
     BLOCKED_UNSOLVABLE,
@@ -161,3 +161,147 @@ def test_collect_gems_is_per_solve_call():
     solver = Solver(world, 2)
     assert solver.solve(collect_gems=False) is not None
     assert solver.solve(collect_gems=True) is not None
+
+
+def test_solve_accepts_gem_objectives():
+    solver = Solver(World("G S0 G\n. X ."), 5)
+    assert solver.solve(3, collect_gems=GemObjective.at_least(1)) is not None
+    assert solver.solve(3, collect_gems=GemObjective.at_least(2)) is None
+    assert solver.solve(3, collect_gems=GemObjective.subset([(0, 2)])) is not None
+    assert solver.solve(5, collect_gems=GemObjective.subset([(0, 0), (0, 2)])) is not None
+
+
+def test_solve_rejects_gems_that_do_not_exist():
+    with pytest.raises(ValueError, match="there is no gem at"):
+        Solver(World("G S0 G\n. X ."), 5).solve(3, collect_gems=GemObjective.subset([(1, 0)]))
+
+
+def test_max_gems_collects_as_many_gems_as_the_horizon_allows():
+    solver = Solver(World("G S0 G\n. X ."), 5)
+    result = solver.max_gems(2)
+    assert result is not None
+    assert result[0] == 0
+    result = solver.max_gems(3)
+    assert result is not None
+    assert result[0] == 1
+    result = solver.max_gems(5)
+    assert result is not None
+    n_gems, plan = result
+    assert n_gems == 2
+    assert len(plan) == 5
+
+
+def test_solver_replans_from_a_mid_episode_state():
+    world = World("G S0 G\n. X .")
+    world.reset()
+    world.step([Action.EAST])
+    solver = Solver(world, 5, state=world.get_state())
+    assert solver.solve(3, collect_gems=True) is None
+    plan = solver.solve(4, collect_gems=True)
+    assert plan is not None
+    for actions in plan:
+        world.step(list(actions))
+    assert world.agents_positions == [(1, 1)]
+    assert world.gems_collected == 2
+
+
+def test_solver_encodes_lasers_in_their_current_state():
+    world = World("S0 @ S1\n. L1W .\n. @ .\nX @ X")
+    assert Solver(world, 6).solve(3) is None
+    world.source_at((1, 1)).disable()
+    assert Solver(world, 6).solve(3) is not None
+    world.source_at((1, 1)).enable()
+    world.source_at((1, 1)).agent_id = 0
+    assert Solver(world, 6).solve(3) is not None
+
+
+WALLED_START = """
+world_string = "X . @ ."
+[[agents]]
+start_positions = [{ i = 0, j = 1 }, { i = 0, j = 3 }]
+"""
+
+
+def test_unsolvable_starts_lists_every_unsolvable_assignment():
+    world = World(WALLED_START)
+    assert world.start_assignments() == [[(0, 1)], [(0, 3)]]
+    solver = Solver(world, 3)
+    assert solver.unsolvable_starts(3) == [[(0, 3)]]
+    assert not solver.solvable_from_every_start(3)
+    assert Solver(World("S0 . X"), 3).solvable_from_every_start(2)
+
+
+def test_solve_accepts_combined_modes():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    assert lle.solve(world, 8, mode="require-cooperation") is not None
+    assert lle.solve(world, 8, mode="no-cooperation+require-cooperation") is None
+    assert lle.solve(world, 8, mode=["no-mutual", "require-cooperation"]) is not None
+
+
+def test_min_help_events_finds_the_least_cooperative_plan():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    result = Solver(world, 8).min_help_events(8)
+    assert result is not None
+    n_help_events, plan = result
+    assert n_help_events == 0
+    assert len(plan) == 8
+
+
+def test_min_help_events_of_a_cooperative_level():
+    solver = Solver(World.level(4), 10)
+    result = solver.min_help_events(10)
+    assert result is not None
+    n_help_events, _ = result
+    assert n_help_events > 0
+    assert solver.solve(10, mode=SolveMode.max_help_events(n_help_events - 1)) is None
+
+
+def test_min_help_events_respects_the_mode():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    result = Solver(world, 8).min_help_events(8, mode="require-cooperation")
+    assert result is not None
+    assert result[0] == 1
+
+
+def test_explain_unsat_returns_none_for_solvable_problems():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    assert Solver(world, 8).explain_unsat(8, mode="no-cooperation") is None
+
+
+def test_explain_unsat_names_the_forbidden_help_pair():
+    world = World(" @  S0 S1\nL0E .  .\n @  X  X")
+    reasons = Solver(world, 6).explain_unsat(6, mode="no-cooperation")
+    assert reasons == [
+        "agent 1 must reach an exit",
+        "no-cooperation forbids agent 0 from helping agent 1",
+    ]
+
+
+def test_explain_unsat_names_the_conflicting_constraints():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    reasons = Solver(world, 8).explain_unsat(8, mode=["forbid-help-0-1", "require-help-0-1"])
+    assert reasons == ["constraint forbid-help-0-1", "constraint require-help-0-1"]
+
+
+def test_explain_unsat_of_a_short_horizon_names_the_goals():
+    reasons = Solver(World("S0 . . X"), 4).explain_unsat(1)
+    assert reasons == ["agent 0 must reach an exit"]
+
+
+def test_enumerate_plans_lists_distinct_plans():
+    plans = list(Solver(World("S0 .\n. X"), 4).enumerate_plans(2))
+    assert len(plans) == 2
+    assert plans[0] != plans[1]
+
+
+def test_enumerate_plans_stops_at_the_limit():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    plans = list(Solver(world, 8).enumerate_plans(8, projection="help-edges", limit=3))
+    assert len(plans) == 3
+
+
+def test_enumerate_plans_over_pairwise_help_lists_the_cooperation_structures():
+    world = World("S0 . . X\nL0E . . .\nS1 . . X")
+    solver = Solver(world, 8)
+    assert len(list(solver.enumerate_plans(8, projection="pairwise-help"))) == 2
+    assert len(list(solver.enumerate_plans(8, mode="no-cooperation", projection="pairwise-help"))) == 1
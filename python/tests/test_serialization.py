@@ -2,7 +2,7 @@ import pickle
 import random
 
 import orjson
-from lle import LLE, World, WorldState
+from lle import LLE, Action, World, WorldState
 
 
 def test_pickle_world_state():
@@ -50,6 +50,30 @@ def test_pickled_world_keeps_same_laser_ids():
         assert world.source_at(pos).direction == deserialised.source_at(pos).direction
 
 
+def test_pickled_world_keeps_its_conflict_rule():
+    world = World("S0 S1 . X X")
+    world.conflict_rule = "allow-following"
+    deserialised: World = pickle.loads(pickle.dumps(world))
+    assert deserialised.conflict_rule == "allow-following"
+    assert deserialised.available_actions() == world.available_actions()
+
+
+def test_older_pickles_get_the_default_runtime_settings():
+    world = World("S0 S1 . X X")
+    world.step([Action.STAY, Action.EAST])
+    restored = World("S0 X")
+    restored.__setstate__((world.world_string, world.get_state()))
+    assert restored.conflict_rule == "no-following"
+    assert restored.get_state() == world.get_state()
+
+
+def test_pickled_world_keeps_its_help_events():
+    world = World("S0 X")
+    world.help_events = True
+    deserialised: World = pickle.loads(pickle.dumps(world))
+    assert deserialised.help_events
+
+
 def test_serialize_env_to_json():
     env = LLE.from_str("S0 L0E X").build()
     s = orjson.dumps(env, option=orjson.OPT_SERIALIZE_NUMPY)
@@ -872,3 +872,96 @@ S13 L13W . . . X
 """)
     assert world.n_agents == 14
     assert len(world.laser_sources) == 14
+
+
+def test_conflict_rule():
+    world = World("S0 S1 . X X")
+    assert world.conflict_rule == "no-following"
+    assert Action.EAST not in world.available_actions()[0]
+    world.conflict_rule = "allow-following"
+    assert Action.EAST in world.available_actions()[0]
+    world.step([Action.EAST, Action.EAST])
+    assert world.agents_positions == [(0, 1), (0, 2)]
+    with pytest.raises(ValueError):
+        world.conflict_rule = "anything-goes"
+
+
+def test_slip_probability():
+    world = World("S0 . X")
+    assert world.slip_probability == 0.0
+    with pytest.raises(ValueError):
+        world.slip_probability = 2.0
+    world.slip_probability = 1.0
+    outcomes = set[tuple[bool, tuple[int, int]]]()
+    for _ in range(50):
+        world.reset()
+        events = world.step([Action.EAST])
+        slipped = [e.event_type for e in events] == [EventType.AGENT_SLIPPED]
+        outcomes.add((slipped, world.agents_positions[0]))
+    # The agent draws EAST or STAY, and only STAY is a slip.
+    assert outcomes == {(False, (0, 1)), (True, (0, 0))}
+
+
+def test_transitions():
+    world = World("S0 . X")
+    world.slip_probability = 0.25
+    state = world.get_state()
+    transitions = {s.agents_positions[0]: p for s, p in world.transitions([Action.EAST])}
+    assert world.get_state() == state
+    assert transitions == pytest.approx({(0, 1): 0.875, (0, 0): 0.125})
+
+
+def test_help_events():
+    world = World("L0E . .\n. S0 S1\nX X .")
+    assert not world.help_events
+    world.help_events = True
+    world.reset()
+    events = world.step([Action.NORTH, Action.NORTH])
+    assert [e.event_type for e in events] == [EventType.LASER_BLOCKED, EventType.HELP]
+    help = events[1]
+    assert (help.agent_id, help.beneficiary, help.laser_id) == (0, 1, 0)
+    world.step([Action.STAY, Action.SOUTH])
+    events = world.step([Action.SOUTH, Action.STAY])
+    assert [e.event_type for e in events] == [EventType.LASER_UNBLOCKED]
+    assert events[0].beneficiary is None
+
+
+def test_distance_map():
+    world = World("S0 . V\n. @ .\n. . X")
+    world.reset()
+    to_exits = world.distance_map()
+    assert to_exits.distances == [[4, 5, None], [3, None, 1], [2, 1, 0]]
+    assert to_exits.path((0, 0)) == [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]
+    assert to_exits.path((1, 1)) is None
+    assert world.distance_map(ignore_voids=True).distance((0, 2)) == 2
+    from_start = world.distance_map((0, 0))
+    assert from_start.distance((2, 2)) == 4
+    assert from_start.path((0, 1)) == [(0, 0), (0, 1)]
+    from_wall = world.distance_map((1, 1))
+    assert from_wall.distances == [[None, None, None], [None, 0, None], [None, None, None]]
+    with pytest.raises(IndexError):
+        world.distance_map((3, 0))
+
+
+def test_snapshot_and_restore():
+    world = World.level(6)
+    world.reset()
+    snapshot = world.snapshot()
+    for _ in range(10):
+        world.step([actions[0] for actions in world.available_actions()])
+    world.restore(snapshot)
+    assert world.get_state() == snapshot.state
+    with pytest.raises(ValueError):
+        world.restore(World("S0 X").snapshot())
+
+
+def test_undo_redo():
+    world = World("S0 G X")
+    assert not world.undo()
+    world.push_snapshot()
+    world.step(Action.EAST)
+    assert world.history_len == 1
+    assert world.undo()
+    assert world.gems_collected == 0
+    assert world.redo()
+    assert world.gems_collected == 1
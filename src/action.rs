@@ -3,9 +3,11 @@ use std::{
     ops::Add,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::{Position, RuntimeWorldError};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Copy)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Copy, Serialize, Deserialize)]
 pub enum Action {
     North = 0,
     South = 1,
@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 pub type AgentId = usize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Agent {
     id: AgentId,
     dead: bool,
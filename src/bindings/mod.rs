@@ -11,9 +11,12 @@ mod world;
 pub use pyexceptions::{
     InvalidActionError, InvalidLevelError, InvalidWorldStateError, ParsingError,
 };
-pub use solver::{PyClauseGenerator, PySolveMode};
+pub use solver::{PyClauseGenerator, PyGemObjective, PySolveMode};
 pub use tiles::{PyLaser, PyLaserSource};
-pub use world::{PyAction, PyEventType, PyPosition, PyWorld, PyWorldEvent, PyWorldState};
+pub use world::{
+    PyAction, PyDistanceMap, PyEventType, PyPosition, PyWorld, PyWorldEvent, PyWorldSnapshot,
+    PyWorldState,
+};
 
 #[pymodule]
 mod lle {
@@ -54,12 +57,16 @@ mod lle {
         #[pymodule_export]
         use super::super::world::PyAction;
         #[pymodule_export]
+        use super::super::world::PyDistanceMap;
+        #[pymodule_export]
         use super::super::world::PyEventType;
         #[pymodule_export]
         use super::super::world::PyWorld;
         #[pymodule_export]
         use super::super::world::PyWorldEvent;
         #[pymodule_export]
+        use super::super::world::PyWorldSnapshot;
+        #[pymodule_export]
         use super::super::world::PyWorldState;
     }
 
@@ -99,6 +106,7 @@ mod lle {
         let clauses = PyModule::new(py, "clauses")?;
         clauses.add_class::<super::solver::PyClauseGenerator>()?;
         clauses.add_class::<super::solver::PySolveMode>()?;
+        clauses.add_class::<super::solver::PyGemObjective>()?;
         sys_modules.set_item("lle.solver.clauses", &clauses)
     }
 }
@@ -1,5 +1,7 @@
 mod pyclause_generator;
+mod pygem_objective;
 mod pysolvemode;
 
 pub use pyclause_generator::PyClauseGenerator;
+pub use pygem_objective::PyGemObjective;
 pub use pysolvemode::PySolveMode;
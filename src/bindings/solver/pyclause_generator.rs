@@ -1,20 +1,72 @@
+use std::collections::HashMap;
+
 use pyo3::{exceptions::PyValueError, prelude::*, types::PyAny};
-use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use pyo3_stub_gen::{
+    PyStubType, TypeInfo,
+    derive::{gen_stub_pyclass, gen_stub_pymethods},
+};
 
-use super::pysolvemode::PySolveMode;
+use super::{pygem_objective::PyGemObjective, pysolvemode::PySolveMode};
 use crate::{
-    bindings::{PyAction, PyWorld, pyexceptions::solver_error_to_exception},
-    solver::{Clause, ClauseGenerator, DeltaStream, Literal, SolveMode},
+    Position,
+    bindings::{PyAction, PyWorld, PyWorldState, pyexceptions::solver_error_to_exception},
+    solver::{
+        Clause, ClauseGenerator, CooperationConstraint, DeltaStream, EncodingConfig, GemObjective,
+        Literal, Projection, SolveMode, SolveSpec,
+    },
 };
 
-fn extract_solve_mode(py: Python, mode: Py<PyAny>) -> PyResult<SolveMode> {
+/// Clauses, assumptions and the description of each selector literal.
+type ExplainedFormula = (Vec<Clause>, Vec<Literal>, HashMap<Literal, String>);
+
+/// A `collect_gems` argument: a `GemObjective`, or a `bool` for all gems or none.
+#[derive(FromPyObject, IntoPyObject)]
+enum GemsArg {
+    Objective(PyGemObjective),
+    Flag(bool),
+}
+
+impl PyStubType for GemsArg {
+    fn type_output() -> TypeInfo {
+        let (flag, objective) = (bool::type_output(), PyGemObjective::type_output());
+        // The stub generator re-spaces `|` itself, so the union name must not contain spaces.
+        TypeInfo {
+            name: format!("{}|{}", flag.name, objective.name),
+            ..flag | objective
+        }
+    }
+}
+
+impl From<GemsArg> for GemObjective {
+    fn from(gems: GemsArg) -> Self {
+        match gems {
+            GemsArg::Objective(objective) => objective.into(),
+            GemsArg::Flag(all) => all.into(),
+        }
+    }
+}
+
+/// Extract a single mode, a `+`-separated string of modes, or a sequence of either.
+fn extract_solve_spec(py: Python, mode: Py<PyAny>) -> PyResult<SolveSpec> {
     if let Ok(m) = mode.extract::<PySolveMode>(py) {
-        Ok(m.into())
+        Ok(SolveMode::from(m).into())
     } else if let Ok(s) = mode.extract::<String>(py) {
-        s.parse::<SolveMode>().map_err(PyValueError::new_err)
+        s.parse::<SolveSpec>().map_err(PyValueError::new_err)
+    } else if let Ok(modes) = mode.extract::<Vec<Py<PyAny>>>(py) {
+        let mut spec = SolveSpec::new();
+        for mode in modes {
+            let part = extract_solve_spec(py, mode)?;
+            spec = part.modes().iter().copied().fold(spec, SolveSpec::with);
+            spec = part
+                .constraints()
+                .iter()
+                .copied()
+                .fold(spec, SolveSpec::with_constraint);
+        }
+        Ok(spec)
     } else {
         Err(PyValueError::new_err(
-            "mode must be a SolveMode enum or a string",
+            "mode must be a SolveMode enum, a string or a sequence of them",
         ))
     }
 }
@@ -55,22 +107,167 @@ pub struct PyClauseGenerator {
     active_stream: Option<DeltaStream>,
 }
 
+impl PyClauseGenerator {
+    /// Wrap `inner` after applying the constructor options.
+    fn configured(
+        inner: ClauseGenerator,
+        t_max: usize,
+        symmetry_breaking: bool,
+        position_encoding: &str,
+        overlap_encoding: &str,
+    ) -> PyResult<Self> {
+        let encoding = EncodingConfig {
+            exactly_one_position: position_encoding.parse().map_err(PyValueError::new_err)?,
+            no_overlap: overlap_encoding.parse().map_err(PyValueError::new_err)?,
+        };
+        let inner = inner
+            .with_symmetry_breaking(symmetry_breaking)
+            .with_encoding(encoding);
+        let solution_lower_bound = inner.solution_lower_bound();
+        Ok(Self {
+            inner,
+            t_max,
+            solution_lower_bound,
+            active_stream: None,
+        })
+    }
+}
+
 #[gen_stub_pymethods]
 #[pymethods]
 impl PyClauseGenerator {
     /// Build a clause generator for the given `world`, considering plans of length up to `t_max`.
+    ///
+    /// With `symmetry_breaking=True`, the formula also forbids plans that wait before moving to
+    /// a position only this agent can reach, since the same plan with the wait one step later is
+    /// equally valid. Satisfiability is unchanged, but the SAT solver explores fewer equivalent
+    /// plans.
+    ///
+    /// `position_encoding` and `overlap_encoding` select the at-most-one encodings of "one position
+    /// per agent" and "one agent per tile": `"auto"` (pairwise up to five variables, sequential
+    /// beyond), `"pairwise"`, `"sequential"`, `"commander"`, `"product"` or `"binary"`. They only
+    /// change the size of the formula, which [`Self::encoding_stats`] reports.
+    ///
+    /// # Raises:
+    /// - `ValueError`: if an encoding is unknown.
     #[new]
-    fn new(world: &PyWorld, t_max: usize) -> PyResult<Self> {
+    #[pyo3(signature = (
+        world,
+        t_max,
+        symmetry_breaking=false,
+        position_encoding="auto",
+        overlap_encoding="pairwise"
+    ))]
+    fn new(
+        world: &PyWorld,
+        t_max: usize,
+        symmetry_breaking: bool,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary']",
+            imports = ("typing",)
+        ))]
+        position_encoding: &str,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary']",
+            imports = ("typing",)
+        ))]
+        overlap_encoding: &str,
+    ) -> PyResult<Self> {
         let inner = world.with_world(|world| ClauseGenerator::new(world, t_max));
+        Self::configured(
+            inner,
+            t_max,
+            symmetry_breaking,
+            position_encoding,
+            overlap_encoding,
+        )
+    }
+
+    /// A clause generator for the same world, horizon and options when the agents start from
+    /// `starts`, one `(i, j)` position per agent, such as one of `World.start_assignments()`.
+    ///
+    /// The world geometry is computed once and shared with this generator, so checking a world
+    /// from every start assignment only pays for what depends on the starts.
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `starts` does not give one walkable position per agent, or if two agents
+    ///   share a position.
+    fn with_starts(&self, starts: Vec<(usize, usize)>) -> PyResult<Self> {
+        let starts = starts.into_iter().map(Position::from).collect::<Vec<_>>();
+        let inner = self
+            .inner
+            .with_starts(&starts)
+            .map_err(solver_error_to_exception)?;
         let solution_lower_bound = inner.solution_lower_bound();
         Ok(Self {
             inner,
-            t_max,
+            t_max: self.t_max,
             solution_lower_bound,
             active_stream: None,
         })
     }
 
+    /// Build a clause generator that plans from `state` instead of the start positions of `world`,
+    /// to replan in the middle of an episode. The other parameters are those of the constructor.
+    ///
+    /// The agents start where `state` puts them, and those on an exit stay there. The gems that
+    /// `state` marks as collected are removed from every gem objective: `GemObjective.all()`,
+    /// `subset` and `by_agent` no longer require them, and they count towards `at_least` and
+    /// `count_gems`.
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `state` is not a valid state of `world`, if one of its agents is dead, or
+    ///   if an encoding is unknown.
+    #[staticmethod]
+    #[pyo3(signature = (
+        world,
+        state,
+        t_max,
+        symmetry_breaking=false,
+        position_encoding="auto",
+        overlap_encoding="pairwise"
+    ))]
+    fn from_state(
+        world: &PyWorld,
+        state: PyWorldState,
+        t_max: usize,
+        symmetry_breaking: bool,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary']",
+            imports = ("typing",)
+        ))]
+        position_encoding: &str,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['auto', 'pairwise', 'sequential', 'commander', 'product', 'binary']",
+            imports = ("typing",)
+        ))]
+        overlap_encoding: &str,
+    ) -> PyResult<Self> {
+        let state = state.into();
+        let inner = world
+            .with_world(|world| ClauseGenerator::from_state(world, &state, t_max))
+            .map_err(solver_error_to_exception)?;
+        Self::configured(
+            inner,
+            t_max,
+            symmetry_breaking,
+            position_encoding,
+            overlap_encoding,
+        )
+    }
+
+    /// The clauses and variables of each world rule, over every step generated so far, as
+    /// `{family: (clauses, variables)}`. The families are the movement rules
+    /// (`"exactly_one_position"`, `"no_overlap"`, ...) and `"lasers"`. A variable counts for the
+    /// first family that uses it.
+    fn encoding_stats(&self) -> HashMap<&'static str, (usize, usize)> {
+        self.inner
+            .encoding_stats()
+            .iter()
+            .map(|(&family, stats)| (family, (stats.clauses, stats.variables)))
+            .collect()
+    }
+
     /// The number of SAT variables allocated so far by this generator.
     #[getter]
     fn n_vars(&self) -> usize {
@@ -83,8 +280,14 @@ impl PyClauseGenerator {
     /// # Parameters
     /// - `mode` accepts either a `SolveMode` instance or its canonical string (`"standard"`,
     /// `"no-cooperation"`, `"no-asymmetric"`, `"no-mutual"`, `"no-fully-coupled"`, `"no-sequence[-N]"`,
-    /// `"no-interdependence[-N]"`, `"no-convergence[-N]"`, `"no-divergence[-N]"`)
-    /// - `collect_gems` adds gem-collection clauses to the objective.
+    /// `"no-interdependence[-N]"`, `"no-convergence[-N]"`, `"no-divergence[-N]"`, and the
+    /// `"require-*"` counterparts). Several modes can be combined, either as a sequence or joined
+    /// with `+` (`"no-mutual+no-divergence-3"`): the plan must then satisfy all of them. The same
+    /// syntax adds targeted cooperation constraints, which are assumed: `"forbid-help-H-B"`,
+    /// `"require-help-H-B"`, `"forbid-helper-H"` and `"forbid-laser-L"`, with agent IDs `H` and
+    /// `B` and laser ID `L` (see [`Self::constraint_clauses`]).
+    /// - `collect_gems` is a `GemObjective` saying which gems the objective requires, or a `bool`
+    /// for all of them (`True`) or none (`False`).
     ///
     /// The result depends only on the arguments: it does not read or affect any stream started
     /// with [`Self::start_delta_stream`]. Its output must not be mixed into a solver that is also
@@ -98,24 +301,72 @@ impl PyClauseGenerator {
     /// - `(clauses, assumptions)` ready to be fed to a SAT solver.
     ///
     /// # Raises:
-    /// - `ValueError`: if `mode` is invalid or its parameter is meaningless.
-    #[pyo3(signature = (t, mode=None, collect_gems=true))]
+    /// - `ValueError`: if `mode` is invalid, its parameter is meaningless, a constraint names an
+    ///     agent or a laser the world does not have, or `collect_gems` names a tile without a gem
+    ///     or an agent the world does not have.
+    #[pyo3(signature = (t, mode=None, collect_gems=GemsArg::Flag(true)))]
     fn generate(
         &mut self,
         py: Python,
         t: usize,
         #[gen_stub(override_type(
-            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str | SolveMode | None",
+            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None",
             imports = ("typing",)
         ))]
         mode: Option<Py<PyAny>>,
-        collect_gems: bool,
+        collect_gems: GemsArg,
     ) -> PyResult<(Vec<Clause>, Vec<Literal>)> {
-        let mode = match mode {
-            Some(mode) => extract_solve_mode(py, mode)?,
-            None => SolveMode::Standard,
+        let spec = match mode {
+            Some(mode) => extract_solve_spec(py, mode)?,
+            None => SolveSpec::new(),
         };
-        Ok(self.inner.generate(t, mode, collect_gems))
+        self.inner
+            .generate(t, spec, collect_gems)
+            .map_err(solver_error_to_exception)
+    }
+
+    /// Generate the formula of [`Self::generate`] with every restriction behind a selector literal,
+    /// to explain why it is unsatisfiable.
+    ///
+    /// Each selector switches on one reason: the goal of one agent, one gem or the gem count of
+    /// `GemObjective.at_least`, one help pair
+    /// forbidden by `"no-cooperation"`, one pattern of `"no-sequence-N"` or
+    /// `"no-interdependence-N"`, or a mode or cooperation constraint as a whole. The selectors are
+    /// part of the assumptions. When the SAT solver fails under them, the reasons of the selectors
+    /// in its assumption core (e.g. `Minisat22.get_core()`) explain the failure.
+    ///
+    /// # Returns
+    /// - `(clauses, assumptions, reasons)`, where `reasons` maps each selector to a human-readable
+    ///     description.
+    ///
+    /// # Raises:
+    /// - `ValueError`: see [`Self::generate`].
+    #[pyo3(signature = (t, mode=None, collect_gems=GemsArg::Flag(true)))]
+    fn generate_explained(
+        &mut self,
+        py: Python,
+        t: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None",
+            imports = ("typing",)
+        ))]
+        mode: Option<Py<PyAny>>,
+        collect_gems: GemsArg,
+    ) -> PyResult<ExplainedFormula> {
+        let spec = match mode {
+            Some(mode) => extract_solve_spec(py, mode)?,
+            None => SolveSpec::new(),
+        };
+        let (clauses, assumptions, explanation) = self
+            .inner
+            .generate_explained(t, spec, collect_gems)
+            .map_err(solver_error_to_exception)?;
+        let reasons = explanation
+            .selectors()
+            .iter()
+            .map(|(selector, reason)| (*selector, reason.to_string()))
+            .collect();
+        Ok((clauses, assumptions, reasons))
     }
 
     /// Start a new incremental stream fixed to `mode` and `collect_gems`, replacing any stream
@@ -132,26 +383,115 @@ impl PyClauseGenerator {
     /// whole stream: to change either, start a new stream (and, in practice, a new solver).
     ///
     /// # Raises:
-    ///   - `ValueError`: if `mode` is invalid or its parameter is meaningless.
-    #[pyo3(signature = (mode=None, collect_gems=true))]
+    ///   - `ValueError`: see [`Self::generate`].
+    #[pyo3(signature = (mode=None, collect_gems=GemsArg::Flag(true)))]
     fn start_delta_stream(
         &mut self,
         py: Python,
         #[gen_stub(override_type(
-            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str | SolveMode | None",
+            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str | SolveMode | typing.Sequence[builtins.str | SolveMode] | None",
             imports = ("typing",)
         ))]
         mode: Option<Py<PyAny>>,
-        collect_gems: bool,
+        collect_gems: GemsArg,
     ) -> PyResult<()> {
-        let mode = match mode {
-            Some(mode) => extract_solve_mode(py, mode)?,
-            None => SolveMode::Standard,
+        let spec = match mode {
+            Some(mode) => extract_solve_spec(py, mode)?,
+            None => SolveSpec::new(),
         };
-        self.active_stream = Some(self.inner.start_delta_stream(mode, collect_gems));
+        let stream = self
+            .inner
+            .start_delta_stream(spec, collect_gems)
+            .map_err(solver_error_to_exception)?;
+        self.active_stream = Some(stream);
         Ok(())
     }
 
+    /// The number of help events through horizon `t` in a SAT model of one of this generator's
+    /// formulas, whatever its mode. This is the quantity bounded by `SolveMode.max_help_events`.
+    fn count_help_events(&mut self, model: Vec<Literal>, t: usize) -> usize {
+        self.inner.count_help_events(&model, t)
+    }
+
+    /// The number of gems collected before horizon `t` in a SAT model of one of this generator's
+    /// formulas, whatever its objective. This is the quantity maximised by `Solver.max_gems`.
+    fn count_gems(&self, model: Vec<Literal>, t: usize) -> usize {
+        self.inner.count_gems(&model, t)
+    }
+
+    /// The help events `(helper, beneficiary, t)` through horizon `t` in a SAT model of one of this
+    /// generator's formulas, whatever its mode, sorted by time step.
+    fn help_edges(&mut self, model: Vec<Literal>, t: usize) -> Vec<(usize, usize, usize)> {
+        self.inner.help_edges(&model, t)
+    }
+
+    /// Clauses defining the variables of `projection` through horizon `t`, to add to a formula of
+    /// this generator before enumerating its solutions with [`Self::blocking_clause`].
+    ///
+    /// `projection` is `"positions"` (every distinct plan), `"help-edges"` (every distinct set of
+    /// help events) or `"pairwise-help"` (every distinct set of helper-beneficiary pairs).
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `projection` is unknown.
+    fn projection_clauses(
+        &mut self,
+        t: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['positions', 'help-edges', 'pairwise-help']",
+            imports = ("typing",)
+        ))]
+        projection: &str,
+    ) -> PyResult<Vec<Clause>> {
+        let projection = projection
+            .parse::<Projection>()
+            .map_err(PyValueError::new_err)?;
+        Ok(self.inner.projection_clauses(t, projection))
+    }
+
+    /// The clause ruling out every solution with the same `projection` as `model` through horizon
+    /// `t`. Adding it to the SAT solver after each model enumerates the distinct solutions.
+    ///
+    /// An empty clause means that every model has the same projection: there is nothing left to
+    /// enumerate.
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `projection` is unknown.
+    fn blocking_clause(
+        &self,
+        model: Vec<Literal>,
+        t: usize,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['positions', 'help-edges', 'pairwise-help']",
+            imports = ("typing",)
+        ))]
+        projection: &str,
+    ) -> PyResult<Clause> {
+        let projection = projection
+            .parse::<Projection>()
+            .map_err(PyValueError::new_err)?;
+        Ok(self.inner.blocking_clause(&model, t, projection))
+    }
+
+    /// Clauses asserting a cooperation constraint through horizon `t`, to add permanently to a
+    /// formula of this generator instead of assuming the constraint through `mode`.
+    ///
+    /// `constraint` is one of `"forbid-help-H-B"` (agent `B` never stands in a beam of agent `H`),
+    /// `"require-help-H-B"` (it does at least once), `"forbid-helper-H"` (nobody stands in a beam
+    /// of `H`) and `"forbid-laser-L"` (nobody but its owner stands in the beam of laser `L`).
+    /// Asserting a forbidding constraint is equivalent to assuming it, but an asserted required
+    /// help event must happen by `t` at every longer horizon solved with the same SAT solver.
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `constraint` is malformed or does not fit the world.
+    fn constraint_clauses(&mut self, constraint: &str, t: usize) -> PyResult<Vec<Clause>> {
+        let constraint = constraint
+            .parse::<CooperationConstraint>()
+            .map_err(PyValueError::new_err)?;
+        self.inner
+            .constraint_clauses(constraint, t)
+            .map_err(solver_error_to_exception)
+    }
+
     /// Generate the clauses the active stream has not sent yet, through horizon `t`.
     ///
     /// Every returned clause can be kept permanently in the caller's solver. The returned
@@ -196,9 +536,20 @@ impl PyClauseGenerator {
     /// # Returns
     /// `(clauses, [])`. Useful for callers that manage the SAT solver directly and want to
     /// append the objective separately.
-    #[pyo3(signature = (t, collect_gems=false))]
-    fn objective(&mut self, t: usize, collect_gems: bool) -> (Vec<Clause>, Vec<Literal>) {
-        (self.inner.objective(t, collect_gems), vec![])
+    ///
+    /// # Raises:
+    /// - `ValueError`: if `collect_gems` does not fit the world (see [`Self::generate`]).
+    #[pyo3(signature = (t, collect_gems=GemsArg::Flag(false)))]
+    fn objective(
+        &mut self,
+        t: usize,
+        collect_gems: GemsArg,
+    ) -> PyResult<(Vec<Clause>, Vec<Literal>)> {
+        let clauses = self
+            .inner
+            .objective(t, collect_gems)
+            .map_err(solver_error_to_exception)?;
+        Ok((clauses, vec![]))
     }
 
     /// Decode a SAT model (as returned by `solver.get_model()`) into a joint-action plan
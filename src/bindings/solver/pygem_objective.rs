@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+use crate::{AgentId, Position, solver::GemObjective};
+
+/// What the objective of a `ClauseGenerator` formula requires about gems, on top of every agent
+/// reaching an exit. A gem is collected when an agent stands on its tile before the horizon.
+///
+/// Every `collect_gems` parameter accepts one of these, or a `bool` meaning `GemObjective.all()`
+/// (`True`) or `GemObjective.none()` (`False`):
+///
+/// - `none()` — gems are ignored.
+/// - `all()` — every gem is collected.
+/// - `at_least(k)` — at least `k` gems are collected, whichever they are (see `Solver.max_gems`).
+/// - `subset([(i, j), ...])` — the gems on these tiles are collected.
+/// - `by_agent({(i, j): agent, ...})` — the gem on each tile is collected by the given agent.
+///
+/// ```python
+/// from lle.solver.clauses import ClauseGenerator, GemObjective
+///
+/// clauses, assumptions = gen.generate(12, collect_gems=GemObjective.at_least(2))
+/// ```
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "GemObjective",
+    module = "lle.solver.clauses",
+    frozen,
+    eq,
+    hash,
+    from_py_object
+)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PyGemObjective {
+    inner: GemObjective,
+}
+
+impl From<PyGemObjective> for GemObjective {
+    fn from(objective: PyGemObjective) -> Self {
+        objective.inner
+    }
+}
+
+impl From<GemObjective> for PyGemObjective {
+    fn from(inner: GemObjective) -> Self {
+        Self { inner }
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyGemObjective {
+    /// Gems are ignored.
+    #[staticmethod]
+    fn none() -> Self {
+        GemObjective::None.into()
+    }
+
+    /// Every gem is collected.
+    #[staticmethod]
+    fn all() -> Self {
+        GemObjective::All.into()
+    }
+
+    /// At least `k` gems are collected, whichever they are.
+    #[staticmethod]
+    fn at_least(k: usize) -> Self {
+        GemObjective::AtLeast(k).into()
+    }
+
+    /// The gems on the `(i, j)` tiles of `positions` are collected.
+    #[staticmethod]
+    fn subset(positions: Vec<(usize, usize)>) -> Self {
+        GemObjective::Subset(positions.into_iter().map(Position::from).collect()).into()
+    }
+
+    /// The gem on each `(i, j)` tile of `collectors` is collected by the agent it maps to.
+    #[staticmethod]
+    fn by_agent(collectors: BTreeMap<(usize, usize), AgentId>) -> Self {
+        let collectors = collectors
+            .into_iter()
+            .map(|(pos, agent)| (Position::from(pos), agent))
+            .collect();
+        GemObjective::ByAgent(collectors).into()
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            GemObjective::None => "GemObjective.none()".to_string(),
+            GemObjective::All => "GemObjective.all()".to_string(),
+            GemObjective::AtLeast(k) => format!("GemObjective.at_least({k})"),
+            GemObjective::Subset(positions) => {
+                let positions = positions
+                    .iter()
+                    .map(|pos| format!("({}, {})", pos.i, pos.j))
+                    .collect::<Vec<_>>();
+                format!("GemObjective.subset([{}])", positions.join(", "))
+            }
+            GemObjective::ByAgent(collectors) => {
+                let collectors = collectors
+                    .iter()
+                    .map(|(pos, agent)| format!("({}, {}): {agent}", pos.i, pos.j))
+                    .collect::<Vec<_>>();
+                format!("GemObjective.by_agent({{{}}})", collectors.join(", "))
+            }
+        }
+    }
+}
@@ -26,6 +26,15 @@ use crate::{bindings::pyexceptions::solver_error_to_exception, solver::SolveMode
 /// - `no_divergence(k=2)` — rules out plans where one helper helps at least `k` distinct
 ///   beneficiaries. This is the outgoing dual of `no_convergence`.
 ///
+/// Each `require_*` factory is the positive counterpart of a `no_*` one: every plan must exhibit
+/// the profile at least once (`require_cooperation()`, `require_asymmetric()`,
+/// `require_mutual()`, `require_sequence(length=2)`, `require_interdependence(order=2)`,
+/// `require_convergence(k=2)`, `require_divergence(k=2)`). A profile that cannot occur makes the
+/// query unsatisfiable.
+///
+/// `max_help_events(k)` and `exact_help_events(k)` bound the number of help events instead, to
+/// study minimal cooperation (see `Solver.min_help_events`).
+///
 /// ```python
 /// from lle.solver.clauses import ClauseGenerator, SolveMode
 /// from lle import World
@@ -153,16 +162,102 @@ impl PySolveMode {
         Ok(Self { inner })
     }
 
+    /// Require at least one help event: some agent enters a laser span that its owner blocks.
+    #[staticmethod]
+    fn require_cooperation() -> Self {
+        SolveMode::RequireCooperation.into()
+    }
+
+    /// Require an agent that helps someone without ever being helped by another agent.
+    #[staticmethod]
+    fn require_asymmetric() -> Self {
+        SolveMode::RequireAsymmetric.into()
+    }
+
+    /// Require two agents that each help the other.
+    ///
+    /// Equivalent to `SolveMode::require_interdependence(2)`.
+    #[staticmethod]
+    fn require_mutual() -> Self {
+        SolveMode::require_interdependence(2)
+            .expect("the default interdependence order is valid")
+            .into()
+    }
+
+    /// Require a non-decreasing-time temporal sequence of `length` help edges or more. `length` must be `>= 2`.
+    #[staticmethod]
+    #[pyo3(signature = (length=2))]
+    fn require_sequence(length: i64) -> PyResult<Self> {
+        let length = usize::try_from(length)
+            .map_err(|_| PyValueError::new_err(format!("length must be >= 2, got {length}")))?;
+        let inner = SolveMode::require_sequence(length).map_err(solver_error_to_exception)?;
+        Ok(Self { inner })
+    }
+
+    /// Require a temporal closed trail with exactly `order` distinct agents. `order` must be `>= 2`.
+    #[staticmethod]
+    #[pyo3(signature = (order=2))]
+    fn require_interdependence(order: i64) -> PyResult<Self> {
+        let order = usize::try_from(order)
+            .map_err(|_| PyValueError::new_err(format!("order must be >= 2, got {order}")))?;
+        let inner = SolveMode::require_interdependence(order).map_err(solver_error_to_exception)?;
+        Ok(Self { inner })
+    }
+
+    /// Require a beneficiary that receives help from at least `k` distinct helpers. `k` must be `>= 2`.
+    #[staticmethod]
+    #[pyo3(signature = (k=2))]
+    fn require_convergence(k: i64) -> PyResult<Self> {
+        let k = usize::try_from(k)
+            .map_err(|_| PyValueError::new_err(format!("k must be >= 2, got {k}")))?;
+        let inner = SolveMode::require_convergence(k).map_err(solver_error_to_exception)?;
+        Ok(Self { inner })
+    }
+
+    /// Require a helper that helps at least `k` distinct beneficiaries. `k` must be `>= 2`.
+    #[staticmethod]
+    #[pyo3(signature = (k=2))]
+    fn require_divergence(k: i64) -> PyResult<Self> {
+        let k = usize::try_from(k)
+            .map_err(|_| PyValueError::new_err(format!("k must be >= 2, got {k}")))?;
+        let inner = SolveMode::require_divergence(k).map_err(solver_error_to_exception)?;
+        Ok(Self { inner })
+    }
+
+    /// Allow at most `k` help events, counting every `(helper, beneficiary, t)` triple once. `k`
+    /// must be `>= 0`.
+    #[staticmethod]
+    fn max_help_events(k: i64) -> PyResult<Self> {
+        let k = usize::try_from(k)
+            .map_err(|_| PyValueError::new_err(format!("k must be >= 0, got {k}")))?;
+        Ok(SolveMode::MaxHelpEvents(k).into())
+    }
+
+    /// Require exactly `k` help events, counted as in `max_help_events`. `k` must be `>= 0`.
+    #[staticmethod]
+    fn exact_help_events(k: i64) -> PyResult<Self> {
+        let k = usize::try_from(k)
+            .map_err(|_| PyValueError::new_err(format!("k must be >= 0, got {k}")))?;
+        Ok(SolveMode::ExactHelpEvents(k).into())
+    }
+
+    /// Whether the mode requires a cooperation profile instead of forbidding one.
+    #[getter]
+    fn is_requirement(&self) -> bool {
+        self.inner.is_requirement()
+    }
+
     /// Parse a canonical string (e.g. `"standard"`, `"no-sequence-3"`, `"no-divergence-3"`).
     ///
     /// `"no-sequence"`, `"no-interdependence"`, `"no-convergence"`, and `"no-divergence"` accept a
-    /// `"-n"` suffix for their parameter. Their bare forms are aliases for the corresponding
-    /// `"-2"` forms.
+    /// `"-n"` suffix for their parameter, and so do their `"require-*"` counterparts. Their bare
+    /// forms are aliases for the corresponding `"-2"` forms. `"max-help-events-k"` and
+    /// `"exact-help-events-k"` require their parameter.
     #[staticmethod]
     #[pyo3(name = "from_str")]
     pub fn parse(
         #[gen_stub(override_type(
-            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence'] | builtins.str"
+            type_repr = "typing.Literal['standard', 'no-cooperation', 'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence', 'no-interdependence', 'no-convergence', 'no-divergence', 'require-cooperation', 'require-asymmetric', 'require-mutual', 'require-sequence', 'require-interdependence', 'require-convergence', 'require-divergence'] | builtins.str"
         ))]
         value: &str,
     ) -> PyResult<Self> {
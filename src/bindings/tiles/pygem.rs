@@ -6,9 +6,12 @@ use std::{
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 
-use crate::{Tile, World, agent::AgentId, bindings::PyPosition, tiles::Gem};
-
-use super::inner;
+use crate::{
+    Tile, World,
+    agent::AgentId,
+    bindings::{PyPosition, pyexceptions::runtime_error_to_pyexception},
+    tiles::Gem,
+};
 
 #[gen_stub_pyclass]
 #[pyclass(name = "Gem", module = "lle.tiles")]
@@ -22,11 +25,6 @@ pub struct PyGem {
     world: Arc<Mutex<World>>,
 }
 
-// PyGem is thread-safe because it wraps a thread-safe object
-// and the other fields are immutable.
-unsafe impl Send for PyGem {}
-unsafe impl Sync for PyGem {}
-
 impl PyGem {
     pub fn new(gem: &Gem, pos: (usize, usize), world: Arc<Mutex<World>>) -> Self {
         Self {
@@ -50,16 +48,9 @@ impl PyGem {
 
     pub fn collect(&mut self) -> PyResult<()> {
         let world = &mut self.world.lock().unwrap();
-        let tile = inner(world, self.pos.into())?;
-        match tile {
-            Tile::Gem(gem) => gem.collect(),
-            _ => {
-                return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                    "Tile at {:?} is not a gem",
-                    self.pos
-                )));
-            }
-        };
+        world
+            .collect_gem(self.pos.into())
+            .map_err(runtime_error_to_pyexception)?;
         self.is_collected = true;
         Ok(())
     }
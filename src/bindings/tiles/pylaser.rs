@@ -7,11 +7,9 @@ use crate::{
     Position, Tile, World,
     agent::AgentId,
     bindings::{PyPosition, tiles::PyDirection},
-    tiles::{Laser, LaserId},
+    tiles::{Laser, LaserBeam, LaserId},
 };
 
-use super::inner;
-
 /// A laser tile of the world.
 #[gen_stub_pyclass]
 #[pyclass(name = "Laser", module = "lle.tiles")]
@@ -37,17 +35,19 @@ pub struct PyLaser {
     world: Arc<Mutex<World>>,
 }
 
-unsafe impl Send for PyLaser {}
-unsafe impl Sync for PyLaser {}
-
 impl PyLaser {
-    pub fn new(laser: &Laser, pos: Position, world: Arc<Mutex<World>>) -> Self {
+    pub fn new(
+        laser: &Laser,
+        beams: &[LaserBeam],
+        pos: Position,
+        world: Arc<Mutex<World>>,
+    ) -> Self {
         Self {
             laser_id: laser.laser_id(),
-            agent_id: laser.agent_id(),
+            agent_id: laser.agent_id(beams),
             direction: PyDirection::from(laser.direction()),
-            is_on: laser.is_on(),
-            is_enabled: laser.is_enabled(),
+            is_on: laser.is_on(beams),
+            is_enabled: laser.is_enabled(beams),
             pos: PyPosition::from(pos),
             world,
         }
@@ -72,9 +72,8 @@ impl PyLaser {
     /// The id of the agent currently standing on the tile, if any.
     #[getter]
     pub fn agent(&self) -> Option<AgentId> {
-        let world = &mut self.world.lock().unwrap();
-        let tile = inner(world, self.pos.into()).unwrap();
-        match tile {
+        let world = self.world.lock().unwrap();
+        match world.at(&self.pos.into())? {
             Tile::Laser(laser) => laser.agent(),
             _ => None,
         }
@@ -8,10 +8,10 @@ use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 
 use crate::{
-    Position, Tile, World,
+    Position, World,
     agent::AgentId,
     bindings::{PyPosition, tiles::PyDirection},
-    tiles::{LaserId, LaserSource},
+    tiles::{LaserBeam, LaserId},
 };
 
 #[gen_stub_pyclass]
@@ -37,11 +37,8 @@ pub struct PyLaserSource {
     world: Arc<Mutex<World>>,
 }
 
-unsafe impl Send for PyLaserSource {}
-unsafe impl Sync for PyLaserSource {}
-
 impl PyLaserSource {
-    pub fn new(world: Arc<Mutex<World>>, pos: (usize, usize), source: &LaserSource) -> Self {
+    pub fn new(world: Arc<Mutex<World>>, pos: (usize, usize), source: &LaserBeam) -> Self {
         Self {
             agent_id: source.agent_id(),
             direction: PyDirection::from(source.direction()),
@@ -58,10 +55,8 @@ impl PyLaserSource {
         }
 
         let world = &mut self.world.lock().unwrap();
-        let tile = world.at_mut(&self.pos.into()).unwrap();
-        // let tile = inner(world, self.pos).unwrap();
-        match tile {
-            Tile::LaserSource(laser_source) => {
+        match world.source_at_mut(self.pos.into()) {
+            Some(mut laser_source) => {
                 if enabled {
                     laser_source.enable();
                 } else {
@@ -69,7 +64,7 @@ impl PyLaserSource {
                 }
                 self.is_enabled = enabled;
             }
-            _ => panic!("Tile at {:?} is not a LaserSource", self.pos),
+            None => panic!("Tile at {:?} is not a LaserSource", self.pos),
         }
     }
 }
@@ -105,13 +100,13 @@ impl PyLaserSource {
 
     #[setter]
     pub fn set_agent_id(&mut self, new_agent_id: usize) -> PyResult<()> {
-        let world = self.world.lock().unwrap();
+        let mut world = self.world.lock().unwrap();
         if new_agent_id >= world.n_agents() {
             return Err(pyo3::exceptions::PyValueError::new_err(
                 "Agent ID is greater than the number of agents",
             ));
         }
-        if let Some(Tile::LaserSource(laser_source)) = world.at(&self.pos.into()) {
+        if let Some(mut laser_source) = world.source_at_mut(self.pos.into()) {
             laser_source.set_agent_id(new_agent_id as AgentId);
         } else {
             return Err(pyo3::exceptions::PyValueError::new_err(
@@ -1,11 +1,15 @@
 mod pyaction;
+mod pydistance_map;
 mod pyevent;
 mod pyposition;
 mod pyworld;
+mod pyworld_snapshot;
 mod pyworld_state;
 
 pub use pyaction::PyAction;
+pub use pydistance_map::PyDistanceMap;
 pub use pyevent::{PyEventType, PyWorldEvent};
 pub use pyposition::PyPosition;
 pub use pyworld::PyWorld;
+pub use pyworld_snapshot::PyWorldSnapshot;
 pub use pyworld_state::PyWorldState;
@@ -0,0 +1,61 @@
+use crate::{Position, bindings::world::PyPosition, core::DistanceMap};
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+/// Breadth-first distances over the tiles of a world, computed with `World.distance_map`.
+///
+/// An agent that reaches an exit can no longer move: exits are dead ends.
+/// ```python
+/// world = World("S0 . X")
+/// distances = world.distance_map()
+/// assert distances.distance((0, 0)) == 2
+/// assert distances.path((0, 0)) == [(0, 0), (0, 1), (0, 2)]
+/// ```
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "DistanceMap",
+    module = "lle.world",
+    frozen,
+    skip_from_py_object
+)]
+pub struct PyDistanceMap {
+    map: DistanceMap,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyDistanceMap {
+    /// The distance of `position`, or `None` if it can not be reached or is out of the world.
+    fn distance(&self, position: PyPosition) -> Option<usize> {
+        self.map.distance(&position.into())
+    }
+
+    /// A shortest path between the origin and `position`, in walking order and with both ends
+    /// included: from the origin to `position`, or from `position` to its nearest exit when the
+    /// distances are measured to the exits.
+    ///
+    /// Returns `None` if `position` can not be reached.
+    fn path(&self, position: PyPosition) -> Option<Vec<PyPosition>> {
+        self.map
+            .path(&position.into())
+            .map(|path| path.into_iter().map(PyPosition::from).collect())
+    }
+
+    /// The distance of every tile, row by row, with `None` for the tiles that can not be reached.
+    #[getter]
+    fn distances(&self) -> Vec<Vec<Option<usize>>> {
+        (0..self.map.height())
+            .map(|i| {
+                (0..self.map.width())
+                    .map(|j| self.map.distance(&Position { i, j }))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl From<DistanceMap> for PyDistanceMap {
+    fn from(map: DistanceMap) -> Self {
+        Self { map }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{AgentId, WorldEvent};
+use crate::{AgentId, WorldEvent, tiles::LaserId};
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum, gen_stub_pymethods};
 
@@ -13,6 +13,14 @@ pub enum PyEventType {
     GemCollected,
     #[pyo3(name = "AGENT_DIED")]
     AgentDied,
+    #[pyo3(name = "AGENT_SLIPPED")]
+    AgentSlipped,
+    #[pyo3(name = "HELP")]
+    Help,
+    #[pyo3(name = "LASER_BLOCKED")]
+    LaserBlocked,
+    #[pyo3(name = "LASER_UNBLOCKED")]
+    LaserUnblocked,
 }
 
 #[gen_stub_pymethods]
@@ -27,6 +35,10 @@ impl PyEventType {
             PyEventType::AgentExit => 0,
             PyEventType::GemCollected => 1,
             PyEventType::AgentDied => 2,
+            PyEventType::AgentSlipped => 3,
+            PyEventType::Help => 4,
+            PyEventType::LaserBlocked => 5,
+            PyEventType::LaserUnblocked => 6,
         }
     }
 }
@@ -38,22 +50,45 @@ pub struct PyWorldEvent {
     #[pyo3(get)]
     event_type: PyEventType,
     // pos: Position,
+    /// The agent concerned by the event, which is the helper for `HELP` events and the agent that
+    /// blocks the laser for `LASER_BLOCKED` and `LASER_UNBLOCKED` events.
     #[pyo3(get)]
     agent_id: AgentId,
+    /// The agent that is helped, for `HELP` events.
+    #[pyo3(get)]
+    beneficiary: Option<AgentId>,
+    /// The laser concerned by `HELP`, `LASER_BLOCKED` and `LASER_UNBLOCKED` events.
+    #[pyo3(get)]
+    laser_id: Option<LaserId>,
 }
 
 #[gen_stub_pymethods]
 #[pymethods]
 impl PyWorldEvent {
     #[new]
-    pub fn new(event_type: PyEventType, agent_id: AgentId) -> Self {
+    #[pyo3(signature = (event_type, agent_id, beneficiary=None, laser_id=None))]
+    pub fn new(
+        event_type: PyEventType,
+        agent_id: AgentId,
+        beneficiary: Option<AgentId>,
+        laser_id: Option<LaserId>,
+    ) -> Self {
         Self {
             event_type,
             agent_id,
+            beneficiary,
+            laser_id,
         }
     }
     fn __str__(&self) -> String {
-        format!("{:?}, agent id: {}", self.event_type, self.agent_id)
+        let mut string = format!("{:?}, agent id: {}", self.event_type, self.agent_id);
+        if let Some(beneficiary) = self.beneficiary {
+            string += &format!(", beneficiary: {beneficiary}");
+        }
+        if let Some(laser_id) = self.laser_id {
+            string += &format!(", laser id: {laser_id}");
+        }
+        string
     }
 
     fn __repr__(&self) -> String {
@@ -67,10 +102,36 @@ impl From<&WorldEvent> for PyWorldEvent {
             WorldEvent::AgentExit { agent_id } => (PyEventType::AgentExit, agent_id),
             WorldEvent::GemCollected { agent_id } => (PyEventType::GemCollected, agent_id),
             WorldEvent::AgentDied { agent_id } => (PyEventType::AgentDied, agent_id),
+            WorldEvent::AgentSlipped { agent_id } => (PyEventType::AgentSlipped, agent_id),
+            WorldEvent::Help {
+                helper,
+                beneficiary,
+                laser_id,
+            } => {
+                return PyWorldEvent::new(
+                    PyEventType::Help,
+                    *helper,
+                    Some(*beneficiary),
+                    Some(*laser_id),
+                );
+            }
+            WorldEvent::LaserBlocked { agent_id, laser_id } => {
+                return PyWorldEvent::new(
+                    PyEventType::LaserBlocked,
+                    *agent_id,
+                    None,
+                    Some(*laser_id),
+                );
+            }
+            WorldEvent::LaserUnblocked { agent_id, laser_id } => {
+                return PyWorldEvent::new(
+                    PyEventType::LaserUnblocked,
+                    *agent_id,
+                    None,
+                    Some(*laser_id),
+                );
+            }
         };
-        PyWorldEvent {
-            agent_id: *agent_id,
-            event_type,
-        }
+        PyWorldEvent::new(event_type, *agent_id, None, None)
     }
 }
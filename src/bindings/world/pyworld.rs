@@ -1,5 +1,6 @@
 use std::{
     fs,
+    str::FromStr,
     sync::{Arc, Mutex},
 };
 
@@ -14,11 +15,16 @@ use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 
 use crate::bindings::{
     pyagent::PyAgent,
-    pyexceptions::{parse_error_to_exception, runtime_error_to_pyexception},
+    pyexceptions::{
+        parse_error_to_exception, parse_error_to_exception_with_source,
+        runtime_error_to_pyexception,
+    },
     tiles::{PyGem, PyLaser, PyLaserSource},
-    world::{PyAction, PyPosition, PyWorldEvent, PyWorldState},
+    world::{PyAction, PyDistanceMap, PyPosition, PyWorldEvent, PyWorldSnapshot, PyWorldState},
+};
+use crate::{
+    Action, AgentId, ConflictRule, DistanceOptions, DistanceOrigin, Renderer, Tile, World,
 };
-use crate::{Action, AgentId, Renderer, Tile, World};
 
 // Implementation notes:
 // - The `PyWorld` struct is a wrapper around the `World` struct.
@@ -66,21 +72,10 @@ pub struct PyWorld {
     /// The number of agents in the world.
     #[pyo3(get)]
     n_agents: usize,
-    // `World` is `!Send + !Sync`, so the type system cannot prove `Arc<Mutex<World>>` is
-    // `Send + Sync`, which triggers `clippy::arc_with_non_send_sync`. The `Arc` is nonetheless
-    // correct: `Mutex` provides exclusive access to `World`, and `Arc`'s atomic ref-count is
-    // required for safe concurrent clone/drop in free-threaded Python. Thread safety for
-    // `PyWorld` itself is asserted manually below.
     world: Arc<Mutex<World>>,
     renderer: Renderer,
 }
 
-/// The `PyWorld` struct is thread-safe because:
-///  - the `World` struct is wrapped in an `Arc<Mutex<_>>`
-///  - the other fields are immutable
-unsafe impl Send for PyWorld {}
-unsafe impl Sync for PyWorld {}
-
 impl From<World> for PyWorld {
     fn from(world: World) -> Self {
         let renderer = Renderer::new(&world);
@@ -147,9 +142,9 @@ impl PyWorld {
     #[new]
     #[gen_stub(skip)]
     pub fn new(map_str: String) -> PyResult<Self> {
-        match World::try_from(map_str) {
+        match World::try_from(map_str.as_str()) {
             Ok(world) => Ok(PyWorld::from(world)),
-            Err(e) => Err(parse_error_to_exception(e)),
+            Err(e) => Err(parse_error_to_exception_with_source(e, Some(&map_str))),
         }
     }
 
@@ -174,7 +169,10 @@ impl PyWorld {
         let filename = filename.to_string_lossy();
         let world = match World::from_file(&filename) {
             Ok(world) => world,
-            Err(e) => return Err(parse_error_to_exception(e)),
+            Err(e) => {
+                let source = fs::read_to_string(&*filename).ok();
+                return Err(parse_error_to_exception_with_source(e, source.as_deref()));
+            }
         };
         Ok(PyWorld::from(world))
     }
@@ -210,13 +208,84 @@ impl PyWorld {
     }
 
     /// Compute the world configuration string from the `World`.
-    /// If possible, the string is returned in "plain text" format.
-    /// Otherwise, it is returned in toml format.
+    /// If possible, the string is returned in "plain text" format (text v1, or text v2 when the
+    /// level has metadata or several start positions per agent).
+    /// Otherwise, it is returned in a compact toml format.
     #[getter]
     fn world_string(&self) -> String {
         self.world.lock().unwrap().world_string()
     }
 
+    /// How `step` resolves agents that get in each other's way: `no-following` (default),
+    /// `allow-following` or `priority`.
+    ///
+    /// Setting the rule recomputes the available actions of the agents. The rule is not part of
+    /// the level: a world loaded from a world string or a TOML file always has the default rule,
+    /// and `world_string` does not record it.
+    #[getter]
+    #[gen_stub(override_return_type(
+        type_repr = "typing.Literal['no-following', 'allow-following', 'priority']",
+        imports = ("typing",)
+    ))]
+    fn conflict_rule(&self) -> &'static str {
+        self.world.lock().unwrap().conflict_rule().canonical()
+    }
+
+    #[setter]
+    fn set_conflict_rule(
+        &mut self,
+        #[gen_stub(override_type(
+            type_repr = "typing.Literal['no-following', 'allow-following', 'priority']",
+            imports = ("typing",)
+        ))]
+        rule: &str,
+    ) -> PyResult<()> {
+        let rule = ConflictRule::from_str(rule).map_err(PyValueError::new_err)?;
+        self.world.lock().unwrap().set_conflict_rule(rule);
+        Ok(())
+    }
+
+    /// The probability that the action of an agent slips at each step and is replaced by one of
+    /// its available actions (possibly `STAY` or the chosen action itself), drawn uniformly. A
+    /// slip that changes the action is reported by an `AGENT_SLIPPED` event.
+    /// The default, 0, keeps the world deterministic.
+    #[getter]
+    fn slip_probability(&self) -> f64 {
+        self.world.lock().unwrap().slip_probability()
+    }
+
+    #[setter]
+    fn set_slip_probability(&mut self, probability: f64) -> PyResult<()> {
+        self.world
+            .lock()
+            .unwrap()
+            .set_slip_probability(probability)
+            .map_err(runtime_error_to_pyexception)
+    }
+
+    /// Whether `step` also reports, after the other events, the lasers that agents start or stop to
+    /// block (`LASER_BLOCKED` and `LASER_UNBLOCKED`) and then the help in the state it reaches
+    /// (`HELP`): an alive agent standing on an enabled beam of the colour of another agent, who
+    /// must therefore block it. Disabled by default.
+    ///
+    /// ```python
+    /// world = World("L0E . .\n. S0 S1\nX X .")
+    /// world.help_events = True
+    /// world.reset()
+    /// events = world.step([Action.NORTH, Action.NORTH])
+    /// assert [e.event_type for e in events] == [EventType.LASER_BLOCKED, EventType.HELP]
+    /// assert (events[1].agent_id, events[1].beneficiary) == (0, 1)
+    /// ```
+    #[getter]
+    fn help_events(&self) -> bool {
+        self.world.lock().unwrap().help_events()
+    }
+
+    #[setter]
+    fn set_help_events(&mut self, enabled: bool) {
+        self.world.lock().unwrap().set_help_events(enabled);
+    }
+
     /// The dimensions (in pixels) of the image redered (width, height)
     #[getter]
     pub fn image_dimensions(&self) -> (u32, u32) {
@@ -344,7 +413,7 @@ impl PyWorld {
         world
             .lasers()
             .iter()
-            .map(|(pos, laser)| PyLaser::new(laser, *pos, arc_world.clone()))
+            .map(|(pos, laser)| PyLaser::new(laser, world.beams(), *pos, arc_world.clone()))
             .collect()
     }
 
@@ -377,15 +446,12 @@ impl PyWorld {
     /// ```
     fn source_at(&self, position: PyPosition) -> PyResult<PyLaserSource> {
         let world = self.world.lock().unwrap();
-        let tile = match world.at(&position.into()) {
-            Some(source) => source,
-            None => return Err(PyIndexError::new_err("Position out of bounds")),
-        };
-        match tile {
-            Tile::LaserSource(source) => {
-                Ok(PyLaserSource::new(self.world.clone(), position, source))
-            }
-            _ => Err(PyValueError::new_err(format!(
+        if world.at(&position.into()).is_none() {
+            return Err(PyIndexError::new_err("Position out of bounds"));
+        }
+        match world.source_at(position.into()) {
+            Some(source) => Ok(PyLaserSource::new(self.world.clone(), position, source)),
+            None => Err(PyValueError::new_err(format!(
                 "Tile at position {position:?} is not a laser source"
             ))),
         }
@@ -398,6 +464,16 @@ impl PyWorld {
         world.starts().iter().map(|p| (*p).into()).collect()
     }
 
+    /// Every start configuration that `reset` can sample: one of the `random_start_pos` of each
+    /// agent, with no two agents on the same position.
+    fn start_assignments(&self) -> Vec<Vec<PyPosition>> {
+        let world = self.world.lock().unwrap();
+        world
+            .start_assignments()
+            .map(|starts| starts.into_iter().map(PyPosition::from).collect())
+            .collect()
+    }
+
     fn seed(&self, seed_value: u64) {
         self.world.lock().unwrap().seed(seed_value);
     }
@@ -444,8 +520,45 @@ impl PyWorld {
             Err(e) => Err(runtime_error_to_pyexception(e)),
         }
     }
+    /// The distribution P(s' | s, a) of the states reached when the agents take the given actions
+    /// from the current state s, accounting for the slip probability.
+    ///
+    /// Returns:
+    ///     A list of (state, probability) pairs in which each reachable state appears once.
+    ///     The world is left in its current state.
+    ///
+    /// Raises:
+    ///     `InvalidActionError` if an agent takes an action that is not available.
+    ///     `ValueError` if the number of actions is different from the number of agents
+    ///
+    /// Example:
+    /// ```python
+    /// world = World("S0 . X")
+    /// world.slip_probability = 0.2
+    /// transitions = world.transitions([Action.EAST])
+    /// assert len(transitions) == 2
+    /// assert abs(sum(p for _, p in transitions) - 1.0) < 1e-9
+    /// ```
+    pub fn transitions(
+        &mut self,
+        py: Python,
+        #[gen_stub(override_type(type_repr = "Action | typing.Sequence[Action]"))] action: Py<
+            PyAny,
+        >,
+    ) -> PyResult<Vec<(PyWorldState, f64)>> {
+        let actions = Self::extract_actions(py, &action)?;
+        match self.world.lock().unwrap().transitions(&actions) {
+            Ok(distribution) => Ok(distribution
+                .into_iter()
+                .map(|(state, p)| (state.into(), p))
+                .collect()),
+            Err(e) => Err(runtime_error_to_pyexception(e)),
+        }
+    }
+
     /// Reset the world to its original state.
     /// This should be done directly after creating the world.
+    /// This also clears the history of `push_snapshot()`.
     pub fn reset(&mut self) {
         self.world.lock().unwrap().reset();
     }
@@ -523,7 +636,8 @@ impl PyWorld {
         PyArray1::from_vec(py, buffer).reshape(dims).unwrap()
     }
 
-    /// Force the world to a given state
+    /// Force the world to a given state. If it succeeds, this also clears the history of
+    /// `push_snapshot()`.
     /// Args:
     ///     state: The state to set the world to.
     /// Returns:
@@ -543,6 +657,120 @@ impl PyWorld {
         state.into()
     }
 
+    /// Take a snapshot of the full state of the world, see `WorldSnapshot`.
+    fn snapshot(&self) -> PyWorldSnapshot {
+        self.world.lock().unwrap().snapshot().into()
+    }
+
+    /// Put the world back in the state of a snapshot taken with `snapshot()` on this world.
+    ///
+    /// Raises:
+    ///     `ValueError` if the snapshot was taken in a world with a different layout.
+    fn restore(&mut self, snapshot: &PyWorldSnapshot) -> PyResult<()> {
+        self.world
+            .lock()
+            .unwrap()
+            .restore(&snapshot.snapshot)
+            .map_err(runtime_error_to_pyexception)
+    }
+
+    /// Breadth-first distances in the current state of the world, from `origin` to every tile, or
+    /// from every tile to the nearest exit if `origin` is `None`. Walls and laser sources are never
+    /// walked on and, unless `ignore_voids` is set, neither are voids.
+    ///
+    /// Args:
+    ///     origin: The position to measure the distances from. If it can not be walked on, it only
+    ///         reaches itself, at distance 0.
+    ///     lasers_as_walls: The colours of the beams to walk around, where they are currently on.
+    ///     ignore_voids: Whether to walk on voids as on floor tiles.
+    ///     agents_as_obstacles: Whether to walk around the alive agents, except at `origin`.
+    ///
+    /// Raises:
+    ///     `IndexError`: if `origin` is out of bounds.
+    ///
+    /// Example:
+    /// ```python
+    /// world = World("S0 . .\nL1E . .\nX X S1")
+    /// world.reset()
+    /// assert world.distance_map((0, 0)).distance((2, 1)) == 3
+    /// assert world.distance_map((0, 0), lasers_as_walls=[1]).distance((2, 1)) is None
+    /// ```
+    #[pyo3(signature = (
+        origin=None,
+        *,
+        lasers_as_walls=vec![],
+        ignore_voids=false,
+        agents_as_obstacles=false
+    ))]
+    fn distance_map(
+        &self,
+        origin: Option<PyPosition>,
+        lasers_as_walls: Vec<AgentId>,
+        ignore_voids: bool,
+        agents_as_obstacles: bool,
+    ) -> PyResult<PyDistanceMap> {
+        let mut options = DistanceOptions::default();
+        for colour in lasers_as_walls {
+            options = options.with_lasers_as_walls(colour);
+        }
+        if ignore_voids {
+            options = options.with_ignore_voids();
+        }
+        if agents_as_obstacles {
+            options = options.with_agents_as_obstacles();
+        }
+        let origin = match origin {
+            Some(pos) => DistanceOrigin::Position(pos.into()),
+            None => DistanceOrigin::Exits,
+        };
+        self.world
+            .lock()
+            .unwrap()
+            .distance_map(origin, &options)
+            .map(PyDistanceMap::from)
+            .map_err(runtime_error_to_pyexception)
+    }
+
+    /// Record a snapshot of the world that the next `undo()` goes back to, e.g. before a step.
+    /// This discards the states that could be redone.
+    ///
+    /// Example:
+    /// ```python
+    /// world = World("S0 G X")
+    /// world.push_snapshot()
+    /// world.step([Action.EAST])
+    /// assert world.undo()
+    /// assert world.agents_positions == [(0, 0)]
+    /// assert world.redo()
+    /// assert world.agents_positions == [(0, 1)]
+    /// ```
+    fn push_snapshot(&mut self) {
+        self.world.lock().unwrap().push_snapshot();
+    }
+
+    /// Go back to the last snapshot recorded by `push_snapshot()` and remove it from the history.
+    /// The current state can then be restored with `redo()`.
+    ///
+    /// Returns:
+    ///     Whether there was a snapshot to go back to. If not, the world is left untouched.
+    fn undo(&mut self) -> bool {
+        self.world.lock().unwrap().undo()
+    }
+
+    /// Go back to the state left by the last `undo()`.
+    ///
+    /// Returns:
+    ///     Whether there was a state to redo. If not, the world is left untouched.
+    fn redo(&mut self) -> bool {
+        self.world.lock().unwrap().redo()
+    }
+
+    /// The number of snapshots that `undo()` can go back to.
+    #[getter]
+    fn history_len(&self) -> usize {
+        self.world.lock().unwrap().history_len()
+    }
+
     /// Returns a deep copy of the object.
     ///
     /// Example:
@@ -566,18 +794,58 @@ impl PyWorld {
     }
 
     /// Enable serialisation with pickle
-    pub fn __getstate__(&self) -> PyResult<(String, PyWorldState)> {
+    pub fn __getstate__(&self) -> PyResult<(String, PyWorldState, String, f64, bool)> {
         let world = self.world.lock().unwrap();
         let state: PyWorldState = world.get_state().into();
         let world_string = world.world_string();
-        Ok((world_string, state))
+        let conflict_rule = world.conflict_rule().canonical().to_string();
+        Ok((
+            world_string,
+            state,
+            conflict_rule,
+            world.slip_probability(),
+            world.help_events(),
+        ))
     }
 
-    /// Enable deserialisation with pickle
-    pub fn __setstate__(&mut self, state: (String, PyWorldState)) -> PyResult<()> {
-        let world = match World::try_from(state.0) {
+    /// Enable deserialisation with pickle.
+    ///
+    /// Pickles made before the runtime settings were recorded only hold the world string and the
+    /// state: the world then gets the default settings.
+    pub fn __setstate__(
+        &mut self,
+        #[gen_stub(override_type(
+            type_repr = "tuple[builtins.str, WorldState, builtins.str, builtins.float, builtins.bool] | tuple[builtins.str, WorldState]",
+            imports = ("builtins",)
+        ))]
+        state: &Bound<PyTuple>,
+    ) -> PyResult<()> {
+        let (world_string, world_state, settings) = match state.len() {
+            2 => (state.get_item(0)?.extract()?, state.get_item(1)?, None),
+            _ => {
+                let (world_string, world_state, conflict_rule, slip_probability, help_events): (
+                    String,
+                    Bound<PyAny>,
+                    String,
+                    f64,
+                    bool,
+                ) = state.extract()?;
+                let settings = (conflict_rule, slip_probability, help_events);
+                (world_string, world_state, Some(settings))
+            }
+        };
+        let world_state: PyWorldState = world_state.extract()?;
+        let world = match World::try_from(world_string) {
             Ok(mut w) => {
-                w.set_state(&state.1.into()).unwrap();
+                if let Some((conflict_rule, slip_probability, help_events)) = settings {
+                    let rule =
+                        ConflictRule::from_str(&conflict_rule).map_err(PyValueError::new_err)?;
+                    w.set_conflict_rule(rule);
+                    w.set_slip_probability(slip_probability)
+                        .map_err(runtime_error_to_pyexception)?;
+                    w.set_help_events(help_events);
+                }
+                w.set_state(&world_state.into()).unwrap();
                 w
             }
             Err(e) => panic!("Could not parse the world: {:?}", e),
@@ -644,7 +912,6 @@ impl Clone for PyWorld {
     }
 }
 
-#[allow(clippy::arc_with_non_send_sync)] // see note on World: !Send + free-threaded refcount
 fn wrap_world(world: World) -> Arc<Mutex<World>> {
     Arc::new(Mutex::new(world))
 }
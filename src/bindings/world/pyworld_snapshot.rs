@@ -0,0 +1,43 @@
+use crate::{bindings::PyWorldState, core::WorldSnapshot};
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+/// A snapshot of the full state of a `World` (agents, gems, laser beams and available actions),
+/// taken with `World.snapshot()` and put back with `World.restore(snapshot)`.
+///
+/// Restoring a snapshot is much faster than `World.set_state`, which makes it suited to tree
+/// search. The conflict rule, the slip probability and the random generator of the world are
+/// not part of the snapshot.
+/// ```python
+/// world = World("S0 G X")
+/// snapshot = world.snapshot()
+/// world.step([Action.EAST])
+/// world.restore(snapshot)
+/// assert world.get_state() == snapshot.state
+/// ```
+#[gen_stub_pyclass]
+#[pyclass(
+    name = "WorldSnapshot",
+    module = "lle.world",
+    frozen,
+    skip_from_py_object
+)]
+pub struct PyWorldSnapshot {
+    pub(crate) snapshot: WorldSnapshot,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PyWorldSnapshot {
+    /// The state of the world when the snapshot was taken.
+    #[getter]
+    fn state(&self) -> PyWorldState {
+        self.snapshot.state().into()
+    }
+}
+
+impl From<WorldSnapshot> for PyWorldSnapshot {
+    fn from(snapshot: WorldSnapshot) -> Self {
+        Self { snapshot }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{AgentId, Position, PositionSet};
+
+/// One [`PositionSet`] per kind of tile and per dynamic property of a [`World`](crate::World),
+/// which the world keeps in sync with its tiles. They turn the checks of `step` and of the
+/// available actions into bit tests, and let callers combine them with word-at-a-time set
+/// operations.
+#[derive(Clone, Debug)]
+pub struct Bitboards {
+    walls: PositionSet,
+    sources: PositionSet,
+    voids: PositionSet,
+    exits: PositionSet,
+    /// The tiles crossed by a beam, whether it is on or not.
+    lasers: PositionSet,
+    /// The gems that are not collected yet.
+    gems: PositionSet,
+    /// The tiles that hold an agent.
+    occupied: PositionSet,
+    /// The tiles where a beam is on, indexed by the colour (agent id) of the beam.
+    beams: Vec<PositionSet>,
+}
+
+impl Bitboards {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        height: usize,
+        width: usize,
+        walls: &[Position],
+        sources: &[Position],
+        voids: &[Position],
+        exits: &[Position],
+        lasers: &[Position],
+        gems: &[Position],
+    ) -> Self {
+        let set = |positions: &[Position]| {
+            PositionSet::from_positions(height, width, positions.iter().copied())
+        };
+        Self {
+            walls: set(walls),
+            sources: set(sources),
+            voids: set(voids),
+            exits: set(exits),
+            lasers: set(lasers),
+            gems: set(gems),
+            occupied: PositionSet::empty(height, width),
+            beams: vec![],
+        }
+    }
+
+    /// The walls, as in [`World::walls`](crate::World::walls).
+    pub fn walls(&self) -> &PositionSet {
+        &self.walls
+    }
+
+    pub fn sources(&self) -> &PositionSet {
+        &self.sources
+    }
+
+    pub fn voids(&self) -> &PositionSet {
+        &self.voids
+    }
+
+    pub fn exits(&self) -> &PositionSet {
+        &self.exits
+    }
+
+    /// The tiles crossed by a beam, whether it is on or not.
+    pub fn lasers(&self) -> &PositionSet {
+        &self.lasers
+    }
+
+    /// The gems that are not collected yet.
+    pub fn gems(&self) -> &PositionSet {
+        &self.gems
+    }
+
+    /// The tiles that hold an agent. An agent killed by a laser does not hold its tile.
+    pub fn occupied(&self) -> &PositionSet {
+        &self.occupied
+    }
+
+    /// The tiles where a beam of colour `agent_id` is on, if there is a laser of that colour.
+    pub fn beam_coverage(&self, agent_id: AgentId) -> Option<&PositionSet> {
+        self.beams.get(agent_id)
+    }
+
+    /// Whether agents can walk on `pos`, which must be in the world.
+    pub(crate) fn is_walkable(&self, pos: &Position) -> bool {
+        !self.walls.contains(pos) && !self.sources.contains(pos)
+    }
+
+    pub(crate) fn set_exits(&mut self, exits: &[Position]) {
+        self.exits.clear();
+        for pos in exits {
+            self.exits.insert(*pos);
+        }
+    }
+
+    pub(crate) fn gems_mut(&mut self) -> &mut PositionSet {
+        &mut self.gems
+    }
+
+    pub(crate) fn occupied_mut(&mut self) -> &mut PositionSet {
+        &mut self.occupied
+    }
+
+    /// The beam coverage of every colour, indexed by colour.
+    pub(crate) fn beams(&self) -> &[PositionSet] {
+        &self.beams
+    }
+
+    pub(crate) fn beams_mut(&mut self) -> &mut Vec<PositionSet> {
+        &mut self.beams
+    }
+
+    /// Clear the beam coverage of every colour.
+    pub(crate) fn clear_beams(&mut self) {
+        for coverage in &mut self.beams {
+            coverage.clear();
+        }
+    }
+
+    /// The beam coverage of colour `agent_id`, making room for that colour if needed.
+    pub(crate) fn beam_coverage_mut(&mut self, agent_id: AgentId) -> &mut PositionSet {
+        if self.beams.len() <= agent_id {
+            let mut empty = self.occupied.clone();
+            empty.clear();
+            self.beams.resize(agent_id + 1, empty);
+        }
+        &mut self.beams[agent_id]
+    }
+}
@@ -0,0 +1,58 @@
+/// How [`World::step`](crate::World::step) resolves agents that get in each other's way.
+///
+/// Whatever the rule, two agents never end a step on the same tile: an agent whose move conflicts
+/// with another one bounces back to where it was, which may in turn bounce the agents moving into
+/// that tile. The SAT encoding of the solver follows the rule of the world it is built from.
+///
+/// The rule is a runtime setting of the world, like its slip probability: it is not part of the
+/// level, so world strings and TOML files always load with the default rule, and
+/// [`World::world_string`](crate::World::world_string) does not record it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ConflictRule {
+    /// An agent cannot move into a tile that is occupied at the start of the step, even if its
+    /// occupant leaves it. Agents that move into the same tile all bounce back.
+    #[default]
+    NoFollowing,
+    /// An agent may follow another one into the tile it leaves, but two agents cannot swap their
+    /// tiles. Agents that move into the same tile all bounce back.
+    AllowFollowing,
+    /// As [`ConflictRule::NoFollowing`], except that when agents move into the same tile, the one
+    /// with the lowest id moves and the others bounce back.
+    Priority,
+}
+
+impl ConflictRule {
+    /// Whether an agent may move into a tile that another agent leaves in the same step.
+    pub fn allows_following(&self) -> bool {
+        *self == ConflictRule::AllowFollowing
+    }
+
+    /// The canonical string representation, inverse of `ConflictRule::from_str`: `no-following`,
+    /// `allow-following` or `priority`.
+    pub fn canonical(&self) -> &'static str {
+        match self {
+            ConflictRule::NoFollowing => "no-following",
+            ConflictRule::AllowFollowing => "allow-following",
+            ConflictRule::Priority => "priority",
+        }
+    }
+}
+
+impl std::str::FromStr for ConflictRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        [
+            ConflictRule::NoFollowing,
+            ConflictRule::AllowFollowing,
+            ConflictRule::Priority,
+        ]
+        .into_iter()
+        .find(|rule| rule.canonical() == s)
+        .ok_or_else(|| {
+            format!(
+                "Unknown conflict rule '{s}'. Expected no-following, allow-following or priority."
+            )
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+
+use crate::{AgentId, Position, PositionSet, tiles::Direction};
+
+use strum::IntoEnumIterator;
+
+/// Where the distances of a [`DistanceMap`] are measured from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistanceOrigin {
+    /// The number of steps to walk from this position to each tile.
+    Position(Position),
+    /// The number of steps to walk from each tile to the nearest exit.
+    Exits,
+}
+
+impl From<Position> for DistanceOrigin {
+    fn from(pos: Position) -> Self {
+        Self::Position(pos)
+    }
+}
+
+/// Which tiles a [`DistanceMap`] walks around, besides the walls and the laser sources.
+///
+/// By default, voids are avoided and lasers and agents are walked through.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DistanceOptions {
+    lasers_as_walls: Vec<AgentId>,
+    ignore_voids: bool,
+    agents_as_obstacles: bool,
+}
+
+impl DistanceOptions {
+    /// Avoid the tiles where a beam of colour `colour` is currently on. Call it once per colour
+    /// to avoid, e.g. with every colour but the one of the agent that walks.
+    pub fn with_lasers_as_walls(mut self, colour: AgentId) -> Self {
+        if !self.lasers_as_walls.contains(&colour) {
+            self.lasers_as_walls.push(colour);
+        }
+        self
+    }
+
+    /// Walk on voids as on floor tiles.
+    pub fn with_ignore_voids(mut self) -> Self {
+        self.ignore_voids = true;
+        self
+    }
+
+    /// Avoid the tiles that currently hold an alive agent, except the origin.
+    pub fn with_agents_as_obstacles(mut self) -> Self {
+        self.agents_as_obstacles = true;
+        self
+    }
+
+    pub fn lasers_as_walls(&self) -> &[AgentId] {
+        &self.lasers_as_walls
+    }
+
+    pub fn ignore_voids(&self) -> bool {
+        self.ignore_voids
+    }
+
+    pub fn agents_as_obstacles(&self) -> bool {
+        self.agents_as_obstacles
+    }
+}
+
+/// Breadth-first distances over the tiles of a world, computed with
+/// [`World::distance_map`](crate::World::distance_map).
+///
+/// As in the solver, an agent that reaches an exit can no longer move: exits are dead ends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistanceMap {
+    origin: DistanceOrigin,
+    height: usize,
+    width: usize,
+    /// Row-major, `None` for the tiles that can not be reached.
+    distances: Vec<Option<usize>>,
+    /// Row-major, the next tile on a shortest path towards the origin.
+    towards_origin: Vec<Option<Position>>,
+}
+
+impl DistanceMap {
+    /// Run the breadth-first search on a world of `height` x `width` tiles where `blocked` holds
+    /// the tiles that can not be walked on. A blocked origin position only reaches itself.
+    pub(crate) fn compute(
+        origin: DistanceOrigin,
+        blocked: &PositionSet,
+        exits: &PositionSet,
+        height: usize,
+        width: usize,
+    ) -> Self {
+        let mut map = Self {
+            origin,
+            height,
+            width,
+            distances: vec![None; height * width],
+            towards_origin: vec![None; height * width],
+        };
+        let mut frontier = VecDeque::new();
+        match origin {
+            DistanceOrigin::Position(pos) => {
+                let index = map.index(&pos);
+                map.distances[index] = Some(0);
+                if !blocked.contains(&pos) {
+                    frontier.push_back(pos);
+                }
+            }
+            DistanceOrigin::Exits => {
+                for pos in exits.iter().filter(|pos| !blocked.contains(pos)) {
+                    let index = map.index(&pos);
+                    map.distances[index] = Some(0);
+                    frontier.push_back(pos);
+                }
+            }
+        }
+        while let Some(current) = frontier.pop_front() {
+            let distance = map.distances[map.index(&current)].unwrap_or_default() + 1;
+            // Walking forward, nothing leaves an exit. Walking backward from the exits, nothing
+            // comes from one.
+            if origin != DistanceOrigin::Exits && exits.contains(&current) {
+                continue;
+            }
+            for direction in Direction::iter() {
+                let Ok(next) = current + direction else {
+                    continue;
+                };
+                if next.i >= height || next.j >= width || blocked.contains(&next) {
+                    continue;
+                }
+                if origin == DistanceOrigin::Exits && exits.contains(&next) {
+                    continue;
+                }
+                let index = map.index(&next);
+                if map.distances[index].is_none() {
+                    map.distances[index] = Some(distance);
+                    map.towards_origin[index] = Some(current);
+                    frontier.push_back(next);
+                }
+            }
+        }
+        map
+    }
+
+    fn index(&self, pos: &Position) -> usize {
+        pos.i * self.width + pos.j
+    }
+
+    pub fn origin(&self) -> DistanceOrigin {
+        self.origin
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The distance of `pos`, or `None` if it can not be reached or is out of the world.
+    pub fn distance(&self, pos: &Position) -> Option<usize> {
+        if pos.i >= self.height || pos.j >= self.width {
+            return None;
+        }
+        self.distances[self.index(pos)]
+    }
+
+    /// Every reachable tile with its distance, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, usize)> + '_ {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, distance)| {
+                let pos = Position {
+                    i: index / self.width,
+                    j: index % self.width,
+                };
+                distance.map(|d| (pos, d))
+            })
+    }
+
+    /// A shortest path between the origin and `pos`, in walking order and with both ends
+    /// included: from the origin to `pos` for a [`DistanceOrigin::Position`], and from `pos` to
+    /// its nearest exit for [`DistanceOrigin::Exits`].
+    ///
+    /// Returns `None` if `pos` can not be reached.
+    pub fn path(&self, pos: &Position) -> Option<Vec<Position>> {
+        self.distance(pos)?;
+        let mut path = vec![*pos];
+        let mut current = *pos;
+        while let Some(next) = self.towards_origin[self.index(&current)] {
+            path.push(next);
+            current = next;
+        }
+        if let DistanceOrigin::Position(_) = self.origin {
+            path.reverse();
+        }
+        Some(path)
+    }
+}
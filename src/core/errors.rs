@@ -41,6 +41,15 @@ pub enum RuntimeWorldError {
         di: i32,
         dj: i32,
     },
+    InvalidSlipProbability {
+        probability: f64,
+    },
+    InvalidSnapshot {
+        reason: String,
+    },
+    NotAGem {
+        position: Position,
+    },
     MutexPoisoned,
 }
 
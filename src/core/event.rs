@@ -1,8 +1,37 @@
-use crate::AgentId;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Debug)]
+use crate::{AgentId, tiles::LaserId};
+
+/// In JSON, the variant is given by the `type` field, e.g. `{"type": "AgentExit", "agent_id": 0}`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum WorldEvent {
-    AgentExit { agent_id: AgentId },
-    GemCollected { agent_id: AgentId },
-    AgentDied { agent_id: AgentId },
+    AgentExit {
+        agent_id: AgentId,
+    },
+    GemCollected {
+        agent_id: AgentId,
+    },
+    AgentDied {
+        agent_id: AgentId,
+    },
+    AgentSlipped {
+        agent_id: AgentId,
+    },
+    /// `helper` blocks the laser `laser_id` of its colour while `beneficiary` stands on its beam.
+    Help {
+        helper: AgentId,
+        beneficiary: AgentId,
+        laser_id: LaserId,
+    },
+    /// Agent `agent_id` started to block the laser `laser_id` of its colour.
+    LaserBlocked {
+        agent_id: AgentId,
+        laser_id: LaserId,
+    },
+    /// Agent `agent_id` stopped blocking the laser `laser_id` of its colour.
+    LaserUnblocked {
+        agent_id: AgentId,
+        laser_id: LaserId,
+    },
 }
@@ -1,13 +1,21 @@
+mod bitboards;
+mod conflict_rule;
+mod distance_map;
 mod errors;
 mod event;
 mod levels;
 pub mod parsing;
 pub mod tiles;
 mod world;
+mod world_snapshot;
 mod world_state;
 
+pub use bitboards::Bitboards;
+pub use conflict_rule::ConflictRule;
+pub use distance_map::{DistanceMap, DistanceOptions, DistanceOrigin};
 pub use errors::RuntimeWorldError;
 pub use event::WorldEvent;
-pub use parsing::ParseError;
-pub use world::World;
+pub use parsing::{LevelMetadata, ParseError, Span};
+pub use world::{SourceBeamMut, World};
+pub use world_snapshot::WorldSnapshot;
 pub use world_state::WorldState;
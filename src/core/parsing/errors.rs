@@ -2,14 +2,14 @@ use std::{error::Error, fmt::Display};
 
 use crate::{Position, agent::AgentId};
 
+use super::Span;
+
 #[derive(Debug)]
 pub enum ParseError {
     EmptyWorld,
     NoAgents,
     InvalidTile {
-        tile_str: String,
-        line: usize,
-        col: usize,
+        span: Span,
     },
     InvalidFileName {
         file_name: String,
@@ -31,12 +31,14 @@ pub enum ParseError {
         agent_id: AgentId,
         start1: Position,
         start2: Position,
+        span: Span,
     },
+    /// `span` covers the whole offending row.
     InconsistentDimensions {
-        row_str: String,
         expected_n_cols: usize,
         actual_n_cols: usize,
         row: usize,
+        span: Span,
     },
     InvalidLaserSourceAgentId {
         asked_id: AgentId,
@@ -44,42 +46,258 @@ pub enum ParseError {
     },
     InvalidAgentId {
         given_agent_id: String,
+        span: Option<Span>,
     },
     InvalidDirection {
         given: String,
         expected: String,
+        span: Option<Span>,
     },
     AgentWithoutStart {
         agent_id: AgentId,
     },
+    /// `span` covers the `width` of the TOML file.
     InconsistentWorldStringWidth {
         toml_width: usize,
         world_str_width: usize,
+        span: Option<Span>,
     },
+    /// `span` covers the `height` of the TOML file.
     InconsistentWorldStringHeight {
         toml_height: usize,
         world_str_height: usize,
+        span: Option<Span>,
     },
+    /// `span` covers the `n_agents` of the TOML file.
     InconsistentNumberOfAgents {
         toml_n_agents_field: usize,
         actual_n_agents: usize,
+        span: Option<Span>,
     },
+    /// `span` covers the first entry of the TOML file that contains the position.
     PositionOutOfBounds {
         i: usize,
         j: usize,
+        span: Option<Span>,
     },
     MissingWidth,
     MissingHeight,
     UnknownTomlKey {
         key: String,
         message: String,
+        span: Option<Span>,
     },
+    /// The content is not a TOML level, which is what `parse_v2` reads.
     NotV2,
+    InvalidJson {
+        message: String,
+        span: Option<Span>,
+    },
+    /// A text v2 level without the `---` line that separates the header from the grid.
+    MissingHeaderSeparator,
+    /// A header line that is not of the form `key: value`.
+    InvalidHeaderLine {
+        span: Span,
+    },
+    UnknownHeaderKey {
+        key: String,
+        span: Span,
+    },
+    DuplicateHeaderKey {
+        key: String,
+        span: Span,
+    },
+    InvalidHeaderValue {
+        key: String,
+        value: String,
+        span: Span,
+    },
+}
+
+impl ParseError {
+    /// The location of the error in the parsed text, if any. For a TOML file, the spans of the
+    /// errors in its `world_string` are in the file, not in the string.
+    ///
+    /// Errors that are not about a piece of the text have no span: the errors about the level as
+    /// a whole (e.g. `NoAgents`, `NotEnoughExitTiles` or `AgentWithoutStart`, which is about a
+    /// missing start tile), the errors that do not come from parsing a text (`InvalidFileName`,
+    /// `InvalidLevel`) and `InvalidLaserSourceAgentId`, which the parsers no longer raise since
+    /// lasers may have a colour without agent.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            ParseError::InvalidTile { span }
+            | ParseError::DuplicateStartTile { span, .. }
+            | ParseError::InconsistentDimensions { span, .. }
+            | ParseError::InvalidHeaderLine { span }
+            | ParseError::UnknownHeaderKey { span, .. }
+            | ParseError::DuplicateHeaderKey { span, .. }
+            | ParseError::InvalidHeaderValue { span, .. } => Some(span),
+            ParseError::InvalidAgentId { span, .. }
+            | ParseError::InvalidDirection { span, .. }
+            | ParseError::UnknownTomlKey { span, .. }
+            | ParseError::InvalidJson { span, .. }
+            | ParseError::InconsistentWorldStringWidth { span, .. }
+            | ParseError::InconsistentWorldStringHeight { span, .. }
+            | ParseError::InconsistentNumberOfAgents { span, .. }
+            | ParseError::PositionOutOfBounds { span, .. } => span.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn span_mut(&mut self) -> Option<&mut Span> {
+        match self {
+            ParseError::InvalidTile { span }
+            | ParseError::DuplicateStartTile { span, .. }
+            | ParseError::InconsistentDimensions { span, .. }
+            | ParseError::InvalidHeaderLine { span }
+            | ParseError::UnknownHeaderKey { span, .. }
+            | ParseError::DuplicateHeaderKey { span, .. }
+            | ParseError::InvalidHeaderValue { span, .. } => Some(span),
+            ParseError::InvalidAgentId { span, .. }
+            | ParseError::InvalidDirection { span, .. }
+            | ParseError::UnknownTomlKey { span, .. }
+            | ParseError::InvalidJson { span, .. }
+            | ParseError::InconsistentWorldStringWidth { span, .. }
+            | ParseError::InconsistentWorldStringHeight { span, .. }
+            | ParseError::InconsistentNumberOfAgents { span, .. }
+            | ParseError::PositionOutOfBounds { span, .. } => span.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Attach `span` to an error that was raised without knowing where it occurred.
+    pub fn with_span(mut self, new_span: Span) -> Self {
+        if let ParseError::InvalidAgentId { span, .. }
+        | ParseError::InvalidDirection { span, .. }
+        | ParseError::UnknownTomlKey { span, .. }
+        | ParseError::InconsistentWorldStringWidth { span, .. }
+        | ParseError::InconsistentWorldStringHeight { span, .. }
+        | ParseError::InconsistentNumberOfAgents { span, .. }
+        | ParseError::PositionOutOfBounds { span, .. } = &mut self
+        {
+            span.get_or_insert(new_span);
+        }
+        self
+    }
+
+    /// A human readable description of the error.
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::EmptyWorld => "Empty world: no tiles".into(),
+            ParseError::NoAgents => "No agents in the world".into(),
+            ParseError::InvalidTile { span } => format!("Invalid tile '{}'", span.token),
+            ParseError::InvalidFileName { file_name } => format!("File not found: {file_name}"),
+            ParseError::InvalidLevel { asked, min, max } => {
+                format!("Invalid level: {asked}. Expected a level between {min} and {max}.")
+            }
+            ParseError::NotEnoughExitTiles { n_starts, n_exits } => {
+                format!("Not enough exit tiles: {n_starts} starts, {n_exits} exits")
+            }
+            ParseError::NotEnoughStartTiles { n_starts, n_agents } => {
+                format!("Not enough start tiles: {n_starts} starts, {n_agents} agents")
+            }
+            ParseError::DuplicateStartTile {
+                agent_id,
+                start1,
+                start2,
+                ..
+            } => format!("Agent {agent_id} has two start tiles: {start1:?} and {start2:?}"),
+            ParseError::InconsistentDimensions {
+                expected_n_cols,
+                actual_n_cols,
+                row,
+                ..
+            } => format!(
+                "Inconsistent number of columns in world string. Row number {row} expected {expected_n_cols} tiles but got {actual_n_cols} tiles"
+            ),
+            ParseError::InvalidLaserSourceAgentId { asked_id, n_agents } => format!(
+                "Invalid laser source agent id: {asked_id}. There are only {n_agents} agents -> expected an id between 0 and {}.",
+                n_agents.saturating_sub(1)
+            ),
+            ParseError::InvalidAgentId { given_agent_id, .. } => {
+                format!("Can not parse agent id: '{given_agent_id}'. Expected an integer >= 0.")
+            }
+            ParseError::InvalidDirection {
+                given, expected, ..
+            } => format!("Invalid direction: '{given}'. Expected one of {expected}"),
+            ParseError::AgentWithoutStart { agent_id } => {
+                format!("Agent {agent_id} has no start tile")
+            }
+            ParseError::InconsistentWorldStringWidth {
+                toml_width,
+                world_str_width,
+                ..
+            } => format!(
+                "Inconsistent world string width: toml width is {toml_width}, world string width is {world_str_width}"
+            ),
+            ParseError::InconsistentWorldStringHeight {
+                toml_height,
+                world_str_height,
+                ..
+            } => format!(
+                "Inconsistent world string height: toml height is {toml_height}, world string height is {world_str_height}"
+            ),
+            ParseError::InconsistentNumberOfAgents {
+                toml_n_agents_field,
+                actual_n_agents,
+                ..
+            } => format!(
+                "It is explicitely specified that there are {toml_n_agents_field} agents, but there are actually {actual_n_agents} agents in the rest of the configuration."
+            ),
+            ParseError::PositionOutOfBounds { i, j, .. } => {
+                format!("Position ({i}, {j}) is out of the world's boundaries")
+            }
+            ParseError::MissingWidth => "Missing width in the world configuration file".into(),
+            ParseError::MissingHeight => "Missing height in the world configuration file".into(),
+            ParseError::UnknownTomlKey { message, .. } => message.clone(),
+            ParseError::NotV2 => "The content is not a TOML level".into(),
+            ParseError::InvalidJson { message, .. } => format!("Invalid JSON level: {message}"),
+            ParseError::MissingHeaderSeparator => {
+                "Missing '---' line between the header and the grid of the level".into()
+            }
+            ParseError::InvalidHeaderLine { .. } => {
+                "Invalid header line. Expected 'key: value'.".into()
+            }
+            ParseError::UnknownHeaderKey { key, .. } => {
+                format!("Unknown header key '{key}'. Expected one of name, author, t_max.")
+            }
+            ParseError::DuplicateHeaderKey { key, .. } => {
+                format!("Header key '{key}' is defined twice")
+            }
+            ParseError::InvalidHeaderValue { key, value, .. } => {
+                format!("Invalid value '{value}' for header key '{key}'")
+            }
+        }
+    }
+
+    /// The message of the error followed, if the error has a span, by the offending line of
+    /// `source` with the token underlined:
+    ///
+    /// ```text
+    /// Invalid tile 'L0Q'
+    ///  --> line 2, column 4
+    ///   |
+    /// 2 | S0 L0Q X
+    ///   |    ^^^
+    /// ```
+    pub fn report(&self, source: &str) -> String {
+        let message = self.message();
+        match self.span().and_then(|span| span.render(source)) {
+            Some(snippet) => format!("{message}\n{snippet}"),
+            None => message,
+        }
+    }
 }
 
+/// The message of the error followed, if the error has a span, by its location and its token.
+/// Use [`ParseError::report`] to show the offending line when the source is at hand.
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        write!(f, "{}", self.message())?;
+        if let Some(span) = self.span() {
+            write!(f, "\n{}", span.render_token())?;
+        }
+        Ok(())
     }
 }
 
@@ -1,8 +1,6 @@
-use std::rc::Rc;
-
 use crate::{
     AgentId,
-    tiles::{Direction, LaserBeam, LaserId, LaserSource},
+    tiles::{Direction, LaserBeam, LaserId},
 };
 
 use super::ParseError;
@@ -15,15 +13,27 @@ pub struct LaserConfig {
 }
 
 impl LaserConfig {
+    /// Parse a laser source token such as `L0E`.
+    ///
     /// Note there is no "TryFrom" implementation for LaserSource because we need the laser_id.
     pub fn from_str(value: &str, laser_id: LaserId) -> Result<LaserConfig, ParseError> {
-        let direction = Direction::try_from(value.chars().last().unwrap()).unwrap();
-        let agent_id_str = &value[1..value.len() - 1];
+        let Some(direction_char) = value.chars().last() else {
+            return Err(ParseError::InvalidDirection {
+                given: String::new(),
+                expected: "{N, E, S, W}.".into(),
+                span: None,
+            });
+        };
+        let direction = Direction::try_from(direction_char)?;
+        let agent_id_str = value
+            .get(1..value.len() - direction_char.len_utf8())
+            .unwrap_or_default();
         let agent_id = match agent_id_str.parse::<AgentId>() {
             Ok(agent_id) => agent_id,
             Err(_) => {
                 return Err(ParseError::InvalidAgentId {
                     given_agent_id: agent_id_str.to_string(),
+                    span: None,
                 });
             }
         };
@@ -34,33 +44,21 @@ impl LaserConfig {
         })
     }
 
-    pub fn build(&self, beam_length: usize) -> LaserSource {
-        let beam = Rc::new(LaserBeam::new(
-            beam_length,
-            self.agent_id,
-            self.direction,
-            self.laser_id,
-        ));
-        LaserSource::new(beam)
+    pub fn build(&self, beam_length: usize) -> LaserBeam {
+        LaserBeam::new(beam_length, self.agent_id, self.direction, self.laser_id)
     }
 }
 
-impl From<&LaserSource> for LaserConfig {
-    fn from(source: &LaserSource) -> Self {
+impl From<&LaserBeam> for LaserConfig {
+    fn from(beam: &LaserBeam) -> Self {
         Self {
-            direction: source.direction(),
-            agent_id: source.agent_id(),
-            laser_id: source.laser_id(),
+            direction: beam.direction(),
+            agent_id: beam.agent_id(),
+            laser_id: beam.laser_id(),
         }
     }
 }
 
-impl From<LaserSource> for LaserConfig {
-    fn from(source: LaserSource) -> Self {
-        Self::from(&source)
-    }
-}
-
 impl std::fmt::Display for LaserConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "L{}{}", self.agent_id, self.direction.to_file_string())
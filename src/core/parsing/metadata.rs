@@ -0,0 +1,14 @@
+/// Descriptive information about a level that does not influence its dynamics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LevelMetadata {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    /// Suggested horizon for episodes (or for the solver) on this level.
+    pub t_max: Option<usize>,
+}
+
+impl LevelMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.author.is_none() && self.t_max.is_none()
+    }
+}
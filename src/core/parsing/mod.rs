@@ -1,19 +1,30 @@
 mod errors;
 mod laser_config;
+mod metadata;
 mod parser_v1;
+mod span;
+mod text_v2;
 mod toml;
 mod world_config;
 
 pub use errors::ParseError;
+pub use metadata::LevelMetadata;
 pub use parser_v1::parse as parse_v1;
+pub use span::Span;
+pub use text_v2::parse as parse_text_v2;
 pub use toml::parse as parse_toml;
 pub use world_config::WorldConfig;
 
 use super::World;
 
 pub fn parse(file_content: &str) -> Result<World, ParseError> {
+    // Neither TOML nor the text formats can start with a `{`.
+    if file_content.trim_start().starts_with('{') {
+        return WorldConfig::from_json(file_content)?.into_world();
+    }
     let config = match parse_toml(file_content) {
         Ok(c) => c,
+        Err(ParseError::NotV2) if text_v2::is_text_v2(file_content) => parse_text_v2(file_content)?,
         Err(ParseError::NotV2) => parse_v1(file_content)?,
         Err(other) => return Err(other),
     };
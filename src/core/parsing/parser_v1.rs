@@ -1,6 +1,8 @@
 use crate::{AgentId, Position};
 
-use super::{ParseError, laser_config::LaserConfig, world_config::WorldConfig};
+use super::{
+    ParseError, Span, laser_config::LaserConfig, span::tokenize, world_config::WorldConfig,
+};
 
 #[derive(Default)]
 pub struct ParsingData {
@@ -28,21 +30,29 @@ impl ParsingData {
         &mut self,
         agent_id: AgentId,
         pos: Position,
+        span: Span,
     ) -> Result<(), ParseError> {
-        while self.start_positions.len() <= agent_id {
-            self.start_positions.push(Vec::new());
-        }
-        if !self.start_positions[agent_id].is_empty() {
+        if let Some(&start1) = self.start_positions.get(agent_id).and_then(|s| s.first()) {
             return Err(ParseError::DuplicateStartTile {
                 agent_id,
-                start1: self.start_positions[agent_id][0],
+                start1,
                 start2: pos,
+                span,
             });
         }
-        self.start_positions[agent_id].push(pos);
+        self.add_random_start(agent_id, pos);
         Ok(())
     }
 
+    /// Add `pos` to the possible start positions of `agent_id`, without checking whether the agent
+    /// already has a start position.
+    pub fn add_random_start(&mut self, agent_id: AgentId, pos: Position) {
+        while self.start_positions.len() <= agent_id {
+            self.start_positions.push(Vec::new());
+        }
+        self.start_positions[agent_id].push(pos);
+    }
+
     pub fn add_gem(&mut self, pos: Position) {
         self.gem_positions.push(pos);
     }
@@ -55,18 +65,19 @@ impl ParsingData {
         self.exit_positions.push(pos);
     }
 
-    fn n_lasers(&self) -> usize {
+    pub fn n_lasers(&self) -> usize {
         self.laser_configs.len()
     }
 
-    pub fn add_row(&mut self, n_cols: usize, line: &str) -> Result<(), ParseError> {
+    /// Register a row of `n_cols` tiles, where `span` covers the row in the parsed text.
+    pub fn add_row(&mut self, n_cols: usize, span: Span) -> Result<(), ParseError> {
         if let Some(w) = self.width {
             if w != n_cols {
                 return Err(ParseError::InconsistentDimensions {
-                    row_str: line.to_string(),
                     expected_n_cols: w,
                     actual_n_cols: n_cols,
                     row: self.height,
+                    span,
                 });
             }
         } else {
@@ -97,53 +108,71 @@ impl TryInto<WorldConfig> for ParsingData {
     }
 }
 
+/// The v1 string of `config`, or `Err(())` if the configuration can not be represented exactly
+/// in v1 (e.g. an agent with several start positions, or a start position on a gem).
 pub fn to_v1_string(config: &WorldConfig) -> Result<String, ()> {
-    let mut res = vec![vec![String::from(" . "); config.width()]; config.height()];
-    for (agent_num, pos) in config.random_starts().iter().enumerate() {
-        if pos.len() > 1 {
+    if !config.laser_ids_follow_reading_order() {
+        return Err(());
+    }
+    let mut res: Vec<Vec<Option<String>>> = vec![vec![None; config.width()]; config.height()];
+    let mut place = |pos: &Position, token: String| {
+        let cell = &mut res[pos.i][pos.j];
+        if cell.is_some() {
             return Err(());
         }
-        let pos = pos[0];
-        res[pos.i][pos.j] = format!("S{agent_num} ");
+        *cell = Some(token);
+        Ok(())
+    };
+    for (agent_num, pos) in config.random_starts().iter().enumerate() {
+        match pos.as_slice() {
+            [pos] => place(pos, format!("S{agent_num} "))?,
+            _ => return Err(()),
+        }
     }
-
+    let source_positions: Vec<_> = config.sources().iter().map(|(pos, _)| *pos).collect();
     for pos in config.gems() {
-        res[pos.i][pos.j] = " G ".into();
+        place(pos, " G ".into())?;
     }
-    for pos in config.walls() {
-        res[pos.i][pos.j] = " @ ".into();
+    for pos in config
+        .walls()
+        .iter()
+        .filter(|p| !source_positions.contains(p))
+    {
+        place(pos, " @ ".into())?;
     }
     for pos in config.exits() {
-        res[pos.i][pos.j] = " X ".into();
+        place(pos, " X ".into())?;
     }
     for pos in config.voids() {
-        res[pos.i][pos.j] = " V ".into();
+        place(pos, " V ".into())?;
     }
     for (pos, config) in config.sources() {
-        res[pos.i][pos.j] = config.to_string();
+        place(pos, config.to_string())?;
     }
     Ok(res
         .into_iter()
-        .map(|row| row.join(" "))
+        .map(|row| {
+            row.into_iter()
+                .map(|token| token.unwrap_or(String::from(" . ")))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
         .collect::<Vec<String>>()
         .join("\n"))
 }
 
 pub fn parse(world_str: &str) -> Result<WorldConfig, ParseError> {
     let mut data = ParsingData::default();
-    for line in world_str.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let tokens = line.split_whitespace();
+    for (line_index, line) in world_str.lines().enumerate() {
+        let line_number = line_index + 1;
         let mut n_cols = 0usize;
-        for (col, token) in tokens.enumerate() {
+        for (col, (char_col, token)) in tokenize(line).enumerate() {
             n_cols += 1;
             let pos = Position {
                 i: data.height,
                 j: col,
             };
+            let span = Span::new(line_number, char_col, token);
             match token.to_uppercase().chars().next().unwrap() {
                 '.' => {}
                 'G' => data.add_gem(pos),
@@ -153,23 +182,21 @@ pub fn parse(world_str: &str) -> Result<WorldConfig, ParseError> {
                 'S' => {
                     let agent_id = token[1..].parse().map_err(|_| ParseError::InvalidAgentId {
                         given_agent_id: token[1..].into(),
+                        span: Some(span.clone()),
                     })?;
-                    data.add_start_position(agent_id, pos)?;
+                    data.add_start_position(agent_id, pos, span)?;
                 }
                 'L' => {
-                    let source_config = LaserConfig::from_str(token, data.n_lasers())?;
+                    let source_config = LaserConfig::from_str(token, data.n_lasers())
+                        .map_err(|e| e.with_span(span))?;
                     data.add_laser_source(pos, source_config);
                 }
-                _ => {
-                    return Err(ParseError::InvalidTile {
-                        tile_str: token.into(),
-                        line: pos.i,
-                        col: pos.j,
-                    });
-                }
+                _ => return Err(ParseError::InvalidTile { span }),
             }
         }
-        data.add_row(n_cols, line)?;
+        if n_cols > 0 {
+            data.add_row(n_cols, Span::row(line_number, line))?;
+        }
     }
     data.try_into()
 }
@@ -0,0 +1,100 @@
+use std::ops::Range;
+
+/// Location of a token in the parsed text. Lines and columns start at 1 and columns are counted
+/// in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+    pub token: String,
+}
+
+impl Span {
+    pub fn new(line: usize, col: usize, token: impl Into<String>) -> Self {
+        Self {
+            line,
+            col,
+            token: token.into(),
+        }
+    }
+
+    /// The span of the non-blank part of `line`.
+    pub fn row(line_number: usize, line: &str) -> Self {
+        let leading = line.len() - line.trim_start().len();
+        Self::new(
+            line_number,
+            line[..leading].chars().count() + 1,
+            line.trim(),
+        )
+    }
+
+    /// The span of the bytes `range` of `source`, truncated to the line on which it starts.
+    pub fn from_byte_range(source: &str, range: Range<usize>) -> Self {
+        let start = range.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let end = range.end.clamp(start, line_end);
+        Self {
+            line: source[..start].matches('\n').count() + 1,
+            col: source[line_start..start].chars().count() + 1,
+            token: source[start..end].trim_end_matches('\r').to_string(),
+        }
+    }
+
+    /// Print the line of `source` that contains the span, with carets below the token.
+    pub fn render(&self, source: &str) -> Option<String> {
+        let row = source.lines().nth(self.line.checked_sub(1)?)?;
+        // Keep the tabs of the row so that the carets line up with the token.
+        let padding: String = row
+            .chars()
+            .take(self.col - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(self.token.chars().count().max(1));
+        let gutter = " ".repeat(self.line.to_string().len());
+        Some(format!(
+            "{gutter}--> line {}, column {}\n{gutter} |\n{} | {row}\n{gutter} | {padding}{carets}",
+            self.line, self.col, self.line
+        ))
+    }
+
+    /// Like [`Self::render`], for when the source is not known: only the token is printed, with
+    /// carets below it.
+    pub fn render_token(&self) -> String {
+        let carets = "^".repeat(self.token.chars().count().max(1));
+        let gutter = " ".repeat(self.line.to_string().len());
+        format!(
+            "{gutter}--> line {}, column {}\n{gutter} |\n{gutter} | {}\n{gutter} | {carets}",
+            self.line, self.col, self.token
+        )
+    }
+}
+
+/// The whitespace-separated tokens of `line`, with their (1-based) column.
+pub fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut col = 0;
+    let mut chars = line.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {
+            col += 1;
+        }
+        let (start, _) = *chars.peek()?;
+        let token_col = col + 1;
+        let mut end = line.len();
+        while let Some(&(index, c)) = chars.peek() {
+            if c.is_whitespace() {
+                end = index;
+                break;
+            }
+            chars.next();
+            col += 1;
+        }
+        Some((token_col, &line[start..end]))
+    })
+}
+
+#[cfg(test)]
+#[path = "../../unit_tests/test_span.rs"]
+mod tests;
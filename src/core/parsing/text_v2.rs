@@ -0,0 +1,357 @@
+//! Version 2 of the text level format.
+//!
+//! A text v2 level is made of a header and a grid, separated by a line that only contains `---`.
+//! Anything after a `#` is a comment, both in the header and in the grid.
+//!
+//! ```text
+//! # Two agents that must cross a laser.
+//! name: Crossing
+//! author: Jane Doe
+//! t_max: 20
+//! ---
+//! S0   S0  .  X
+//! L1E  .   .  @
+//! S1   G   .  X   # agent 1 can be helped by agent 0
+//! ```
+//!
+//! The header is a (possibly empty) list of `key: value` lines, where the keys are `name`,
+//! `author` and `t_max`. The grid is a list of whitespace-separated tokens whose meaning is
+//! strict, i.e. the whole token must match:
+//! - `.` floor, `@` wall, `X` exit, `G` gem, `V` void;
+//! - `S<id>` a start position of agent `<id>`. The same agent can have several (random) starts;
+//! - `L<id><N|E|S|W>` a laser source of colour `<id>` pointing in the given direction.
+//!
+//! Start tokens can be combined together and with a gem or a void with `+`, e.g. `G+S0+S1`.
+use std::collections::HashSet;
+
+use crate::{AgentId, Position, tiles::Direction};
+
+use super::{
+    LevelMetadata, ParseError, Span, laser_config::LaserConfig, parser_v1::ParsingData,
+    span::tokenize, world_config::WorldConfig,
+};
+
+const SEPARATOR: &str = "---";
+const COMMENT: char = '#';
+
+#[derive(Clone, Copy)]
+enum Atom {
+    Floor,
+    Wall,
+    Exit,
+    Gem,
+    Void,
+    Start(AgentId),
+    Source(AgentId, Direction),
+}
+
+/// Whether `content` is written in the text v2 format, i.e. whether it contains a header
+/// separator.
+pub fn is_text_v2(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| strip_comment(line).trim() == SEPARATOR)
+}
+
+pub fn parse(content: &str) -> Result<WorldConfig, ParseError> {
+    if !is_text_v2(content) {
+        return Err(ParseError::MissingHeaderSeparator);
+    }
+    let mut lines = content.lines().enumerate();
+    let mut metadata = LevelMetadata::default();
+    for (index, line) in lines.by_ref() {
+        let line = strip_comment(line);
+        if line.trim() == SEPARATOR {
+            break;
+        }
+        if !line.trim().is_empty() {
+            parse_header_line(line, index + 1, &mut metadata)?;
+        }
+    }
+
+    let mut data = ParsingData::default();
+    for (index, line) in lines {
+        let line = strip_comment(line);
+        let mut n_cols = 0usize;
+        for (col, (char_col, token)) in tokenize(line).enumerate() {
+            n_cols += 1;
+            let pos = Position {
+                i: data.height,
+                j: col,
+            };
+            parse_tile(Span::new(index + 1, char_col, token), pos, &mut data)?;
+        }
+        if n_cols > 0 {
+            data.add_row(n_cols, Span::row(index + 1, line))?;
+        }
+    }
+    let config: WorldConfig = data.try_into()?;
+    Ok(config.with_metadata(metadata))
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(COMMENT) {
+        Some(index) => &line[..index],
+        None => line,
+    }
+}
+
+fn parse_header_line(
+    line: &str,
+    line_number: usize,
+    metadata: &mut LevelMetadata,
+) -> Result<(), ParseError> {
+    let Some(colon) = line.find(':') else {
+        return Err(ParseError::InvalidHeaderLine {
+            span: Span::row(line_number, line),
+        });
+    };
+    let (key_str, value_str) = (&line[..colon], &line[colon + 1..]);
+    let (key, value) = (key_str.trim(), value_str.trim());
+    let span_at =
+        |byte: usize, token: &str| Span::new(line_number, line[..byte].chars().count() + 1, token);
+    let key_span = span_at(key_str.len() - key_str.trim_start().len(), key);
+    let value_span = span_at(
+        colon + 1 + value_str.len() - value_str.trim_start().len(),
+        value,
+    );
+    let invalid_value = || ParseError::InvalidHeaderValue {
+        key: key.into(),
+        value: value.into(),
+        span: value_span.clone(),
+    };
+    let duplicate = || ParseError::DuplicateHeaderKey {
+        key: key.into(),
+        span: key_span.clone(),
+    };
+    if value.is_empty() {
+        return Err(invalid_value());
+    }
+    match key {
+        "name" => {
+            if metadata.name.replace(value.into()).is_some() {
+                return Err(duplicate());
+            }
+        }
+        "author" => {
+            if metadata.author.replace(value.into()).is_some() {
+                return Err(duplicate());
+            }
+        }
+        "t_max" => {
+            let t_max = parse_number(value).ok_or_else(invalid_value)?;
+            if metadata.t_max.replace(t_max).is_some() {
+                return Err(duplicate());
+            }
+        }
+        _ => {
+            return Err(ParseError::UnknownHeaderKey {
+                key: key.into(),
+                span: key_span,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parse a non-negative integer written without sign nor leading zeros.
+fn parse_number(s: &str) -> Option<usize> {
+    let canonical =
+        !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) && (s == "0" || !s.starts_with('0'));
+    if !canonical {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn parse_atom(s: &str) -> Option<Atom> {
+    match s {
+        "." => return Some(Atom::Floor),
+        "@" => return Some(Atom::Wall),
+        "X" => return Some(Atom::Exit),
+        "G" => return Some(Atom::Gem),
+        "V" => return Some(Atom::Void),
+        _ => {}
+    }
+    if let Some(agent_id) = s.strip_prefix('S') {
+        return parse_number(agent_id).map(Atom::Start);
+    }
+    let rest = s.strip_prefix('L')?;
+    let direction = match rest.chars().last()? {
+        'N' => Direction::North,
+        'E' => Direction::East,
+        'S' => Direction::South,
+        'W' => Direction::West,
+        _ => return None,
+    };
+    let agent_id = parse_number(&rest[..rest.len() - 1])?;
+    Some(Atom::Source(agent_id, direction))
+}
+
+fn parse_tile(span: Span, pos: Position, data: &mut ParsingData) -> Result<(), ParseError> {
+    let invalid = || ParseError::InvalidTile { span: span.clone() };
+    let atoms = span
+        .token
+        .split('+')
+        .map(|atom| parse_atom(atom).ok_or_else(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let [atom] = atoms.as_slice() {
+        match *atom {
+            Atom::Floor => {}
+            Atom::Wall => data.add_wall(pos),
+            Atom::Exit => data.add_exit(pos),
+            Atom::Gem => data.add_gem(pos),
+            Atom::Void => data.add_void(pos),
+            Atom::Start(agent_id) => data.add_random_start(agent_id, pos),
+            Atom::Source(agent_id, direction) => {
+                let config = LaserConfig {
+                    direction,
+                    agent_id,
+                    laser_id: data.n_lasers(),
+                };
+                data.add_laser_source(pos, config);
+            }
+        }
+        return Ok(());
+    }
+
+    // Combinations are only allowed for start positions, optionally on a gem or a void.
+    let mut terrain = None;
+    let mut agents = HashSet::new();
+    for atom in atoms {
+        match atom {
+            Atom::Gem | Atom::Void if terrain.is_none() => terrain = Some(atom),
+            Atom::Start(agent_id) => {
+                if !agents.insert(agent_id) {
+                    return Err(ParseError::DuplicateStartTile {
+                        agent_id,
+                        start1: pos,
+                        start2: pos,
+                        span,
+                    });
+                }
+                data.add_random_start(agent_id, pos);
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    match terrain {
+        Some(Atom::Gem) => data.add_gem(pos),
+        Some(Atom::Void) => data.add_void(pos),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The text v2 string of `config`, or `None` if the configuration can not be represented exactly
+/// (e.g. if two tiles are defined on the same position).
+pub fn to_text_v2_string(config: &WorldConfig) -> Option<String> {
+    if !config.laser_ids_follow_reading_order() {
+        return None;
+    }
+    let mut header = vec![];
+    let metadata = config.metadata();
+    for (key, value) in [("name", &metadata.name), ("author", &metadata.author)] {
+        if let Some(value) = value {
+            if !is_representable_header_value(value) {
+                return None;
+            }
+            header.push(format!("{key}: {value}"));
+        }
+    }
+    if let Some(t_max) = metadata.t_max {
+        header.push(format!("t_max: {t_max}"));
+    }
+    header.push(SEPARATOR.into());
+
+    let mut terrain: Vec<Vec<Option<String>>> = vec![vec![None; config.width()]; config.height()];
+    let mut place = |pos: &Position, token: String| {
+        let cell = &mut terrain[pos.i][pos.j];
+        if cell.is_some() {
+            return None;
+        }
+        *cell = Some(token);
+        Some(())
+    };
+    let source_positions: HashSet<_> = config.sources().iter().map(|(pos, _)| *pos).collect();
+    for pos in config.gems() {
+        place(pos, "G".into())?;
+    }
+    for pos in config
+        .walls()
+        .iter()
+        .filter(|p| !source_positions.contains(p))
+    {
+        place(pos, "@".into())?;
+    }
+    for pos in config.exits() {
+        place(pos, "X".into())?;
+    }
+    for pos in config.voids() {
+        place(pos, "V".into())?;
+    }
+    for (pos, source) in config.sources() {
+        place(pos, source.to_string())?;
+    }
+
+    let mut starts = vec![vec![Vec::new(); config.width()]; config.height()];
+    for (agent_id, positions) in config.random_starts().iter().enumerate() {
+        if positions.is_empty() {
+            return None;
+        }
+        for pos in positions {
+            let cell: &mut Vec<String> = &mut starts[pos.i][pos.j];
+            let start = format!("S{agent_id}");
+            if cell.contains(&start) {
+                return None;
+            }
+            cell.push(start);
+        }
+    }
+
+    let mut rows = vec![];
+    for (terrain_row, starts_row) in terrain.into_iter().zip(starts) {
+        let mut row = vec![];
+        for (terrain, starts) in terrain_row.into_iter().zip(starts_row) {
+            let token = match (terrain, starts.is_empty()) {
+                (None, true) => ".".to_string(),
+                (Some(terrain), true) => terrain,
+                (None, false) => starts.join("+"),
+                (Some(terrain), false) if terrain == "G" || terrain == "V" => {
+                    format!("{terrain}+{}", starts.join("+"))
+                }
+                (Some(_), false) => return None,
+            };
+            row.push(token);
+        }
+        rows.push(row);
+    }
+    let widths: Vec<usize> = (0..config.width())
+        .map(|j| rows.iter().map(|row| row[j].len()).max().unwrap_or(0))
+        .collect();
+    let grid = rows.into_iter().map(|row| {
+        row.iter()
+            .zip(&widths)
+            .map(|(token, width)| format!("{token:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    });
+    Some(
+        header
+            .into_iter()
+            .chain(grid)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/// Header values are trimmed and cut at the first `#`, so some strings can not be written.
+fn is_representable_header_value(value: &str) -> bool {
+    !value.is_empty() && value.trim() == value && !value.contains([COMMENT, '\n', '\r'])
+}
+
+#[cfg(test)]
+#[path = "../../unit_tests/test_text_v2.rs"]
+mod tests;
@@ -1,9 +1,11 @@
 mod agent_config;
 mod position_config;
+mod spans;
 mod toml_config;
 mod toml_laser_config;
 
 pub use agent_config::AgentConfig;
 pub use position_config::PositionsConfig;
+pub use spans::TomlSpans;
 pub use toml_config::{TomlConfig, parse};
 pub use toml_laser_config::TomlLaserConfig;
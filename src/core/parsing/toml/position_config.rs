@@ -18,21 +18,66 @@ pub enum PositionsConfig {
     Rect {
         #[serde(default)]
         i_min: usize,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         i_max: Option<usize>,
         #[serde(default)]
         j_min: usize,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         j_max: Option<usize>,
     },
 }
 
 impl PositionsConfig {
+    /// Describe `positions` with as few entries as possible, preferring whole rows and columns.
+    ///
+    /// Rectangles are chosen greedily in reading order (extend right, then down) and never
+    /// overlap, so that every position is produced exactly once by `to_positions`.
+    pub fn compact(positions: &[Position], width: usize, height: usize) -> Vec<Self> {
+        let mut remaining = vec![vec![false; width]; height];
+        for pos in positions {
+            remaining[pos.i][pos.j] = true;
+        }
+        let mut res = vec![];
+        for i in 0..height {
+            for j in 0..width {
+                if !remaining[i][j] {
+                    continue;
+                }
+                let j_max = (j..width).take_while(|&j| remaining[i][j]).last().unwrap();
+                let i_max = (i..height)
+                    .take_while(|&i| (j..=j_max).all(|j| remaining[i][j]))
+                    .last()
+                    .unwrap();
+                for row in remaining.iter_mut().take(i_max + 1).skip(i) {
+                    row[j..=j_max].fill(false);
+                }
+                let full_width = j == 0 && j_max == width - 1;
+                let full_height = i == 0 && i_max == height - 1;
+                res.push(match (i == i_max, j == j_max) {
+                    (true, true) => Self::IJ { i, j },
+                    (true, false) if full_width => Self::Row { row: i },
+                    (false, true) if full_height => Self::Column { col: j },
+                    _ => Self::Rect {
+                        i_min: i,
+                        i_max: Some(i_max),
+                        j_min: j,
+                        j_max: Some(j_max),
+                    },
+                });
+            }
+        }
+        res
+    }
+
     pub fn to_positions(&self, width: usize, height: usize) -> Result<Vec<Position>, ParseError> {
         match self {
             Self::IJ { i, j } => {
                 if *i >= height || *j >= width {
-                    return Err(ParseError::PositionOutOfBounds { i: *i, j: *j });
+                    return Err(ParseError::PositionOutOfBounds {
+                        i: *i,
+                        j: *j,
+                        span: None,
+                    });
                 }
                 Ok(vec![Position { i: *i, j: *j }])
             }
@@ -46,13 +91,14 @@ impl PositionsConfig {
                     return Err(ParseError::PositionOutOfBounds {
                         i: *i_min,
                         j: *j_min,
+                        span: None,
                     });
                 }
                 let mut positions = vec![];
                 for i in *i_min..=i_max.unwrap_or(height - 1) {
                     for j in *j_min..=j_max.unwrap_or(width - 1) {
                         if i >= height || j >= width {
-                            return Err(ParseError::PositionOutOfBounds { i, j });
+                            return Err(ParseError::PositionOutOfBounds { i, j, span: None });
                         }
                         positions.push(Position { i, j });
                     }
@@ -61,13 +107,21 @@ impl PositionsConfig {
             }
             Self::Row { row } => {
                 if *row >= height {
-                    return Err(ParseError::PositionOutOfBounds { i: *row, j: 0 });
+                    return Err(ParseError::PositionOutOfBounds {
+                        i: *row,
+                        j: 0,
+                        span: None,
+                    });
                 }
                 Ok((0..width).map(|j| Position { i: *row, j }).collect())
             }
             Self::Column { col } => {
                 if *col >= width {
-                    return Err(ParseError::PositionOutOfBounds { i: 0, j: *col });
+                    return Err(ParseError::PositionOutOfBounds {
+                        i: 0,
+                        j: *col,
+                        span: None,
+                    });
                 }
                 Ok((0..height).map(|i| Position { i, j: *col }).collect())
             }
@@ -75,6 +129,28 @@ impl PositionsConfig {
     }
 }
 
+impl PositionsConfig {
+    /// Whether the entry describes the position `(i, j)`, whatever the size of the world.
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        match self {
+            Self::IJ { i: row, j: col } => (*row, *col) == (i, j),
+            Self::Row { row } => *row == i,
+            Self::Column { col } => *col == j,
+            Self::Rect {
+                i_min,
+                i_max,
+                j_min,
+                j_max,
+            } => {
+                *i_min <= i
+                    && i_max.is_none_or(|i_max| i <= i_max)
+                    && *j_min <= j
+                    && j_max.is_none_or(|j_max| j <= j_max)
+            }
+        }
+    }
+}
+
 impl From<&Position> for PositionsConfig {
     fn from(pos: &Position) -> Self {
         Self::IJ { i: pos.i, j: pos.j }
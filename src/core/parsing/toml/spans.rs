@@ -0,0 +1,162 @@
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{ParseError, core::parsing::Span};
+
+use super::PositionsConfig;
+
+/// The location in a TOML file of the values that the errors of [`TomlConfig`](super::TomlConfig)
+/// can point at. It is only read when an error occurs, and it tolerates fields that it does not
+/// know since `TomlConfig` already checked them.
+#[derive(Deserialize, Default)]
+pub struct TomlSpans {
+    world_string: Option<Spanned<String>>,
+    width: Option<Spanned<usize>>,
+    height: Option<Spanned<usize>>,
+    n_agents: Option<Spanned<usize>>,
+    #[serde(default)]
+    starts: Vec<Spanned<PositionsConfig>>,
+    #[serde(default)]
+    walls: Vec<Spanned<PositionsConfig>>,
+    #[serde(default)]
+    exits: Vec<Spanned<PositionsConfig>>,
+    #[serde(default)]
+    agents: Vec<AgentSpans>,
+    #[serde(default)]
+    gems: Vec<Spanned<PositionsConfig>>,
+    #[serde(default)]
+    voids: Vec<Spanned<PositionsConfig>>,
+}
+
+#[derive(Deserialize, Default)]
+struct AgentSpans {
+    #[serde(default, alias = "start_positions")]
+    starts: Vec<Spanned<PositionsConfig>>,
+}
+
+impl TomlSpans {
+    pub fn parse(toml_content: &str) -> Self {
+        toml::from_str(toml_content).unwrap_or_default()
+    }
+
+    /// Point `error`, raised while converting the TOML file `toml_content`, at that file.
+    pub fn locate(&self, mut error: ParseError, toml_content: &str) -> ParseError {
+        let to_span = |range| Span::from_byte_range(toml_content, range);
+        let key_span = match &error {
+            ParseError::InconsistentWorldStringWidth { .. } => self.width.as_ref(),
+            ParseError::InconsistentWorldStringHeight { .. } => self.height.as_ref(),
+            ParseError::InconsistentNumberOfAgents { .. } => self.n_agents.as_ref(),
+            _ => None,
+        };
+        if let Some(value) = key_span {
+            return error.with_span(to_span(value.span()));
+        }
+        if let ParseError::PositionOutOfBounds { i, j, .. } = error {
+            // The entries in the order in which `TomlConfig` converts them.
+            let agent_starts = self.agents.iter().flat_map(|agent| &agent.starts);
+            let entry = self
+                .starts
+                .iter()
+                .chain(&self.walls)
+                .chain(&self.exits)
+                .chain(agent_starts)
+                .chain(&self.gems)
+                .chain(&self.voids)
+                .find(|entry| entry.get_ref().contains(i, j));
+            return match entry {
+                Some(entry) => error.with_span(to_span(entry.span())),
+                None => error,
+            };
+        }
+        // The other errors with a span come from the `world_string`, in which they are located.
+        if let (Some(world_string), Some(span)) = (&self.world_string, error.span_mut()) {
+            let offsets = source_offsets(toml_content, world_string);
+            if let Some(range) = offsets.and_then(|offsets| {
+                let start = byte_offset(world_string.get_ref(), span)?;
+                let end = start + span.token.len();
+                Some(offsets[start]..offsets[end])
+            }) {
+                *span = to_span(range);
+            }
+        }
+        error
+    }
+}
+
+/// The byte offset in `value` of the start of `span`.
+fn byte_offset(value: &str, span: &Span) -> Option<usize> {
+    let line_start: usize = value
+        .split_inclusive('\n')
+        .take(span.line - 1)
+        .map(str::len)
+        .sum();
+    let line = value[line_start..].lines().next()?;
+    let col = line
+        .char_indices()
+        .nth(span.col - 1)
+        .map_or(line.len(), |(index, _)| index);
+    Some(line_start + col)
+}
+
+/// For each byte of the TOML string `value` (and its end), the offset in `toml_content` of the
+/// text that produces it, taking the delimiters and the escapes of the string into account.
+///
+/// Returns `None` if the text of the string can not be matched with its value.
+fn source_offsets(toml_content: &str, value: &Spanned<String>) -> Option<Vec<usize>> {
+    let range = value.span();
+    let raw = toml_content.get(range.clone())?;
+    let (delimiter, literal) = [("\"\"\"", false), ("'''", true), ("\"", false), ("'", true)]
+        .into_iter()
+        .find(|(delimiter, _)| raw.starts_with(delimiter))?;
+    let mut start = range.start + delimiter.len();
+    let end = range.end.checked_sub(delimiter.len())?;
+    // A newline right after the opening delimiter of a multi-line string is trimmed.
+    if delimiter.len() == 3 {
+        let content = toml_content.get(start..end)?;
+        start += ["\r\n", "\n"]
+            .into_iter()
+            .find(|newline| content.starts_with(newline))
+            .map_or(0, str::len);
+    }
+    let content = toml_content.get(start..end)?;
+    let mut offsets = Vec::with_capacity(value.get_ref().len() + 1);
+    let mut chars = content.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let produced = if literal || c != '\\' {
+            Some(c)
+        } else {
+            match chars.next()?.1 {
+                'b' => Some('\u{8}'),
+                't' => Some('\t'),
+                'n' => Some('\n'),
+                'f' => Some('\u{c}'),
+                'r' => Some('\r'),
+                'e' => Some('\u{1b}'),
+                '"' => Some('"'),
+                '\\' => Some('\\'),
+                escape @ ('x' | 'u' | 'U') => {
+                    let n_digits = match escape {
+                        'x' => 2,
+                        'u' => 4,
+                        _ => 8,
+                    };
+                    let digits: String = (0..n_digits)
+                        .map(|_| chars.next().map(|(_, c)| c))
+                        .collect::<Option<_>>()?;
+                    char::from_u32(u32::from_str_radix(&digits, 16).ok()?)
+                }
+                // A line ending backslash trims the whitespace up to the next character.
+                c if c.is_whitespace() => {
+                    while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                    None
+                }
+                _ => return None,
+            }
+        };
+        if let Some(produced) = produced {
+            offsets.extend(std::iter::repeat_n(start + index, produced.len_utf8()));
+        }
+    }
+    offsets.push(end);
+    (offsets.len() == value.get_ref().len() + 1).then_some(offsets)
+}
@@ -1,19 +1,32 @@
 use serde::{Deserialize, Serialize};
 use toml;
+use toml_edit::{Array, Item};
 
 use crate::{
     ParseError, Position,
-    core::parsing::{WorldConfig, parse_v1},
+    core::parsing::{LevelMetadata, Span, WorldConfig, parse_v1},
 };
 
-use super::{AgentConfig, PositionsConfig, TomlLaserConfig};
+use super::{AgentConfig, PositionsConfig, TomlLaserConfig, TomlSpans};
 
+/// The structure of TOML level files, which is also the JSON representation of a
+/// [`WorldConfig`].
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct TomlConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub t_max: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub n_agents: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub world_string: Option<String>,
     #[serde(default)]
     pub agents: Vec<AgentConfig>,
@@ -43,6 +56,7 @@ impl TomlConfig {
                 return Err(ParseError::InconsistentWorldStringWidth {
                     toml_width: w,
                     world_str_width: config.width(),
+                    span: None,
                 });
             }
         } else {
@@ -53,6 +67,7 @@ impl TomlConfig {
                 return Err(ParseError::InconsistentWorldStringHeight {
                     toml_height: h,
                     world_str_height: config.height(),
+                    span: None,
                 });
             }
         } else {
@@ -71,6 +86,7 @@ impl TomlConfig {
             return Err(ParseError::InconsistentNumberOfAgents {
                 toml_n_agents_field: n,
                 actual_n_agents: self.agents.len(),
+                span: None,
             });
         }
 
@@ -94,9 +110,43 @@ impl TomlConfig {
         Ok(())
     }
 
+    /// Write the configuration with one inline table per line for positions, which is much
+    /// easier to read and edit than one `[[table]]` per entry.
     pub fn to_toml_string(&self) -> String {
-        toml::to_string(self).unwrap()
+        let mut document =
+            toml_edit::ser::to_document(self).expect("A TOML configuration can be written in TOML");
+        let root = document.as_table_mut();
+        root.retain(|_, item| !item.as_array().is_some_and(Array::is_empty));
+        root.iter_mut()
+            .filter_map(|(_, item)| item.as_array_mut())
+            .for_each(one_entry_per_line);
+        if let Some(agents) = root.remove("agents") {
+            let mut agents = agents
+                .into_array_of_tables()
+                .expect("The agents are a list of tables");
+            for agent in agents.iter_mut() {
+                agent.decor_mut().set_prefix("\n");
+                agent.retain(|_, item| !item.as_array().is_some_and(Array::is_empty));
+                if let Some(starts) = agent.get_mut("starts").and_then(Item::as_array_mut) {
+                    one_entry_per_line(starts);
+                }
+            }
+            root.insert("agents", Item::ArrayOfTables(agents));
+        }
+        document.to_string()
+    }
+}
+
+/// Put each entry of `array` on its own line if it has more than one entry.
+fn one_entry_per_line(array: &mut Array) {
+    if array.len() < 2 {
+        return;
+    }
+    for entry in array.iter_mut() {
+        entry.decor_mut().set_prefix("\n    ");
     }
+    array.set_trailing_comma(true);
+    array.set_trailing("\n");
 }
 
 fn compute_positions(
@@ -115,17 +165,21 @@ pub fn parse(toml_content: &str) -> Result<WorldConfig, ParseError> {
     let data: TomlConfig = match toml::from_str(toml_content) {
         Ok(d) => d,
         Err(e) => {
-            let message = e.to_string();
+            let message = e.message().to_string();
             // There is probably a better way of finding out which key is unknown
             // but I could not find it.
             if message.contains("unknown field") {
                 let key = message.split('`').nth(1).unwrap_or("<unknown key>").into();
-                return Err(ParseError::UnknownTomlKey { key, message });
+                let span = e
+                    .span()
+                    .map(|range| Span::from_byte_range(toml_content, range));
+                return Err(ParseError::UnknownTomlKey { key, message, span });
             }
             return Err(ParseError::NotV2);
         }
     };
     data.try_into()
+        .map_err(|error| TomlSpans::parse(toml_content).locate(error, toml_content))
 }
 
 impl TryInto<WorldConfig> for TomlConfig {
@@ -162,6 +216,11 @@ impl TryInto<WorldConfig> for TomlConfig {
             })
             .collect::<Result<Vec<_>, _>>()?;
         let source_configs = self.lasers.iter().map(|l| (l.position, l.into())).collect();
+        let metadata = LevelMetadata {
+            name: self.name,
+            author: self.author,
+            t_max: self.t_max,
+        };
         Ok(WorldConfig::new(
             width,
             height,
@@ -171,7 +230,8 @@ impl TryInto<WorldConfig> for TomlConfig {
             exit_positions,
             walls_positions,
             source_configs,
-        ))
+        )
+        .with_metadata(metadata))
     }
 }
 
@@ -179,33 +239,57 @@ impl From<&WorldConfig> for TomlConfig {
     fn from(value: &WorldConfig) -> Self {
         let width = value.width();
         let height = value.height();
-        let mut agents = vec![];
-        for starts in value.random_starts() {
-            agents.push(AgentConfig {
-                starts: starts.iter().map(PositionsConfig::from).collect(),
+        let compact = |positions: &[Position]| PositionsConfig::compact(positions, width, height);
+        // Starts that all agents share are written once in the global `starts`.
+        let random_starts = value.random_starts();
+        let shared_starts: Vec<Position> = match random_starts.split_first() {
+            Some((first, others)) if !others.is_empty() => first
+                .iter()
+                .filter(|pos| others.iter().all(|starts| starts.contains(pos)))
+                .copied()
+                .collect(),
+            _ => vec![],
+        };
+        let agents = random_starts
+            .iter()
+            .map(|starts| {
+                let own_starts: Vec<Position> = starts
+                    .iter()
+                    .filter(|pos| !shared_starts.contains(pos))
+                    .copied()
+                    .collect();
+                AgentConfig {
+                    starts: compact(&own_starts),
+                }
             })
-        }
-        let exits = value.exits().iter().map(PositionsConfig::from).collect();
-        let gems = value.gems().iter().map(PositionsConfig::from).collect();
-        let walls = value.walls().iter().map(PositionsConfig::from).collect();
-        let voids = value.voids().iter().map(PositionsConfig::from).collect();
+            .collect::<Vec<_>>();
         let lasers = value
             .sources()
             .iter()
             .map(|(pos, laser)| TomlLaserConfig::from_laser_config(laser, *pos))
             .collect();
+        // The order of the gems defines their index in the `WorldState`, so they are only
+        // collapsed if the entries expand to the same order.
+        let mut gems = compact(value.gems());
+        if compute_positions(&gems, width, height).ok().as_ref() != Some(value.gems()) {
+            gems = value.gems().iter().map(PositionsConfig::from).collect();
+        }
+        let metadata = value.metadata().clone();
         Self {
+            name: metadata.name,
+            author: metadata.author,
+            t_max: metadata.t_max,
             width: Some(width),
             height: Some(height),
             n_agents: Some(agents.len()),
             world_string: None,
             agents,
-            exits,
+            exits: compact(value.exits()),
             gems,
-            walls,
-            voids,
+            walls: compact(value.walls()),
+            voids: compact(value.voids()),
             lasers,
-            starts: vec![],
+            starts: compact(&shared_starts),
         }
     }
 }
@@ -1,13 +1,18 @@
 use std::{collections::HashSet, fmt::Display, vec};
 
+use itertools::Itertools;
+
 use crate::{
     Position, World,
-    tiles::{Gem, Laser, Tile, Void},
+    tiles::{Gem, Laser, LaserBeam, LaserSource, Tile, Void},
 };
 
 use crate::ParseError;
 
-use super::{laser_config::LaserConfig, parser_v1::to_v1_string, toml::TomlConfig};
+use super::{
+    LevelMetadata, Span, laser_config::LaserConfig, parser_v1::to_v1_string,
+    text_v2::to_text_v2_string, toml::TomlConfig,
+};
 
 #[derive(Debug)]
 pub struct WorldConfig {
@@ -19,6 +24,7 @@ pub struct WorldConfig {
     exits: Vec<Position>,
     walls: Vec<Position>,
     lasers: Vec<(Position, LaserConfig)>,
+    metadata: LevelMetadata,
 }
 
 impl WorldConfig {
@@ -42,9 +48,19 @@ impl WorldConfig {
             exits: exit_positions,
             walls: walls_positions,
             lasers: source_configs,
+            metadata: LevelMetadata::default(),
         }
     }
 
+    pub fn with_metadata(mut self, metadata: LevelMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn metadata(&self) -> &LevelMetadata {
+        &self.metadata
+    }
+
     pub fn exits(&self) -> &Vec<Position> {
         &self.exits
     }
@@ -77,6 +93,37 @@ impl WorldConfig {
         &self.lasers
     }
 
+    /// Whether the laser ids are those that the text formats would assign, i.e. `0, 1, ...` in
+    /// reading order of the sources.
+    pub fn laser_ids_follow_reading_order(&self) -> bool {
+        self.lasers
+            .iter()
+            .sorted_by_key(|(pos, _)| (pos.i, pos.j))
+            .enumerate()
+            .all(|(index, (_, laser))| laser.laser_id == index)
+    }
+
+    /// The configuration in JSON, with the same structure as TOML level files.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&TomlConfig::from(self))
+            .expect("a world configuration is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ParseError> {
+        let config: TomlConfig = serde_json::from_str(json).map_err(|e| {
+            let mut message = e.to_string();
+            // The location is given by the span.
+            if let Some(index) = message.rfind(" at line ") {
+                message.truncate(index);
+            }
+            ParseError::InvalidJson {
+                message,
+                span: Some(Span::new(e.line(), e.column().max(1), "")),
+            }
+        })?;
+        config.try_into()
+    }
+
     pub fn add_random_starts(&mut self, starts: Vec<Vec<Position>>) {
         for (i, start) in starts.into_iter().enumerate() {
             let start = self.filter_positions(start, &self.walls);
@@ -106,10 +153,12 @@ impl WorldConfig {
 
     pub fn into_world(mut self) -> Result<World, ParseError> {
         self.pre_validate()?;
-        let (grid, lasers_positions) = self.make_grid();
+        let (grid, lasers_positions, beams) = self.make_grid();
         self.post_validate()?;
         let source_positions = self.lasers.iter().map(|(pos, _)| *pos).collect();
-        Ok(World::new(
+        let mut world = World::new(
+            self.width,
+            self.height,
             grid,
             self.gems,
             self.random_starts,
@@ -118,7 +167,10 @@ impl WorldConfig {
             self.walls,
             source_positions,
             lasers_positions,
-        ))
+            beams,
+        );
+        world.set_metadata(self.metadata);
+        Ok(world)
     }
 
     fn pre_validate(&self) -> Result<(), ParseError> {
@@ -173,38 +225,35 @@ impl WorldConfig {
         self.random_starts.len()
     }
 
-    fn make_grid(&mut self) -> (Vec<Vec<Tile>>, Vec<Position>) {
-        let mut grid = Vec::with_capacity(self.height);
-        for _ in 0..self.height {
-            let mut row = Vec::with_capacity(self.width);
-            for _ in 0..self.width {
-                row.push(Tile::Floor { agent: None });
-            }
-            grid.push(row);
-        }
+    /// The tiles, row after row, the positions of the laser tiles and the beams of the sources.
+    fn make_grid(&mut self) -> (Vec<Tile>, Vec<Position>, Vec<LaserBeam>) {
+        let width = self.width;
+        let mut grid = vec![Tile::Floor { agent: None }; self.height * width];
         for pos in &self.gems {
-            grid[pos.i][pos.j] = Tile::Gem(Gem::default());
+            grid[pos.i * width + pos.j] = Tile::Gem(Gem::default());
         }
         for pos in &self.exits {
-            grid[pos.i][pos.j] = Tile::Exit { agent: None };
+            grid[pos.i * width + pos.j] = Tile::Exit { agent: None };
         }
         for pos in &self.voids {
-            grid[pos.i][pos.j] = Tile::Void(Void::default());
+            grid[pos.i * width + pos.j] = Tile::Void(Void::default());
         }
         for pos in &self.walls {
-            grid[pos.i][pos.j] = Tile::Wall;
+            grid[pos.i * width + pos.j] = Tile::Wall;
         }
-        let laser_positions = self.laser_setup(&mut grid).into_iter().collect();
-        (grid, laser_positions)
+        let (laser_positions, beams) = self.laser_setup(&mut grid);
+        (grid, laser_positions.into_iter().collect(), beams)
     }
 
     /// Place the laser sources and wrap the required tiles behind a
-    /// `Laser` tile.
-    fn laser_setup(&mut self, grid: &mut [Vec<Tile>]) -> HashSet<Position> {
+    /// `Laser` tile. Return the positions of the laser tiles and the beams of the sources, in
+    /// the order of the sources.
+    fn laser_setup(&mut self, grid: &mut [Tile]) -> (HashSet<Position>, Vec<LaserBeam>) {
         let mut laser_positions = HashSet::new();
-        let width = grid[0].len() as i32;
-        let height: i32 = grid.len() as i32;
-        for (pos, source) in &self.lasers {
+        let mut beams = Vec::with_capacity(self.lasers.len());
+        let width = self.width as i32;
+        let height = self.height as i32;
+        for (index, (pos, source)) in self.lasers.iter().enumerate() {
             let mut beam_positions = vec![];
             let delta = source.direction.delta();
             let (mut i, mut j) = (pos.i as i32, pos.j as i32);
@@ -214,45 +263,60 @@ impl WorldConfig {
                     i: i as usize,
                     j: j as usize,
                 };
-                if !grid[pos.i][pos.j].is_walkable() {
+                if !grid[pos.i * self.width + pos.j].is_walkable() {
                     break;
                 }
                 beam_positions.push(pos);
                 (i, j) = ((i + delta.0), (j + delta.1));
             }
             laser_positions.extend(&beam_positions);
-            let source = source.build(beam_positions.len());
+            let beam = source.build(beam_positions.len());
             let mut is_blocked = false;
             for (i, pos) in beam_positions.into_iter().enumerate() {
-                if let Some(agent_starts) = self.random_starts.get(source.agent_id())
+                if let Some(agent_starts) = self.random_starts.get(beam.agent_id())
                     && agent_starts.len() == 1
                     && agent_starts.contains(&pos)
                 {
                     is_blocked = true;
                 }
-                let wrapped = grid[pos.i].remove(pos.j);
-                let laser = Tile::Laser(Laser::new(wrapped, source.beam(), i));
+                let tile = &mut grid[pos.i * self.width + pos.j];
+                let wrapped = std::mem::replace(tile, Tile::Wall);
+                let laser = Tile::Laser(Laser::new(
+                    wrapped,
+                    index,
+                    beam.laser_id(),
+                    beam.direction(),
+                    i,
+                ));
                 if !is_blocked {
                     // Remove the random starts on this location for agents of a different ID if the agent would die on reset
                     for (start_agent_id, starts) in self.random_starts.iter_mut().enumerate() {
-                        if start_agent_id == source.agent_id() {
+                        if start_agent_id == beam.agent_id() {
                             continue;
                         }
                         starts.retain(|start| *start != pos);
                     }
                 }
 
-                grid[pos.i].insert(pos.j, laser);
+                grid[pos.i * self.width + pos.j] = laser;
             }
-            grid[pos.i][pos.j] = Tile::LaserSource(source);
+            grid[pos.i * self.width + pos.j] =
+                Tile::LaserSource(LaserSource::new(index, beam.laser_id(), beam.direction()));
+            beams.push(beam);
         }
-        laser_positions
+        (laser_positions, beams)
     }
 }
 
 impl Display for WorldConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Ok(string) = to_v1_string(self) {
+        // v1 is preferred for backward compatibility, but it can not carry metadata.
+        if self.metadata.is_empty()
+            && let Ok(string) = to_v1_string(self)
+        {
+            return write!(f, "{string}");
+        }
+        if let Some(string) = to_text_v2_string(self) {
             return write!(f, "{string}");
         }
         let toml_config: TomlConfig = self.into();
@@ -61,7 +61,8 @@ impl TryFrom<&str> for Direction {
             "w" | "west" => Ok(Direction::West),
             _ => Err(ParseError::InvalidDirection {
                 given: value.into(),
-                expected: "{{N, E, S, W, north, east, south, west}}.".into(),
+                expected: "{N, E, S, W, north, east, south, west}.".into(),
+                span: None,
             }),
         }
     }
@@ -3,7 +3,7 @@ use crate::{
     agent::{Agent, AgentId},
 };
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Gem {
     agent: Option<AgentId>,
     collected: bool,
@@ -18,6 +18,14 @@ impl Gem {
         self.collected = true;
     }
 
+    pub(crate) fn set_collected(&mut self, collected: bool) {
+        self.collected = collected;
+    }
+
+    pub(crate) fn set_agent(&mut self, agent: Option<AgentId>) {
+        self.agent = agent;
+    }
+
     pub fn reset(&mut self) {
         self.collected = false;
         self.agent = None;
@@ -1,7 +1,3 @@
-use std::cell::{Cell, RefCell};
-use std::fmt::Debug;
-use std::rc::Rc;
-
 use crate::RuntimeWorldError;
 use crate::{
     WorldEvent,
@@ -11,28 +7,47 @@ use crate::{
 
 use super::Gem;
 
-#[derive(Debug, Clone)]
+/// The beam of a laser source. Beams are owned by the [`World`](crate::World), and the
+/// [`LaserSource`](super::LaserSource) and [`Laser`] tiles refer to theirs by its index in
+/// [`World::beams`](crate::World::beams).
+///
+/// The tiles of the beam are a bitset of `u64` words, bit `offset` being set when the beam is on
+/// at that tile, so that turning the beam on or off from a tile is a few word operations.
+#[derive(Debug, Clone, PartialEq)]
 pub struct LaserBeam {
-    beam: RefCell<Vec<bool>>,
-    is_enabled: Cell<bool>,
-    agent_id: Cell<AgentId>,
+    words: Vec<u64>,
+    len: usize,
+    is_enabled: bool,
+    agent_id: AgentId,
     direction: Direction,
     laser_id: LaserId,
 }
 
 impl LaserBeam {
     pub fn new(size: usize, agent_id: AgentId, direction: Direction, laser_id: LaserId) -> Self {
-        Self {
-            beam: RefCell::new(vec![true; size]),
-            is_enabled: Cell::new(true),
-            agent_id: Cell::new(agent_id),
+        let mut beam = Self {
+            words: vec![0; size.div_ceil(64)],
+            len: size,
+            is_enabled: true,
+            agent_id,
             direction,
             laser_id,
-        }
+        };
+        beam.turn_on(0);
+        beam
+    }
+
+    /// The number of tiles of the beam.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn agent_id(&self) -> AgentId {
-        self.agent_id.get()
+        self.agent_id
     }
 
     pub fn direction(&self) -> Direction {
@@ -40,39 +55,85 @@ impl LaserBeam {
     }
 
     pub fn is_on(&self, offset: usize) -> bool {
-        self.beam.borrow()[offset]
+        self.words[offset / 64] & (1 << (offset % 64)) != 0
     }
 
     pub fn is_off(&self, offset: usize) -> bool {
         !self.is_on(offset)
     }
 
-    pub fn turn_on(&self, offset: usize) {
+    /// The offsets of the tiles where the beam is on, in increasing order.
+    pub fn on_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(index * 64 + bit)
+            })
+        })
+    }
+
+    pub fn turn_on(&mut self, offset: usize) {
         if self.is_disabled() {
             return;
         }
-        self.beam.borrow_mut()[offset..].fill(true);
+        self.set_from(offset, true);
     }
 
-    pub fn turn_off(&self, offset: usize) {
-        self.beam.borrow_mut()[offset..].fill(false);
+    pub fn turn_off(&mut self, offset: usize) {
+        self.set_from(offset, false);
+    }
+
+    /// Set the bits from `offset` to the end of the beam to `on`.
+    fn set_from(&mut self, offset: usize, on: bool) {
+        if offset >= self.len {
+            return;
+        }
+        let first = offset / 64;
+        let last = (self.len - 1) / 64;
+        for (index, word) in self.words[first..=last].iter_mut().enumerate() {
+            let index = first + index;
+            let mut mask = u64::MAX;
+            if index == first {
+                mask &= u64::MAX << (offset % 64);
+            }
+            if index == last && !self.len.is_multiple_of(64) {
+                mask &= u64::MAX >> (64 - self.len % 64);
+            }
+            if on {
+                *word |= mask;
+            } else {
+                *word &= !mask;
+            }
+        }
     }
 
     pub fn is_enabled(&self) -> bool {
-        self.is_enabled.get()
+        self.is_enabled
     }
 
     pub fn is_disabled(&self) -> bool {
         !self.is_enabled()
     }
 
-    pub fn enable(&self) {
-        self.is_enabled.set(true);
+    /// Whether the beam is enabled but turned off from one of its tiles, which only an agent of
+    /// its colour can do.
+    pub fn is_blocked(&self) -> bool {
+        let n_on = self.words.iter().map(|word| word.count_ones() as usize);
+        self.is_enabled && n_on.sum::<usize>() < self.len
+    }
+
+    pub fn enable(&mut self) {
+        self.is_enabled = true;
         self.turn_on(0);
     }
 
-    pub fn disable(&self) {
-        self.is_enabled.set(false);
+    pub fn disable(&mut self) {
+        self.is_enabled = false;
         self.turn_off(0);
     }
 
@@ -80,28 +141,34 @@ impl LaserBeam {
         self.laser_id
     }
 
-    pub fn set_agent_id(&self, agent_id: AgentId) {
-        self.agent_id.set(agent_id);
+    pub fn set_agent_id(&mut self, agent_id: AgentId) {
+        self.agent_id = agent_id;
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Laser {
-    beam: Rc<LaserBeam>,
+    /// The index of the beam in `World::beams`.
+    beam: usize,
+    laser_id: LaserId,
+    direction: Direction,
     wrapped: Box<Tile>,
     offset: usize,
 }
 
-impl Debug for Laser {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Laser").field("beam", &self.beam).finish()
-    }
-}
-
 impl Laser {
-    pub fn new(wrapped: Tile, beam: Rc<LaserBeam>, offset: usize) -> Self {
+    pub fn new(
+        wrapped: Tile,
+        beam: usize,
+        laser_id: LaserId,
+        direction: Direction,
+        offset: usize,
+    ) -> Self {
         Self {
             wrapped: Box::new(wrapped),
             beam,
+            laser_id,
+            direction,
             offset,
         }
     }
@@ -126,79 +193,101 @@ impl Laser {
         }
     }
 
+    pub(crate) fn gem_mut(&mut self) -> Option<&mut Gem> {
+        self.wrapped.gem_mut()
+    }
+
+    pub(crate) fn set_agent(&mut self, agent: Option<AgentId>) {
+        self.wrapped.set_agent(agent);
+    }
+
+    /// The index of the beam of the laser in `World::beams`.
+    pub fn beam(&self) -> usize {
+        self.beam
+    }
+
+    /// The position of the tile along the beam, `0` being the tile next to the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn laser_id(&self) -> LaserId {
-        self.beam.laser_id()
+        self.laser_id
     }
 
-    pub fn agent_id(&self) -> AgentId {
-        self.beam.agent_id()
+    pub fn direction(&self) -> Direction {
+        self.direction
     }
 
-    pub fn is_on(&self) -> bool {
-        self.beam.is_on(self.offset)
+    pub fn agent_id(&self, beams: &[LaserBeam]) -> AgentId {
+        beams[self.beam].agent_id()
     }
 
-    pub fn is_off(&self) -> bool {
-        !self.is_on()
+    pub fn is_on(&self, beams: &[LaserBeam]) -> bool {
+        beams[self.beam].is_on(self.offset)
     }
 
-    pub fn is_enabled(&self) -> bool {
-        self.beam.is_enabled()
+    pub fn is_off(&self, beams: &[LaserBeam]) -> bool {
+        !self.is_on(beams)
     }
 
-    pub fn is_disabled(&self) -> bool {
-        !self.is_enabled()
+    pub fn is_enabled(&self, beams: &[LaserBeam]) -> bool {
+        beams[self.beam].is_enabled()
     }
 
-    pub fn direction(&self) -> Direction {
-        self.beam.direction
+    pub fn is_disabled(&self, beams: &[LaserBeam]) -> bool {
+        !self.is_enabled(beams)
     }
 
-    pub fn turn_on(&mut self) {
-        if self.is_on() {
+    pub fn turn_on(&self, beams: &mut [LaserBeam]) {
+        if self.is_on(beams) {
             return;
         }
-        self.beam.turn_on(self.offset);
+        beams[self.beam].turn_on(self.offset);
     }
 
-    pub fn turn_off(&mut self) {
-        self.beam.turn_off(self.offset);
+    pub fn turn_off(&self, beams: &mut [LaserBeam]) {
+        beams[self.beam].turn_off(self.offset);
     }
 
-    pub fn reset(&mut self) {
-        self.turn_on();
-        self.wrapped.reset();
+    pub fn reset(&mut self, beams: &mut [LaserBeam]) {
+        self.turn_on(beams);
+        self.wrapped.reset(beams);
     }
 
-    pub fn pre_enter(&mut self, agent: &Agent) -> Result<(), RuntimeWorldError> {
-        let res = self.wrapped.pre_enter(agent);
-        if self.is_disabled() {
+    pub fn pre_enter(
+        &mut self,
+        agent: &Agent,
+        beams: &mut [LaserBeam],
+    ) -> Result<(), RuntimeWorldError> {
+        let res = self.wrapped.pre_enter(agent, beams);
+        if self.is_disabled(beams) {
             return res;
         }
-        if agent.is_alive() && agent.id() == self.agent_id() {
-            self.turn_off();
+        if agent.is_alive() && agent.id() == self.agent_id(beams) {
+            self.turn_off(beams);
         }
         res
     }
 
-    pub fn enter(&mut self, agent: &mut Agent) -> Option<WorldEvent> {
+    pub fn enter(&mut self, agent: &mut Agent, beams: &mut [LaserBeam]) -> Option<WorldEvent> {
         // Note: turning off the beam happens in `pre_enter`
-        if self.is_on() && agent.id() != self.agent_id() {
+        if self.is_on(beams) && agent.id() != self.agent_id(beams) {
             if agent.is_alive() {
                 agent.die();
-                self.turn_on();
+                self.turn_on(beams);
                 return Some(WorldEvent::AgentDied {
                     agent_id: agent.id(),
                 });
             }
             return None;
         }
-        self.wrapped.enter(agent)
+        self.wrapped.enter(agent, beams)
     }
 
-    pub fn leave(&mut self) -> AgentId {
-        self.turn_on();
-        self.wrapped.leave()
+    pub fn leave(&mut self, beams: &mut [LaserBeam]) -> AgentId {
+        self.turn_on(beams);
+        self.wrapped.leave(beams)
     }
 
     pub fn agent(&self) -> Option<AgentId> {
@@ -1,50 +1,35 @@
-use std::rc::Rc;
-
-use crate::{agent::AgentId, tiles::Direction};
-
-use super::LaserBeam;
+use crate::tiles::Direction;
 
 pub type LaserId = usize;
 
+/// The tile of a laser source. The state of its beam (colour, enabled) is the
+/// [`LaserBeam`](super::LaserBeam) at index `beam` in [`World::beams`](crate::World::beams).
 #[derive(Clone, Debug)]
 pub struct LaserSource {
-    beam: Rc<LaserBeam>,
+    beam: usize,
+    laser_id: LaserId,
+    direction: Direction,
 }
 
 impl LaserSource {
-    pub fn new(beam: Rc<LaserBeam>) -> Self {
-        Self { beam }
-    }
-
-    pub fn is_enabled(&self) -> bool {
-        self.beam.is_enabled()
+    pub fn new(beam: usize, laser_id: LaserId, direction: Direction) -> Self {
+        Self {
+            beam,
+            laser_id,
+            direction,
+        }
     }
 
-    pub fn agent_id(&self) -> AgentId {
-        self.beam.agent_id()
+    /// The index of the beam of the source in `World::beams`.
+    pub fn beam(&self) -> usize {
+        self.beam
     }
 
     pub fn direction(&self) -> Direction {
-        self.beam.direction()
+        self.direction
     }
 
     pub fn laser_id(&self) -> LaserId {
-        self.beam.laser_id()
-    }
-
-    pub fn enable(&self) {
-        self.beam.enable()
-    }
-
-    pub fn disable(&self) {
-        self.beam.disable()
-    }
-
-    pub fn set_agent_id(&self, agent_id: AgentId) {
-        self.beam.set_agent_id(agent_id)
-    }
-
-    pub fn beam(&self) -> Rc<LaserBeam> {
-        self.beam.clone()
+        self.laser_id
     }
 }
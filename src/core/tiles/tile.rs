@@ -4,9 +4,11 @@ use crate::{
 };
 use core::panic;
 
-use super::{Gem, Laser, LaserSource, Void};
+use super::{Gem, Laser, LaserBeam, LaserSource, Void};
 
-#[derive(Debug)]
+/// A tile of the grid. The beams of the lasers are owned by the `World`, which is why the
+/// operations of the agents on the tiles take them as argument.
+#[derive(Debug, Clone)]
 pub enum Tile {
     Gem(Gem),
     Floor { agent: Option<AgentId> },
@@ -18,15 +20,19 @@ pub enum Tile {
 }
 
 impl Tile {
-    pub fn pre_enter(&mut self, agent: &Agent) -> Result<(), RuntimeWorldError> {
+    pub fn pre_enter(
+        &mut self,
+        agent: &Agent,
+        beams: &mut [LaserBeam],
+    ) -> Result<(), RuntimeWorldError> {
         match self {
-            Self::Laser(laser) => laser.pre_enter(agent),
+            Self::Laser(laser) => laser.pre_enter(agent, beams),
             Self::Wall | Self::LaserSource(_) => Err(RuntimeWorldError::TileNotWalkable),
             _ => Ok(()),
         }
     }
 
-    pub fn enter(&mut self, agent: &mut Agent) -> Option<WorldEvent> {
+    pub fn enter(&mut self, agent: &mut Agent, beams: &mut [LaserBeam]) -> Option<WorldEvent> {
         match self {
             Self::Wall | Self::LaserSource(_) => panic!("Cannot enter a wall or a laser source"),
             Self::Exit { agent: slot } => {
@@ -44,18 +50,18 @@ impl Tile {
                 None
             }
             Self::Void(void) => void.enter(agent),
-            Self::Laser(laser) => laser.enter(agent),
+            Self::Laser(laser) => laser.enter(agent, beams),
             Self::Gem(gem) => gem.enter(agent),
         }
     }
 
-    pub fn leave(&mut self) -> AgentId {
+    pub fn leave(&mut self, beams: &mut [LaserBeam]) -> AgentId {
         match self {
             Self::Wall | Self::LaserSource(_) => panic!("Cannot leave a wall or a laser source"),
             Self::Exit { agent: slot } => slot.take().expect("No agent to leave"),
             Self::Floor { agent: slot } => slot.take().expect("No agent to leave"),
             Self::Void(void) => void.leave(),
-            Self::Laser(laser) => laser.leave(),
+            Self::Laser(laser) => laser.leave(beams),
             Self::Gem(gem) => gem.leave(),
         }
     }
@@ -72,14 +78,14 @@ impl Tile {
         }
     }
 
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, beams: &mut [LaserBeam]) {
         match self {
             Self::Gem(gem) => gem.reset(),
             Self::LaserSource(..) | Self::Wall => {}
             Self::Exit { agent } => *agent = None,
             Self::Floor { agent } => *agent = None,
             Self::Void(void) => void.reset(),
-            Self::Laser(laser) => laser.reset(),
+            Self::Laser(laser) => laser.reset(beams),
         }
     }
 
@@ -94,17 +100,47 @@ impl Tile {
         }
     }
 
+    /// Overwrite the agent on the tile, without any of the side effects of `enter` and `leave`.
+    pub(crate) fn set_agent(&mut self, agent: Option<AgentId>) {
+        match self {
+            Self::Gem(gem) => gem.set_agent(agent),
+            Self::Wall | Self::LaserSource(_) => {}
+            Self::Exit { agent: slot } => *slot = agent,
+            Self::Floor { agent: slot } => *slot = agent,
+            Self::Void(void) => void.set_agent(agent),
+            Self::Laser(laser) => laser.set_agent(agent),
+        }
+    }
+
+    /// The gem of the tile, if any, including gems below lasers.
+    pub(crate) fn gem(&self) -> Option<&Gem> {
+        match self {
+            Self::Gem(gem) => Some(gem),
+            Self::Laser(laser) => laser.gem(),
+            _ => None,
+        }
+    }
+
+    /// The gem of the tile, if any, including gems below lasers.
+    pub(crate) fn gem_mut(&mut self) -> Option<&mut Gem> {
+        match self {
+            Self::Gem(gem) => Some(gem),
+            Self::Laser(laser) => laser.gem_mut(),
+            _ => None,
+        }
+    }
+
     pub fn is_occupied(&self) -> bool {
         self.agent().is_some()
     }
 
-    pub fn to_file_string(&self) -> String {
+    pub fn to_file_string(&self, beams: &[LaserBeam]) -> String {
         match self {
-            Self::Laser(laser) => return laser.wrapped().to_file_string(),
+            Self::Laser(laser) => return laser.wrapped().to_file_string(beams),
             Self::LaserSource(source) => {
                 return format!(
                     "L{}{}",
-                    source.agent_id(),
+                    beams[source.beam()].agent_id(),
                     source.direction().to_file_string()
                 );
             }
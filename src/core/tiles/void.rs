@@ -1,6 +1,6 @@
 use crate::{AgentId, WorldEvent, agent::Agent};
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Void {
     agent: Option<AgentId>,
 }
@@ -25,6 +25,10 @@ impl Void {
         self.agent.take().unwrap()
     }
 
+    pub(crate) fn set_agent(&mut self, agent: Option<AgentId>) {
+        self.agent = agent;
+    }
+
     pub fn reset(&mut self) {
         self.agent = None;
     }
@@ -1,18 +1,21 @@
 /// The core logic of LLE, which should not be parametrisable.
 use itertools::{Itertools, izip};
+use rand::RngExt;
 use std::{
     fs::File,
     io::{BufReader, Read},
+    ops::{Deref, DerefMut},
 };
 
 use crate::{
-    Action, AgentId, ParseError, Position, RuntimeWorldError, WorldEvent, WorldState,
+    Action, AgentId, Bitboards, ConflictRule, DistanceMap, DistanceOptions, DistanceOrigin,
+    ParseError, Position, PositionSet, RuntimeWorldError, WorldEvent, WorldSnapshot, WorldState,
     agent::Agent,
     core::{
         levels,
-        parsing::{WorldConfig, parse},
+        parsing::{LevelMetadata, WorldConfig, parse},
     },
-    tiles::{Gem, Laser, LaserId, LaserSource, Tile},
+    tiles::{Gem, Laser, LaserBeam, LaserId, Tile},
     utils::{find_duplicates, find_duplicates_into, sample_different},
 };
 
@@ -22,9 +25,17 @@ pub struct World {
     width: usize,
     height: usize,
 
-    grid: Vec<Vec<Tile>>,
+    /// The tiles, row after row: the tile at `(i, j)` is at index `i * width + j`.
+    grid: Vec<Tile>,
     agents: Vec<Agent>,
     laser_source_positions: Vec<Position>,
+    /// The beam of each laser source, in the order of `laser_source_positions`.
+    beams: Vec<LaserBeam>,
+    /// The position of each tile of each beam, in the order of `beams` and of the offsets.
+    beam_paths: Vec<Vec<Position>>,
+    /// The tiles of each beam, in the order of `beams`.
+    beam_tiles: Vec<PositionSet>,
+    bitboards: Bitboards,
     lasers_positions: Vec<Position>,
     gems_positions: Vec<Position>,
     /// Possible random start position of each agent.
@@ -37,16 +48,31 @@ pub struct World {
     available_actions: Vec<Vec<Action>>,
     /// The actual start position of the agents since the last `reset`.
     start_positions: Vec<Position>,
-    /// Scratch buffer reused by `solve_vertex_conflicts` across steps to avoid a fresh
+    /// How moves that get in each other's way are resolved.
+    conflict_rule: ConflictRule,
+    /// Probability that the action of an agent is replaced by one of its available actions.
+    slip_probability: f64,
+    /// Whether `step` reports the `Help`, `LaserBlocked` and `LaserUnblocked` events.
+    help_events: bool,
+    /// Scratch buffer reused by `solve_conflicts` across steps to avoid a fresh
     /// allocation on every call.
     conflict_scratch: Vec<bool>,
+    /// Scratch buffer reused by `sync_beams_at` for the colours of the beams to bring in line.
+    colour_scratch: Vec<AgentId>,
     rng: rand::rngs::StdRng,
+    metadata: LevelMetadata,
+    /// Snapshots recorded by `push_snapshot`, the most recent last.
+    history: Vec<WorldSnapshot>,
+    /// Snapshots of the states left by `undo`, the most recently undone last.
+    redo_history: Vec<WorldSnapshot>,
 }
 
 impl World {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        grid: Vec<Vec<Tile>>,
+        width: usize,
+        height: usize,
+        grid: Vec<Tile>,
         gem_positions: Vec<Position>,
         random_start_positions: Vec<Vec<Position>>,
         void_positions: Vec<Position>,
@@ -54,6 +80,7 @@ impl World {
         walls_positions: Vec<Position>,
         source_positions: Vec<Position>,
         lasers_positions: Vec<Position>,
+        beams: Vec<LaserBeam>,
     ) -> Self {
         let agents: Vec<Agent> = random_start_positions
             .iter()
@@ -61,9 +88,19 @@ impl World {
             .map(|(id, _)| Agent::new(id as AgentId))
             .collect();
         let n_agents = agents.len();
+        let bitboards = Bitboards::new(
+            height,
+            width,
+            &walls_positions,
+            &source_positions,
+            &void_positions,
+            &exit_positions,
+            &lasers_positions,
+            &gem_positions,
+        );
         let mut w = Self {
-            width: grid[0].len(),
-            height: grid.len(),
+            width,
+            height,
             gems_positions: gem_positions,
             agents_positions: Vec::with_capacity(n_agents),
             random_start_positions,
@@ -74,15 +111,44 @@ impl World {
             grid,
             start_positions: Vec::with_capacity(n_agents),
             available_actions: vec![Vec::with_capacity(5); n_agents], // There are 5 actions
+            conflict_rule: ConflictRule::default(),
+            slip_probability: 0.0,
+            help_events: false,
             conflict_scratch: Vec::with_capacity(n_agents),
+            colour_scratch: vec![],
             laser_source_positions: source_positions,
+            beams,
+            beam_paths: vec![],
+            beam_tiles: vec![],
+            bitboards,
             lasers_positions,
             rng: rand::SeedableRng::seed_from_u64(0u64),
+            metadata: LevelMetadata::default(),
+            history: vec![],
+            redo_history: vec![],
         };
+        let mut beam_paths = w
+            .beams
+            .iter()
+            .map(|beam| Vec::with_capacity(beam.len()))
+            .collect_vec();
+        for (pos, laser) in w.lasers().into_iter().sorted_by_key(|(_, l)| l.offset()) {
+            beam_paths[laser.beam()].push(pos);
+        }
+        w.beam_tiles = beam_paths
+            .iter()
+            .map(|path| PositionSet::from_positions(height, width, path.iter().copied()))
+            .collect();
+        w.beam_paths = beam_paths;
         w.reset();
         w
     }
 
+    #[inline]
+    fn index(&self, pos: &Position) -> usize {
+        pos.i * self.width + pos.j
+    }
+
     pub fn n_agents(&self) -> usize {
         self.agents.len()
     }
@@ -107,6 +173,26 @@ impl World {
             self.wall_positions.clone(),
             source_configs,
         )
+        .with_metadata(self.metadata.clone())
+    }
+
+    /// The name, author and horizon hint of the level, if the level file provides them.
+    pub fn metadata(&self) -> &LevelMetadata {
+        &self.metadata
+    }
+
+    pub fn conflict_rule(&self) -> ConflictRule {
+        self.conflict_rule
+    }
+
+    /// Change how conflicting moves are resolved from the next step on (see [`ConflictRule`]).
+    pub fn set_conflict_rule(&mut self, conflict_rule: ConflictRule) {
+        self.conflict_rule = conflict_rule;
+        self.compute_available_actions();
+    }
+
+    pub fn set_metadata(&mut self, metadata: LevelMetadata) {
+        self.metadata = metadata;
     }
 
     /// The world string, taking into account the fact that some tiles may have changed (laser direction or colour).
@@ -130,36 +216,50 @@ impl World {
         // Important: gems can be wrapped into lasers !
         self.gems_positions
             .iter()
-            .map(|pos| match &self.grid[pos.i][pos.j] {
-                Tile::Gem(gem) => gem,
-                Tile::Laser(laser) => laser.gem().unwrap(),
-                _ => unreachable!(),
-            })
+            .map(|pos| self.grid[self.index(pos)].gem().unwrap())
             .collect()
     }
 
-    pub fn sources(&self) -> impl Iterator<Item = (Position, &LaserSource)> + '_ {
-        self.laser_source_positions.iter().map(|&pos| {
-            if let Tile::LaserSource(source) = &self.grid[pos.i][pos.j] {
-                (pos, source)
-            } else {
-                unreachable!()
-            }
-        })
+    /// The position and the beam of each laser source.
+    pub fn sources(&self) -> impl Iterator<Item = (Position, &LaserBeam)> + '_ {
+        izip!(self.laser_source_positions.iter().copied(), &self.beams)
     }
 
-    pub fn source_at(&self, pos: Position) -> Option<&LaserSource> {
-        if let Tile::LaserSource(source) = &self.grid[pos.i][pos.j] {
-            Some(source)
-        } else {
-            None
+    /// The beam of the laser source at `pos`, if any.
+    pub fn source_at(&self, pos: Position) -> Option<&LaserBeam> {
+        match self.at(&pos) {
+            Some(Tile::LaserSource(source)) => Some(&self.beams[source.beam()]),
+            _ => None,
+        }
+    }
+
+    /// The beam of the laser source at `pos`, if any, to enable, disable or recolour it. The
+    /// bitboards of the world follow the beam once it is dropped.
+    pub fn source_at_mut(&mut self, pos: Position) -> Option<SourceBeamMut<'_>> {
+        match self.at(&pos) {
+            Some(Tile::LaserSource(source)) => {
+                let beam = source.beam();
+                let colour = self.beams[beam].agent_id();
+                Some(SourceBeamMut {
+                    world: self,
+                    beam,
+                    colour,
+                })
+            }
+            _ => None,
         }
     }
 
+    /// The beams of the laser sources, in the order of `sources`. The `Laser` and `LaserSource`
+    /// tiles refer to their beam by its index in this slice.
+    pub fn beams(&self) -> &[LaserBeam] {
+        &self.beams
+    }
+
     pub fn lasers(&self) -> Vec<(Position, &Laser)> {
         let mut lasers = vec![];
         for pos in &self.lasers_positions {
-            if let Tile::Laser(laser) = &self.grid[pos.i][pos.j] {
+            if let Tile::Laser(laser) = &self.grid[self.index(pos)] {
                 lasers.push((*pos, laser));
                 if let Tile::Laser(wrapped) = laser.wrapped() {
                     lasers.push((*pos, wrapped));
@@ -201,8 +301,9 @@ impl World {
         }
         // Replace current exits by floor tiles
         for pos in &self.exits {
-            let tile = self.grid[pos.i].remove(pos.j);
-            let replacement = match tile {
+            let index = self.index(pos);
+            let tile = &mut self.grid[index];
+            let replacement = match std::mem::replace(tile, Tile::Wall) {
                 Tile::Exit { agent } => Tile::Floor { agent },
                 Tile::Laser(mut laser) => {
                     laser.set_tile(Tile::Floor {
@@ -212,13 +313,14 @@ impl World {
                 }
                 other => panic!("Tile is not an exit: {:?}", other),
             };
-            self.grid[pos.i].insert(pos.j, replacement);
+            *tile = replacement;
         }
         // Set new exits
         self.exits = exits;
         for pos in &self.exits {
-            let tile = self.grid[pos.i].remove(pos.j);
-            let replacement = match tile {
+            let index = self.index(pos);
+            let tile = &mut self.grid[index];
+            let replacement = match std::mem::replace(tile, Tile::Wall) {
                 Tile::Floor { agent } => Tile::Exit { agent },
                 Tile::Laser(mut laser) => {
                     laser.set_tile(Tile::Exit {
@@ -228,8 +330,9 @@ impl World {
                 }
                 other => panic!("Tile is not a floor: {:?}", other),
             };
-            self.grid[pos.i].insert(pos.j, replacement);
+            *tile = replacement;
         }
+        self.bitboards.set_exits(&self.exits);
         Ok(())
     }
 
@@ -263,15 +366,7 @@ impl World {
     }
 
     pub fn n_gems_collected(&self) -> usize {
-        let mut res = 0;
-        for pos in &self.gems_positions {
-            if let Tile::Gem(gem) = &self.grid[pos.i][pos.j]
-                && gem.is_collected()
-            {
-                res += 1;
-            }
-        }
-        res
+        self.n_gems() - self.bitboards.gems().size()
     }
 
     pub fn n_agents_arrived(&self) -> usize {
@@ -298,6 +393,17 @@ impl World {
         self.random_start_positions.clone()
     }
 
+    /// Every start configuration that [`World::reset`] can sample: one of its possible starts per
+    /// agent, with no two agents on the same position. Their number is up to the product of the
+    /// numbers of possible starts.
+    pub fn start_assignments(&self) -> impl Iterator<Item = Vec<Position>> + '_ {
+        self.random_start_positions
+            .iter()
+            .map(|starts| starts.iter().copied())
+            .multi_cartesian_product()
+            .filter(|assignment| assignment.iter().all_unique())
+    }
+
     pub fn void_positions(&self) -> Vec<Position> {
         self.void_positions.clone()
     }
@@ -311,7 +417,7 @@ impl World {
         let agents_positions = (0..self.height)
             .cartesian_product(0..self.width)
             .map(|(i, j)| Position { i, j })
-            .filter(|pos| !self.wall_positions.contains(pos))
+            .filter(|pos| !self.bitboards.walls().contains(pos))
             .combinations(self.n_agents());
 
         let collection_status = (0..self.n_gems())
@@ -350,9 +456,11 @@ impl World {
             if agent.is_alive() && !agent.has_arrived() {
                 for action in [Action::North, Action::East, Action::South, Action::West] {
                     if let Ok(pos) = &action + agent_pos
-                        && let Some(tile) = self.at(&pos)
-                        && tile.is_walkable()
-                        && !tile.is_occupied()
+                        && pos.i < self.height
+                        && pos.j < self.width
+                        && self.bitboards.is_walkable(&pos)
+                        && (self.conflict_rule.allows_following()
+                            || !self.bitboards.occupied().contains(&pos))
                     {
                         agent_actions.push(action);
                     }
@@ -362,16 +470,41 @@ impl World {
         self.available_actions = buffer;
     }
 
-    fn solve_vertex_conflicts(&mut self, new_pos: &mut [Position]) {
+    /// Send back to their current position the agents whose move conflicts with another agent
+    /// under `self.conflict_rule`, until no conflict is left.
+    ///
+    /// An agent that moves into a tile that another agent also ends in bounces back, unless it has
+    /// priority over every other such agent, all of them moving. With following allowed, two agents
+    /// swapping their tiles both bounce back as well.
+    fn solve_conflicts(&mut self, new_pos: &mut [Position]) {
+        let old_pos = &self.agents_positions;
         let mut conflict = true;
-
         while conflict {
             conflict = false;
             find_duplicates_into(new_pos, &mut self.conflict_scratch);
-            for (i, is_duplicate) in self.conflict_scratch.iter().enumerate() {
-                if *is_duplicate {
+            for i in 0..new_pos.len() {
+                if !self.conflict_scratch[i] || new_pos[i] == old_pos[i] {
+                    continue;
+                }
+                let has_priority = self.conflict_rule == ConflictRule::Priority
+                    && (0..new_pos.len()).all(|j| {
+                        j == i || new_pos[j] != new_pos[i] || (j > i && new_pos[j] != old_pos[j])
+                    });
+                if !has_priority {
+                    new_pos[i] = old_pos[i];
                     conflict = true;
-                    new_pos[i] = self.agents_positions[i];
+                }
+            }
+            if self.conflict_rule.allows_following() {
+                for (i, j) in (0..new_pos.len()).tuple_combinations() {
+                    if new_pos[i] != old_pos[i]
+                        && new_pos[i] == old_pos[j]
+                        && new_pos[j] == old_pos[i]
+                    {
+                        new_pos[i] = old_pos[i];
+                        new_pos[j] = old_pos[j];
+                        conflict = true;
+                    }
                 }
             }
         }
@@ -379,15 +512,86 @@ impl World {
 
     /// Creates an iterator over all tiles in the grid with their (i, j) coordinates
     pub fn tiles(&self) -> Vec<(Position, &Tile)> {
-        let mut res = vec![];
-        for (i, row) in self.grid.iter().enumerate() {
-            for (j, tile) in row.iter().enumerate() {
-                res.push((Position { i, j }, tile));
+        self.grid
+            .iter()
+            .enumerate()
+            .map(|(index, tile)| {
+                let pos = Position {
+                    i: index / self.width,
+                    j: index % self.width,
+                };
+                (pos, tile)
+            })
+            .collect()
+    }
+
+    /// The bitboards of the world, in sync with its tiles.
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    /// Breadth-first distances from `from` (a position or all the exits) in the current state of
+    /// the world. Walls and laser sources are never walked on, and `options` tells how to treat
+    /// voids, beams and agents.
+    ///
+    /// Colours in `options` without any laser add no obstacle. A position that can not be walked
+    /// on, such as a wall, is a valid origin that only reaches itself: its map is empty except
+    /// for the origin, at distance 0.
+    pub fn distance_map(
+        &self,
+        from: impl Into<DistanceOrigin>,
+        options: &DistanceOptions,
+    ) -> Result<DistanceMap, RuntimeWorldError> {
+        let origin = from.into();
+        if let DistanceOrigin::Position(pos) = origin
+            && (pos.i >= self.height || pos.j >= self.width)
+        {
+            return Err(RuntimeWorldError::OutOfWorldPosition { position: pos });
+        }
+        let mut blocked = self.bitboards.walls().clone();
+        blocked.union_with(self.bitboards.sources());
+        if !options.ignore_voids() {
+            blocked.union_with(self.bitboards.voids());
+        }
+        for &colour in options.lasers_as_walls() {
+            if let Some(coverage) = self.bitboards.beam_coverage(colour) {
+                blocked.union_with(coverage);
+            }
+        }
+        if options.agents_as_obstacles() {
+            let mut occupied = self.bitboards.occupied().clone();
+            if let DistanceOrigin::Position(pos) = origin {
+                occupied.remove(&pos);
             }
+            blocked.union_with(&occupied);
         }
-        res
+        Ok(DistanceMap::compute(
+            origin,
+            &blocked,
+            self.bitboards.exits(),
+            self.height,
+            self.width,
+        ))
+    }
+
+    pub fn help_events(&self) -> bool {
+        self.help_events
     }
 
+    /// Make `step` report, after the other events, the lasers that agents start or stop to block
+    /// (`LaserBlocked` and `LaserUnblocked`) and then the help in the state it reaches (`Help`).
+    ///
+    /// Help is defined as in the solver (`VarKey::Help`): an alive agent stands on a tile of an
+    /// enabled beam of the colour of another agent, the helper, who must therefore block it.
+    pub fn set_help_events(&mut self, enabled: bool) {
+        self.help_events = enabled;
+    }
+
+    /// The tile at `pos`, if it is in the world.
+    ///
+    /// There is no `at_mut` since the bitboards: changing a tile behind the back of the world
+    /// would leave them out of sync. Use [`World::collect_gem`], [`World::source_at_mut`] or
+    /// [`World::set_exit_positions`] instead.
     pub fn at(&self, pos: &Position) -> Option<&Tile> {
         if pos.i >= self.height {
             return None;
@@ -395,24 +599,45 @@ impl World {
         if pos.j >= self.width {
             return None;
         }
-        Some(&self.grid[pos.i][pos.j])
+        Some(&self.grid[self.index(pos)])
     }
 
-    pub fn at_mut(&mut self, pos: &Position) -> Option<&mut Tile> {
-        if pos.i >= self.height {
-            return None;
+    pub fn slip_probability(&self) -> f64 {
+        self.slip_probability
+    }
+
+    /// Make the transitions stochastic: at each step, the action of each agent slips with
+    /// probability `probability` and is replaced by one of its available actions (possibly `Stay`
+    /// or the chosen action itself), drawn uniformly with the seeded rng of the world. The default,
+    /// `0`, keeps the world deterministic.
+    pub fn set_slip_probability(&mut self, probability: f64) -> Result<(), RuntimeWorldError> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(RuntimeWorldError::InvalidSlipProbability { probability });
         }
-        if pos.j >= self.width {
-            return None;
+        self.slip_probability = probability;
+        Ok(())
+    }
+
+    /// Collect the gem at `pos`, as if an agent had walked on it.
+    pub fn collect_gem(&mut self, pos: Position) -> Result<(), RuntimeWorldError> {
+        if pos.i >= self.height || pos.j >= self.width {
+            return Err(RuntimeWorldError::OutOfWorldPosition { position: pos });
+        }
+        let index = self.index(&pos);
+        match self.grid[index].gem_mut() {
+            Some(gem) => gem.collect(),
+            None => return Err(RuntimeWorldError::NotAGem { position: pos }),
         }
-        Some(&mut self.grid[pos.i][pos.j])
+        self.bitboards.gems_mut().remove(&pos);
+        Ok(())
     }
 
+    /// Put the world back in a start state. This also clears the history of `push_snapshot`.
     pub fn reset(&mut self) {
-        for row in self.grid.iter_mut() {
-            for tile in row.iter_mut() {
-                tile.reset();
-            }
+        self.history.clear();
+        self.redo_history.clear();
+        for tile in &mut self.grid {
+            tile.reset(&mut self.beams);
         }
         // Reset (dead=false) the agents such that they can block lasers on spacwn
         for agent in &mut self.agents {
@@ -420,19 +645,120 @@ impl World {
         }
         self.start_positions = sample_different(&mut self.rng, &self.random_start_positions);
         self.agents_positions = self.start_positions.clone();
+        self.bitboards.occupied_mut().clear();
         for (pos, agent) in izip!(&self.agents_positions, &self.agents) {
-            self.grid[pos.i][pos.j]
-                .pre_enter(agent)
+            let index = self.index(pos);
+            self.grid[index]
+                .pre_enter(agent, &mut self.beams)
                 .expect("The agent should be able to pre-enter");
         }
-        for (pos, agent) in izip!(&self.agents_positions, &mut self.agents) {
-            self.grid[pos.i][pos.j].enter(agent);
+        for (agent_id, pos) in self.agents_positions.iter().enumerate() {
+            let index = self.index(pos);
+            self.grid[index].enter(&mut self.agents[agent_id], &mut self.beams);
         }
+        self.sync_agent_tiles(&[]);
+        self.sync_gems();
+        self.sync_all_beam_coverage();
         self.compute_available_actions();
     }
 
     /// Perform one step in the environment and return the corresponding events.
+    ///
+    /// If the world has a slip probability, the actions actually taken may differ from `actions`,
+    /// in which case a `WorldEvent::AgentSlipped` is reported first for each agent that slipped.
     pub fn step(&mut self, actions: &[Action]) -> Result<Vec<WorldEvent>, RuntimeWorldError> {
+        self.check_actions(actions)?;
+        let mut actions = actions.to_vec();
+        let mut events = self.slip(&mut actions);
+        let blocked = self
+            .help_events
+            .then(|| self.beams.iter().map(LaserBeam::is_blocked).collect_vec());
+        events.extend(self.apply_actions(&actions)?);
+        if let Some(blocked) = blocked {
+            events.extend(self.cooperation_events(&blocked));
+        }
+        Ok(events)
+    }
+
+    /// The `LaserBlocked` and `LaserUnblocked` events of the beams whose status differs from
+    /// `blocked_before`, followed by the `Help` events of the current state.
+    fn cooperation_events(&self, blocked_before: &[bool]) -> Vec<WorldEvent> {
+        let mut events = vec![];
+        for (beam, &was_blocked) in izip!(&self.beams, blocked_before) {
+            let (agent_id, laser_id) = (beam.agent_id(), beam.laser_id());
+            match (was_blocked, beam.is_blocked()) {
+                (false, true) => events.push(WorldEvent::LaserBlocked { agent_id, laser_id }),
+                (true, false) => events.push(WorldEvent::LaserUnblocked { agent_id, laser_id }),
+                _ => {}
+            }
+        }
+        for (beam, tiles) in izip!(&self.beams, &self.beam_tiles) {
+            if beam.is_disabled() {
+                continue;
+            }
+            for (beneficiary, (agent, pos)) in
+                izip!(&self.agents, &self.agents_positions).enumerate()
+            {
+                if agent.is_alive() && beneficiary != beam.agent_id() && tiles.contains(pos) {
+                    events.push(WorldEvent::Help {
+                        helper: beam.agent_id(),
+                        beneficiary,
+                        laser_id: beam.laser_id(),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// The distribution `P(s' | s, a)` of the states reached when the agents take `actions` from
+    /// the current state `s` of the world, accounting for the slip probability.
+    ///
+    /// Each reachable state is given once, with a non-zero probability. The world is left in its
+    /// current state and its rng is not used.
+    pub fn transitions(
+        &mut self,
+        actions: &[Action],
+    ) -> Result<Vec<(WorldState, f64)>, RuntimeWorldError> {
+        self.check_actions(actions)?;
+        let state = self.get_state();
+        let per_agent = izip!(actions, &self.available_actions)
+            .map(|(action, availables)| {
+                // Slipping draws uniformly among all the available actions, `action` included.
+                let slip = self.slip_probability / availables.len() as f64;
+                availables
+                    .iter()
+                    .map(|a| {
+                        if a == action {
+                            (*a, 1.0 - self.slip_probability + slip)
+                        } else {
+                            (*a, slip)
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<(Action, f64)>>>();
+
+        let mut distribution: Vec<(WorldState, f64)> = vec![];
+        for joint in per_agent.iter().map(|a| a.iter()).multi_cartesian_product() {
+            let probability: f64 = joint.iter().map(|(_, p)| p).product();
+            if probability == 0.0 {
+                continue;
+            }
+            let joint_actions = joint.iter().map(|(a, _)| *a).collect_vec();
+            self.set_state(&state)?;
+            self.apply_actions(&joint_actions)?;
+            let next = self.get_state();
+            match distribution.iter_mut().find(|(s, _)| *s == next) {
+                Some((_, p)) => *p += probability,
+                None => distribution.push((next, probability)),
+            }
+        }
+        self.set_state(&state)?;
+        Ok(distribution)
+    }
+
+    fn check_actions(&self, actions: &[Action]) -> Result<(), RuntimeWorldError> {
         if self.n_agents() != actions.len() {
             return Err(RuntimeWorldError::InvalidNumberOfActions {
                 given: actions.len(),
@@ -451,6 +777,34 @@ impl World {
                 });
             }
         }
+        Ok(())
+    }
+
+    /// Replace, with the slip probability, the action of each agent by one of its available
+    /// actions and return an `AgentSlipped` event for each action that changed. An agent that
+    /// slips may draw its chosen action again, in which case it takes that action as usual.
+    fn slip(&mut self, actions: &mut [Action]) -> Vec<WorldEvent> {
+        let mut events = vec![];
+        if self.slip_probability == 0.0 {
+            return events;
+        }
+        for (agent_id, (action, availables)) in
+            izip!(actions.iter_mut(), &self.available_actions).enumerate()
+        {
+            if !self.rng.random_bool(self.slip_probability) {
+                continue;
+            }
+            let drawn = availables[self.rng.random_range(0..availables.len())];
+            if drawn != *action {
+                *action = drawn;
+                events.push(WorldEvent::AgentSlipped { agent_id });
+            }
+        }
+        events
+    }
+
+    /// Move the agents according to `actions`, which must be available, and return the events.
+    fn apply_actions(&mut self, actions: &[Action]) -> Result<Vec<WorldEvent>, RuntimeWorldError> {
         let mut new_positions = self
             .agents_positions
             .iter()
@@ -458,18 +812,22 @@ impl World {
             .map(|(pos, action)| action + pos)
             .collect::<Result<Vec<Position>, RuntimeWorldError>>()?;
 
-        // Check for vertex conflicts
+        // Check for vertex conflicts (and swaps if following is allowed)
         // If a new_pos occurs more than once, then set it back to its original position
-        self.solve_vertex_conflicts(&mut new_positions);
+        self.solve_conflicts(&mut new_positions);
         let (mut events, mut agent_died) = self.move_agents(&new_positions)?;
-        self.agents_positions.clone_from(&new_positions);
+        let left = std::mem::replace(&mut self.agents_positions, new_positions);
         // At this stage, all agents are on their new positions.
         // However, some events (death) could still happen if an agent has died.
         while agent_died {
-            let (additional_events, died2) = self.move_agents(&new_positions)?;
+            let positions = self.agents_positions.clone();
+            let (additional_events, died2) = self.move_agents(&positions)?;
             events.extend(additional_events);
             agent_died = died2;
         }
+        // The agents only changed the tiles they left and the ones they are on.
+        self.sync_agent_tiles(&left);
+        self.sync_beams_at(&left);
         self.compute_available_actions();
         Ok(events)
     }
@@ -481,20 +839,24 @@ impl World {
         // Leave old position
         for (agent, pos) in izip!(&self.agents, &self.agents_positions) {
             if agent.is_alive() {
-                self.grid[pos.i][pos.j].leave();
+                let index = self.index(pos);
+                self.grid[index].leave(&mut self.beams);
             }
         }
         // Pre-enter
         for (agent, pos) in izip!(&self.agents, new_positions) {
-            self.grid[pos.i][pos.j]
-                .pre_enter(agent)
+            let index = self.index(pos);
+            self.grid[index]
+                .pre_enter(agent, &mut self.beams)
                 .expect("When moving agents, the pre-enter should not fail");
         }
         // Enter
         let mut events = vec![];
         let mut agent_died = false;
-        for (agent, pos) in izip!(&mut self.agents, new_positions) {
-            if let Some(event) = self.grid[pos.i][pos.j].enter(agent) {
+        for (agent_id, pos) in new_positions.iter().enumerate() {
+            let index = self.index(pos);
+            if let Some(event) = self.grid[index].enter(&mut self.agents[agent_id], &mut self.beams)
+            {
                 if let WorldEvent::AgentDied { .. } = event {
                     agent_died = true;
                 }
@@ -507,11 +869,16 @@ impl World {
     pub fn get_state(&self) -> WorldState {
         WorldState {
             agents_positions: self.agents_positions.clone(),
-            gems_collected: self.gems().iter().map(|gem| gem.is_collected()).collect(),
+            gems_collected: self
+                .gems_positions
+                .iter()
+                .map(|pos| !self.bitboards.gems().contains(pos))
+                .collect(),
             agents_alive: self.agents.iter().map(|agent| agent.is_alive()).collect(),
         }
     }
 
+    /// Put the world in `state`. If it succeeds, this also clears the history of `push_snapshot`.
     pub fn set_state(&mut self, state: &WorldState) -> Result<Vec<WorldEvent>, RuntimeWorldError> {
         if state.gems_collected.len() != self.n_gems() {
             return Err(RuntimeWorldError::InvalidNumberOfGems {
@@ -541,19 +908,20 @@ impl World {
         let current_state = self.get_state();
 
         // Reset tiles and agents (but do not enter the new tiles)
-        for row in &mut self.grid {
-            for tile in row {
-                tile.reset();
-            }
+        for tile in &mut self.grid {
+            tile.reset(&mut self.beams);
         }
+        self.bitboards.occupied_mut().clear();
         // Collect the necessary gems BEFORE entering the tiles with the agents
         for (pos, &collect) in izip!(&self.gems_positions, &state.gems_collected) {
-            if collect && let Tile::Gem(gem) = &mut self.grid[pos.i][pos.j] {
+            let index = self.index(pos);
+            if collect && let Some(gem) = self.grid[index].gem_mut() {
                 gem.collect();
             }
         }
         for (pos, agent) in izip!(&state.agents_positions, &self.agents) {
-            if let Err(error) = self.grid[pos.i][pos.j].pre_enter(agent) {
+            let index = self.index(pos);
+            if let Err(error) = self.grid[index].pre_enter(agent, &mut self.beams) {
                 let reason = match error {
                     RuntimeWorldError::TileNotWalkable => "The tile is not walkable",
                     _ => "Unknown reason",
@@ -569,14 +937,16 @@ impl World {
         }
         // Set the agents positions after the pre-enter in case it fails
         self.agents_positions = state.agents_positions.clone();
+        self.history.clear();
+        self.redo_history.clear();
         let mut events = vec![];
-        for (pos, alive, agent) in izip!(
-            &self.agents_positions,
-            &state.agents_alive,
-            &mut self.agents
-        ) {
+        for (agent_id, (pos, alive)) in
+            izip!(&self.agents_positions, &state.agents_alive).enumerate()
+        {
+            let index = self.index(pos);
+            let agent = &mut self.agents[agent_id];
             agent.reset();
-            if let Some(event) = self.grid[pos.i][pos.j].enter(agent) {
+            if let Some(event) = self.grid[index].enter(agent, &mut self.beams) {
                 events.push(event);
             }
             // If agents were specifically set to be dead, then do so.
@@ -584,6 +954,9 @@ impl World {
                 agent.die();
             }
         }
+        self.sync_agent_tiles(&[]);
+        self.sync_gems();
+        self.sync_all_beam_coverage();
 
         let actual_state = self.get_state();
         if actual_state != *state {
@@ -596,6 +969,193 @@ impl World {
         Ok(events)
     }
 
+    /// Take a snapshot of the world, which `restore` puts back much faster than `set_state` does.
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            agents: self.agents.clone(),
+            agents_positions: self.agents_positions.clone(),
+            tile_agents: self
+                .agents_positions
+                .iter()
+                .map(|pos| self.grid[self.index(pos)].agent())
+                .collect(),
+            gems_collected: self.gems().iter().map(|gem| gem.is_collected()).collect(),
+            beams: self.beams.clone(),
+            beam_coverage: self.bitboards.beams().to_vec(),
+            available_actions: self.available_actions.clone(),
+        }
+    }
+
+    /// Put the world back in the state of `snapshot`, which must come from this world (or a clone
+    /// of it). The history of `push_snapshot` is left untouched.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) -> Result<(), RuntimeWorldError> {
+        if snapshot.agents.len() != self.n_agents()
+            || snapshot.gems_collected.len() != self.n_gems()
+            || snapshot.beams.len() != self.beams.len()
+        {
+            return Err(RuntimeWorldError::InvalidSnapshot {
+                reason: "The snapshot was taken in a world with a different layout".into(),
+            });
+        }
+        // Only the tiles of the agents hold an agent, so they are the only ones to clear.
+        for pos in &self.agents_positions {
+            let index = self.index(pos);
+            self.grid[index].set_agent(None);
+            self.bitboards.occupied_mut().remove(pos);
+        }
+        for (pos, agent) in izip!(&snapshot.agents_positions, &snapshot.tile_agents) {
+            let index = self.index(pos);
+            self.grid[index].set_agent(*agent);
+            if agent.is_some() {
+                self.bitboards.occupied_mut().insert(*pos);
+            }
+        }
+        for (pos, &collected) in izip!(&self.gems_positions, &snapshot.gems_collected) {
+            let index = self.index(pos);
+            if let Some(gem) = self.grid[index].gem_mut() {
+                gem.set_collected(collected);
+            }
+            let gems = self.bitboards.gems_mut();
+            if collected {
+                gems.remove(pos);
+            } else {
+                gems.insert(*pos);
+            }
+        }
+        self.beams.clone_from(&snapshot.beams);
+        self.bitboards
+            .beams_mut()
+            .clone_from(&snapshot.beam_coverage);
+        self.agents.clone_from(&snapshot.agents);
+        self.agents_positions.clone_from(&snapshot.agents_positions);
+        self.available_actions
+            .clone_from(&snapshot.available_actions);
+        Ok(())
+    }
+
+    /// Bring the occupancy and the gems of the bitboards in line with the tiles left by the agents,
+    /// `left`, and with the tiles they are on.
+    fn sync_agent_tiles(&mut self, left: &[Position]) {
+        for pos in left.iter().chain(&self.agents_positions) {
+            let tile = &self.grid[self.index(pos)];
+            let (occupied, collected) = (
+                tile.is_occupied(),
+                tile.gem().is_some_and(Gem::is_collected),
+            );
+            if occupied {
+                self.bitboards.occupied_mut().insert(*pos);
+            } else {
+                self.bitboards.occupied_mut().remove(pos);
+            }
+            if collected {
+                self.bitboards.gems_mut().remove(pos);
+            }
+        }
+    }
+
+    /// Bring the bitboard of the gems in line with the tiles, after they were all reset.
+    fn sync_gems(&mut self) {
+        for pos in &self.gems_positions {
+            let collected = self.grid[self.index(pos)]
+                .gem()
+                .is_some_and(Gem::is_collected);
+            let gems = self.bitboards.gems_mut();
+            if collected {
+                gems.remove(pos);
+            } else {
+                gems.insert(*pos);
+            }
+        }
+    }
+
+    /// Bring the beam coverage in line with the beams that cross the tiles left by the agents,
+    /// `left`, and the tiles they are on, which are the only beams that moving agents turn on or
+    /// off.
+    fn sync_beams_at(&mut self, left: &[Position]) {
+        let mut colours = std::mem::take(&mut self.colour_scratch);
+        colours.clear();
+        for pos in left.iter().chain(&self.agents_positions) {
+            if !self.bitboards.lasers().contains(pos) {
+                continue;
+            }
+            if let Tile::Laser(laser) = &self.grid[self.index(pos)] {
+                colours.push(laser.agent_id(&self.beams));
+                if let Tile::Laser(wrapped) = laser.wrapped() {
+                    colours.push(wrapped.agent_id(&self.beams));
+                }
+            }
+        }
+        colours.sort_unstable();
+        colours.dedup();
+        for &colour in &colours {
+            self.sync_beam_coverage(colour);
+        }
+        self.colour_scratch = colours;
+    }
+
+    /// Recompute the coverage of colour `colour` from the beams of that colour.
+    fn sync_beam_coverage(&mut self, colour: AgentId) {
+        let coverage = self.bitboards.beam_coverage_mut(colour);
+        coverage.clear();
+        for (beam, path, tiles) in izip!(&self.beams, &self.beam_paths, &self.beam_tiles) {
+            if beam.agent_id() == colour {
+                add_coverage(coverage, beam, path, tiles);
+            }
+        }
+    }
+
+    /// Recompute the coverage of every colour, after all the beams changed.
+    fn sync_all_beam_coverage(&mut self) {
+        self.bitboards.clear_beams();
+        for (beam, path, tiles) in izip!(&self.beams, &self.beam_paths, &self.beam_tiles) {
+            let coverage = self.bitboards.beam_coverage_mut(beam.agent_id());
+            add_coverage(coverage, beam, path, tiles);
+        }
+    }
+
+    /// Record a snapshot of the world that the next `undo` goes back to, e.g. before a `step`.
+    /// This discards the states that could be redone.
+    pub fn push_snapshot(&mut self) {
+        self.history.push(self.snapshot());
+        self.redo_history.clear();
+    }
+
+    /// Go back to the last snapshot recorded by `push_snapshot` and remove it from the history.
+    /// The current state can then be restored with `redo`.
+    ///
+    /// Returns `false`, and leaves the world untouched, if there is no snapshot to go back to.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(snapshot) => {
+                self.redo_history.push(self.snapshot());
+                self.restore(&snapshot)
+                    .expect("Snapshots of the history come from this world");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Go back to the state left by the last `undo`.
+    ///
+    /// Returns `false`, and leaves the world untouched, if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_history.pop() {
+            Some(snapshot) => {
+                self.history.push(self.snapshot());
+                self.restore(&snapshot)
+                    .expect("Snapshots of the history come from this world");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The number of snapshots that `undo` can go back to.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
     pub fn get_level(level: usize) -> Result<Self, ParseError> {
         let content = levels::LEVELS
             .get(level - 1)
@@ -642,12 +1202,95 @@ impl TryFrom<&str> for World {
     }
 }
 
+/// Add to `coverage` the tiles where `beam` is on, given the positions of its tiles in the order
+/// of the offsets (`path`) and as a set (`tiles`). An unblocked beam is on all its tiles.
+fn add_coverage(
+    coverage: &mut PositionSet,
+    beam: &LaserBeam,
+    path: &[Position],
+    tiles: &PositionSet,
+) {
+    if beam.is_disabled() {
+        return;
+    }
+    if !beam.is_blocked() {
+        coverage.union_with(tiles);
+        return;
+    }
+    for offset in beam.on_offsets() {
+        coverage.insert(path[offset]);
+    }
+}
+
+/// The beam of a laser source, borrowed mutably with [`World::source_at_mut`]. When it is
+/// dropped, the beam coverage of the world is brought in line with the beam, which may have been
+/// enabled, disabled or recoloured.
+pub struct SourceBeamMut<'w> {
+    world: &'w mut World,
+    beam: usize,
+    /// The colour of the beam when it was borrowed.
+    colour: AgentId,
+}
+
+impl Deref for SourceBeamMut<'_> {
+    type Target = LaserBeam;
+
+    fn deref(&self) -> &LaserBeam {
+        &self.world.beams[self.beam]
+    }
+}
+
+impl DerefMut for SourceBeamMut<'_> {
+    fn deref_mut(&mut self) -> &mut LaserBeam {
+        &mut self.world.beams[self.beam]
+    }
+}
+
+impl Drop for SourceBeamMut<'_> {
+    fn drop(&mut self) {
+        let colour = self.world.beams[self.beam].agent_id();
+        self.world.sync_beam_coverage(self.colour);
+        if colour != self.colour {
+            self.world.sync_beam_coverage(colour);
+        }
+    }
+}
+
+/// A memberwise copy of the world, which the clone can then be stepped independently of.
+/// The random generator can not be cloned: the clone gets a new one, seeded with `0` like a new
+/// world (see `World::seed`). The clone starts with an empty undo and redo history, so that
+/// cloning stays cheap however many snapshots the world recorded.
 impl Clone for World {
     fn clone(&self) -> Self {
-        let state = self.get_state();
-        let mut clone = self.get_config().into_world().unwrap();
-        clone.set_state(&state).unwrap();
-        clone
+        Self {
+            width: self.width,
+            height: self.height,
+            grid: self.grid.clone(),
+            agents: self.agents.clone(),
+            laser_source_positions: self.laser_source_positions.clone(),
+            beams: self.beams.clone(),
+            beam_paths: self.beam_paths.clone(),
+            beam_tiles: self.beam_tiles.clone(),
+            bitboards: self.bitboards.clone(),
+            lasers_positions: self.lasers_positions.clone(),
+            gems_positions: self.gems_positions.clone(),
+            random_start_positions: self.random_start_positions.clone(),
+            void_positions: self.void_positions.clone(),
+            exits: self.exits.clone(),
+            agents_positions: self.agents_positions.clone(),
+            wall_positions: self.wall_positions.clone(),
+            available_actions: self.available_actions.clone(),
+            start_positions: self.start_positions.clone(),
+            conflict_rule: self.conflict_rule,
+            slip_probability: self.slip_probability,
+            help_events: self.help_events,
+            conflict_scratch: Vec::with_capacity(self.agents.len()),
+            colour_scratch: vec![],
+            rng: rand::SeedableRng::seed_from_u64(0u64),
+            metadata: self.metadata.clone(),
+            history: vec![],
+            redo_history: vec![],
+        }
     }
 }
 
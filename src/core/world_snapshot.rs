@@ -0,0 +1,34 @@
+use crate::{Action, AgentId, Position, PositionSet, WorldState, agent::Agent, tiles::LaserBeam};
+
+/// The full mutable state of a [`World`](crate::World), taken with `World::snapshot` and put
+/// back with `World::restore`.
+///
+/// Unlike a [`WorldState`](crate::WorldState), which only describes where the agents are and
+/// which gems are collected, a snapshot also holds the laser beams and their coverage, the agents
+/// and the available actions. Restoring it therefore does not replay the tiles of the world and
+/// only costs as much as there are agents, gems and laser beam tiles.
+///
+/// The settings of the world (conflict rule, slip probability) and its rng are not part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldSnapshot {
+    pub(crate) agents: Vec<Agent>,
+    pub(crate) agents_positions: Vec<Position>,
+    /// The agent registered on the tile of each agent position.
+    pub(crate) tile_agents: Vec<Option<AgentId>>,
+    pub(crate) gems_collected: Vec<bool>,
+    pub(crate) beams: Vec<LaserBeam>,
+    /// The beam coverage of each colour in the bitboards of the world.
+    pub(crate) beam_coverage: Vec<PositionSet>,
+    pub(crate) available_actions: Vec<Vec<Action>>,
+}
+
+impl WorldSnapshot {
+    /// The state of the world when the snapshot was taken.
+    pub fn state(&self) -> WorldState {
+        WorldState {
+            agents_positions: self.agents_positions.clone(),
+            gems_collected: self.gems_collected.clone(),
+            agents_alive: self.agents.iter().map(|agent| agent.is_alive()).collect(),
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::Position;
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WorldState {
     pub agents_positions: Vec<Position>,
     pub gems_collected: Vec<bool>,
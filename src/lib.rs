@@ -4,6 +4,7 @@ mod agent;
 pub mod bindings;
 mod core;
 mod position;
+pub mod position_set;
 pub mod rendering;
 pub mod solver;
 
@@ -12,8 +13,13 @@ mod utils;
 pub use action::Action;
 pub use agent::{Agent, AgentId};
 pub use core::parsing::parse_toml as parse_v2;
-pub use core::{ParseError, RuntimeWorldError, World, WorldEvent, WorldState, tiles, tiles::Tile};
+pub use core::{
+    Bitboards, ConflictRule, DistanceMap, DistanceOptions, DistanceOrigin, LevelMetadata,
+    ParseError, RuntimeWorldError, SourceBeamMut, Span, World, WorldEvent, WorldSnapshot,
+    WorldState, tiles, tiles::Tile,
+};
 pub use position::Position;
+pub use position_set::PositionSet;
 pub use rendering::Renderer;
 // Include the version number of the crate from the build script
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
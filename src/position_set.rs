@@ -2,15 +2,31 @@ use crate::Position;
 
 /// A dense bitset over every `(i, j)` position of a `height x width` grid, indexed as
 /// `i * width + j`. Used in place of `HashSet<Position>` in the solver's hot paths
-/// (reachability caches, exit-reachability), where positions are dense, bounded, and
-/// repeatedly intersected/iterated — operations a word-at-a-time bitset handles far more
-/// cheaply than hashing individual `Position`s.
-#[derive(Clone, Debug)]
+/// (reachability caches, exit-reachability) and for the [`Bitboards`](crate::Bitboards) of the
+/// world, where positions are dense, bounded, and repeatedly intersected/iterated — operations a
+/// word-at-a-time bitset handles far more cheaply than hashing individual `Position`s.
+#[derive(Debug, PartialEq, Eq)]
 pub struct PositionSet {
     width: usize,
     words: Vec<u64>,
 }
 
+/// `clone_from` reuses the words of the set, which `World::restore` relies on to copy the beam
+/// coverage of a snapshot without allocating.
+impl Clone for PositionSet {
+    fn clone(&self) -> Self {
+        PositionSet {
+            width: self.width,
+            words: self.words.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.width = source.width;
+        self.words.clone_from(&source.words);
+    }
+}
+
 #[inline]
 fn word_and_bit(width: usize, pos: &Position) -> (usize, u32) {
     let idx = pos.i * width + pos.j;
@@ -85,6 +101,11 @@ impl PositionSet {
         self.words[word] & (1u64 << bit) != 0
     }
 
+    /// Remove every position from the set, keeping its grid.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.words.iter().all(|&w| w == 0)
     }
@@ -1,3 +1,4 @@
+mod procedural;
 mod renderer;
 mod sprites;
 
@@ -0,0 +1,161 @@
+use image::{ImageBuffer, Pixel};
+
+/// Largest agent id that receives a procedurally generated sprite. Agents above this id share the
+/// grey `n.png` fallback sprite.
+pub const MAX_PROCEDURAL_SPRITE_ID: usize = 255;
+
+/// The golden angle, in degrees. Stepping the hue by this irrational fraction of a full turn never
+/// lands twice on the same hue, and successive ids stay far apart on the colour wheel.
+const GOLDEN_ANGLE: f32 = 137.507_76;
+
+/// Glyphs of a 3x5 bitmap font for the digits `0..=9`, one row per `u8` (most significant of the
+/// three low bits is the leftmost pixel).
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+
+/// Hue rotation (in degrees) applied to the sprite of agent `0` to obtain the colour of
+/// `agent_id`, for ids beyond the hand-drawn sprites.
+pub fn hue_shift(agent_id: usize) -> f32 {
+    (agent_id as f32 * GOLDEN_ANGLE) % 360.0
+}
+
+/// Derive the sprites of agents `first_id..=MAX_PROCEDURAL_SPRITE_ID` from `base`, the sprite of
+/// agent `0`.
+///
+/// Each sprite is `base` with its hue rotated by [`hue_shift`]. When `labelled` is set, the agent
+/// id is also written in the bottom-right corner so that agents with similar hues remain
+/// distinguishable.
+pub fn generate<P>(
+    base: &ImageBuffer<P, Vec<u8>>,
+    first_id: usize,
+    labelled: bool,
+) -> Vec<ImageBuffer<P, Vec<u8>>>
+where
+    P: Pixel<Subpixel = u8>,
+{
+    (first_id..=MAX_PROCEDURAL_SPRITE_ID)
+        .map(|agent_id| {
+            let mut sprite = rotate_hue(base, hue_shift(agent_id));
+            if labelled {
+                draw_label(&mut sprite, agent_id);
+            }
+            sprite
+        })
+        .collect()
+}
+
+/// Rotate the hue of every pixel of `image` by `degrees`, leaving the alpha channel untouched.
+///
+/// Grey pixels have no hue and are therefore unchanged, which preserves outlines and highlights.
+pub fn rotate_hue<P>(image: &ImageBuffer<P, Vec<u8>>, degrees: f32) -> ImageBuffer<P, Vec<u8>>
+where
+    P: Pixel<Subpixel = u8>,
+{
+    let mut result = image.clone();
+    for pixel in result.pixels_mut() {
+        let channels = pixel.channels_mut();
+        let (h, s, v) = rgb_to_hsv(channels[0], channels[1], channels[2]);
+        let (r, g, b) = hsv_to_rgb((h + degrees) % 360.0, s, v);
+        channels[0] = r;
+        channels[1] = g;
+        channels[2] = b;
+    }
+    result
+}
+
+/// Write `number` in white with a black outline in the bottom-right corner of `image`.
+pub fn draw_label<P>(image: &mut ImageBuffer<P, Vec<u8>>, number: usize)
+where
+    P: Pixel<Subpixel = u8>,
+{
+    let digits: Vec<usize> = number
+        .to_string()
+        .bytes()
+        .map(|digit| (digit - b'0') as usize)
+        .collect();
+    let label_width = digits.len() as u32 * (GLYPH_WIDTH + 1) - 1;
+    // Keep one pixel for the outline on each side.
+    let x0 = image.width().saturating_sub(label_width + 2);
+    let y0 = image.height().saturating_sub(GLYPH_HEIGHT + 2);
+
+    let mut lit = Vec::new();
+    for (index, &digit) in digits.iter().enumerate() {
+        let x_offset = x0 + index as u32 * (GLYPH_WIDTH + 1);
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                    lit.push((x_offset + col, y0 + row as u32));
+                }
+            }
+        }
+    }
+    // Outline first, then the glyphs on top of it.
+    for &(x, y) in &lit {
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if nx >= 0 && ny >= 0 && (nx as u32) < image.width() && (ny as u32) < image.height() {
+                set_colour(image.get_pixel_mut(nx as u32, ny as u32), 0);
+            }
+        }
+    }
+    for &(x, y) in &lit {
+        if x < image.width() && y < image.height() {
+            set_colour(image.get_pixel_mut(x, y), 255);
+        }
+    }
+}
+
+/// Paint an opaque grey level on `pixel`.
+fn set_colour<P: Pixel<Subpixel = u8>>(pixel: &mut P, level: u8) {
+    let channels = pixel.channels_mut();
+    channels[..3].fill(level);
+    if let Some(alpha) = channels.get_mut(3) {
+        *alpha = 255;
+    }
+}
+
+fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    (h, s, max)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match h as u32 {
+        0..60 => (c, x, 0.0),
+        60..120 => (x, c, 0.0),
+        120..180 => (0.0, c, x),
+        180..240 => (0.0, x, c),
+        240..300 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_u8 = |channel: f32| ((channel + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
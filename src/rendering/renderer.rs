@@ -4,7 +4,7 @@ use itertools::izip;
 use super::{BLACK, GRID_GREY, sprites};
 use crate::{
     core::World,
-    tiles::{Direction, Gem, Laser, LaserSource, Tile},
+    tiles::{Direction, Gem, Laser, LaserBeam, Tile},
 };
 
 use super::{BACKGROUND_GREY, TILE_SIZE};
@@ -13,6 +13,7 @@ pub struct VisitorData<'a> {
     x: u32,
     y: u32,
     frame: &'a mut RgbImage,
+    beams: &'a [LaserBeam],
 }
 
 #[derive(Clone)]
@@ -79,6 +80,7 @@ impl Renderer {
                 x: pos.x() as u32 * TILE_SIZE,
                 y: pos.y() as u32 * TILE_SIZE,
                 frame: &mut frame,
+                beams: world.beams(),
             };
             self.draw_laser(laser, &mut data);
         }
@@ -87,6 +89,7 @@ impl Renderer {
                 x: pos.x() as u32 * TILE_SIZE,
                 y: pos.y() as u32 * TILE_SIZE,
                 frame: &mut frame,
+                beams: world.beams(),
             };
             self.draw_gem(gem, &mut data);
         }
@@ -100,6 +103,7 @@ impl Renderer {
                 x: pos.x() as u32 * TILE_SIZE,
                 y: pos.y() as u32 * TILE_SIZE,
                 frame: &mut frame,
+                beams: world.beams(),
             };
             self.draw_laser_source(source, &mut data);
         }
@@ -173,7 +177,10 @@ impl Renderer {
         match tile {
             Tile::Gem(gem) => self.draw_gem(gem, data),
             Tile::Laser(laser) => self.draw_laser(laser, data),
-            Tile::LaserSource(source) => self.draw_laser_source(source, data),
+            Tile::LaserSource(source) => {
+                let beams = data.beams;
+                self.draw_laser_source(&beams[source.beam()], data)
+            }
             _ => {} // Nothing to draw.
         }
     }
@@ -185,8 +192,8 @@ impl Renderer {
     }
 
     fn draw_laser(&self, laser: &Laser, data: &mut VisitorData) {
-        if laser.is_on() {
-            let agent_id = laser.agent_id();
+        if laser.is_on(data.beams) {
+            let agent_id = laser.agent_id(data.beams);
             let laser_sprite = match laser.direction() {
                 Direction::North | Direction::South => sprites::vertical_laser(agent_id),
                 Direction::East | Direction::West => sprites::horizontal_laser(agent_id),
@@ -197,7 +204,7 @@ impl Renderer {
         self.draw_tile(laser.wrapped(), data);
     }
 
-    fn draw_laser_source(&self, source: &LaserSource, data: &mut VisitorData) {
+    fn draw_laser_source(&self, source: &LaserBeam, data: &mut VisitorData) {
         let agent_id = source.agent_id();
         let source_sprite = match source.direction() {
             Direction::North => sprites::laser_source_north(agent_id),
@@ -1,6 +1,6 @@
 use image::{self, RgbImage, RgbaImage};
 
-use super::{BLACK, TILE_SIZE};
+use super::{BLACK, TILE_SIZE, procedural};
 use std::sync::LazyLock;
 
 // Image binary data is included at compile time with the build.rs script.
@@ -29,6 +29,23 @@ fn load_rgb(bytes: &[&[u8]]) -> Vec<RgbImage> {
         .collect()
 }
 
+/// Load the numbered RGBA sprites and append the procedurally generated ones for the higher agent
+/// ids, derived from sprite `0`.
+fn load_rgba_with_procedural(bytes: &[&[u8]], labelled: bool) -> Vec<RgbaImage> {
+    let mut sprites = load_rgba(bytes);
+    let generated = procedural::generate(&sprites[0], sprites.len(), labelled);
+    sprites.extend(generated);
+    sprites
+}
+
+/// Same as `load_rgba_with_procedural`, for RGB sprites.
+fn load_rgb_with_procedural(bytes: &[&[u8]], labelled: bool) -> Vec<RgbImage> {
+    let mut sprites = load_rgb(bytes);
+    let generated = procedural::generate(&sprites[0], sprites.len(), labelled);
+    sprites.extend(generated);
+    sprites
+}
+
 pub static GEM: LazyLock<RgbaImage> = LazyLock::new(|| {
     image::load_from_memory_with_format(GEM_BYTES, image::ImageFormat::Png)
         .unwrap()
@@ -53,37 +70,38 @@ fn load_single_rgb(bytes: &[u8]) -> RgbImage {
         .to_rgb8()
 }
 
-pub static AGENTS: LazyLock<Vec<RgbaImage>> = LazyLock::new(|| load_rgba(AGENT_BYTES));
+pub static AGENTS: LazyLock<Vec<RgbaImage>> =
+    LazyLock::new(|| load_rgba_with_procedural(AGENT_BYTES, true));
 pub static AGENT_FALLBACK: LazyLock<RgbaImage> =
     LazyLock::new(|| load_single_rgba(AGENT_FALLBACK_BYTES));
 
 pub static HORIZONTAL_LASERS: LazyLock<Vec<RgbaImage>> =
-    LazyLock::new(|| load_rgba(HORIZONTAL_LASER_BYTES));
+    LazyLock::new(|| load_rgba_with_procedural(HORIZONTAL_LASER_BYTES, false));
 pub static HORIZONTAL_LASER_FALLBACK: LazyLock<RgbaImage> =
     LazyLock::new(|| load_single_rgba(HORIZONTAL_LASER_FALLBACK_BYTES));
 
 pub static VERTICAL_LASERS: LazyLock<Vec<RgbaImage>> =
-    LazyLock::new(|| load_rgba(VERTICAL_LASER_BYTES));
+    LazyLock::new(|| load_rgba_with_procedural(VERTICAL_LASER_BYTES, false));
 pub static VERTICAL_LASER_FALLBACK: LazyLock<RgbaImage> =
     LazyLock::new(|| load_single_rgba(VERTICAL_LASER_FALLBACK_BYTES));
 
 pub static LASER_SOURCES_NORTH: LazyLock<Vec<RgbImage>> =
-    LazyLock::new(|| load_rgb(LASER_SOURCE_NORTH_BYTES));
+    LazyLock::new(|| load_rgb_with_procedural(LASER_SOURCE_NORTH_BYTES, true));
 pub static LASER_SOURCE_NORTH_FALLBACK: LazyLock<RgbImage> =
     LazyLock::new(|| load_single_rgb(LASER_SOURCE_NORTH_FALLBACK_BYTES));
 
 pub static LASER_SOURCES_EAST: LazyLock<Vec<RgbImage>> =
-    LazyLock::new(|| load_rgb(LASER_SOURCE_EAST_BYTES));
+    LazyLock::new(|| load_rgb_with_procedural(LASER_SOURCE_EAST_BYTES, true));
 pub static LASER_SOURCE_EAST_FALLBACK: LazyLock<RgbImage> =
     LazyLock::new(|| load_single_rgb(LASER_SOURCE_EAST_FALLBACK_BYTES));
 
 pub static LASER_SOURCES_SOUTH: LazyLock<Vec<RgbImage>> =
-    LazyLock::new(|| load_rgb(LASER_SOURCE_SOUTH_BYTES));
+    LazyLock::new(|| load_rgb_with_procedural(LASER_SOURCE_SOUTH_BYTES, true));
 pub static LASER_SOURCE_SOUTH_FALLBACK: LazyLock<RgbImage> =
     LazyLock::new(|| load_single_rgb(LASER_SOURCE_SOUTH_FALLBACK_BYTES));
 
 pub static LASER_SOURCES_WEST: LazyLock<Vec<RgbImage>> =
-    LazyLock::new(|| load_rgb(LASER_SOURCE_WEST_BYTES));
+    LazyLock::new(|| load_rgb_with_procedural(LASER_SOURCE_WEST_BYTES, true));
 pub static LASER_SOURCE_WEST_FALLBACK: LazyLock<RgbImage> =
     LazyLock::new(|| load_single_rgb(LASER_SOURCE_WEST_FALLBACK_BYTES));
 
@@ -110,11 +128,7 @@ fn rgb_or_fallback<'a>(
 
 #[inline]
 pub fn agent(agent_id: usize) -> &'static RgbaImage {
-    if agent_id <= MAX_NUMBERED_AGENT_SPRITE_ID {
-        &AGENTS[agent_id]
-    } else {
-        &AGENT_FALLBACK
-    }
+    rgba_or_fallback(&AGENTS, &AGENT_FALLBACK, agent_id)
 }
 
 #[inline]
@@ -146,3 +160,7 @@ pub fn laser_source_south(agent_id: usize) -> &'static RgbImage {
 pub fn laser_source_west(agent_id: usize) -> &'static RgbImage {
     rgb_or_fallback(&LASER_SOURCES_WEST, &LASER_SOURCE_WEST_FALLBACK, agent_id)
 }
+
+#[cfg(test)]
+#[path = "../unit_tests/test_sprites.rs"]
+mod tests;
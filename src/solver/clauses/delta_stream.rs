@@ -1,12 +1,13 @@
 use super::generator::ClauseGenerator;
 use super::mode_requirements::ModeRequirements;
 use super::{Clause, Literal};
-use crate::solver::SolveMode;
+use crate::solver::errors::SolverError;
+use crate::solver::{GemObjective, SolveSpec};
 
 /// An incremental clause-generation session for one retained SAT solver instance.
 ///
-/// A stream is fixed to one `mode` and `collect_gems` for its whole life and its horizon may only
-/// grow, so [`Self::advance_to`] never needs to retract a clause it already returned: every clause
+/// A stream is fixed to one solve spec and one [`GemObjective`] for its whole life and its horizon may
+/// only grow, so [`Self::advance_to`] never needs to retract a clause it already returned: every clause
 /// it hands out can be asserted permanently into the caller's solver, and only the objective's
 /// activation literal changes as the horizon grows.
 ///
@@ -19,37 +20,44 @@ use crate::solver::SolveMode;
 /// value across two different solver instances is the caller's own bug to avoid, the same way
 /// reusing a `File`'s cursor across two different underlying files would be.
 pub struct DeltaStream {
-    mode: SolveMode,
+    spec: SolveSpec,
     requirements: ModeRequirements,
-    collect_gems: bool,
+    gems: GemObjective,
     /// The last horizon requested and the activation literal guarding its objective, once at least
     /// one horizon has been requested.
     last: Option<(usize, Literal)>,
 }
 
 impl ClauseGenerator {
-    /// Start a new incremental stream fixed to `mode` and `collect_gems`.
+    /// Start a new incremental stream fixed to `spec` and the gem objective `gems`.
     ///
-    /// `mode` is normalized exactly as [`Self::generate`] normalizes it (see
-    /// [`Self::effective_mode`]), so a structurally impossible restriction opens a `Standard`
-    /// stream instead.
+    /// `spec` is a single [`SolveMode`](crate::solver::SolveMode) or a conjunction of them, normalized
+    /// exactly as [`Self::generate`] normalizes it (see [`Self::effective_spec`]), so a structurally
+    /// impossible restriction is dropped from the stream.
+    ///
+    /// # Errors
+    /// Same as [`Self::generate`].
     ///
     /// @ai-generated
-    pub fn start_delta_stream(&self, mode: SolveMode, collect_gems: bool) -> DeltaStream {
-        let mode = self.effective_mode(mode);
-        DeltaStream {
-            mode,
-            requirements: ModeRequirements::of(mode),
-            collect_gems,
+    pub fn start_delta_stream(
+        &self,
+        spec: impl Into<SolveSpec>,
+        gems: impl Into<GemObjective>,
+    ) -> Result<DeltaStream, SolverError> {
+        let spec = self.effective_spec(spec.into())?;
+        Ok(DeltaStream {
+            requirements: ModeRequirements::of_spec(&spec),
+            spec,
+            gems: self.checked_gem_objective(gems.into())?,
             last: None,
-        }
+        })
     }
 }
 
 impl DeltaStream {
-    /// The effective mode this stream is fixed to (after structural-impossibility normalization).
-    pub fn mode(&self) -> SolveMode {
-        self.mode
+    /// The effective spec this stream is fixed to (after structural-impossibility normalization).
+    pub fn spec(&self) -> &SolveSpec {
+        &self.spec
     }
 
     /// The last horizon this stream has been advanced to, or `None` before the first call.
@@ -95,7 +103,7 @@ impl DeltaStream {
 
         let mut assumptions = generator.mode_assumptions(&self.requirements, t);
         let guard = generator.fresh_literal();
-        let objective = generator.objective(t, self.collect_gems);
+        let objective = generator.checked_objective(t, &self.gems);
         clauses.extend(conditional_on(objective, guard));
         assumptions.push(guard);
 
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::solver::Clause;
 #[cfg(test)]
 use crate::solver::VarKey;
 use crate::solver::clauses::VarPool;
@@ -12,7 +11,8 @@ use crate::solver::interdependence::{
 };
 use crate::solver::position_set::PositionSet;
 use crate::solver::sequences::{SequencePattern, enumerate_sequence_patterns};
-use crate::{Action, World};
+use crate::solver::{Clause, EncodingConfig, EncodingStats, GemObjective};
+use crate::{Action, Position, World, WorldState};
 
 /// Mutable substrate shared by every clause-producing routine.
 ///
@@ -28,7 +28,14 @@ pub struct ClauseEngine {
     pub ctx: ConstraintContext,
     pub pool: VarPool,
     pub exits: PositionSet,
+    /// The gems that are still to collect.
     pub gems: PositionSet,
+    /// The gems collected before the first step, when the engine starts from a [`WorldState`].
+    pub collected_gems: PositionSet,
+    /// At-most-one encodings of the movement clauses.
+    pub encoding: EncodingConfig,
+    /// Clauses and variables of the world-enforcing families generated so far.
+    stats: EncodingStats,
     sequence_patterns: HashMap<usize, Arc<[SequencePattern]>>,
     interdependence_patterns: HashMap<usize, Arc<[ClosedTrailPattern]>>,
 }
@@ -36,19 +43,61 @@ pub struct ClauseEngine {
 impl ClauseEngine {
     pub fn new(world: &World, t_max: usize) -> Self {
         let ctx = ConstraintContext::new(world, t_max);
+        Self::with_context(
+            world,
+            ctx,
+            PositionSet::empty(world.height(), world.width()),
+        )
+    }
+
+    /// The engine of the problem that starts from `state`, a valid state of `world`. The gems
+    /// that `state` marks as collected are moved from `gems` to `collected_gems`.
+    pub fn from_state(world: &World, state: &WorldState, t_max: usize) -> Self {
+        let ctx = ConstraintContext::from_state(world, state, t_max);
+        let collected = world
+            .gems_positions()
+            .into_iter()
+            .zip(&state.gems_collected)
+            .filter(|&(_, &collected)| collected)
+            .map(|(pos, _)| pos);
+        let collected_gems = PositionSet::from_positions(world.height(), world.width(), collected);
+        Self::with_context(world, ctx, collected_gems)
+    }
+
+    /// The engine of the same problem when the agents start from `starts`, sharing the world
+    /// geometry of `self` (see [`ConstraintContext::with_starts`]). The variable pool starts over.
+    pub fn with_starts(&self, starts: Vec<Position>) -> Self {
+        Self {
+            ctx: self.ctx.with_starts(starts),
+            pool: VarPool::new(),
+            exits: self.exits.clone(),
+            gems: self.gems.clone(),
+            collected_gems: self.collected_gems.clone(),
+            encoding: self.encoding,
+            stats: EncodingStats::new(),
+            // Sequence patterns only depend on the laser owners, help arcs also on the starts.
+            sequence_patterns: self.sequence_patterns.clone(),
+            interdependence_patterns: HashMap::new(),
+        }
+    }
+
+    fn with_context(world: &World, ctx: ConstraintContext, collected_gems: PositionSet) -> Self {
+        let remaining_gems = world
+            .gems_positions()
+            .into_iter()
+            .filter(|pos| !collected_gems.contains(pos));
         Self {
             exits: PositionSet::from_positions(
                 world.height(),
                 world.width(),
                 world.exits_positions().into_iter(),
             ),
-            gems: PositionSet::from_positions(
-                world.height(),
-                world.width(),
-                world.gems_positions().into_iter(),
-            ),
+            gems: PositionSet::from_positions(world.height(), world.width(), remaining_gems),
+            collected_gems,
             ctx,
             pool: VarPool::new(),
+            encoding: EncodingConfig::default(),
+            stats: EncodingStats::new(),
             sequence_patterns: HashMap::new(),
             interdependence_patterns: HashMap::new(),
         }
@@ -127,42 +176,70 @@ impl ClauseEngine {
     pub fn generate_movement_clauses(&mut self, t: usize) -> Vec<Clause> {
         self.ctx.update(t);
         let mut clauses = Vec::new();
-        clauses.extend(self.initialization(t));
-        clauses.extend(self.exactly_one_position(t));
-        clauses.extend(self.time_wise_adjacency(t));
-        clauses.extend(self.no_overlap(t));
-        clauses.extend(self.no_following_conflict(t));
-        clauses.extend(self.stays_on_exit(t));
+        clauses.extend(self.measured("initialization", |e| e.initialization(t)));
+        clauses.extend(self.measured("exactly_one_position", |e| e.exactly_one_position(t)));
+        clauses.extend(self.measured("time_wise_adjacency", |e| e.time_wise_adjacency(t)));
+        clauses.extend(self.measured("no_overlap", |e| e.no_overlap(t)));
+        if self.ctx.conflict_rule.allows_following() {
+            clauses.extend(self.measured("no_swap", |e| e.no_swap(t)));
+        } else {
+            clauses.extend(self.measured("no_following_conflict", |e| e.no_following_conflict(t)));
+        }
+        clauses.extend(self.measured("stays_on_exit", |e| e.stays_on_exit(t)));
         clauses
     }
 
     /// Laser-only world-enforcing clauses for a single step `t`.
     pub fn generate_laser_clauses(&mut self, t: usize) -> Vec<Clause> {
         self.ctx.update(t);
-        let (beam_clauses, active_lit) = self.beam_activation(t);
-        let mut clauses = beam_clauses;
-        clauses.extend(self.no_step_on_active_laser(t, &active_lit));
+        self.measured("lasers", |e| {
+            let (beam_clauses, active_lit) = e.beam_activation(t);
+            let mut clauses = beam_clauses;
+            clauses.extend(e.no_step_on_active_laser(t, &active_lit));
+            clauses
+        })
+    }
+
+    /// Run `generate` and add its clauses and the variables it created to the stats of `family`.
+    fn measured(
+        &mut self,
+        family: &'static str,
+        generate: impl FnOnce(&mut Self) -> Vec<Clause>,
+    ) -> Vec<Clause> {
+        let n_vars = self.pool.n_vars();
+        let clauses = generate(self);
+        let stats = self.stats.entry(family).or_default();
+        stats.clauses += clauses.len();
+        stats.variables += self.pool.n_vars() - n_vars;
         clauses
     }
 
-    /// Objective clauses for horizon `t`: every agent must be on an exit. Not cached.
-    pub fn objective(&mut self, t: usize, collect_gems: bool) -> Vec<Clause> {
+    /// Clauses and variables per world-enforcing family (each movement rule and the lasers), over
+    /// every step generated so far.
+    pub fn stats(&self) -> &EncodingStats {
+        &self.stats
+    }
+
+    /// Objective clauses for horizon `t`: every agent must be on an exit, and the gems must be
+    /// collected as `gems` requires. Not cached.
+    pub fn objective(&mut self, t: usize, gems: &GemObjective) -> Vec<Clause> {
+        let mut clauses = self.generate_gem_objective(gems, t);
+        clauses.extend(self.agent_goals(t));
+        clauses
+    }
+
+    /// One clause per agent, in agent order, requiring it to stand on an exit at horizon `t`.
+    pub fn agent_goals(&mut self, t: usize) -> Vec<Clause> {
         self.ctx.update(t);
-        let mut clauses = if collect_gems {
-            self.gems_must_be_collected(t)
-        } else {
-            Vec::with_capacity(self.ctx.n_agents)
-        };
-        for agent in 0..self.ctx.n_agents {
-            let reachable = self.ctx.relevant_positions_for_agent(agent, t);
-            clauses.push(
+        (0..self.ctx.n_agents)
+            .map(|agent| {
+                let reachable = self.ctx.relevant_positions_for_agent(agent, t);
                 self.exits
                     .intersection(reachable)
                     .map(|p| self.pool.agent(agent, p, t))
-                    .collect(),
-            );
-        }
-        clauses
+                    .collect()
+            })
+            .collect()
     }
 
     #[inline]
@@ -0,0 +1,132 @@
+use crate::{
+    AgentId,
+    solver::{Clause, CooperationConstraint, Literal, clauses::ClauseEngine},
+};
+
+impl ClauseEngine {
+    /// Return the occupancy literals that `constraint` forbids at exactly time `t`, the positive
+    /// literals of the agents standing on a beam they must avoid. `RequireHelp` forbids nothing.
+    pub(crate) fn forbidden_occupancies_at(
+        &mut self,
+        constraint: CooperationConstraint,
+        t: usize,
+    ) -> Vec<Literal> {
+        let mut occupancies = match constraint {
+            CooperationConstraint::ForbidHelp {
+                helper,
+                beneficiary,
+            } => self.beam_occupancies(helper, beneficiary, t),
+            CooperationConstraint::ForbidHelper(helper) => (0..self.ctx.n_agents)
+                .filter(|&beneficiary| beneficiary != helper)
+                .flat_map(|beneficiary| self.beam_occupancies(helper, beneficiary, t))
+                .collect(),
+            CooperationConstraint::ForbidLaser(laser_id) => {
+                self.ctx.update(t);
+                let mut occupancies = Vec::new();
+                for source in self
+                    .ctx
+                    .laser_sources
+                    .iter()
+                    .filter(|source| source.laser_id == laser_id)
+                {
+                    let path = self.ctx.relevant_laser_tiles(source.laser_id, t);
+                    for agent in (0..self.ctx.n_agents).filter(|&agent| agent != source.agent_id) {
+                        let positions = self.ctx.relevant_positions_for_agent(agent, t);
+                        for pos in path.intersection(positions) {
+                            occupancies.push(self.pool.agent(agent, pos, t));
+                        }
+                    }
+                }
+                occupancies
+            }
+            CooperationConstraint::RequireHelp { .. } => Vec::new(),
+        };
+        occupancies.sort_unstable();
+        occupancies.dedup();
+        occupancies
+    }
+
+    /// Return literals asserting that `constraint` holds at exactly time `t`. Like
+    /// [`Self::assume_no_cooperation_at`], they only negate agent positions.
+    pub(crate) fn assume_constraint_at(
+        &mut self,
+        constraint: CooperationConstraint,
+        t: usize,
+    ) -> Vec<Literal> {
+        self.forbidden_occupancies_at(constraint, t)
+            .into_iter()
+            .map(|occupancy| -occupancy)
+            .collect()
+    }
+
+    /// The clause `required_profile(horizon) → OR(beneficiary on a beam of helper at t ≤ horizon)`.
+    pub fn generate_required_pair_help_clauses(
+        &mut self,
+        horizon: usize,
+        helper: AgentId,
+        beneficiary: AgentId,
+    ) -> Vec<Clause> {
+        let witnesses = (0..=horizon)
+            .flat_map(|t| self.beam_occupancies(helper, beneficiary, t))
+            .collect();
+        vec![self.require_one_of(horizon, witnesses)]
+    }
+
+    /// Clauses asserting `constraint` through `horizon`: one unit clause per forbidden occupancy,
+    /// or a single clause listing the occupancies that can realize a required help event.
+    ///
+    /// Unlike the assumptions of a [`SolveSpec`](crate::solver::SolveSpec), these clauses are
+    /// permanent. They are not guarded by the required-profile flag, so a required help event
+    /// asserted for `horizon` must also happen by `horizon` at any longer horizon.
+    pub fn generate_constraint_clauses(
+        &mut self,
+        constraint: CooperationConstraint,
+        horizon: usize,
+    ) -> Vec<Clause> {
+        if let CooperationConstraint::RequireHelp {
+            helper,
+            beneficiary,
+        } = constraint
+        {
+            let witnesses = (0..=horizon)
+                .flat_map(|t| self.beam_occupancies(helper, beneficiary, t))
+                .collect();
+            return vec![witnesses];
+        }
+        (0..=horizon)
+            .flat_map(|t| self.assume_constraint_at(constraint, t))
+            .map(|literal| vec![literal])
+            .collect()
+    }
+
+    /// The literals of `beneficiary` standing on a beam of `helper` at `t`: each of them is a help
+    /// event from `helper` to `beneficiary` (see [`Self::generate_help_clauses`]).
+    pub(super) fn beam_occupancies(
+        &mut self,
+        helper: AgentId,
+        beneficiary: AgentId,
+        t: usize,
+    ) -> Vec<Literal> {
+        self.ctx.update(t);
+        let mut occupancies = Vec::new();
+        let positions = self.ctx.relevant_positions_for_agent(beneficiary, t);
+        for source in self
+            .ctx
+            .laser_sources
+            .iter()
+            .filter(|source| source.agent_id == helper)
+        {
+            let path = self.ctx.relevant_laser_tiles(source.laser_id, t);
+            for pos in path.intersection(positions) {
+                occupancies.push(self.pool.agent(beneficiary, pos, t));
+            }
+        }
+        occupancies.sort_unstable();
+        occupancies.dedup();
+        occupancies
+    }
+}
+
+#[cfg(test)]
+#[path = "../../../unit_tests/engine/test_constraints.rs"]
+mod tests;
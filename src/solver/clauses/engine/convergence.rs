@@ -9,6 +9,18 @@ impl ClauseEngine {
     pub fn generate_no_convergence_clauses(&self, horizon: usize, k: usize) -> Vec<Clause> {
         self.generate_degree_blocking_clauses(horizon, k, FixedEndpoint::Beneficiary)
     }
+
+    /// Require a beneficiary to receive help from at least `k` distinct helpers, under the required-profile flag of `horizon`.
+    ///
+    /// Pairwise-help equivalences for the same `horizon` must be generated first. This is the
+    /// positive dual of [`ClauseEngine::generate_no_convergence_clauses`].
+    pub fn generate_required_convergence_clauses(
+        &mut self,
+        horizon: usize,
+        k: usize,
+    ) -> Vec<Clause> {
+        self.generate_degree_requiring_clauses(horizon, k, FixedEndpoint::Beneficiary)
+    }
 }
 
 #[cfg(test)]
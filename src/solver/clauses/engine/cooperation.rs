@@ -1,7 +1,12 @@
+use std::collections::HashSet;
+
 use itertools::Itertools;
 
-use super::utils::implies;
-use crate::solver::{Clause, Literal, VarKey, clauses::ClauseEngine};
+use super::utils::{implies, sequential_counter};
+use crate::{
+    AgentId,
+    solver::{Clause, Literal, VarKey, clauses::ClauseEngine},
+};
 
 impl ClauseEngine {
     /// Generate `Help(helper, beneficiary, t)` variables for time step `t`.
@@ -179,6 +184,90 @@ impl ClauseEngine {
     pub fn assume_no_asymmetry(&mut self, horizon: usize) -> Vec<Literal> {
         vec![-self.pool.asymmetric(horizon)]
     }
+
+    /// Return the assumption that requires an asymmetric help event.
+    pub fn assume_asymmetry(&mut self, horizon: usize) -> Vec<Literal> {
+        vec![self.pool.asymmetric(horizon)]
+    }
+
+    /// Require at least one help event over the prefix `0..=horizon`, under the required-profile
+    /// flag of `horizon`.
+    pub fn generate_required_help_clauses(&mut self, horizon: usize) -> Vec<Clause> {
+        let help_variables = (0..self.ctx.n_agents)
+            .flat_map(|helper| self.pool.helper_variables(helper, horizon))
+            .collect::<Vec<_>>();
+        vec![self.require_one_of(horizon, help_variables)]
+    }
+
+    /// Count the help events over the prefix `0..=horizon`, up to `bound`.
+    ///
+    /// For `1 <= j <= bound`, `help_events(horizon, j)` then holds if and only if at least `j`
+    /// `Help` variables are true. Help variables are counted in creation order, which keeps the
+    /// encoding deterministic.
+    pub fn generate_help_event_counter(&mut self, horizon: usize, bound: usize) -> Vec<Clause> {
+        let mut help_variables = (0..self.ctx.n_agents)
+            .flat_map(|helper| self.pool.helper_variables(helper, horizon))
+            .collect::<Vec<_>>();
+        help_variables.sort_unstable();
+        let outputs = (1..=bound)
+            .map(|at_least| self.pool.help_events(horizon, at_least))
+            .collect::<Vec<_>>();
+        sequential_counter(&help_variables, &outputs, &mut self.pool)
+    }
+
+    /// Count the help events through `horizon` in `model`, from the agent positions alone.
+    ///
+    /// Unlike the `Help` variables, which only exist in formulas that encode help, the positions
+    /// are part of every formula, so this works whatever the mode the model was solved with.
+    pub fn count_help_events(&mut self, model: &[Literal], horizon: usize) -> usize {
+        self.help_edges(model, horizon).len()
+    }
+
+    /// The help events `(helper, beneficiary, t)` through `horizon` in `model`, sorted by time
+    /// step, read from the agent positions like [`Self::count_help_events`].
+    pub fn help_edges(
+        &mut self,
+        model: &[Literal],
+        horizon: usize,
+    ) -> Vec<(AgentId, AgentId, usize)> {
+        let model: HashSet<Literal> = model.iter().copied().filter(|&lit| lit > 0).collect();
+        let mut edges = Vec::new();
+        for t in 0..=horizon {
+            for agents in (0..self.ctx.n_agents).permutations(2) {
+                let occupancies = self.beam_occupancies(agents[0], agents[1], t);
+                if occupancies
+                    .iter()
+                    .any(|occupancy| model.contains(occupancy))
+                {
+                    edges.push((agents[0], agents[1], t));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Return the assumptions bounding the number of help events through `horizon` to
+    /// `min..=max`.
+    ///
+    /// The counter of [`Self::generate_help_event_counter`] must reach `max + 1`.
+    pub fn assume_help_events(&mut self, horizon: usize, min: usize, max: usize) -> Vec<Literal> {
+        let mut assumptions = vec![-self.pool.help_events(horizon, max + 1)];
+        if min > 0 {
+            assumptions.push(self.pool.help_events(horizon, min));
+        }
+        assumptions
+    }
+
+    /// The clause `required_profile(horizon) → OR(witnesses)`.
+    ///
+    /// Each witness must imply the required profile. Without any witness, the profile cannot occur
+    /// and the clause forces the flag to false, which makes the mode's assumption fail.
+    pub(super) fn require_one_of(&mut self, horizon: usize, witnesses: Vec<Literal>) -> Clause {
+        let mut clause = Vec::with_capacity(1 + witnesses.len());
+        clause.push(-self.pool.required_profile(horizon));
+        clause.extend(witnesses);
+        clause
+    }
 }
 
 #[cfg(test)]
@@ -10,6 +10,18 @@ impl ClauseEngine {
     pub fn generate_no_divergence_clauses(&self, horizon: usize, k: usize) -> Vec<Clause> {
         self.generate_degree_blocking_clauses(horizon, k, FixedEndpoint::Helper)
     }
+
+    /// Require a helper to help at least `k` distinct beneficiaries, under the required-profile flag of `horizon`.
+    ///
+    /// Pairwise-help equivalences for the same `horizon` must be generated first. This is the
+    /// positive dual of [`ClauseEngine::generate_no_divergence_clauses`].
+    pub fn generate_required_divergence_clauses(
+        &mut self,
+        horizon: usize,
+        k: usize,
+    ) -> Vec<Clause> {
+        self.generate_degree_requiring_clauses(horizon, k, FixedEndpoint::Helper)
+    }
 }
 
 #[cfg(test)]
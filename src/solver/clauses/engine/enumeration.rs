@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::solver::{Clause, Literal, Projection, VarKey, clauses::ClauseEngine};
+
+impl ClauseEngine {
+    /// The clause ruling out every model that agrees with `model` on `projection` through
+    /// `horizon`.
+    ///
+    /// Positions are blocked by negating the occupied ones, since every agent has exactly one
+    /// position per step. Help edges and pairwise summaries are equivalent to the positions, so
+    /// the clause flips each of them: some help event (or pair) has to appear or disappear. Their
+    /// defining clauses must be part of the formula (see
+    /// [`ClauseGenerator::projection_clauses`](crate::solver::ClauseGenerator::projection_clauses)).
+    ///
+    /// An empty clause means that the projection has no variable: every model is the same solution.
+    pub fn blocking_clause(
+        &self,
+        model: &[Literal],
+        horizon: usize,
+        projection: Projection,
+    ) -> Clause {
+        let model: HashSet<Literal> = model.iter().copied().filter(|&lit| lit > 0).collect();
+        let variables = match projection {
+            Projection::Positions => {
+                let mut occupied = model
+                    .iter()
+                    .copied()
+                    .filter(|&lit| {
+                        matches!(self.pool.key(lit), Some(VarKey::Agent { t, .. }) if t <= horizon)
+                    })
+                    .collect::<Vec<_>>();
+                occupied.sort_unstable();
+                return occupied.into_iter().map(|lit| -lit).collect();
+            }
+            Projection::HelpEdges => (0..self.ctx.n_agents)
+                .permutations(2)
+                .flat_map(|agents| {
+                    self.pool
+                        .help_variables_for_pair(agents[0], agents[1], horizon)
+                })
+                .collect::<Vec<_>>(),
+            Projection::PairwiseHelp => (0..self.ctx.n_agents)
+                .permutations(2)
+                .filter_map(|agents| {
+                    self.pool.get(&VarKey::PairwiseHelp {
+                        helper: agents[0],
+                        beneficiary: agents[1],
+                        horizon,
+                    })
+                })
+                .collect(),
+        };
+        variables
+            .into_iter()
+            .map(|var| if model.contains(&var) { -var } else { var })
+            .collect()
+    }
+}
@@ -1,27 +1,138 @@
+use std::collections::HashSet;
+
 use itertools::Itertools;
 
-use crate::solver::{Clause, clauses::ClauseEngine};
+use super::utils::{implies, sequential_counter};
+use crate::{
+    AgentId, Position,
+    solver::{Clause, GemObjective, Literal, VarKey, clauses::ClauseEngine},
+};
 
 impl ClauseEngine {
     /// In comparison to other clauses, gem collection should be expressed at a single time-step.
     /// The most intuitive formulation of the clause is simply a large OR-clause that checks if
     /// at some point in time, an agent is standing on the gem tile.
     pub fn gems_must_be_collected(&mut self, t: usize) -> Vec<Clause> {
+        let gems = self.gems.iter().collect::<Vec<_>>();
+        gems.into_iter()
+            .map(|gem_pos| self.gem_collection(gem_pos, t, None))
+            .collect()
+    }
+
+    /// The agent literals that collect the gem at `gem_pos` before horizon `t`, restricted to
+    /// `collector` if given.
+    fn gem_collection(
+        &mut self,
+        gem_pos: Position,
+        t: usize,
+        collector: Option<AgentId>,
+    ) -> Clause {
         self.ctx.update(t);
-        self.gems
-            .iter()
-            .map(|gem_pos| {
-                // We exclude tau=0 and tau=t because a gem cannot be on start or exit tiles
-                (1..t)
-                    .cartesian_product(0..self.ctx.n_agents)
-                    .filter(|&(tau, agent)| {
-                        self.ctx
-                            .relevant_positions_for_agent(agent, tau)
-                            .contains(&gem_pos)
-                    })
-                    .map(|(tau, agent)| self.pool.agent(agent, gem_pos, tau))
-                    .collect()
+        // We exclude tau=0 and tau=t because a gem cannot be on start or exit tiles
+        (1..t)
+            .cartesian_product(0..self.ctx.n_agents)
+            .filter(|&(tau, agent)| {
+                collector.is_none_or(|collector| collector == agent)
+                    && self
+                        .ctx
+                        .relevant_positions_for_agent(agent, tau)
+                        .contains(&gem_pos)
             })
+            .map(|(tau, agent)| self.pool.agent(agent, gem_pos, tau))
             .collect()
     }
+
+    /// Clauses of `objective` for horizon `t`. The gems it names must be gems of this world.
+    ///
+    /// [`GemObjective::AtLeast`] counts `GemCollected` variables, each equivalent to the
+    /// collection clause of its gem, with a sequential counter whose last output is asserted.
+    pub fn generate_gem_objective(&mut self, objective: &GemObjective, t: usize) -> Vec<Clause> {
+        match objective {
+            GemObjective::None | GemObjective::AtLeast(0) => vec![],
+            GemObjective::All => self.gems_must_be_collected(t),
+            GemObjective::Subset(gems) => gems
+                .iter()
+                .map(|&gem_pos| self.gem_collection(gem_pos, t, None))
+                .collect(),
+            GemObjective::ByAgent(collectors) => collectors
+                .iter()
+                .map(|&(gem_pos, agent)| self.gem_collection(gem_pos, t, Some(agent)))
+                .collect(),
+            &GemObjective::AtLeast(k) => {
+                let mut clauses = self.generate_gem_counter(t, k);
+                clauses.push(vec![self.pool.gems_collected(t, k)]);
+                clauses
+            }
+        }
+    }
+
+    /// Count the gems collected before `horizon`, up to `bound`.
+    ///
+    /// For `1 <= j <= bound`, `gems_collected(horizon, j)` then holds if and only if at least `j`
+    /// gems are collected. Counts beyond the number of gems are forced to false.
+    pub fn generate_gem_counter(&mut self, horizon: usize, bound: usize) -> Vec<Clause> {
+        let mut clauses = Vec::new();
+        let gems = self.gems.iter().collect::<Vec<_>>();
+        let mut collected = Vec::with_capacity(gems.len());
+        for gem_pos in gems {
+            let var = self.pool.gem_collected(gem_pos, horizon);
+            let collection = self.gem_collection(gem_pos, horizon, None);
+            clauses.extend(collection.iter().map(|&lit| implies(lit, var)));
+            let mut support = collection;
+            support.push(-var);
+            clauses.push(support);
+            collected.push(var);
+        }
+        let outputs = (1..=bound)
+            .map(|at_least| self.pool.gems_collected(horizon, at_least))
+            .collect::<Vec<_>>();
+        clauses.extend(sequential_counter(&collected, &outputs, &mut self.pool));
+        clauses
+    }
+
+    /// What remains of `objective` once `collected_gems` are collected: they are dropped from
+    /// [`GemObjective::Subset`] and [`GemObjective::ByAgent`], whoever collected them, and they
+    /// count towards [`GemObjective::AtLeast`].
+    pub fn remaining_objective(&self, objective: GemObjective) -> GemObjective {
+        if self.collected_gems.is_empty() {
+            return objective;
+        }
+        match objective {
+            GemObjective::None | GemObjective::All => objective,
+            GemObjective::AtLeast(k) => {
+                GemObjective::AtLeast(k.saturating_sub(self.collected_gems.size()))
+            }
+            GemObjective::Subset(gems) => GemObjective::Subset(
+                gems.into_iter()
+                    .filter(|gem_pos| self.gems.contains(gem_pos))
+                    .collect(),
+            ),
+            GemObjective::ByAgent(collectors) => GemObjective::ByAgent(
+                collectors
+                    .into_iter()
+                    .filter(|(gem_pos, _)| self.gems.contains(gem_pos))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Count the gems collected before `horizon` in `model`, from the agent positions alone, so
+    /// that this works whatever the objective the model was solved with. The `collected_gems`
+    /// count as well.
+    pub fn count_gems(&self, model: &[Literal], horizon: usize) -> usize {
+        let model: HashSet<Literal> = model.iter().copied().filter(|&lit| lit > 0).collect();
+        self.collected_gems.size()
+            + self
+                .gems
+                .iter()
+                .filter(|&gem_pos| {
+                    (1..horizon)
+                        .cartesian_product(0..self.ctx.n_agents)
+                        .filter_map(|(tau, agent)| {
+                            self.pool.get(&VarKey::agent(agent, gem_pos, tau))
+                        })
+                        .any(|lit| model.contains(&lit))
+                })
+                .count()
+    }
 }
@@ -1,4 +1,5 @@
-use crate::solver::{Clause, VarKey, clauses::ClauseEngine};
+use super::utils::supported_by;
+use crate::solver::{Clause, Literal, VarKey, clauses::ClauseEngine};
 
 impl ClauseEngine {
     /// Generate exact-order closed-trail rejection clauses for one time column.
@@ -9,6 +10,49 @@ impl ClauseEngine {
     /// This one-sided (at-most-one) Horn encoding is intentionally suitable only for
     /// `NoInterdependence`: a completed reachable prefix is forbidden at every horizon.
     pub fn generate_interdependence_clauses(&mut self, t: usize, order: usize) -> Vec<Clause> {
+        self.interdependence_clauses(t, order, false)
+    }
+
+    /// Generate the two-sided closed-trail progress encoding of one time column, used by
+    /// `RequireInterdependence`.
+    ///
+    /// It extends [`Self::generate_interdependence_clauses`] with a progress state for each complete
+    /// pattern, in place of the blocking clause, and with the converse clauses (see
+    /// [`supported_by`]) so that every progress state witnesses an actual chronological prefix.
+    pub fn generate_required_interdependence_clauses(
+        &mut self,
+        t: usize,
+        order: usize,
+    ) -> Vec<Clause> {
+        self.interdependence_clauses(t, order, true)
+    }
+
+    /// Require some closed-trail pattern of exactly `order` agents to be complete by `horizon`,
+    /// under the required-profile flag of `horizon`.
+    ///
+    /// [`Self::generate_required_interdependence_clauses`] must have been generated up to `horizon`.
+    pub fn generate_required_interdependence_goal(
+        &mut self,
+        horizon: usize,
+        order: usize,
+    ) -> Vec<Clause> {
+        let patterns = self.interdependence_patterns(order);
+        let witnesses = patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(pattern_index, pattern)| {
+                self.pool.get(&VarKey::InterdependenceProgress {
+                    order,
+                    pattern: pattern_index,
+                    prefix_len: pattern.arcs.len(),
+                    t: horizon,
+                })
+            })
+            .collect();
+        vec![self.require_one_of(horizon, witnesses)]
+    }
+
+    fn interdependence_clauses(&mut self, t: usize, order: usize, required: bool) -> Vec<Clause> {
         self.ctx.update(t);
         let patterns = self.interdependence_patterns(order);
         let mut clauses = Vec::new();
@@ -18,57 +62,70 @@ impl ClauseEngine {
             for (arc_index, arc) in pattern.arcs.iter().enumerate() {
                 let prefix_len = arc_index + 1;
                 let is_complete = prefix_len == pattern_len;
-                if !is_complete {
-                    let progress =
+                let mut progress = None;
+                let mut previous_time = None;
+                if !is_complete || required {
+                    let current =
                         self.pool
                             .interdependence_progress(order, pattern_index, prefix_len, t);
                     if t > 0 {
-                        let previous_time = self.pool.interdependence_progress(
+                        let previous = self.pool.interdependence_progress(
                             order,
                             pattern_index,
                             prefix_len,
                             t - 1,
                         );
-                        clauses.push(vec![-previous_time, progress]);
+                        clauses.push(vec![-previous, current]);
+                        previous_time = Some(previous);
                     }
+                    progress = Some(current);
                 }
 
-                let Some(help) = self.pool.get(&VarKey::Help {
+                // The conjunction that extends the previous prefix with this arc at `t`.
+                let help = self.pool.get(&VarKey::Help {
                     helper: arc.helper,
                     beneficiary: arc.beneficiary,
                     t,
-                }) else {
-                    continue;
-                };
-                let mut transition = vec![-help];
-                if prefix_len > 1 {
-                    transition.push(-self.pool.interdependence_progress(
-                        order,
-                        pattern_index,
-                        prefix_len - 1,
-                        t,
-                    ));
-                }
-                if let Some(previous_prefix_len) = pattern.previous_same_prefix_len[arc_index] {
-                    if t == 0 {
-                        continue;
-                    }
-                    transition.push(-self.pool.interdependence_progress(
-                        order,
-                        pattern_index,
-                        previous_prefix_len,
-                        t - 1,
-                    ));
+                });
+                let repeats_at_start =
+                    t == 0 && pattern.previous_same_prefix_len[arc_index].is_some();
+                let transition: Option<Vec<Literal>> =
+                    help.filter(|_| !repeats_at_start).map(|help| {
+                        let mut transition = vec![help];
+                        if prefix_len > 1 {
+                            transition.push(self.pool.interdependence_progress(
+                                order,
+                                pattern_index,
+                                prefix_len - 1,
+                                t,
+                            ));
+                        }
+                        if let Some(previous_prefix_len) =
+                            pattern.previous_same_prefix_len[arc_index]
+                        {
+                            transition.push(self.pool.interdependence_progress(
+                                order,
+                                pattern_index,
+                                previous_prefix_len,
+                                t - 1,
+                            ));
+                        }
+                        transition
+                    });
+
+                if let Some(transition) = &transition {
+                    let mut clause: Clause = transition.iter().map(|&literal| -literal).collect();
+                    clause.extend(progress);
+                    clauses.push(clause);
                 }
-                if !is_complete {
-                    transition.push(self.pool.interdependence_progress(
-                        order,
-                        pattern_index,
-                        prefix_len,
-                        t,
+                if required {
+                    clauses.extend(supported_by(
+                        progress.expect("required progress states are always materialized"),
+                        previous_time,
+                        transition.as_deref(),
+                        &mut self.pool,
                     ));
                 }
-                clauses.push(transition);
             }
         }
         clauses
@@ -1,14 +1,17 @@
 mod assumptions;
 mod sequences;
 mod clause_engine;
+mod constraints;
 mod convergence;
 mod cooperation;
 mod divergence;
+mod enumeration;
 mod gems;
 mod interdependence;
 mod lasers;
 mod movement;
 mod pairwise_help;
+mod symmetry;
 mod utils;
 
 pub use clause_engine::ClauseEngine;
@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
+
 use itertools::Itertools;
 
-use crate::solver::{Clause, clauses::ClauseEngine};
+use crate::solver::{Clause, Literal, clauses::ClauseEngine};
 
-use super::utils::{PAIRWISE_ATMOST_MAX, at_most_one_sequential, implies};
+use super::utils::{at_most_one, implies};
+use crate::solver::AtMostOneEncoding;
 
 impl ClauseEngine {
     /// Clauses fixing each agent at its start position at `t == 0`.
@@ -20,7 +23,7 @@ impl ClauseEngine {
 
     /// Every agent is in exactly one position at any given time step.
     /// This actually encodes an "at most one" constraint, but it is required to
-    /// be exactly one to find a solution.
+    /// be exactly one to find a solution. The encoding is `self.encoding.exactly_one_position`.
     pub(super) fn exactly_one_position(&mut self, t: usize) -> Vec<Clause> {
         let mut clauses = Vec::new();
         for agent in 0..self.ctx.n_agents {
@@ -32,16 +35,11 @@ impl ClauseEngine {
                 .into_iter()
                 .map(|p| self.pool.agent(agent, p, t))
                 .collect();
-            if vars.len() <= PAIRWISE_ATMOST_MAX {
-                for i in 0..vars.len() {
-                    for j in i + 1..vars.len() {
-                        clauses.push(implies(vars[i], -vars[j]));
-                        // clauses.push(vec![-vars[i], -vars[j]]);
-                    }
-                }
-            } else {
-                clauses.extend(at_most_one_sequential(&vars, &mut self.pool));
-            }
+            clauses.extend(at_most_one(
+                self.encoding.exactly_one_position,
+                &vars,
+                &mut self.pool,
+            ));
         }
         clauses
     }
@@ -68,16 +66,35 @@ impl ClauseEngine {
     }
 
     /// Two agents cannot occupy the same cell at the same time.
+    ///
+    /// The pairwise encoding is emitted pair of agents by pair of agents. Any other
+    /// `self.encoding.no_overlap` encodes at most one agent per tile over the agents that can
+    /// stand on it.
     pub(super) fn no_overlap(&mut self, t: usize) -> Vec<Clause> {
         let mut clauses = Vec::new();
-        for c1 in 0..self.ctx.n_agents {
-            for c2 in c1 + 1..self.ctx.n_agents {
-                for pos in self.ctx.relevant_positions(t, &[c1, c2]) {
-                    let v1 = self.pool.agent(c1, pos, t);
-                    let v2 = self.pool.agent(c2, pos, t);
-                    clauses.push(vec![-v1, -v2]);
+        if self.encoding.no_overlap == AtMostOneEncoding::Pairwise {
+            for c1 in 0..self.ctx.n_agents {
+                for c2 in c1 + 1..self.ctx.n_agents {
+                    for pos in self.ctx.relevant_positions(t, &[c1, c2]) {
+                        let v1 = self.pool.agent(c1, pos, t);
+                        let v2 = self.pool.agent(c2, pos, t);
+                        clauses.push(vec![-v1, -v2]);
+                    }
                 }
             }
+            return clauses;
+        }
+        let mut occupants = BTreeMap::<(usize, usize), Vec<Literal>>::new();
+        for agent in 0..self.ctx.n_agents {
+            for pos in self.ctx.relevant_positions_for_agent(agent, t) {
+                occupants
+                    .entry((pos.i, pos.j))
+                    .or_default()
+                    .push(self.pool.agent(agent, pos, t));
+            }
+        }
+        for vars in occupants.values() {
+            clauses.extend(at_most_one(self.encoding.no_overlap, vars, &mut self.pool));
         }
         clauses
     }
@@ -107,6 +124,37 @@ impl ClauseEngine {
         clauses
     }
 
+    /// Prevent two agents from swapping their positions between `t - 1` and `t`, when following
+    /// is allowed (see [`ConflictRule::AllowFollowing`](crate::ConflictRule::AllowFollowing)).
+    /// This replaces [`Self::no_following_conflict`], which forbids swaps as well.
+    pub(super) fn no_swap(&mut self, t: usize) -> Vec<Clause> {
+        if t == 0 {
+            return Vec::new();
+        }
+        let mut clauses = Vec::new();
+        for (c1, c2) in (0..self.ctx.n_agents).tuple_combinations() {
+            // c1 goes from `pos` to `next` while c2 goes from `next` to `pos`.
+            let from_c1 = self.ctx.relevant_positions(t - 1, &[c1]);
+            let to_c2 = self.ctx.relevant_positions(t, &[c2]);
+            let to_c1 = self.ctx.relevant_positions(t, &[c1]);
+            let from_c2 = self.ctx.relevant_positions(t - 1, &[c2]);
+            for pos in from_c1.intersection(&to_c2) {
+                for next in &self.ctx.neighbours[pos.i][pos.j] {
+                    if next == pos || !to_c1.contains(&next) || !from_c2.contains(&next) {
+                        continue;
+                    }
+                    clauses.push(vec![
+                        -self.pool.agent(c1, pos, t - 1),
+                        -self.pool.agent(c1, next, t),
+                        -self.pool.agent(c2, next, t - 1),
+                        -self.pool.agent(c2, pos, t),
+                    ]);
+                }
+            }
+        }
+        clauses
+    }
+
     /// If an agent was on an exit at `t - 1`, it must remain on an exit at `t`.
     ///
     /// The clause is only emitted for exits that remain in the agent's relevant set at `t`.
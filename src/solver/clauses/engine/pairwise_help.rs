@@ -1,7 +1,10 @@
 use itertools::Itertools;
 
 use super::utils::implies;
-use crate::solver::{Clause, Literal, VarKey, clauses::ClauseEngine};
+use crate::{
+    AgentId,
+    solver::{Clause, Literal, VarKey, clauses::ClauseEngine},
+};
 
 /// Which endpoint of the directed `helper -> beneficiary` relation is held fixed while grouping
 /// pairwise-help summaries into degree-blocking clauses.
@@ -107,25 +110,11 @@ impl ClauseEngine {
         }
         let mut clauses = Vec::new();
         for anchor in 0..self.ctx.n_agents {
-            let summaries: Vec<Literal> = (0..self.ctx.n_agents)
-                .filter(|&other| other != anchor)
-                .filter_map(|other| {
-                    let (helper, beneficiary) = match fixed {
-                        FixedEndpoint::Helper => (anchor, other),
-                        FixedEndpoint::Beneficiary => (other, anchor),
-                    };
-                    self.pool.get(&VarKey::PairwiseHelp {
-                        helper,
-                        beneficiary,
-                        horizon,
-                    })
-                })
-                .collect();
             // Enforce "at most k - 1 counterparts" by forbidding every size-k subset from being
             // true simultaneously. Each subset becomes the clause (¬r₁ ∨ ... ∨ ¬rₖ), while
             // `combinations` ensures that agent order does not produce duplicate clauses.
             clauses.extend(
-                summaries
+                self.incident_summaries(anchor, horizon, fixed)
                     .into_iter()
                     .combinations(k)
                     .map(|combination| combination.into_iter().map(|lit| -lit).collect()),
@@ -133,6 +122,64 @@ impl ClauseEngine {
         }
         clauses
     }
+
+    /// Require some agent to be incident to `k` distinct pairwise-help summaries at `horizon`,
+    /// under the required-profile flag of `horizon`.
+    ///
+    /// This is the positive dual of [`Self::generate_degree_blocking_clauses`]: every size-`k`
+    /// combination of summaries sharing the fixed endpoint gets a witness literal that implies each
+    /// of them, and the required profile implies one of the witnesses. Pairwise-help equivalences
+    /// for the same `horizon` must be generated first. When no combination exists, the single
+    /// emitted clause forces the required-profile flag to false.
+    pub(super) fn generate_degree_requiring_clauses(
+        &mut self,
+        horizon: usize,
+        k: usize,
+        fixed: FixedEndpoint,
+    ) -> Vec<Clause> {
+        let mut clauses = Vec::new();
+        let mut witnesses = Vec::new();
+        for anchor in 0..self.ctx.n_agents {
+            for combination in self
+                .incident_summaries(anchor, horizon, fixed)
+                .into_iter()
+                .combinations(k)
+            {
+                let witness = self.pool.aux();
+                clauses.extend(
+                    combination
+                        .into_iter()
+                        .map(|summary| implies(witness, summary)),
+                );
+                witnesses.push(witness);
+            }
+        }
+        clauses.push(self.require_one_of(horizon, witnesses));
+        clauses
+    }
+
+    /// The allocated pairwise-help summaries incident to `anchor` on its `fixed` endpoint.
+    fn incident_summaries(
+        &self,
+        anchor: AgentId,
+        horizon: usize,
+        fixed: FixedEndpoint,
+    ) -> Vec<Literal> {
+        (0..self.ctx.n_agents)
+            .filter(|&other| other != anchor)
+            .filter_map(|other| {
+                let (helper, beneficiary) = match fixed {
+                    FixedEndpoint::Helper => (anchor, other),
+                    FixedEndpoint::Beneficiary => (other, anchor),
+                };
+                self.pool.get(&VarKey::PairwiseHelp {
+                    helper,
+                    beneficiary,
+                    horizon,
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
-use crate::solver::{Clause, VarKey, clauses::ClauseEngine};
+use super::utils::supported_by;
+use crate::solver::{Clause, Literal, VarKey, clauses::ClauseEngine};
 
 impl ClauseEngine {
     /// Generate a sparse Horn encoding that forbids temporal help trails of exactly `length`.
@@ -8,6 +9,44 @@ impl ClauseEngine {
     /// previous occurrence by `t - 1`, preventing reuse of the same temporal help edge. States and
     /// transitions are materialized only when their help literals and predecessor prefixes exist.
     pub fn generate_sequence_clauses(&mut self, t: usize, length: usize) -> Vec<Clause> {
+        self.sequence_clauses(t, length, false)
+    }
+
+    /// Generate the two-sided progress encoding of temporal help trails of exactly `length`, used to
+    /// require one.
+    ///
+    /// It shares the progress states and Horn clauses of [`Self::generate_sequence_clauses`], but
+    /// also materializes the state of each complete pattern instead of blocking it, and adds the
+    /// converse clauses (see [`supported_by`]): a progress state only holds if its prefix is actually
+    /// realized. See [`Self::generate_required_sequence_goal`] for the requirement itself.
+    pub fn generate_required_sequence_clauses(&mut self, t: usize, length: usize) -> Vec<Clause> {
+        self.sequence_clauses(t, length, true)
+    }
+
+    /// Require some sequence pattern of `length` arcs to be complete by `horizon`, under the
+    /// required-profile flag of `horizon`.
+    ///
+    /// [`Self::generate_required_sequence_clauses`] must have been generated up to `horizon`.
+    pub fn generate_required_sequence_goal(
+        &mut self,
+        horizon: usize,
+        length: usize,
+    ) -> Vec<Clause> {
+        let n_patterns = self.sequence_patterns(length).len();
+        let witnesses = (0..n_patterns)
+            .filter_map(|pattern| {
+                self.pool.get(&VarKey::SequenceProgress {
+                    length,
+                    pattern,
+                    prefix_len: length,
+                    t: horizon,
+                })
+            })
+            .collect();
+        vec![self.require_one_of(horizon, witnesses)]
+    }
+
+    fn sequence_clauses(&mut self, t: usize, length: usize, required: bool) -> Vec<Clause> {
         self.ctx.update(t);
         let n_helpers = self
             .ctx
@@ -70,45 +109,52 @@ impl ClauseEngine {
                     beneficiary: arc.beneficiary,
                     t,
                 });
-                let previous_time = (t > 0).then(|| {
+                let previous_time = if t > 0 {
                     self.pool.get(&VarKey::SequenceProgress {
                         length,
                         pattern: pattern_index,
                         prefix_len,
                         t: t - 1,
                     })
-                });
-                if help.is_none() && previous_time.flatten().is_none() {
+                } else {
+                    None
+                };
+                if help.is_none() && previous_time.is_none() {
                     continue;
                 }
+                // The conjunction that extends the previous prefix with this arc at `t`.
+                let transition: Option<Vec<Literal>> = help.map(|help| {
+                    [help]
+                        .into_iter()
+                        .chain(previous_progress)
+                        .chain(repeated_arc_progress)
+                        .collect()
+                });
 
-                if !is_complete {
-                    let progress = self
-                        .pool
-                        .sequence_progress(length, pattern_index, prefix_len, t);
-                    if let Some(previous_time) = previous_time.flatten() {
-                        clauses.push(vec![-previous_time, progress]);
-                    }
-                    if let Some(help) = help {
-                        let mut transition = vec![-help];
-                        if let Some(previous_progress) = previous_progress {
-                            transition.push(-previous_progress);
-                        }
-                        if let Some(repeated_arc_progress) = repeated_arc_progress {
-                            transition.push(-repeated_arc_progress);
-                        }
-                        transition.push(progress);
-                        clauses.push(transition);
-                    }
-                } else if let Some(help) = help {
-                    let mut blocking = vec![-help];
-                    if let Some(previous_progress) = previous_progress {
-                        blocking.push(-previous_progress);
+                if is_complete && !required {
+                    if let Some(transition) = transition {
+                        clauses.push(transition.iter().map(|&literal| -literal).collect());
                     }
-                    if let Some(repeated_arc_progress) = repeated_arc_progress {
-                        blocking.push(-repeated_arc_progress);
-                    }
-                    clauses.push(blocking);
+                    continue;
+                }
+                let progress = self
+                    .pool
+                    .sequence_progress(length, pattern_index, prefix_len, t);
+                if let Some(previous_time) = previous_time {
+                    clauses.push(vec![-previous_time, progress]);
+                }
+                if let Some(transition) = &transition {
+                    let mut forward: Clause = transition.iter().map(|&literal| -literal).collect();
+                    forward.push(progress);
+                    clauses.push(forward);
+                }
+                if required {
+                    clauses.extend(supported_by(
+                        progress,
+                        previous_time,
+                        transition.as_deref(),
+                        &mut self.pool,
+                    ));
                 }
             }
         }
@@ -0,0 +1,52 @@
+use crate::solver::{Clause, clauses::ClauseEngine};
+
+impl ClauseEngine {
+    /// Trajectory symmetry-breaking clauses for step `t`: an agent may not wait on a position `p`
+    /// at `t - 2` and `t - 1` and then move to a private position `q` at `t`.
+    ///
+    /// The trajectories `(p, p, q)` and `(p, q, q)` visit the same positions. The second one is
+    /// kept as the canonical representative: waits are pushed as late as possible. The clauses
+    /// preserve satisfiability because any plan with the forbidden pattern can be rewritten into
+    /// one without it by moving the agent to `q` one step earlier.
+    /// - `q` is private at `t - 1` (see `ConstraintContext::private_positions`), so occupying it
+    ///   earlier cannot collide with another agent or touch a laser.
+    /// - `p` is neither a beam tile nor a gem, so leaving it earlier can neither unblock a beam
+    ///   nor change which gems are collected.
+    ///
+    /// Each rewrite moves one wait strictly later, so repeating them terminates on a plan that
+    /// satisfies every clause. These clauses only remove redundant plans: they add no variable.
+    pub fn generate_symmetry_breaking_clauses(&mut self, t: usize) -> Vec<Clause> {
+        if t < 2 {
+            return Vec::new();
+        }
+        self.ctx.update(t);
+        let mut clauses = Vec::new();
+        for agent in 0..self.ctx.n_agents {
+            let private = self.ctx.private_positions(agent, t - 1);
+            let waiting = self.ctx.relevant_positions(t - 2, &[agent]);
+            for p in waiting.intersection(self.ctx.relevant_positions_for_agent(agent, t - 1)) {
+                if self.ctx.is_beam_tile(&p) || self.gems.contains(&p) {
+                    continue;
+                }
+                for q in &self.ctx.neighbours[p.i][p.j] {
+                    if q == p
+                        || !private.contains(&q)
+                        || !self.ctx.relevant_positions_for_agent(agent, t).contains(&q)
+                    {
+                        continue;
+                    }
+                    clauses.push(vec![
+                        -self.pool.agent(agent, p, t - 2),
+                        -self.pool.agent(agent, p, t - 1),
+                        -self.pool.agent(agent, q, t),
+                    ]);
+                }
+            }
+        }
+        clauses
+    }
+}
+
+#[cfg(test)]
+#[path = "../../../unit_tests/engine/test_symmetry.rs"]
+mod tests;
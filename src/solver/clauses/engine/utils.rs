@@ -1,4 +1,4 @@
-use crate::solver::{Clause, Literal, clauses::VarPool};
+use crate::solver::{AtMostOneEncoding, Clause, Literal, clauses::VarPool};
 
 /// At-most-one encoding crossover: for small variable sets, the naive pairwise encoding
 /// (n(n-1)/2 binary clauses, no auxiliary variables) uses fewer-or-equal clauses *and* zero
@@ -35,6 +35,178 @@ pub fn at_most_one_sequential(vars: &[Literal], pool: &mut VarPool) -> Vec<Claus
     clauses
 }
 
+/// At most one of `vars` is true, with `encoding`.
+pub fn at_most_one(
+    encoding: AtMostOneEncoding,
+    vars: &[Literal],
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    if vars.len() <= 1 {
+        return Vec::new();
+    }
+    match encoding {
+        AtMostOneEncoding::Auto if vars.len() <= PAIRWISE_ATMOST_MAX => at_most_one_pairwise(vars),
+        AtMostOneEncoding::Auto | AtMostOneEncoding::Sequential => {
+            at_most_one_sequential(vars, pool)
+        }
+        AtMostOneEncoding::Pairwise => at_most_one_pairwise(vars),
+        AtMostOneEncoding::Commander => at_most_one_commander(vars, pool),
+        AtMostOneEncoding::Product => at_most_one_product(vars, pool),
+        AtMostOneEncoding::Binary => at_most_one_binary(vars, pool),
+    }
+}
+
+/// Pairwise at-most-one encoding: one binary clause per pair of variables.
+pub fn at_most_one_pairwise(vars: &[Literal]) -> Vec<Clause> {
+    let mut clauses = Vec::with_capacity(vars.len() * vars.len().saturating_sub(1) / 2);
+    for i in 0..vars.len() {
+        for j in i + 1..vars.len() {
+            clauses.push(implies(vars[i], -vars[j]));
+        }
+    }
+    clauses
+}
+
+/// Commander at-most-one encoding (Klieber and Kwon, 2007).
+///
+/// The variables are split into groups of three, each with a commander implied by its members.
+/// At most one member per group is encoded pairwise, and at most one commander recursively.
+pub fn at_most_one_commander(vars: &[Literal], pool: &mut VarPool) -> Vec<Clause> {
+    const GROUP_SIZE: usize = 3;
+    if vars.len() <= GROUP_SIZE {
+        return at_most_one_pairwise(vars);
+    }
+    let mut clauses = Vec::new();
+    let mut commanders = Vec::with_capacity(vars.len().div_ceil(GROUP_SIZE));
+    for group in vars.chunks(GROUP_SIZE) {
+        let commander = pool.aux();
+        clauses.extend(group.iter().map(|&var| implies(var, commander)));
+        clauses.extend(at_most_one_pairwise(group));
+        commanders.push(commander);
+    }
+    clauses.extend(at_most_one_commander(&commanders, pool));
+    clauses
+}
+
+/// Product at-most-one encoding (Chen, 2010).
+///
+/// The variables are laid out on a `rows × columns` grid close to a square. Each variable implies
+/// its row and its column, and at most one row and one column are encoded recursively.
+pub fn at_most_one_product(vars: &[Literal], pool: &mut VarPool) -> Vec<Clause> {
+    if vars.len() <= PAIRWISE_ATMOST_MAX {
+        return at_most_one_pairwise(vars);
+    }
+    let root = vars.len().isqrt();
+    let columns = if root * root < vars.len() {
+        root + 1
+    } else {
+        root
+    };
+    let rows = vars.len().div_ceil(columns);
+    let row_vars = (0..rows).map(|_| pool.aux()).collect::<Vec<_>>();
+    let column_vars = (0..columns).map(|_| pool.aux()).collect::<Vec<_>>();
+    let mut clauses = Vec::with_capacity(2 * vars.len());
+    for (i, &var) in vars.iter().enumerate() {
+        clauses.push(implies(var, row_vars[i / columns]));
+        clauses.push(implies(var, column_vars[i % columns]));
+    }
+    clauses.extend(at_most_one_product(&row_vars, pool));
+    clauses.extend(at_most_one_product(&column_vars, pool));
+    clauses
+}
+
+/// Binary at-most-one encoding: the `i`-th variable implies the binary representation of `i` over
+/// `⌈log₂ n⌉` auxiliary bits, so two true variables would need two different representations.
+pub fn at_most_one_binary(vars: &[Literal], pool: &mut VarPool) -> Vec<Clause> {
+    let n_bits = (vars.len() - 1).ilog2() as usize + 1;
+    let bits = (0..n_bits).map(|_| pool.aux()).collect::<Vec<_>>();
+    let mut clauses = Vec::with_capacity(vars.len() * n_bits);
+    for (i, &var) in vars.iter().enumerate() {
+        for (k, &bit) in bits.iter().enumerate() {
+            let bit = if i >> k & 1 == 1 { bit } else { -bit };
+            clauses.push(implies(var, bit));
+        }
+    }
+    clauses
+}
+
+/// Two-sided sequential counter (Sinz, 2005): `outputs[j]` holds if and only if at least `j + 1`
+/// of `vars` are true.
+///
+/// Each prefix of `vars` gets one row of counter states, auxiliary except for the last row, which
+/// uses `outputs`. The counter stops at `outputs.len()`, so it uses `O(vars.len() · outputs.len())`
+/// clauses and variables. Counts that `vars` cannot reach are forced to false.
+pub fn sequential_counter(
+    vars: &[Literal],
+    outputs: &[Literal],
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    let bound = outputs.len();
+    let mut clauses = Vec::new();
+    // `previous[j]`: at least `j + 1` of the variables seen so far; `None` when unreachable.
+    let mut previous: Vec<Option<Literal>> = vec![None; bound];
+    for (i, &var) in vars.iter().enumerate() {
+        let is_last = i + 1 == vars.len();
+        let mut current = vec![None; bound];
+        for j in 0..bound.min(i + 1) {
+            let state = if is_last { outputs[j] } else { pool.aux() };
+            let below = (j > 0).then(|| previous[j - 1].expect("lower counts are reachable"));
+            // The count never decreases, and `var` increments it.
+            if let Some(previous) = previous[j] {
+                clauses.push(implies(previous, state));
+            }
+            let mut increment = vec![-var, state];
+            increment.extend(below.map(|below| -below));
+            clauses.push(increment);
+            // Conversely, a count is reached before `var` or `var` increments a lower count.
+            let mut support = vec![-state, var];
+            support.extend(previous[j]);
+            clauses.push(support);
+            if let Some(below) = below {
+                let mut support = vec![-state, below];
+                support.extend(previous[j]);
+                clauses.push(support);
+            }
+            current[j] = Some(state);
+        }
+        previous = current;
+    }
+    for (&output, state) in outputs.iter().zip(previous) {
+        if state != Some(output) {
+            clauses.push(vec![-output]);
+        }
+    }
+    clauses
+}
+
+/// Clauses making a progress literal imply its own causes: the progress already held at `t - 1`
+/// (`previous_time`), or the conjunction `transition` holds at `t`.
+///
+/// This is the converse of the Horn progress clauses, used when a progress state must witness an
+/// actual trail rather than only over-approximate it. A conjunction of several literals is reified
+/// into an auxiliary variable that implies each of them.
+pub fn supported_by(
+    progress: Literal,
+    previous_time: Option<Literal>,
+    transition: Option<&[Literal]>,
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    let mut support = vec![-progress];
+    support.extend(previous_time);
+    match transition {
+        Some(&[single]) => support.push(single),
+        Some(conjunction) => {
+            let step = pool.aux();
+            clauses.extend(conjunction.iter().map(|&literal| implies(step, literal)));
+            support.push(step);
+        }
+        None => {}
+    }
+    clauses.push(support);
+    clauses
+}
+
 #[cfg(test)]
 #[path = "../../../unit_tests/test_clause_utils.rs"]
 mod tests;
@@ -1,13 +1,16 @@
-use crate::solver::SolveMode;
+use itertools::Itertools;
+
 use crate::solver::errors::SolverError;
-use crate::{Action, World};
+use crate::solver::{
+    CooperationConstraint, EncodingConfig, EncodingStats, Explanation, GemObjective, Projection,
+    SolveMode, SolveSpec, UnsatReason,
+};
+use crate::{Action, AgentId, Position, World, WorldState};
 
-#[cfg(test)]
-use super::VarKey;
 use super::engine::ClauseEngine;
 use super::layout_facts::LayoutFacts;
 use super::mode_requirements::{HorizonFamily, ModeAssumptions, ModeRequirements, StepFamily};
-use super::{Clause, Literal, ParameterizedStepBuffer, StepBuffer};
+use super::{Clause, Literal, ParameterizedStepBuffer, StepBuffer, VarKey};
 
 type ClauseBuffer = StepBuffer<Clause>;
 type ParameterizedClauseBuffer = ParameterizedStepBuffer<Clause>;
@@ -19,8 +22,8 @@ type LiteralBuffer = StepBuffer<Literal>;
 /// for one step) and a set of [`StepBuffer`]s (which cache those clauses per time step). A
 /// [`ModeRequirements`] says which buffers a mode needs; the buffers fill themselves on demand.
 ///
-/// One generator answers repeated queries with different [`SolveMode`]s without rebuilding the
-/// shared world constraints, because the relevant buffers persist between calls. [`Self::generate`]
+/// One generator answers repeated queries with different [`SolveMode`]s, or conjunctions of them
+/// ([`SolveSpec`]), without rebuilding the shared world constraints, because the relevant buffers persist between calls. [`Self::generate`]
 /// reads them without remembering how far it got; [`Self::start_delta_stream`] (see
 /// [`super::DeltaStream`]) opens a session that does remember, for incremental SAT solving.
 pub struct ClauseGenerator {
@@ -37,15 +40,30 @@ pub struct ClauseGenerator {
     sequences: ParameterizedClauseBuffer,
     /// Closed-trail interdependence clauses cached independently for every exact order.
     interdependence: ParameterizedClauseBuffer,
+    /// Two-sided sequence progress clauses for `RequireSequence`, per sequence length.
+    required_sequences: ParameterizedClauseBuffer,
+    /// Two-sided closed-trail progress clauses for `RequireInterdependence`, per exact order.
+    required_interdependence: ParameterizedClauseBuffer,
     no_cooperation_assumptions: LiteralBuffer,
+    /// Opt-in trajectory symmetry-breaking clauses, shared by every solve mode.
+    symmetry: ClauseBuffer,
+    symmetry_breaking: bool,
 }
 
 impl ClauseGenerator {
     pub fn new(world: &World, t_max: usize) -> Self {
+        Self::with_engine(world, ClauseEngine::new(world, t_max), t_max)
+    }
+
+    fn with_engine(world: &World, engine: ClauseEngine, t_max: usize) -> Self {
+        Self::from_parts(LayoutFacts::new(world), engine, t_max)
+    }
+
+    fn from_parts(layout: LayoutFacts, engine: ClauseEngine, t_max: usize) -> Self {
         let capacity = t_max + 1;
         Self {
-            layout: LayoutFacts::new(world),
-            engine: ClauseEngine::new(world, t_max),
+            layout,
+            engine,
             movements: StepBuffer::new(ClauseEngine::generate_movement_clauses, capacity),
             lasers: StepBuffer::new(ClauseEngine::generate_laser_clauses, capacity),
             help: StepBuffer::new(ClauseEngine::generate_help_clauses, capacity),
@@ -57,30 +75,249 @@ impl ClauseGenerator {
                 ClauseEngine::generate_interdependence_clauses,
                 capacity,
             ),
+            required_sequences: ParameterizedStepBuffer::new(
+                ClauseEngine::generate_required_sequence_clauses,
+                capacity,
+            ),
+            required_interdependence: ParameterizedStepBuffer::new(
+                ClauseEngine::generate_required_interdependence_clauses,
+                capacity,
+            ),
             no_cooperation_assumptions: StepBuffer::new(
                 ClauseEngine::assume_no_cooperation_at,
                 capacity,
             ),
+            symmetry: StepBuffer::new(ClauseEngine::generate_symmetry_breaking_clauses, capacity),
+            symmetry_breaking: false,
+        }
+    }
+
+    /// A generator for the problem that starts from `state` instead of the start positions of
+    /// `world`, to replan in the middle of an episode.
+    ///
+    /// The agents start where `state` puts them; those that stand on an exit have arrived and stay
+    /// there. The gems that `state` marks as collected are removed from every gem objective: they
+    /// are no longer required by [`GemObjective::All`], [`GemObjective::Subset`] and
+    /// [`GemObjective::ByAgent`], and they count towards [`GemObjective::AtLeast`] and
+    /// [`Self::count_gems`]. Plans decoded from this generator start at `state`.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidState`] if `state` is not a valid state of `world` (see
+    /// [`World::set_state`]) or if one of its agents is dead, since a dead agent never reaches an
+    /// exit.
+    pub fn from_state(
+        world: &World,
+        state: &WorldState,
+        t_max: usize,
+    ) -> Result<Self, SolverError> {
+        if let Err(error) = world.clone().set_state(state) {
+            return Err(SolverError::InvalidState {
+                reason: error.to_string(),
+            });
         }
+        if let Some(agent) = state.agents_alive.iter().position(|&alive| !alive) {
+            return Err(SolverError::InvalidState {
+                reason: format!("agent {agent} is dead and can never reach an exit"),
+            });
+        }
+        let engine = ClauseEngine::from_state(world, state, t_max);
+        Ok(Self::with_engine(world, engine, t_max))
+    }
+
+    /// A generator for the same world, horizon and options when the agents start from `starts`,
+    /// such as one of [`World::start_assignments`].
+    ///
+    /// The world geometry (movement graph, exit distances, laser paths) is computed once and
+    /// shared, so checking a world from every start assignment only pays for what depends on the
+    /// starts. The clauses cached by `self` are not reused, since their variables depend on them.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidState`] if `starts` does not give one walkable position per
+    /// agent, or if two agents share a position.
+    pub fn with_starts(&self, starts: &[Position]) -> Result<Self, SolverError> {
+        let n_agents = self.engine.ctx.n_agents;
+        let reason = if starts.len() != n_agents {
+            Some(format!(
+                "expected {n_agents} start positions, got {}",
+                starts.len()
+            ))
+        } else if !starts.iter().all_unique() {
+            Some("there are two agents at the same position".to_string())
+        } else {
+            starts
+                .iter()
+                .find(|pos| {
+                    let neighbours = self
+                        .engine
+                        .ctx
+                        .neighbours
+                        .get(pos.i)
+                        .and_then(|row| row.get(pos.j));
+                    neighbours.is_none_or(|neighbours| neighbours.is_empty())
+                })
+                .map(|pos| format!("({}, {}) is not a walkable tile", pos.i, pos.j))
+        };
+        if let Some(reason) = reason {
+            return Err(SolverError::InvalidState { reason });
+        }
+        let engine = self.engine.with_starts(starts.to_vec());
+        Ok(
+            Self::from_parts(self.layout.clone(), engine, self.engine.ctx.t_max)
+                .with_symmetry_breaking(self.symmetry_breaking),
+        )
+    }
+
+    /// Enable or disable the trajectory symmetry-breaking clauses (disabled by default).
+    ///
+    /// They forbid plans that wait on a position before moving to a position private to the agent,
+    /// since the same plan with the wait one step later is equally valid. The formula stays
+    /// satisfiable for exactly the same horizons and modes, but the SAT solver explores fewer
+    /// equivalent plans. See [`ClauseEngine::generate_symmetry_breaking_clauses`].
+    pub fn with_symmetry_breaking(mut self, enabled: bool) -> Self {
+        self.symmetry_breaking = enabled;
+        self
+    }
+
+    pub fn symmetry_breaking(&self) -> bool {
+        self.symmetry_breaking
+    }
+
+    /// Select the at-most-one encodings of the movement clauses (see [`EncodingConfig`]).
+    ///
+    /// Every encoding gives the same satisfiable horizons and the same plans; they only differ in
+    /// size. Compare them on a map with [`Self::encoding_stats`].
+    ///
+    /// # Panics
+    /// Panics if this generator already generated clauses, which would mix encodings.
+    pub fn with_encoding(mut self, encoding: EncodingConfig) -> Self {
+        assert_eq!(
+            self.engine.pool.n_vars(),
+            0,
+            "the encoding must be selected before generating clauses"
+        );
+        self.engine.encoding = encoding;
+        self
+    }
+
+    pub fn encoding(&self) -> EncodingConfig {
+        self.engine.encoding
+    }
+
+    /// Clauses and variables per world-enforcing family, over every step generated so far: one
+    /// entry per movement rule (`exactly_one_position`, `no_overlap`, ...) and one for `lasers`.
+    ///
+    /// Steps are generated once and cached, so after [`Self::generate`] at horizon `t`, these are
+    /// the sizes of the world rules of that formula (and of any shorter one).
+    pub fn encoding_stats(&self) -> &EncodingStats {
+        self.engine.stats()
     }
 
     /// Reduce a mode whose forbidden profile is structurally impossible to the standard mode.
     ///
     /// Parameterized modes are valid by construction, so this method only applies the layout-level
-    /// feasibility shortcut.
+    /// feasibility shortcut. `Require*` modes are kept as they are: an impossible required profile
+    /// makes the formula unsatisfiable instead.
     pub(super) fn effective_mode(&self, mode: SolveMode) -> SolveMode {
-        if self.layout.positive_profile_is_possible(mode) {
+        if mode.is_requirement() || self.layout.positive_profile_is_possible(mode) {
             mode
         } else {
             SolveMode::Standard
         }
     }
 
+    /// Apply [`Self::effective_mode`] to every mode of `spec`, dropping those that become standard,
+    /// after checking its cooperation constraints with [`Self::check_spec`].
+    pub(super) fn effective_spec(&self, spec: SolveSpec) -> Result<SolveSpec, SolverError> {
+        self.check_spec(&spec)?;
+        let constraints = spec.constraints().iter().copied();
+        Ok(constraints.fold(
+            spec.modes()
+                .iter()
+                .map(|&mode| self.effective_mode(mode))
+                .collect(),
+            SolveSpec::with_constraint,
+        ))
+    }
+
+    /// Check that every cooperation constraint of `spec` names agents and lasers of this world, and
+    /// that no agent is asked to help itself.
+    pub fn check_spec(&self, spec: &SolveSpec) -> Result<(), SolverError> {
+        spec.constraints()
+            .iter()
+            .try_for_each(|&constraint| self.layout.check_constraint(constraint))
+    }
+
+    /// Check that every gem named by `gems` is a gem of this world, collected by one of its agents.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidGemObjective`] otherwise.
+    pub fn check_gem_objective(&self, gems: &GemObjective) -> Result<(), SolverError> {
+        let tiles: Vec<(Position, Option<AgentId>)> = match gems {
+            GemObjective::None | GemObjective::All | GemObjective::AtLeast(_) => vec![],
+            GemObjective::Subset(tiles) => tiles.iter().map(|&pos| (pos, None)).collect(),
+            GemObjective::ByAgent(tiles) => tiles
+                .iter()
+                .map(|&(pos, agent)| (pos, Some(agent)))
+                .collect(),
+        };
+        let n_agents = self.engine.ctx.n_agents;
+        for (pos, agent) in tiles {
+            let is_gem =
+                self.engine.gems.contains(&pos) || self.engine.collected_gems.contains(&pos);
+            let reason = if !is_gem {
+                format!("there is no gem at ({}, {})", pos.i, pos.j)
+            } else if agent.is_some_and(|agent| agent >= n_agents) {
+                format!("the world only has {n_agents} agents")
+            } else {
+                continue;
+            };
+            return Err(SolverError::InvalidGemObjective { reason });
+        }
+        Ok(())
+    }
+
+    /// What remains of `gems` to collect (see [`Self::from_state`]), after checking it with
+    /// [`Self::check_gem_objective`].
+    pub(super) fn checked_gem_objective(
+        &self,
+        gems: GemObjective,
+    ) -> Result<GemObjective, SolverError> {
+        self.check_gem_objective(&gems)?;
+        Ok(self.engine.remaining_objective(gems))
+    }
+
+    /// Clauses asserting `constraint` through horizon `t`, to add permanently to a formula of this
+    /// generator instead of assuming the constraint through a [`SolveSpec`].
+    ///
+    /// Asserting a forbidding constraint is equivalent to assuming it. A required help event, on
+    /// the other hand, is not scoped to `t`: it must then happen by `t` at every longer horizon
+    /// solved with the same SAT solver.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidConstraint`] if `constraint` does not fit this world.
+    pub fn constraint_clauses(
+        &mut self,
+        constraint: CooperationConstraint,
+        t: usize,
+    ) -> Result<Vec<Clause>, SolverError> {
+        self.layout.check_constraint(constraint)?;
+        Ok(self.engine.generate_constraint_clauses(constraint, t))
+    }
+
     /// Generate the complete formula for horizon `t`: every clause and assumption needed to solve
     /// the problem, from step 0.
     ///
-    /// Modes whose forbidden cooperation profile is structurally impossible in this world are
-    /// normalized to [`SolveMode::Standard`] beforehand (see [`Self::effective_mode`]).
+    /// `spec` is a single [`SolveMode`] or a [`SolveSpec`] combining several of them with
+    /// cooperation constraints, which are assumed. Modes whose forbidden cooperation profile is
+    /// structurally impossible in this world are normalized to [`SolveMode::Standard`] beforehand
+    /// (see [`Self::effective_mode`]).
+    ///
+    /// `gems` is a [`GemObjective`], or a `bool` that requires all gems or none.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidConstraint`] if a constraint of `spec` does not fit this
+    /// world (see [`Self::check_spec`]), or [`SolverError::InvalidGemObjective`] if `gems` does
+    /// not (see [`Self::check_gem_objective`]).
     ///
     /// The result is a function of the arguments alone. This call does not read or affect any
     /// [`DeltaStream`](super::DeltaStream), so the two outputs must not be fed to the same SAT
@@ -90,20 +327,216 @@ impl ClauseGenerator {
     pub fn generate(
         &mut self,
         t: usize,
-        mode: SolveMode,
-        collect_gems: bool,
-    ) -> (Vec<Clause>, Vec<Literal>) {
-        let mode = self.effective_mode(mode);
-        let requirements = ModeRequirements::of(mode);
+        spec: impl Into<SolveSpec>,
+        gems: impl Into<GemObjective>,
+    ) -> Result<(Vec<Clause>, Vec<Literal>), SolverError> {
+        let spec = self.effective_spec(spec.into())?;
+        let gems = self.checked_gem_objective(gems.into())?;
+        let requirements = ModeRequirements::of_spec(&spec);
         let mut clauses = self.step_clauses(&requirements, 0, t);
         clauses.extend(self.horizon_clauses(&requirements, t));
-        clauses.extend(self.engine.objective(t, collect_gems));
+        clauses.extend(self.engine.objective(t, &gems));
         let assumptions = self.mode_assumptions(&requirements, t);
-        (clauses, assumptions)
+        Ok((clauses, assumptions))
+    }
+
+    /// Generate the formula of [`Self::generate`] with every restriction behind a selector
+    /// literal, to explain why it is unsatisfiable.
+    ///
+    /// Each selector switches on one [`UnsatReason`]: the goal of one agent, one gem or the gem
+    /// count of [`GemObjective::AtLeast`], one help pair
+    /// forbidden by [`SolveMode::NoCooperation`], one pattern of [`SolveMode::NoSequentialCooperation`]
+    /// or [`SolveMode::NoInterdependence`], or the remaining restriction of a mode or a cooperation
+    /// constraint as a whole. The selectors are part of the returned assumptions; with all of them,
+    /// the formula is satisfiable exactly when the formula of [`Self::generate`] is. When it is not,
+    /// an assumption core of the SAT solver maps back to its reasons through
+    /// [`Explanation::reasons`].
+    ///
+    /// Removing a selector from the assumptions relaxes its reason, down to the standard problem
+    /// without objective. The laser clauses are always included for that purpose, even under
+    /// [`SolveMode::NoCooperation`] alone.
+    ///
+    /// # Errors
+    /// Same as [`Self::generate`].
+    pub fn generate_explained(
+        &mut self,
+        t: usize,
+        spec: impl Into<SolveSpec>,
+        gems: impl Into<GemObjective>,
+    ) -> Result<(Vec<Clause>, Vec<Literal>, Explanation), SolverError> {
+        let spec = self.effective_spec(spec.into())?;
+        let gems = self.checked_gem_objective(gems.into())?;
+        let requirements = ModeRequirements::of_spec(&spec).explained();
+        let mut clauses = self.step_clauses(&requirements, 0, t);
+        clauses.extend(self.horizon_clauses(&requirements, t));
+        let mut assumptions = self.mode_assumptions(&requirements, t);
+
+        let mut groups = Vec::new();
+        for (agent, goal) in self.engine.agent_goals(t).into_iter().enumerate() {
+            groups.push((UnsatReason::AgentGoal(agent), vec![goal]));
+        }
+        groups.extend(self.explained_gem_clauses(&gems, t));
+        for &mode in spec.modes() {
+            groups.extend(self.explained_mode_clauses(mode, t));
+        }
+        for &constraint in spec.constraints() {
+            let group = match constraint {
+                CooperationConstraint::RequireHelp {
+                    helper,
+                    beneficiary,
+                } => self
+                    .engine
+                    .generate_required_pair_help_clauses(t, helper, beneficiary),
+                _ => self
+                    .assumption_literals(ModeAssumptions::Forbid(constraint), t)
+                    .into_iter()
+                    .map(|literal| vec![literal])
+                    .collect(),
+            };
+            groups.push((UnsatReason::Constraint(constraint), group));
+        }
+
+        let mut explanation = Explanation::default();
+        for (reason, group) in groups {
+            if group.is_empty() {
+                continue;
+            }
+            let selector = self.engine.pool.aux();
+            clauses.extend(group.into_iter().map(|mut clause| {
+                clause.push(-selector);
+                clause
+            }));
+            assumptions.push(selector);
+            explanation.push(selector, reason);
+        }
+        Ok((clauses, assumptions, explanation))
     }
 
-    /// Step-indexed clauses (`requirements`' subset of movements/lasers/help/step family) for the
-    /// inclusive range `start..=t`.
+    /// The gem objective for [`Self::generate_explained`], grouped by reason: one group per gem
+    /// to collect, or a single group for the gem count of [`GemObjective::AtLeast`].
+    fn explained_gem_clauses(
+        &mut self,
+        gems: &GemObjective,
+        t: usize,
+    ) -> Vec<(UnsatReason, Vec<Clause>)> {
+        let clauses = self.engine.generate_gem_objective(gems, t);
+        let reasons = match gems {
+            GemObjective::None => vec![],
+            &GemObjective::AtLeast(k) => return vec![(UnsatReason::GemCount(k), clauses)],
+            GemObjective::All => self.engine.gems.iter().map(UnsatReason::Gem).collect(),
+            GemObjective::Subset(tiles) => tiles.iter().copied().map(UnsatReason::Gem).collect(),
+            GemObjective::ByAgent(tiles) => tiles
+                .iter()
+                .map(|&(pos, agent)| UnsatReason::GemCollector { pos, agent })
+                .collect(),
+        };
+        reasons
+            .into_iter()
+            .zip(clauses)
+            .map(|(reason, clause)| (reason, vec![clause]))
+            .collect()
+    }
+
+    /// The restrictions of `mode` for [`Self::generate_explained`], grouped by reason. A reason may
+    /// come with no clause when it cannot be violated by `t`.
+    fn explained_mode_clauses(
+        &mut self,
+        mode: SolveMode,
+        t: usize,
+    ) -> Vec<(UnsatReason, Vec<Clause>)> {
+        match mode {
+            SolveMode::NoCooperation => self
+                .engine
+                .potential_help_arcs()
+                .into_iter()
+                .map(|arc| {
+                    let constraint = CooperationConstraint::ForbidHelp {
+                        helper: arc.helper,
+                        beneficiary: arc.beneficiary,
+                    };
+                    let units = (0..=t)
+                        .flat_map(|step| self.engine.assume_constraint_at(constraint, step))
+                        .map(|literal| vec![literal])
+                        .collect();
+                    let reason = UnsatReason::ForbiddenHelp {
+                        helper: arc.helper,
+                        beneficiary: arc.beneficiary,
+                    };
+                    (reason, units)
+                })
+                .collect(),
+            // A complete progress state holds iff its pattern happened by `t`.
+            SolveMode::NoSequentialCooperation(length) => {
+                let length = length.get();
+                let patterns = self.engine.sequence_patterns(length);
+                patterns
+                    .iter()
+                    .enumerate()
+                    .map(|(pattern, arcs)| {
+                        let complete = self.engine.pool.get(&VarKey::SequenceProgress {
+                            length,
+                            pattern,
+                            prefix_len: length,
+                            t,
+                        });
+                        let reason = UnsatReason::SequencePattern {
+                            length,
+                            arcs: arcs
+                                .arcs
+                                .iter()
+                                .map(|a| (a.helper, a.beneficiary))
+                                .collect(),
+                        };
+                        (reason, complete.map(|c| vec![-c]).into_iter().collect())
+                    })
+                    .collect()
+            }
+            SolveMode::NoInterdependence(order) => {
+                let order = order.get();
+                let patterns = self.engine.interdependence_patterns(order);
+                patterns
+                    .iter()
+                    .enumerate()
+                    .map(|(pattern, arcs)| {
+                        let complete = self.engine.pool.get(&VarKey::InterdependenceProgress {
+                            order,
+                            pattern,
+                            prefix_len: arcs.arcs.len(),
+                            t,
+                        });
+                        let reason = UnsatReason::InterdependencePattern {
+                            order,
+                            arcs: arcs
+                                .arcs
+                                .iter()
+                                .map(|a| (a.helper, a.beneficiary))
+                                .collect(),
+                        };
+                        (reason, complete.map(|c| vec![-c]).into_iter().collect())
+                    })
+                    .collect()
+            }
+            _ => {
+                let requirements = ModeRequirements::of(mode);
+                let mut group = Vec::new();
+                for &family in &requirements.horizon_families {
+                    if !family.is_definition() {
+                        group.extend(self.horizon_family_clauses(family, t));
+                    }
+                }
+                for &kind in &requirements.assumptions {
+                    if kind != ModeAssumptions::RequiredProfile {
+                        let literals = self.assumption_literals(kind, t);
+                        group.extend(literals.into_iter().map(|literal| vec![literal]));
+                    }
+                }
+                vec![(UnsatReason::Mode(mode), group)]
+            }
+        }
+    }
+
+    /// Step-indexed clauses (`requirements`' subset of movements/lasers/help/step families, plus the
+    /// symmetry-breaking clauses when enabled) for the inclusive range `start..=t`.
     ///
     /// Shared by [`Self::generate`] (always `start = 0`) and [`super::DeltaStream::advance_to`]
     /// (`start` is the first step it has not sent yet).
@@ -122,21 +555,28 @@ impl ClauseGenerator {
         if requirements.lasers {
             clauses.extend(self.lasers.gather_range(&mut self.engine, start, t));
         }
+        if self.symmetry_breaking {
+            clauses.extend(self.symmetry.gather_range(&mut self.engine, start, t));
+        }
         if requirements.help {
             clauses.extend(self.help.gather_range(&mut self.engine, start, t));
         }
-        if let Some(family) = requirements.step_family {
+        for &family in &requirements.step_families {
             let (buffer, parameter) = match family {
                 StepFamily::Sequences(length) => (&mut self.sequences, length),
                 StepFamily::Interdependence(order) => (&mut self.interdependence, order),
+                StepFamily::RequiredSequences(length) => (&mut self.required_sequences, length),
+                StepFamily::RequiredInterdependence(order) => {
+                    (&mut self.required_interdependence, order)
+                }
             };
             clauses.extend(buffer.gather_range(&mut self.engine, start, t, parameter));
         }
         clauses
     }
 
-    /// Horizon-wide clauses (`requirements`' subset of asymmetry/pairwise-help/degree blockers) for
-    /// exactly horizon `t`.
+    /// Horizon-wide clauses (`requirements`' subset of asymmetry/pairwise-help/degree blockers and
+    /// required-profile goals) for exactly horizon `t`.
     ///
     /// Unlike [`Self::step_clauses`], these are not incremental: every call regenerates the complete
     /// family for `t`, because their defining variables and clauses only make sense at that one
@@ -150,37 +590,76 @@ impl ClauseGenerator {
     ) -> Vec<Clause> {
         let mut clauses = Vec::new();
         for &family in &requirements.horizon_families {
-            clauses.extend(match family {
-                HorizonFamily::Asymmetry => {
-                    let mut asymmetry = self.engine.generate_is_helped(t);
-                    asymmetry.extend(self.engine.generate_provides_help(t));
-                    asymmetry.extend(self.engine.encode_asymmetry(t));
-                    asymmetry
-                }
-                HorizonFamily::PairwiseHelp => self.engine.generate_pairwise_help_clauses(t),
-                HorizonFamily::NoConvergence(k) => {
-                    self.engine.generate_no_convergence_clauses(t, k)
-                }
-                HorizonFamily::NoDivergence(k) => self.engine.generate_no_divergence_clauses(t, k),
-                HorizonFamily::NoFullyCoupled => self.engine.generate_no_fully_coupled_clauses(t),
-            });
+            clauses.extend(self.horizon_family_clauses(family, t));
         }
         clauses
     }
 
+    /// The clauses of one horizon-wide family for exactly horizon `t`.
+    fn horizon_family_clauses(&mut self, family: HorizonFamily, t: usize) -> Vec<Clause> {
+        match family {
+            HorizonFamily::Asymmetry => {
+                let mut asymmetry = self.engine.generate_is_helped(t);
+                asymmetry.extend(self.engine.generate_provides_help(t));
+                asymmetry.extend(self.engine.encode_asymmetry(t));
+                asymmetry
+            }
+            HorizonFamily::PairwiseHelp => self.engine.generate_pairwise_help_clauses(t),
+            HorizonFamily::NoConvergence(k) => self.engine.generate_no_convergence_clauses(t, k),
+            HorizonFamily::NoDivergence(k) => self.engine.generate_no_divergence_clauses(t, k),
+            HorizonFamily::NoFullyCoupled => self.engine.generate_no_fully_coupled_clauses(t),
+            HorizonFamily::RequiredHelp => self.engine.generate_required_help_clauses(t),
+            HorizonFamily::RequiredSequence(length) => {
+                self.engine.generate_required_sequence_goal(t, length)
+            }
+            HorizonFamily::RequiredInterdependence(order) => {
+                self.engine.generate_required_interdependence_goal(t, order)
+            }
+            HorizonFamily::RequiredConvergence(k) => {
+                self.engine.generate_required_convergence_clauses(t, k)
+            }
+            HorizonFamily::RequiredDivergence(k) => {
+                self.engine.generate_required_divergence_clauses(t, k)
+            }
+            HorizonFamily::HelpEventCounter(bound) => {
+                self.engine.generate_help_event_counter(t, bound)
+            }
+            HorizonFamily::RequiredPairHelp {
+                helper,
+                beneficiary,
+            } => self
+                .engine
+                .generate_required_pair_help_clauses(t, helper, beneficiary),
+        }
+    }
+
     /// Assumptions `requirements` needs on top of its clauses, for horizon `t`.
     pub(super) fn mode_assumptions(
         &mut self,
         requirements: &ModeRequirements,
         t: usize,
     ) -> Vec<Literal> {
-        match requirements.assumptions {
-            ModeAssumptions::None => vec![],
+        let mut assumptions = Vec::new();
+        for &kind in &requirements.assumptions {
+            assumptions.extend(self.assumption_literals(kind, t));
+        }
+        assumptions
+    }
+
+    /// The literals of one kind of assumptions, for horizon `t`.
+    fn assumption_literals(&mut self, kind: ModeAssumptions, t: usize) -> Vec<Literal> {
+        match kind {
             ModeAssumptions::NoCooperation => self
                 .no_cooperation_assumptions
                 .gather_until(&mut self.engine, t)
                 .collect(),
             ModeAssumptions::NoAsymmetry => self.engine.assume_no_asymmetry(t),
+            ModeAssumptions::Asymmetry => self.engine.assume_asymmetry(t),
+            ModeAssumptions::RequiredProfile => vec![self.engine.pool.required_profile(t)],
+            ModeAssumptions::HelpEvents { min, max } => self.engine.assume_help_events(t, min, max),
+            ModeAssumptions::Forbid(constraint) => (0..=t)
+                .flat_map(|step| self.engine.assume_constraint_at(constraint, step))
+                .collect(),
         }
     }
 
@@ -190,9 +669,68 @@ impl ClauseGenerator {
         self.engine.pool.aux()
     }
 
-    /// Objective clauses for horizon `t`. Not cached.
-    pub fn objective(&mut self, t: usize, collect_gems: bool) -> Vec<Clause> {
-        self.engine.objective(t, collect_gems)
+    /// Number of help events through horizon `t` in a SAT model of one of this generator's
+    /// formulas, whatever its mode. This is the quantity bounded by [`SolveMode::MaxHelpEvents`].
+    pub fn count_help_events(&mut self, model: &[Literal], t: usize) -> usize {
+        self.engine.count_help_events(model, t)
+    }
+
+    /// The help events `(helper, beneficiary, t)` through horizon `t` in a SAT model of one of this
+    /// generator's formulas, whatever its mode.
+    pub fn help_edges(&mut self, model: &[Literal], t: usize) -> Vec<(AgentId, AgentId, usize)> {
+        self.engine.help_edges(model, t)
+    }
+
+    /// Clauses defining the variables of `projection` through horizon `t`, to add to a formula of
+    /// this generator before enumerating its solutions with [`Self::blocking_clause`].
+    ///
+    /// Positions need none; help edges need the `help(h, b, t)` encoding, and pairwise help also
+    /// the summaries of horizon `t`. The clauses may repeat some of the formula, which is harmless.
+    pub fn projection_clauses(&mut self, t: usize, projection: Projection) -> Vec<Clause> {
+        let mut clauses = Vec::new();
+        if projection != Projection::Positions {
+            clauses.extend(self.help.gather_until(&mut self.engine, t));
+        }
+        if projection == Projection::PairwiseHelp {
+            clauses.extend(self.engine.generate_pairwise_help_clauses(t));
+        }
+        clauses
+    }
+
+    /// The clause ruling out every solution with the same `projection` as `model` through horizon
+    /// `t`. Adding it after each model and solving again enumerates the distinct solutions, until
+    /// the formula becomes unsatisfiable.
+    ///
+    /// The clauses of [`Self::projection_clauses`] must be part of the formula. An empty clause
+    /// means that every model has the same projection, so there is nothing left to enumerate.
+    pub fn blocking_clause(&self, model: &[Literal], t: usize, projection: Projection) -> Clause {
+        self.engine.blocking_clause(model, t, projection)
+    }
+
+    /// Objective clauses for horizon `t`, with the gem objective `gems`. Not cached.
+    ///
+    /// # Errors
+    /// Returns [`SolverError::InvalidGemObjective`] if `gems` does not fit this world (see
+    /// [`Self::check_gem_objective`]).
+    pub fn objective(
+        &mut self,
+        t: usize,
+        gems: impl Into<GemObjective>,
+    ) -> Result<Vec<Clause>, SolverError> {
+        let gems = self.checked_gem_objective(gems.into())?;
+        Ok(self.checked_objective(t, &gems))
+    }
+
+    /// Objective clauses for horizon `t`, with a gem objective already returned by
+    /// [`Self::checked_gem_objective`].
+    pub(super) fn checked_objective(&mut self, t: usize, gems: &GemObjective) -> Vec<Clause> {
+        self.engine.objective(t, gems)
+    }
+
+    /// Number of gems collected before horizon `t` in `model`, whatever the objective it was
+    /// solved with, including those collected before [`Self::from_state`].
+    pub fn count_gems(&self, model: &[Literal], t: usize) -> usize {
+        self.engine.count_gems(model, t)
     }
 
     #[inline]
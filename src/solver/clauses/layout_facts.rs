@@ -1,38 +1,56 @@
+use itertools::Itertools;
+
 use crate::World;
-use crate::solver::SolveMode;
+use crate::solver::errors::SolverError;
+use crate::solver::{CooperationConstraint, SolveMode};
 
 /// Immutable layout facts used to decide whether a cooperation profile can occur at all.
 ///
 /// They are cheap counts of the world geometry, gathered once when the generator is built. They
 /// deliberately ignore reachability and the planning horizon: finer impossibility cases are already
 /// handled by the geometric pruning of [`ClauseEngine`](super::ClauseEngine).
+#[derive(Clone)]
 pub(super) struct LayoutFacts {
     /// Number of agents in the world.
     n_agents: usize,
-    /// Number of laser sources, whatever their colour.
+    /// Number of laser sources, whatever their colour, enabled or not.
     n_lasers: usize,
-    /// Number of distinct laser owners. Laser colours are agent IDs, so this is also the number of
-    /// agents that can possibly act as a helper.
+    /// Number of enabled laser sources. Disabled beams never need to be blocked.
+    n_enabled_lasers: usize,
+    /// Number of distinct owners of enabled lasers, in their current colour. Laser colours are
+    /// agent IDs, so this is also the number of agents that can possibly act as a helper.
     n_laser_colours: usize,
 }
 
 impl LayoutFacts {
     pub(super) fn new(world: &World) -> Self {
+        let enabled = world
+            .sources()
+            .filter(|(_, source)| source.is_enabled())
+            .collect::<Vec<_>>();
         Self {
             n_agents: world.n_agents(),
             n_lasers: world.sources().count(),
-            n_laser_colours: world.n_laser_colours(),
+            n_enabled_lasers: enabled.len(),
+            n_laser_colours: enabled
+                .iter()
+                .map(|(_, source)| source.agent_id())
+                .unique()
+                .count(),
         }
     }
 
-    /// Whether the cooperation profile *forbidden* by `mode` can structurally occur in this layout.
+    /// Whether the cooperation profile forbidden or required by `mode` can structurally occur in
+    /// this layout.
     ///
-    /// The public modes are negative encodings: `NoSequentialCooperation` forbids sequences, and so on.
-    /// When this predicate returns `false`, the positive property cannot occur at all, so the
-    /// restriction is tautologically satisfied and the mode reduces to [`SolveMode::Standard`].
+    /// For the negative modes (`NoSequentialCooperation` forbids sequences, and so on), a `false`
+    /// answer means the positive property cannot occur at all, so the restriction is
+    /// tautologically satisfied and the mode reduces to [`SolveMode::Standard`]. For the `Require*`
+    /// modes, it means that no plan can satisfy the mode.
     ///
-    /// The conditions below are *necessary*, not sufficient: they only count agents, laser sources
-    /// and laser owners, so a mode may still be feasible here and impossible for geometric reasons.
+    /// The conditions below are *necessary*, not sufficient: they only count agents, enabled laser
+    /// sources and laser owners, so a mode may still be feasible here and impossible for geometric
+    /// reasons.
     ///
     /// - Every profile needs a helper, a distinct beneficiary, and a beam to block.
     /// - A sequence `a → b → c` needs two distinct laser-owning helpers. Lasers and help events may
@@ -45,24 +63,27 @@ impl LayoutFacts {
     ///   colour: only the agent count matters.
     /// - Fully coupled cooperation makes every agent a helper, so every agent must own a laser.
     pub(super) fn positive_profile_is_possible(&self, mode: SolveMode) -> bool {
-        let cooperation_is_possible = self.n_agents >= 2 && self.n_lasers >= 1;
+        let cooperation_is_possible = self.n_agents >= 2 && self.n_enabled_lasers >= 1;
         match mode {
             SolveMode::Standard => true,
-            SolveMode::NoCooperation | SolveMode::NoAsymmetricCooperation => {
-                cooperation_is_possible
-            }
-            SolveMode::NoSequentialCooperation(_) => {
+            SolveMode::NoCooperation
+            | SolveMode::NoAsymmetricCooperation
+            | SolveMode::RequireCooperation
+            | SolveMode::RequireAsymmetric
+            | SolveMode::MaxHelpEvents(_)
+            | SolveMode::ExactHelpEvents(_) => cooperation_is_possible,
+            SolveMode::NoSequentialCooperation(_) | SolveMode::RequireSequence(_) => {
                 cooperation_is_possible && self.n_laser_colours >= 2
             }
-            SolveMode::NoInterdependence(order) => {
+            SolveMode::NoInterdependence(order) | SolveMode::RequireInterdependence(order) => {
                 let order = order.get();
                 cooperation_is_possible && self.n_agents >= order && self.n_laser_colours >= order
             }
-            SolveMode::NoConvergentCooperation(k) => {
+            SolveMode::NoConvergentCooperation(k) | SolveMode::RequireConvergence(k) => {
                 let k = k.get();
                 cooperation_is_possible && self.n_agents > k && self.n_laser_colours >= k
             }
-            SolveMode::NoDivergentCooperation(k) => {
+            SolveMode::NoDivergentCooperation(k) | SolveMode::RequireDivergence(k) => {
                 cooperation_is_possible && self.n_agents > k.get()
             }
             SolveMode::NoFullyCoupledCooperation => {
@@ -70,4 +91,37 @@ impl LayoutFacts {
             }
         }
     }
+
+    /// Check that `constraint` names agents and lasers of this layout, and no self-help.
+    pub(super) fn check_constraint(
+        &self,
+        constraint: CooperationConstraint,
+    ) -> Result<(), SolverError> {
+        let reason = match constraint {
+            CooperationConstraint::ForbidHelp {
+                helper,
+                beneficiary,
+            }
+            | CooperationConstraint::RequireHelp {
+                helper,
+                beneficiary,
+            } => {
+                if helper.max(beneficiary) >= self.n_agents {
+                    Some(format!("the world only has {} agents", self.n_agents))
+                } else if helper == beneficiary {
+                    Some("an agent never helps itself".to_string())
+                } else {
+                    None
+                }
+            }
+            CooperationConstraint::ForbidHelper(helper) => (helper >= self.n_agents)
+                .then(|| format!("the world only has {} agents", self.n_agents)),
+            CooperationConstraint::ForbidLaser(laser_id) => (laser_id >= self.n_lasers)
+                .then(|| format!("the world only has {} lasers", self.n_lasers)),
+        };
+        match reason {
+            Some(reason) => Err(SolverError::InvalidConstraint { constraint, reason }),
+            None => Ok(()),
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use crate::solver::SolveMode;
+use crate::{
+    AgentId,
+    solver::{CooperationConstraint, SolveMode, SolveSpec},
+};
 
 /// A per-step clause family whose generation depends on a mode parameter.
 ///
@@ -10,6 +13,10 @@ pub(super) enum StepFamily {
     Sequences(usize),
     /// Closed-trail rejection clauses for exactly this order.
     Interdependence(usize),
+    /// Two-sided progress clauses for temporal help trails of exactly this length.
+    RequiredSequences(usize),
+    /// Two-sided progress clauses for closed trails of exactly this order.
+    RequiredInterdependence(usize),
 }
 
 /// A clause family produced for a whole horizon at once instead of step by step.
@@ -28,47 +35,154 @@ pub(super) enum HorizonFamily {
     NoDivergence(usize),
     /// The clause requiring one ordered pair to lack help.
     NoFullyCoupled,
+    /// The required profile implies some help event.
+    RequiredHelp,
+    /// The required profile implies some completed sequence pattern of this length.
+    RequiredSequence(usize),
+    /// The required profile implies some completed closed-trail pattern of this order.
+    RequiredInterdependence(usize),
+    /// The required profile implies `k` distinct helpers for one beneficiary.
+    RequiredConvergence(usize),
+    /// The required profile implies `k` distinct beneficiaries for one helper.
+    RequiredDivergence(usize),
+    /// Unary counter of the help events, up to this bound.
+    HelpEventCounter(usize),
+    /// The required profile implies some help event from `helper` to `beneficiary`.
+    RequiredPairHelp {
+        helper: AgentId,
+        beneficiary: AgentId,
+    },
+}
+
+impl HorizonFamily {
+    /// Whether the family only defines auxiliary variables, without restricting plans by itself.
+    pub(super) fn is_definition(&self) -> bool {
+        matches!(
+            self,
+            HorizonFamily::Asymmetry
+                | HorizonFamily::PairwiseHelp
+                | HorizonFamily::HelpEventCounter(_)
+        )
+    }
 }
 
 /// The assumptions a mode needs on top of its clauses.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub(super) enum ModeAssumptions {
-    /// The mode restricts nothing through assumptions.
-    None,
     /// One literal per agent position that would stand in someone else's beam.
     NoCooperation,
     /// The negation of the global asymmetry flag for the requested horizon.
     NoAsymmetry,
+    /// The global asymmetry flag for the requested horizon.
+    Asymmetry,
+    /// The required-profile flag for the requested horizon.
+    RequiredProfile,
+    /// Bounds on the output of the help-event counter for the requested horizon.
+    HelpEvents { min: usize, max: usize },
+    /// The negated occupancies a forbidding cooperation constraint rules out, up to the requested
+    /// horizon.
+    Forbid(CooperationConstraint),
 }
 
-/// The clause families and assumptions one solve mode is made of.
+/// The clause families and assumptions one solve mode, or a conjunction of them, is made of.
 ///
 /// This is the single description of what a mode needs. Both [`ClauseGenerator::generate`] and
 /// the delta-stream path read it, so the two entry points cannot disagree about a mode.
 ///
+/// The requirements of a [`SolveSpec`] are the union of those of its modes: each family is listed
+/// once, so modes sharing a buffer or a horizon family (such as the pairwise help summaries) share
+/// its variables. Required profiles all hang off the same per-horizon flag, so assuming it
+/// requires every one of them.
+///
 /// [`ClauseGenerator::generate`]: super::ClauseGenerator::generate
 pub(super) struct ModeRequirements {
     /// Laser propagation and beam activation.
     pub(super) lasers: bool,
     /// The shared `help(h, b, t)` encoding.
     pub(super) help: bool,
-    /// At most one parameterized per-step family.
-    pub(super) step_family: Option<StepFamily>,
-    /// Horizon-wide families, in generation order: pairwise summaries come before the blockers that
-    /// read their variables.
+    /// Parameterized per-step families.
+    pub(super) step_families: Vec<StepFamily>,
+    /// Horizon-wide families, in generation order: pairwise summaries come before the blockers and
+    /// requirements that read their variables.
     pub(super) horizon_families: Vec<HorizonFamily>,
-    pub(super) assumptions: ModeAssumptions,
+    pub(super) assumptions: Vec<ModeAssumptions>,
 }
 
 impl ModeRequirements {
+    /// Merge the requirements of every mode and cooperation constraint of `spec`.
+    pub(super) fn of_spec(spec: &SolveSpec) -> Self {
+        let mut merged = Self::of(SolveMode::Standard);
+        for &mode in spec.modes() {
+            let requirements = Self::of(mode);
+            // `NoCooperation` alone drops the laser clauses; any other mode needs them back.
+            if spec.modes().len() == 1 {
+                merged.lasers = requirements.lasers;
+            }
+            merged.help |= requirements.help;
+            push_new(&mut merged.step_families, requirements.step_families);
+            push_new(&mut merged.horizon_families, requirements.horizon_families);
+            push_new(&mut merged.assumptions, requirements.assumptions);
+        }
+        for &constraint in spec.constraints() {
+            match constraint {
+                CooperationConstraint::RequireHelp {
+                    helper,
+                    beneficiary,
+                } => {
+                    // The required help must be survivable, hence the laser clauses.
+                    merged.lasers = true;
+                    push_new(
+                        &mut merged.horizon_families,
+                        vec![HorizonFamily::RequiredPairHelp {
+                            helper,
+                            beneficiary,
+                        }],
+                    );
+                    push_new(
+                        &mut merged.assumptions,
+                        vec![ModeAssumptions::RequiredProfile],
+                    );
+                }
+                _ => merged.assumptions.push(ModeAssumptions::Forbid(constraint)),
+            }
+        }
+        merged
+    }
+
+    /// Keep only what defines the variables of an explained formula (see
+    /// [`ClauseGenerator::generate_explained`]), whose restrictions are generated per reason.
+    ///
+    /// The laser clauses are always needed, so that dropping a restriction relaxes the problem
+    /// towards the standard one. Forbidden patterns use the two-sided progress encoding, whose
+    /// complete states can be negated one pattern at a time. The required-profile flag stays a plain
+    /// assumption, since each goal it enables is guarded by the selector of its own mode.
+    ///
+    /// [`ClauseGenerator::generate_explained`]: super::ClauseGenerator::generate_explained
+    pub(super) fn explained(mut self) -> Self {
+        self.lasers = true;
+        let step_families = std::mem::take(&mut self.step_families)
+            .into_iter()
+            .map(|family| match family {
+                StepFamily::Sequences(length) => StepFamily::RequiredSequences(length),
+                StepFamily::Interdependence(order) => StepFamily::RequiredInterdependence(order),
+                required => required,
+            })
+            .collect();
+        push_new(&mut self.step_families, step_families);
+        self.horizon_families.retain(HorizonFamily::is_definition);
+        self.assumptions
+            .retain(|&kind| kind == ModeAssumptions::RequiredProfile);
+        self
+    }
+
     /// Describe the clause families and assumptions `mode` is made of.
     pub(super) fn of(mode: SolveMode) -> Self {
         let cooperation_support = Self {
             lasers: true,
             help: true,
-            step_family: None,
+            step_families: vec![],
             horizon_families: vec![],
-            assumptions: ModeAssumptions::None,
+            assumptions: vec![],
         };
         match mode {
             SolveMode::Standard => Self {
@@ -80,20 +194,20 @@ impl ModeRequirements {
             SolveMode::NoCooperation => Self {
                 lasers: false,
                 help: false,
-                assumptions: ModeAssumptions::NoCooperation,
+                assumptions: vec![ModeAssumptions::NoCooperation],
                 ..cooperation_support
             },
             SolveMode::NoAsymmetricCooperation => Self {
                 horizon_families: vec![HorizonFamily::Asymmetry],
-                assumptions: ModeAssumptions::NoAsymmetry,
+                assumptions: vec![ModeAssumptions::NoAsymmetry],
                 ..cooperation_support
             },
             SolveMode::NoSequentialCooperation(length) => Self {
-                step_family: Some(StepFamily::Sequences(length.get())),
+                step_families: vec![StepFamily::Sequences(length.get())],
                 ..cooperation_support
             },
             SolveMode::NoInterdependence(order) => Self {
-                step_family: Some(StepFamily::Interdependence(order.get())),
+                step_families: vec![StepFamily::Interdependence(order.get())],
                 ..cooperation_support
             },
             SolveMode::NoConvergentCooperation(k) => Self {
@@ -114,6 +228,63 @@ impl ModeRequirements {
                 horizon_families: vec![HorizonFamily::PairwiseHelp, HorizonFamily::NoFullyCoupled],
                 ..cooperation_support
             },
+            SolveMode::RequireCooperation => Self {
+                horizon_families: vec![HorizonFamily::RequiredHelp],
+                assumptions: vec![ModeAssumptions::RequiredProfile],
+                ..cooperation_support
+            },
+            SolveMode::RequireAsymmetric => Self {
+                horizon_families: vec![HorizonFamily::Asymmetry],
+                assumptions: vec![ModeAssumptions::Asymmetry],
+                ..cooperation_support
+            },
+            SolveMode::RequireSequence(length) => Self {
+                step_families: vec![StepFamily::RequiredSequences(length.get())],
+                horizon_families: vec![HorizonFamily::RequiredSequence(length.get())],
+                assumptions: vec![ModeAssumptions::RequiredProfile],
+                ..cooperation_support
+            },
+            SolveMode::RequireInterdependence(order) => Self {
+                step_families: vec![StepFamily::RequiredInterdependence(order.get())],
+                horizon_families: vec![HorizonFamily::RequiredInterdependence(order.get())],
+                assumptions: vec![ModeAssumptions::RequiredProfile],
+                ..cooperation_support
+            },
+            SolveMode::RequireConvergence(k) => Self {
+                horizon_families: vec![
+                    HorizonFamily::PairwiseHelp,
+                    HorizonFamily::RequiredConvergence(k.get()),
+                ],
+                assumptions: vec![ModeAssumptions::RequiredProfile],
+                ..cooperation_support
+            },
+            SolveMode::RequireDivergence(k) => Self {
+                horizon_families: vec![
+                    HorizonFamily::PairwiseHelp,
+                    HorizonFamily::RequiredDivergence(k.get()),
+                ],
+                assumptions: vec![ModeAssumptions::RequiredProfile],
+                ..cooperation_support
+            },
+            SolveMode::MaxHelpEvents(k) => Self {
+                horizon_families: vec![HorizonFamily::HelpEventCounter(k + 1)],
+                assumptions: vec![ModeAssumptions::HelpEvents { min: 0, max: k }],
+                ..cooperation_support
+            },
+            SolveMode::ExactHelpEvents(k) => Self {
+                horizon_families: vec![HorizonFamily::HelpEventCounter(k + 1)],
+                assumptions: vec![ModeAssumptions::HelpEvents { min: k, max: k }],
+                ..cooperation_support
+            },
+        }
+    }
+}
+
+/// Append the items of `items` that `target` does not contain yet, keeping their order.
+fn push_new<T: PartialEq>(target: &mut Vec<T>, items: Vec<T>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
         }
     }
 }
@@ -59,6 +59,26 @@ pub enum VarKey {
         prefix_len: usize,
         t: usize,
     },
+    /// Whether the cooperation profile required by a `Require*` solve mode occurs through
+    /// `horizon`. It only implies the profile; the mode assumes it.
+    RequiredProfile {
+        horizon: usize,
+    },
+    /// Whether at least `at_least` help events occur through `horizon`.
+    HelpEvents {
+        horizon: usize,
+        at_least: usize,
+    },
+    /// Whether an agent stands on the gem at `pos` at some time step before `horizon`.
+    GemCollected {
+        pos: Position,
+        horizon: usize,
+    },
+    /// Whether at least `at_least` gems are collected before `horizon`.
+    GemsCollected {
+        horizon: usize,
+        at_least: usize,
+    },
     /// Auxiliary variable used internally by cardinality encodings; carries a unique counter.
     Aux(i32),
 }
@@ -257,6 +277,22 @@ impl VarPool {
         })
     }
 
+    pub fn required_profile(&mut self, horizon: usize) -> Literal {
+        self.id(VarKey::RequiredProfile { horizon })
+    }
+
+    pub fn help_events(&mut self, horizon: usize, at_least: usize) -> Literal {
+        self.id(VarKey::HelpEvents { horizon, at_least })
+    }
+
+    pub fn gem_collected(&mut self, pos: Position, horizon: usize) -> Literal {
+        self.id(VarKey::GemCollected { pos, horizon })
+    }
+
+    pub fn gems_collected(&mut self, horizon: usize, at_least: usize) -> Literal {
+        self.id(VarKey::GemsCollected { horizon, at_least })
+    }
+
     /// Variable id already assigned to `key`, or `None` if it was never created.
     ///
     /// Unlike the factory methods above, this never *creates* a variable, so it is safe to use
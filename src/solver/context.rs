@@ -3,8 +3,9 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use strum::IntoEnumIterator;
 
 use super::position_set::PositionSet;
-use crate::Position;
-use crate::{World, tiles::Direction};
+use crate::{
+    ConflictRule, DistanceOptions, DistanceOrigin, Position, World, WorldState, tiles::Direction,
+};
 
 const MAX_NEIGHBOURS: usize = 5;
 
@@ -79,15 +80,18 @@ fn neighbours_of(
 }
 
 /// One laser source's relevant info for constraint generation.
+#[derive(Clone)]
 pub struct LaserSourceInfo {
     pub agent_id: usize,
     pub laser_id: usize,
-    /// Beam tiles, in order, starting right after the source tile.
+    /// Beam tiles, in order, starting right after the source tile. Empty when the source is
+    /// disabled, since its beam then never kills anyone.
     pub path: Vec<Position>,
 }
 
 /// Data shared across all constraint-generation routines.
 /// Built once per `(world, t_max)` pair. Data is computed on-demand and cached for efficiency.
+#[derive(Clone)]
 pub struct ConstraintContext {
     pub t_max: usize,
     pub n_agents: usize,
@@ -96,6 +100,8 @@ pub struct ConstraintContext {
     pub predecessors: Vec<Vec<NeighbourList>>,
     pub solution_lower_bound: usize,
     pub laser_sources: Vec<LaserSourceInfo>,
+    /// The conflict rule of the world, which decides between following and swap clauses.
+    pub conflict_rule: ConflictRule,
     exits: PositionSet,
     height: usize,
     width: usize,
@@ -104,6 +110,11 @@ pub struct ConstraintContext {
     /// `neighbours[i][j]` = `[(i, j), ...reachable single-step neighbours]`.
     pub(crate) neighbours: Vec<Vec<NeighbourList>>,
 
+    /// Shortest distance from each position to the nearest exit, regardless of lasers.
+    exit_distance: HashMap<Position, usize>,
+    /// The gems that are still to collect.
+    gems: PositionSet,
+
     /// `distance_buckets[d]` = positions whose distance to the nearest exit is exactly `d`
     /// (only for `d <= t_max`, the only distances that ever matter). Used to incrementally
     /// build `exit_reachable` as `t` grows, instead of recomputing each entry from scratch.
@@ -128,19 +139,49 @@ pub struct ConstraintContext {
     /// the beam is constant-active and the non-owner dies. This is pre-computed once and applied at
     /// every time step during `update_relevant_positions`.
     forbidden_first_beam_tiles: Vec<PositionSet>,
+
+    /// Every tile crossed by a laser beam, whatever its colour.
+    beam_tiles: PositionSet,
+
+    /// `useless_stays[agent]` = positions where `agent` never needs to stay in place. See
+    /// `compute_useless_stays`.
+    useless_stays: Vec<PositionSet>,
 }
 
 impl ConstraintContext {
     /// Build the static geometry and time-indexed context caches for a world.
     pub fn new(world: &World, t_max: usize) -> Self {
+        let gems = PositionSet::from_positions(
+            world.height(),
+            world.width(),
+            world.gems_positions().into_iter(),
+        );
+        Self::build(world, t_max, world.starts(), gems)
+    }
+
+    /// Build the context of `world` from `state` instead of the start positions: the agents start
+    /// where `state` puts them, and the gems it marks as collected no longer exist.
+    ///
+    /// `state` must be a valid state of `world` (see [`World::set_state`]).
+    pub fn from_state(world: &World, state: &WorldState, t_max: usize) -> Self {
+        let remaining_gems = world
+            .gems_positions()
+            .into_iter()
+            .zip(&state.gems_collected)
+            .filter(|&(_, &collected)| !collected)
+            .map(|(pos, _)| pos);
+        let gems = PositionSet::from_positions(world.height(), world.width(), remaining_gems);
+        Self::build(world, t_max, state.agents_positions.clone(), gems)
+    }
+
+    fn build(world: &World, t_max: usize, start_pos: Vec<Position>, gems: PositionSet) -> Self {
         let height = world.height();
         let width = world.width();
         let n_agents = world.n_agents();
-        let walls = PositionSet::from_positions(height, width, world.walls().into_iter());
-        let voids = PositionSet::from_positions(height, width, world.void_positions().into_iter());
-        let exits = PositionSet::from_positions(height, width, world.exits_positions().into_iter());
+        let walls = world.bitboards().walls().clone();
+        let voids = world.bitboards().voids().clone();
+        let exits = world.bitboards().exits().clone();
         // let exits: HashSet<Position> = exit_positions.iter().collect();
-        let start_pos: Vec<Position> = world.starts().into_iter().collect();
 
         let mut valid_positions = HashSet::new();
         for i in 0..height {
@@ -172,19 +213,20 @@ impl ConstraintContext {
                 predecessors[succ.i][succ.j].push(pos);
             }
         }
-        let exit_distance = compute_exit_distance(&exits, &predecessors);
-        let solution_lower_bound = start_pos
+        let exit_distance: HashMap<Position, usize> = world
+            .distance_map(DistanceOrigin::Exits, &DistanceOptions::default())
+            .expect("exits are always a valid origin")
             .iter()
-            .map(|p| exit_distance.get(p).copied().unwrap_or(0))
-            .max()
-            .unwrap_or(0);
+            .collect();
 
         let mut laser_sources = Vec::new();
         for (pos, source) in world.sources() {
             let d = source.direction();
             let mut prev = pos;
             let mut path = Vec::new();
-            while let Ok(current) = prev + d
+            // Disabled sources keep their entry so that `laser_sources[laser_id]` stays valid.
+            while source.is_enabled()
+                && let Ok(current) = prev + d
                 && current.i < height
                 && current.j < width
                 && !walls.contains(&current)
@@ -211,6 +253,13 @@ impl ConstraintContext {
             }
         }
 
+        let mut beam_tiles = PositionSet::empty(height, width);
+        for source in &laser_sources {
+            for &pos in &source.path {
+                beam_tiles.insert(pos);
+            }
+        }
+
         // Bucket positions by their exact distance to the nearest exit (capped at `t_max`,
         // since farther positions can never be exit-reachable within the horizon).
         let mut distance_buckets = vec![PositionSet::empty(height, width); t_max + 1];
@@ -220,32 +269,69 @@ impl ConstraintContext {
             }
         }
 
-        let exit_reachable = Vec::with_capacity(t_max + 1);
-        let relevant_positions = (0..n_agents)
-            .map(|_| Vec::with_capacity(t_max + 1))
-            .collect();
-        let relevant_laser_paths = (0..laser_sources.len())
-            .map(|_| Vec::with_capacity(t_max + 1))
-            .collect();
-
-        Self {
+        let mut ctx = Self {
             t_max,
             n_agents,
-            start_pos,
+            start_pos: Vec::new(),
             predecessors,
-            solution_lower_bound,
+            solution_lower_bound: 0,
             laser_sources,
+            conflict_rule: world.conflict_rule(),
             exits,
             height,
             width,
             neighbours,
             updated_until: None,
             distance_buckets,
-            exit_reachable,
-            relevant_positions,
+            exit_reachable: Vec::new(),
+            relevant_positions: Vec::new(),
             forbidden_first_beam_tiles,
-            relevant_laser_paths,
-        }
+            beam_tiles,
+            useless_stays: Vec::new(),
+            relevant_laser_paths: Vec::new(),
+            exit_distance,
+            gems,
+        };
+        ctx.seed(start_pos);
+        ctx
+    }
+
+    /// The context of the same world and horizon when the agents start from `start_pos`.
+    ///
+    /// Only the start-dependent data is computed again: the movement graph, the exit distances
+    /// and the laser geometry are shared with `self`.
+    pub fn with_starts(&self, start_pos: Vec<Position>) -> Self {
+        let mut ctx = self.clone();
+        ctx.seed(start_pos);
+        ctx
+    }
+
+    /// Set the start positions, compute the data that depends on them, and clear the caches of
+    /// `update`.
+    fn seed(&mut self, start_pos: Vec<Position>) {
+        self.solution_lower_bound = start_pos
+            .iter()
+            .map(|p| self.exit_distance.get(p).copied().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        self.useless_stays = compute_useless_stays(
+            &start_pos,
+            &self.neighbours,
+            &self.exit_distance,
+            &self.beam_tiles,
+            &self.exits,
+            &self.gems,
+            self.t_max,
+        );
+        self.start_pos = start_pos;
+        self.updated_until = None;
+        self.exit_reachable = Vec::with_capacity(self.t_max + 1);
+        self.relevant_positions = (0..self.n_agents)
+            .map(|_| Vec::with_capacity(self.t_max + 1))
+            .collect();
+        self.relevant_laser_paths = (0..self.laser_sources.len())
+            .map(|_| Vec::with_capacity(self.t_max + 1))
+            .collect();
     }
 
     /// Compute and cache the positions from which an exit is still reachable at time `t`.
@@ -274,7 +360,8 @@ impl ConstraintContext {
     /// Update the relevant positions for each agent at time step t.
     /// # Details
     /// A position is relevant to a given agent at time step t if:
-    ///     - the agent can reach it at time step t
+    ///     - the agent can reach it at time step t, without staying in place where staying is
+    ///       useless (see `compute_useless_stays`)
     ///     - the agent can still access the exit within `t_max - t` steps
     fn update_relevant_positions(&mut self, t: usize) {
         for agent in 0..self.n_agents {
@@ -284,7 +371,9 @@ impl ConstraintContext {
                 let mut reachable = PositionSet::empty(self.height, self.width);
                 for pos in &self.relevant_positions[agent][t - 1] {
                     for n in &self.neighbours[pos.i][pos.j] {
-                        reachable.insert(n);
+                        if n != pos || self.can_stay(agent, &pos) {
+                            reachable.insert(n);
+                        }
                     }
                 }
                 reachable
@@ -293,7 +382,7 @@ impl ConstraintContext {
             // At t=1 no agent can occupy another agent's t=0 start position.
             // The no-following-conflict rule forbids agent A from being at start_B at t=1
             // because B was there at t=0 (implies(-a_cur, -b_prev) ⇒ ¬A here when B was here).
-            if t == 1 {
+            if t == 1 && !self.conflict_rule.allows_following() {
                 for (other, &start) in self.start_pos.iter().enumerate() {
                     if other != agent {
                         result.remove(&start);
@@ -435,7 +524,8 @@ impl ConstraintContext {
         reachable
     }
 
-    /// Positions the agent could have occupied at time `t - 1` to reach `(i, j)` at `t`.
+    /// Positions the agent could have occupied at time `t - 1` to reach `(i, j)` at `t`, including
+    /// `(i, j)` itself unless staying there is useless.
     /// Assumes `update` has already been called for this `t`.
     pub fn prev_neighbours(
         &self,
@@ -443,13 +533,44 @@ impl ConstraintContext {
         pos: &Position,
         t: usize,
     ) -> impl Iterator<Item = Position> + '_ {
+        let pos = *pos;
         self.predecessors[pos.i][pos.j]
             .iter()
             .filter(move |predecessor| {
-                t > 0 && self.relevant_positions[agent][t - 1].contains(predecessor)
+                t > 0
+                    && self.relevant_positions[agent][t - 1].contains(predecessor)
+                    && (*predecessor != pos || self.can_stay(agent, &pos))
             })
     }
 
+    /// Whether `agent` may stay in place on `pos`, i.e. staying there is not provably useless.
+    pub fn can_stay(&self, agent: usize, pos: &Position) -> bool {
+        !self.useless_stays[agent].contains(pos)
+    }
+
+    /// Positions that only `agent` can occupy around time `t`, and where its presence does not
+    /// interact with any laser.
+    ///
+    /// A position is private at `t` if it is relevant to `agent` at `t`, relevant to no other agent
+    /// at `t - 1`, `t` or `t + 1`, and not on a beam tile. Moving `agent` onto or off such a position
+    /// at `t` can therefore not create an overlap, a following conflict, a laser death or a help
+    /// event. Assumes `update` has already been called for `t + 1`.
+    pub fn private_positions(&self, agent: usize, t: usize) -> PositionSet {
+        let mut private = self.relevant_positions[agent][t].clone();
+        private.subtract_with(&self.beam_tiles);
+        for other in (0..self.n_agents).filter(|&other| other != agent) {
+            for tau in t.saturating_sub(1)..=t + 1 {
+                private.subtract_with(&self.relevant_positions[other][tau]);
+            }
+        }
+        private
+    }
+
+    /// Whether `pos` lies on the beam of any laser.
+    pub fn is_beam_tile(&self, pos: &Position) -> bool {
+        self.beam_tiles.contains(pos)
+    }
+
     /// The reachable laser tiles positions for a given laser source at time `t`: the beam tiles
     /// that can still be blocked. Assumes `update` has already been called for this `t`.
     pub fn relevant_laser_tiles(&self, laser_id: usize, t: usize) -> &PositionSet {
@@ -520,17 +641,83 @@ fn compute_relevant_laser_path(
     result
 }
 
-fn compute_exit_distance(
+/// Compute, for every agent, the positions where staying in place is useless.
+///
+/// # Details
+/// Staying in place is only useful on an exit, to block a beam, to wait for a beam to be blocked,
+/// or to wait for another agent to move away. A position `p` rules out all of these for `agent`
+/// if `p` is neither an exit, a gem nor a beam tile, and neither `p` nor any of its neighbours is
+/// a beam tile or can be reached by another agent within the horizon. The latter is
+/// over-approximated by the positions `q` such that the other agent's distance to `q` plus the exit
+/// distance of `q` fits within `t_max`.
+///
+/// Any plan that stays on such a `p` can be rewritten so that the agent moves on to its next
+/// position `q` one step earlier and stays on `q` instead: `q` is a neighbour of `p`, so the move
+/// interacts with no other agent and no laser. Each rewrite moves a stay strictly later, hence a
+/// valid plan that never stays on a useless position exists whenever a valid plan exists.
+fn compute_useless_stays(
+    start_pos: &[Position],
+    neighbours: &[Vec<NeighbourList>],
+    exit_distance: &HashMap<Position, usize>,
+    beam_tiles: &PositionSet,
     exits: &PositionSet,
-    predecessors: &[Vec<NeighbourList>],
+    gems: &PositionSet,
+    t_max: usize,
+) -> Vec<PositionSet> {
+    let height = neighbours.len();
+    let width = neighbours.first().map_or(0, Vec::len);
+    let within_horizon: Vec<PositionSet> = start_pos
+        .iter()
+        .map(|&start| {
+            let mut positions = PositionSet::empty(height, width);
+            for (pos, d) in compute_distances_from(start, neighbours) {
+                if exit_distance
+                    .get(&pos)
+                    .is_some_and(|exit_d| d + exit_d <= t_max)
+                {
+                    positions.insert(pos);
+                }
+            }
+            positions
+        })
+        .collect();
+
+    (0..start_pos.len())
+        .map(|agent| {
+            let mut shared = beam_tiles.clone();
+            for (other, positions) in within_horizon.iter().enumerate() {
+                if other != agent {
+                    shared.union_with(positions);
+                }
+            }
+            let mut useless = PositionSet::empty(height, width);
+            for pos in &within_horizon[agent] {
+                if !exits.contains(&pos)
+                    && !gems.contains(&pos)
+                    && neighbours[pos.i][pos.j]
+                        .iter()
+                        .all(|n| !shared.contains(&n))
+                {
+                    useless.insert(pos);
+                }
+            }
+            useless
+        })
+        .collect()
+}
+
+/// Breadth-first distances from `start` along the movement graph.
+fn compute_distances_from(
+    start: Position,
+    neighbours: &[Vec<NeighbourList>],
 ) -> HashMap<Position, usize> {
-    let mut dist: HashMap<Position, usize> = exits.iter().map(|p| (p, 0)).collect();
-    let mut frontier: VecDeque<Position> = exits.iter().collect();
+    let mut dist = HashMap::from([(start, 0)]);
+    let mut frontier = VecDeque::from([start]);
     while let Some(current) = frontier.pop_front() {
         let current_dist = dist[&current];
-        for pred in &predecessors[current.i][current.j] {
-            dist.entry(pred).or_insert_with(|| {
-                frontier.push_back(pred);
+        for next in &neighbours[current.i][current.j] {
+            dist.entry(next).or_insert_with(|| {
+                frontier.push_back(next);
                 current_dist + 1
             });
         }
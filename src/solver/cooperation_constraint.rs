@@ -0,0 +1,124 @@
+use crate::{AgentId, tiles::LaserId};
+
+/// A targeted restriction on who may help whom, for ablations that a global [`SolveMode`] cannot
+/// express.
+///
+/// Help from `helper` to `beneficiary` at `t` means that the beneficiary stands on one of the
+/// helper's beams at `t` (see [`ClauseEngine::generate_help_clauses`]), so every constraint is
+/// encoded directly on agent positions. Add one to a [`SolveSpec`] to assume it, or assert it with
+/// [`ClauseGenerator::constraint_clauses`].
+///
+/// [`SolveMode`]: super::SolveMode
+/// [`SolveSpec`]: super::SolveSpec
+/// [`ClauseEngine::generate_help_clauses`]: super::clauses::ClauseEngine::generate_help_clauses
+/// [`ClauseGenerator::constraint_clauses`]: super::ClauseGenerator::constraint_clauses
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CooperationConstraint {
+    /// `helper` never helps `beneficiary`: the beneficiary never enters a beam of the helper.
+    ForbidHelp {
+        helper: AgentId,
+        beneficiary: AgentId,
+    },
+    /// `helper` helps `beneficiary` at least once.
+    RequireHelp {
+        helper: AgentId,
+        beneficiary: AgentId,
+    },
+    /// The agent never helps anyone: no other agent enters one of its beams.
+    ForbidHelper(AgentId),
+    /// No agent but its owner ever enters the beam of this laser.
+    ForbidLaser(LaserId),
+}
+
+impl CooperationConstraint {
+    /// The canonical string representation, inverse of `CooperationConstraint::from_str`:
+    /// `forbid-help-H-B`, `require-help-H-B`, `forbid-helper-H` or `forbid-laser-L`.
+    pub fn canonical(&self) -> String {
+        match self {
+            CooperationConstraint::ForbidHelp {
+                helper,
+                beneficiary,
+            } => format!("forbid-help-{helper}-{beneficiary}"),
+            CooperationConstraint::RequireHelp {
+                helper,
+                beneficiary,
+            } => format!("require-help-{helper}-{beneficiary}"),
+            CooperationConstraint::ForbidHelper(helper) => format!("forbid-helper-{helper}"),
+            CooperationConstraint::ForbidLaser(laser_id) => format!("forbid-laser-{laser_id}"),
+        }
+    }
+}
+
+impl std::str::FromStr for CooperationConstraint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        fn ids<const N: usize>(s: &str, prefix: &str) -> Option<[usize; N]> {
+            let ids = s
+                .strip_prefix(prefix)?
+                .split('-')
+                .map(|id| id.parse().ok())
+                .collect::<Option<Vec<usize>>>()?;
+            ids.try_into().ok()
+        }
+
+        if let Some([helper, beneficiary]) = ids(s, "forbid-help-") {
+            Ok(CooperationConstraint::ForbidHelp {
+                helper,
+                beneficiary,
+            })
+        } else if let Some([helper, beneficiary]) = ids(s, "require-help-") {
+            Ok(CooperationConstraint::RequireHelp {
+                helper,
+                beneficiary,
+            })
+        } else if let Some([helper]) = ids(s, "forbid-helper-") {
+            Ok(CooperationConstraint::ForbidHelper(helper))
+        } else if let Some([laser_id]) = ids(s, "forbid-laser-") {
+            Ok(CooperationConstraint::ForbidLaser(laser_id))
+        } else {
+            Err(format!(
+                "Unknown cooperation constraint '{s}'. Expected forbid-help-H-B, require-help-H-B, forbid-helper-H or forbid-laser-L."
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CooperationConstraint;
+
+    #[test]
+    fn canonical_round_trips() {
+        for constraint in [
+            CooperationConstraint::ForbidHelp {
+                helper: 0,
+                beneficiary: 12,
+            },
+            CooperationConstraint::RequireHelp {
+                helper: 3,
+                beneficiary: 1,
+            },
+            CooperationConstraint::ForbidHelper(2),
+            CooperationConstraint::ForbidLaser(0),
+        ] {
+            assert_eq!(constraint.canonical().parse(), Ok(constraint));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_constraints() {
+        for s in [
+            "forbid-help-0",
+            "forbid-help-0-1-2",
+            "require-help-a-1",
+            "forbid-helper-",
+            "forbid-helper--1",
+            "forbid-laser",
+            "forbid-laser-0-1",
+            "require-helper-0",
+        ] {
+            assert!(s.parse::<CooperationConstraint>().is_err(), "{s}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+/// A CNF encoding of "at most one of these variables is true".
+///
+/// Every encoding is equisatisfiable with the others; they trade clauses for auxiliary variables.
+/// For `n` variables:
+///
+/// | Encoding | Clauses | Auxiliary variables |
+/// | --- | --- | --- |
+/// | `Pairwise` | `n(n-1)/2` | 0 |
+/// | `Sequential` | `3n - 4` | `n - 1` |
+/// | `Commander` | `O(n)` | `~n/2` |
+/// | `Product` | `2n + O(√n)` | `O(√n)` |
+/// | `Binary` | `n·⌈log₂ n⌉` | `⌈log₂ n⌉` |
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum AtMostOneEncoding {
+    /// `Pairwise` up to five variables, where it uses the fewest clauses, and `Sequential` beyond.
+    #[default]
+    Auto,
+    /// One binary clause per pair of variables.
+    Pairwise,
+    /// The sequential counter (ladder) of Sinz (2005).
+    Sequential,
+    /// The commander encoding of Klieber and Kwon (2007), with groups of three variables.
+    Commander,
+    /// The product encoding of Chen (2010): the variables are laid out on a grid, and at most one
+    /// row and one column may be used.
+    Product,
+    /// The binary (bitwise) encoding: each variable implies the bits of its index.
+    Binary,
+}
+
+impl AtMostOneEncoding {
+    /// The canonical string representation, inverse of `AtMostOneEncoding::from_str`: `auto`,
+    /// `pairwise`, `sequential`, `commander`, `product` or `binary`.
+    pub fn canonical(&self) -> &'static str {
+        match self {
+            AtMostOneEncoding::Auto => "auto",
+            AtMostOneEncoding::Pairwise => "pairwise",
+            AtMostOneEncoding::Sequential => "sequential",
+            AtMostOneEncoding::Commander => "commander",
+            AtMostOneEncoding::Product => "product",
+            AtMostOneEncoding::Binary => "binary",
+        }
+    }
+}
+
+impl std::str::FromStr for AtMostOneEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        [
+            AtMostOneEncoding::Auto,
+            AtMostOneEncoding::Pairwise,
+            AtMostOneEncoding::Sequential,
+            AtMostOneEncoding::Commander,
+            AtMostOneEncoding::Product,
+            AtMostOneEncoding::Binary,
+        ]
+        .into_iter()
+        .find(|encoding| encoding.canonical() == s)
+        .ok_or_else(|| {
+            format!(
+                "Unknown at-most-one encoding '{s}'. Expected auto, pairwise, sequential, commander, product or binary."
+            )
+        })
+    }
+}
+
+/// The at-most-one encodings of the clause families that need one.
+///
+/// The default reproduces the fixed encodings: [`AtMostOneEncoding::Auto`] for the position of an
+/// agent and [`AtMostOneEncoding::Pairwise`] for the agents on a tile.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EncodingConfig {
+    /// At most one position per agent and time step.
+    pub exactly_one_position: AtMostOneEncoding,
+    /// At most one agent per tile and time step.
+    pub no_overlap: AtMostOneEncoding,
+}
+
+impl Default for EncodingConfig {
+    fn default() -> Self {
+        Self {
+            exactly_one_position: AtMostOneEncoding::Auto,
+            no_overlap: AtMostOneEncoding::Pairwise,
+        }
+    }
+}
+
+/// The clauses emitted and the variables created by one clause family.
+///
+/// A variable counts for the family that creates it first, so agent position variables mostly
+/// count for `exactly_one_position`, and each family owns its auxiliary variables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct FamilyStats {
+    pub clauses: usize,
+    pub variables: usize,
+}
+
+/// [`FamilyStats`] per clause family, over every time step generated so far.
+pub type EncodingStats = BTreeMap<&'static str, FamilyStats>;
+
+#[cfg(test)]
+mod tests {
+    use super::AtMostOneEncoding;
+
+    #[test]
+    fn canonical_round_trips() {
+        for encoding in [
+            AtMostOneEncoding::Auto,
+            AtMostOneEncoding::Pairwise,
+            AtMostOneEncoding::Sequential,
+            AtMostOneEncoding::Commander,
+            AtMostOneEncoding::Product,
+            AtMostOneEncoding::Binary,
+        ] {
+            assert_eq!(encoding.canonical().parse(), Ok(encoding));
+        }
+        assert!("ladder".parse::<AtMostOneEncoding>().is_err());
+    }
+}
@@ -2,7 +2,7 @@ use std::{error::Error, fmt::Display};
 
 use crate::{AgentId, Position};
 
-use super::clauses::VarKey;
+use super::{CooperationConstraint, clauses::VarKey};
 
 #[derive(Debug)]
 pub enum SolverError {
@@ -33,6 +33,22 @@ pub enum SolverError {
         value: usize,
         reason: String,
     },
+    /// A [`CooperationConstraint`] names an agent or a laser that the world does not have, or asks
+    /// an agent to help itself.
+    InvalidConstraint {
+        constraint: CooperationConstraint,
+        reason: String,
+    },
+    /// A [`GemObjective`](crate::solver::GemObjective) names a tile without a gem or an agent that
+    /// the world does not have.
+    InvalidGemObjective {
+        reason: String,
+    },
+    /// A [`WorldState`](crate::WorldState) to plan from is not a valid state of the world, or one
+    /// of its agents is dead.
+    InvalidState {
+        reason: String,
+    },
 }
 
 impl Display for SolverError {
@@ -67,6 +83,15 @@ impl Display for SolverError {
                 f,
                 "Invalid parameter {value} for SolveMode::{variant}: {reason}"
             ),
+            SolverError::InvalidConstraint { constraint, reason } => write!(
+                f,
+                "Invalid cooperation constraint {}: {reason}",
+                constraint.canonical()
+            ),
+            SolverError::InvalidGemObjective { reason } => {
+                write!(f, "Invalid gem objective: {reason}")
+            }
+            SolverError::InvalidState { reason } => write!(f, "Invalid world state: {reason}"),
         }
     }
 }
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AgentId, Position};
+
+/// What the objective of a formula requires about gems, on top of every agent reaching an exit.
+///
+/// A gem is collected when an agent stands on its tile at some step before the horizon. Every API
+/// taking an objective also accepts a `bool` through `Into<GemObjective>`: `true` is
+/// [`GemObjective::All`] and `false` is [`GemObjective::None`].
+///
+/// In JSON, the objective is `"none"`, `"all"`, `{"at_least": k}`, `{"subset": [position, ...]}`
+/// or `{"by_agent": [[position, agent], ...]}`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GemObjective {
+    /// Gems are ignored.
+    #[default]
+    None,
+    /// Every gem is collected.
+    All,
+    /// At least this many gems are collected, whichever they are. Increasing the bound until the
+    /// formula is unsatisfiable maximises the number of gems collected at a fixed horizon.
+    AtLeast(usize),
+    /// Every gem on these tiles is collected.
+    Subset(Vec<Position>),
+    /// Every gem on these tiles is collected by the given agent.
+    ByAgent(Vec<(Position, AgentId)>),
+}
+
+impl From<bool> for GemObjective {
+    fn from(collect_gems: bool) -> Self {
+        if collect_gems {
+            GemObjective::All
+        } else {
+            GemObjective::None
+        }
+    }
+}
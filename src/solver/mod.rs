@@ -1,10 +1,29 @@
 mod clauses;
 mod context;
+mod cooperation_constraint;
+mod encoding;
 pub mod errors;
+mod gem_objective;
 mod interdependence;
-pub mod position_set;
+mod plan;
+mod projection;
 mod sequences;
 mod solve_mode;
+mod solve_spec;
+mod unsat_reason;
 
+/// Kept at its former path: `PositionSet` is also used by the bitboards of the world.
+pub use crate::position_set;
 pub use clauses::{Clause, ClauseGenerator, DeltaStream, Literal, VarKey};
+pub use cooperation_constraint::CooperationConstraint;
+pub use encoding::{AtMostOneEncoding, EncodingConfig, EncodingStats, FamilyStats};
+pub use gem_objective::GemObjective;
+pub use plan::Plan;
+pub use projection::Projection;
 pub use solve_mode::{SolveMode, SolveModeParameter};
+pub use solve_spec::SolveSpec;
+pub use unsat_reason::{Explanation, UnsatReason};
+
+#[cfg(test)]
+#[path = "../unit_tests/sat.rs"]
+pub(crate) mod sat;
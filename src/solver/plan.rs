@@ -0,0 +1,79 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::Action;
+
+use super::{GemObjective, SolveSpec};
+
+/// A joint action plan together with the query that produced it.
+///
+/// In JSON, `mode` is the canonical string of the solve spec (e.g. `"no-sequence-3"` or
+/// `"no-asymmetric+forbid-help-0-1"`), `collect_gems` is the [`GemObjective`] or a `bool` for all
+/// gems or none, and each joint action lists the action of every agent, e.g. `["North", "Stay"]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    /// The number of time steps of the plan.
+    pub horizon: usize,
+    #[serde(default)]
+    pub mode: SolveSpec,
+    /// Plans written before gem objectives store a `bool` for all gems or none.
+    #[serde(default, deserialize_with = "objective_or_flag")]
+    pub collect_gems: GemObjective,
+    /// `actions[t][agent_id]` is the action of `agent_id` at time step `t`.
+    pub actions: Vec<Vec<Action>>,
+}
+
+impl Plan {
+    pub fn new(
+        actions: Vec<Vec<Action>>,
+        mode: impl Into<SolveSpec>,
+        collect_gems: impl Into<GemObjective>,
+    ) -> Self {
+        Self {
+            horizon: actions.len(),
+            mode: mode.into(),
+            collect_gems: collect_gems.into(),
+            actions,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a plan is always serializable")
+    }
+
+    /// Parse a plan from JSON, checking that the horizon matches the number of joint actions and
+    /// that every joint action has the same number of agents.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        use serde::de::Error;
+        let plan: Self = serde_json::from_str(json)?;
+        if plan.actions.len() != plan.horizon {
+            return Err(serde_json::Error::custom(format!(
+                "the horizon is {} but the plan has {} joint actions",
+                plan.horizon,
+                plan.actions.len()
+            )));
+        }
+        if let Some(first) = plan.actions.first()
+            && plan.actions.iter().any(|joint| joint.len() != first.len())
+        {
+            return Err(serde_json::Error::custom(
+                "all joint actions must have the same number of agents",
+            ));
+        }
+        Ok(plan)
+    }
+}
+
+/// Read a gem objective, or a `bool` for all gems or none.
+fn objective_or_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GemObjective, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ObjectiveOrFlag {
+        Flag(bool),
+        Objective(GemObjective),
+    }
+    Ok(match ObjectiveOrFlag::deserialize(deserializer)? {
+        ObjectiveOrFlag::Flag(all) => all.into(),
+        ObjectiveOrFlag::Objective(objective) => objective,
+    })
+}
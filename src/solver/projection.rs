@@ -0,0 +1,64 @@
+/// The part of a plan that tells solutions apart when enumerating them (see
+/// [`ClauseGenerator::blocking_clause`]).
+///
+/// Two plans with the same projection are the same solution: enumerating over
+/// [`Projection::HelpEdges`] lists each cooperation structure a map admits once, with one plan
+/// realizing it.
+///
+/// [`ClauseGenerator::blocking_clause`]: super::ClauseGenerator::blocking_clause
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Projection {
+    /// The position of every agent at every time step: every distinct plan.
+    #[default]
+    Positions,
+    /// The set of help events `(helper, beneficiary, t)`.
+    HelpEdges,
+    /// The set of ordered pairs `(helper, beneficiary)` with at least one help event.
+    PairwiseHelp,
+}
+
+impl Projection {
+    /// The canonical string representation, inverse of `Projection::from_str`: `positions`,
+    /// `help-edges` or `pairwise-help`.
+    pub fn canonical(&self) -> &'static str {
+        match self {
+            Projection::Positions => "positions",
+            Projection::HelpEdges => "help-edges",
+            Projection::PairwiseHelp => "pairwise-help",
+        }
+    }
+}
+
+impl std::str::FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        [
+            Projection::Positions,
+            Projection::HelpEdges,
+            Projection::PairwiseHelp,
+        ]
+        .into_iter()
+        .find(|projection| projection.canonical() == s)
+        .ok_or_else(|| {
+            format!("Unknown projection '{s}'. Expected positions, help-edges or pairwise-help.")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Projection;
+
+    #[test]
+    fn canonical_round_trips() {
+        for projection in [
+            Projection::Positions,
+            Projection::HelpEdges,
+            Projection::PairwiseHelp,
+        ] {
+            assert_eq!(projection.canonical().parse(), Ok(projection));
+        }
+        assert!("help_edges".parse::<Projection>().is_err());
+    }
+}
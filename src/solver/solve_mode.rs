@@ -2,12 +2,20 @@ use super::errors::SolverError;
 
 /// Determines which extra clauses/assumptions `ClauseGenerator::generate` emits.
 ///
-/// Every variant but [`SolveMode::Standard`] forbids a cooperation profile. When the world layout
-/// makes that profile structurally impossible — too few agents, no laser source, or too few
-/// distinct laser colours — the restriction is tautologically satisfied and the mode is normalized
-/// to [`SolveMode::Standard`], which emits no cooperation clause, variable, or assumption. This is
-/// a cheap layout-level check based on counts only; impossibility that depends on the geometry or
-/// on the planning horizon is handled by the pruning of the clause engine.
+/// The `No*` variants forbid a cooperation profile. When the world layout makes that profile
+/// structurally impossible — too few agents, no laser source, or too few distinct laser colours —
+/// the restriction is tautologically satisfied and the mode is normalized to
+/// [`SolveMode::Standard`], which emits no cooperation clause, variable, or assumption. This is a
+/// cheap layout-level check based on counts only; impossibility that depends on the geometry or on
+/// the planning horizon is handled by the pruning of the clause engine.
+///
+/// The `Require*` variants are their positive counterparts: every plan must exhibit the profile at
+/// least once. They are never normalized, since an impossible profile makes the query
+/// unsatisfiable rather than unrestricted.
+///
+/// [`SolveMode::MaxHelpEvents`] and [`SolveMode::ExactHelpEvents`] count help events instead of
+/// looking at their structure, to study how little cooperation a plan can get away with. Only the
+/// former is normalized, since a world without help trivially has at most `k` help events.
 #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
 pub enum SolveMode {
     /// Standard world rules only.
@@ -40,6 +48,27 @@ pub enum SolveMode {
     /// At least one ordered pair of distinct agents must lack a help event over the trajectory.
     /// Equivalently, forbids fully coupled cooperation.
     NoFullyCoupledCooperation,
+    /// Some non-owner agent must enter a laser span that its owner blocks, i.e. at least one help
+    /// event must occur.
+    RequireCooperation,
+    /// Some agent must help another one without ever being helped itself.
+    RequireAsymmetric,
+    /// A temporal help sequence of at least `n` edges must occur, with the same rules as
+    /// [`SolveMode::NoSequentialCooperation`]. The wrapped value is `n >= 2`.
+    RequireSequence(SolveModeParameter),
+    /// A temporal closed trail with exactly `n` distinct agents must occur, with the same rules as
+    /// [`SolveMode::NoInterdependence`]. The wrapped value is `n >= 2`.
+    RequireInterdependence(SolveModeParameter),
+    /// Some beneficiary must receive help from at least `k` distinct helpers. The wrapped value is
+    /// `k >= 2`.
+    RequireConvergence(SolveModeParameter),
+    /// Some helper must help at least `k` distinct beneficiaries. The wrapped value is `k >= 2`.
+    RequireDivergence(SolveModeParameter),
+    /// At most `k` help events may occur, counting every `(helper, beneficiary, t)` triple once.
+    /// Any `k` is meaningful, and `MaxHelpEvents(0)` forbids every help event.
+    MaxHelpEvents(usize),
+    /// Exactly `k` help events must occur, counted as in [`SolveMode::MaxHelpEvents`].
+    ExactHelpEvents(usize),
 }
 
 /// A parameter carried by a parameterized [`SolveMode`].
@@ -64,6 +93,13 @@ impl std::str::FromStr for SolveMode {
 
     /// Parse a canonical solve-mode string.
     fn from_str(s: &str) -> Result<Self, String> {
+        /// Parse the mandatory `-K` suffix of the help-event modes.
+        fn help_events(s: &str, k: &str) -> Result<usize, String> {
+            k.parse::<usize>().map_err(|_| {
+                format!("Invalid help-event count in solve mode '{s}': expected an integer.")
+            })
+        }
+
         /// Parse the optional `-N` suffix shared by the parameterized modes.
         fn parametrized(s: &str, prefix: &str) -> Option<Result<usize, String>> {
             if s == prefix {
@@ -82,7 +118,17 @@ impl std::str::FromStr for SolveMode {
             "no-mutual" => Ok(SolveMode::no_interdependence(MIN_LENGTH)
                 .expect("the minimum solve-mode parameter is valid")),
             "no-fully-coupled" => Ok(SolveMode::NoFullyCoupledCooperation),
+            "require-cooperation" => Ok(SolveMode::RequireCooperation),
+            "require-asymmetric" => Ok(SolveMode::RequireAsymmetric),
+            "require-mutual" => Ok(SolveMode::require_interdependence(MIN_LENGTH)
+                .expect("the minimum solve-mode parameter is valid")),
             other => {
+                if let Some(k) = s.strip_prefix("max-help-events-") {
+                    return help_events(s, k).map(SolveMode::MaxHelpEvents);
+                }
+                if let Some(k) = s.strip_prefix("exact-help-events-") {
+                    return help_events(s, k).map(SolveMode::ExactHelpEvents);
+                }
                 if let Some(res) = parametrized(s, "no-sequence") {
                     return res.and_then(|n| {
                         SolveMode::no_sequential_cooperation(n).map_err(|error| error.to_string())
@@ -103,11 +149,34 @@ impl std::str::FromStr for SolveMode {
                         SolveMode::no_divergent_cooperation(n).map_err(|error| error.to_string())
                     });
                 }
+                if let Some(res) = parametrized(s, "require-sequence") {
+                    return res.and_then(|n| {
+                        SolveMode::require_sequence(n).map_err(|error| error.to_string())
+                    });
+                }
+                if let Some(res) = parametrized(s, "require-interdependence") {
+                    return res.and_then(|n| {
+                        SolveMode::require_interdependence(n).map_err(|error| error.to_string())
+                    });
+                }
+                if let Some(res) = parametrized(s, "require-convergence") {
+                    return res.and_then(|n| {
+                        SolveMode::require_convergence(n).map_err(|error| error.to_string())
+                    });
+                }
+                if let Some(res) = parametrized(s, "require-divergence") {
+                    return res.and_then(|n| {
+                        SolveMode::require_divergence(n).map_err(|error| error.to_string())
+                    });
+                }
                 Err(format!(
                     "Unknown solve mode: '{other}'. Expected one of: 'standard', 'no-cooperation', \
                      'no-asymmetric', 'no-mutual', 'no-fully-coupled', 'no-sequence[-N]', \
-                     'no-interdependence[-N]', 'no-convergence[-N]', 'no-divergence[-N]' \
-                     (N >= {MIN_LENGTH})."
+                     'no-interdependence[-N]', 'no-convergence[-N]', 'no-divergence[-N]', \
+                     'require-cooperation', 'require-asymmetric', 'require-mutual', \
+                     'require-sequence[-N]', 'require-interdependence[-N]', \
+                     'require-convergence[-N]', 'require-divergence[-N]' (N >= {MIN_LENGTH}), \
+                     'max-help-events-K', 'exact-help-events-K' (K >= 0)."
                 ))
             }
         }
@@ -174,6 +243,79 @@ impl SolveMode {
         )
     }
 
+    /// Construct a mode that requires a temporal help sequence of at least `length` edges.
+    ///
+    /// Returns [`SolverError::InvalidModeParameter`] when `length` is below `2`.
+    pub fn require_sequence(length: usize) -> Result<Self, SolverError> {
+        Self::parameterized(
+            length,
+            "RequireSequence",
+            "the parameter is a number of help edges: a sequence of 0 edges is contained in every \
+             plan, and a sequence of 1 edge is a lone help event, i.e. plain cooperation. Use \
+             SolveMode::RequireCooperation to require that, and a length >= 2 to require an actual \
+             sequence.",
+            Self::RequireSequence,
+        )
+    }
+
+    /// Construct a mode that requires a temporal closed trail of exactly `order` agents.
+    ///
+    /// Returns [`SolverError::InvalidModeParameter`] when `order` is below `2`.
+    pub fn require_interdependence(order: usize) -> Result<Self, SolverError> {
+        Self::parameterized(
+            order,
+            "RequireInterdependence",
+            "the parameter is the number of distinct agents of a closed trail: no trail closes \
+             over 0 agents, and an agent cannot depend on itself, so no trail closes over 1 agent \
+             either. Use an order >= 2, the smallest one being mutual cooperation.",
+            Self::RequireInterdependence,
+        )
+    }
+
+    /// Construct a mode that requires convergence from at least `k` distinct helpers.
+    ///
+    /// Returns [`SolverError::InvalidModeParameter`] when `k` is below `2`.
+    pub fn require_convergence(k: usize) -> Result<Self, SolverError> {
+        Self::parameterized(
+            k,
+            "RequireConvergence",
+            "the parameter is a number of distinct helpers converging on one beneficiary: 0 \
+             helpers are found in every plan, and 1 helper is plain cooperation. Use \
+             SolveMode::RequireCooperation to require any help, and k >= 2 to require \
+             convergence.",
+            Self::RequireConvergence,
+        )
+    }
+
+    /// Construct a mode that requires divergence to at least `k` distinct beneficiaries.
+    ///
+    /// Returns [`SolverError::InvalidModeParameter`] when `k` is below `2`.
+    pub fn require_divergence(k: usize) -> Result<Self, SolverError> {
+        Self::parameterized(
+            k,
+            "RequireDivergence",
+            "the parameter is a number of distinct beneficiaries assisted by one helper: 0 \
+             beneficiaries are found in every plan, and 1 beneficiary is plain cooperation. Use \
+             SolveMode::RequireCooperation to require any help, and k >= 2 to require \
+             divergence.",
+            Self::RequireDivergence,
+        )
+    }
+
+    /// Whether the mode requires a cooperation profile instead of forbidding one.
+    pub const fn is_requirement(&self) -> bool {
+        matches!(
+            self,
+            SolveMode::RequireCooperation
+                | SolveMode::RequireAsymmetric
+                | SolveMode::RequireSequence(_)
+                | SolveMode::RequireInterdependence(_)
+                | SolveMode::RequireConvergence(_)
+                | SolveMode::RequireDivergence(_)
+                | SolveMode::ExactHelpEvents(_)
+        )
+    }
+
     /// Validate and wrap a parameter before constructing its solve mode.
     fn parameterized(
         value: usize,
@@ -204,10 +346,32 @@ impl SolveMode {
             SolveMode::NoConvergentCooperation(k) => suffixed("no-convergence", k.get()),
             SolveMode::NoDivergentCooperation(k) => suffixed("no-divergence", k.get()),
             SolveMode::NoFullyCoupledCooperation => "no-fully-coupled".to_string(),
+            SolveMode::RequireCooperation => "require-cooperation".to_string(),
+            SolveMode::RequireAsymmetric => "require-asymmetric".to_string(),
+            SolveMode::RequireSequence(n) => suffixed("require-sequence", n.get()),
+            SolveMode::RequireInterdependence(n) => suffixed("require-interdependence", n.get()),
+            SolveMode::RequireConvergence(k) => suffixed("require-convergence", k.get()),
+            SolveMode::RequireDivergence(k) => suffixed("require-divergence", k.get()),
+            SolveMode::MaxHelpEvents(k) => format!("max-help-events-{k}"),
+            SolveMode::ExactHelpEvents(k) => format!("exact-help-events-{k}"),
         }
     }
 }
 
+/// Solve modes are serialized as their [canonical](SolveMode::canonical) string.
+impl serde::Serialize for SolveMode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.canonical())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SolveMode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 fn suffixed(prefix: &str, n: usize) -> String {
     if n == MIN_LENGTH {
         prefix.to_string()
@@ -370,6 +534,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_required_modes() {
+        assert_eq!(
+            SolveMode::from_str("require-cooperation").unwrap(),
+            SolveMode::RequireCooperation
+        );
+        assert_eq!(
+            SolveMode::from_str("require-asymmetric").unwrap(),
+            SolveMode::RequireAsymmetric
+        );
+        assert_eq!(
+            SolveMode::from_str("require-mutual").unwrap(),
+            SolveMode::require_interdependence(2).unwrap()
+        );
+        assert_eq!(
+            SolveMode::from_str("require-sequence-3").unwrap(),
+            SolveMode::require_sequence(3).unwrap()
+        );
+        assert_eq!(
+            SolveMode::from_str("require-convergence").unwrap(),
+            SolveMode::require_convergence(2).unwrap()
+        );
+        for s in [
+            "require-cooperation",
+            "require-asymmetric",
+            "require-sequence",
+            "require-sequence-4",
+            "require-interdependence",
+            "require-interdependence-3",
+            "require-convergence-3",
+            "require-divergence",
+        ] {
+            let mode = SolveMode::from_str(s).unwrap();
+            assert!(mode.is_requirement());
+            assert_eq!(mode.canonical(), s);
+        }
+        assert!(!SolveMode::NoCooperation.is_requirement());
+    }
+
+    #[test]
+    fn required_modes_reject_parameters_below_two() {
+        for s in [
+            "require-sequence-1",
+            "require-interdependence-0",
+            "require-convergence-1",
+            "require-divergence-x",
+        ] {
+            assert!(SolveMode::from_str(s).is_err(), "{s} should be rejected");
+        }
+        let message = SolveMode::require_sequence(1).unwrap_err().to_string();
+        assert!(message.contains("RequireSequence"), "{message}");
+        assert!(message.contains("RequireCooperation"), "{message}");
+    }
+
     /// Invalid divergence thresholds and malformed suffixes are rejected.
     #[test]
     fn divergence_rejects_invalid_thresholds() {
@@ -383,4 +601,26 @@ mod tests {
             assert!(SolveMode::from_str(s).is_err(), "{s} should be rejected");
         }
     }
+
+    #[test]
+    fn parses_help_event_modes() {
+        for (s, mode) in [
+            ("max-help-events-0", SolveMode::MaxHelpEvents(0)),
+            ("max-help-events-3", SolveMode::MaxHelpEvents(3)),
+            ("exact-help-events-1", SolveMode::ExactHelpEvents(1)),
+        ] {
+            assert_eq!(SolveMode::from_str(s), Ok(mode));
+            assert_eq!(mode.canonical(), s);
+        }
+        assert!(SolveMode::ExactHelpEvents(2).is_requirement());
+        assert!(!SolveMode::MaxHelpEvents(2).is_requirement());
+        for s in [
+            "max-help-events",
+            "max-help-events-",
+            "max-help-events--1",
+            "exact-help-events-x",
+        ] {
+            assert!(SolveMode::from_str(s).is_err(), "{s}");
+        }
+    }
 }
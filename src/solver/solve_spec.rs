@@ -0,0 +1,218 @@
+use super::{CooperationConstraint, SolveMode};
+
+/// A conjunction of solve modes and targeted cooperation constraints: a plan satisfies the spec
+/// when it satisfies every one of them.
+///
+/// This is how to ask combined questions such as "no mutual cooperation and no divergence-3", or
+/// to mix restrictions with `Require*` modes ("some help, but no sequence of three"). Modes and
+/// constraints are kept in insertion order without duplicates, and [`SolveMode::Standard`] is the
+/// neutral element: the empty spec is the standard problem.
+///
+/// Every API taking a spec also accepts a single [`SolveMode`] through `Into<SolveSpec>`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SolveSpec {
+    modes: Vec<SolveMode>,
+    constraints: Vec<CooperationConstraint>,
+}
+
+impl SolveSpec {
+    /// The standard problem, without any cooperation restriction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `mode` to the conjunction.
+    pub fn with(mut self, mode: SolveMode) -> Self {
+        if mode != SolveMode::Standard && !self.modes.contains(&mode) {
+            self.modes.push(mode);
+        }
+        self
+    }
+
+    /// Add `constraint` to the conjunction.
+    pub fn with_constraint(mut self, constraint: CooperationConstraint) -> Self {
+        if !self.constraints.contains(&constraint) {
+            self.constraints.push(constraint);
+        }
+        self
+    }
+
+    /// The modes of the conjunction, in insertion order. Empty for the standard problem.
+    pub fn modes(&self) -> &[SolveMode] {
+        &self.modes
+    }
+
+    /// The cooperation constraints of the conjunction, in insertion order.
+    pub fn constraints(&self) -> &[CooperationConstraint] {
+        &self.constraints
+    }
+
+    /// Whether the spec restricts nothing.
+    pub fn is_standard(&self) -> bool {
+        self.modes.is_empty() && self.constraints.is_empty()
+    }
+
+    /// The canonical string representation, inverse of `SolveSpec::from_str`: the canonical
+    /// strings of the modes, then of the constraints, joined with `+`, or `"standard"` for the
+    /// empty spec.
+    pub fn canonical(&self) -> String {
+        if self.is_standard() {
+            return SolveMode::Standard.canonical();
+        }
+        self.modes
+            .iter()
+            .map(SolveMode::canonical)
+            .chain(
+                self.constraints
+                    .iter()
+                    .map(CooperationConstraint::canonical),
+            )
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+impl From<SolveMode> for SolveSpec {
+    fn from(mode: SolveMode) -> Self {
+        Self::new().with(mode)
+    }
+}
+
+impl FromIterator<SolveMode> for SolveSpec {
+    fn from_iter<I: IntoIterator<Item = SolveMode>>(modes: I) -> Self {
+        modes.into_iter().fold(Self::new(), Self::with)
+    }
+}
+
+/// Parse `+`-separated solve modes and constraints, e.g. `"no-mutual+forbid-help-0-1"`. A single
+/// mode string is a valid spec.
+impl std::str::FromStr for SolveSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut spec = Self::new();
+        for part in s.split('+').map(str::trim) {
+            spec = match part.parse::<SolveMode>() {
+                Ok(mode) => spec.with(mode),
+                Err(mode_error) => match part.parse::<CooperationConstraint>() {
+                    Ok(constraint) => spec.with_constraint(constraint),
+                    // Constraints are an addition to the modes: report the mode error unless the
+                    // string was meant as a constraint.
+                    Err(constraint_error)
+                        if ["forbid-", "require-help-"]
+                            .iter()
+                            .any(|prefix| part.starts_with(prefix)) =>
+                    {
+                        return Err(constraint_error);
+                    }
+                    Err(_) => return Err(mode_error),
+                },
+            };
+        }
+        Ok(spec)
+    }
+}
+
+/// Solve specs are serialized as their [canonical](SolveSpec::canonical) string.
+impl serde::Serialize for SolveSpec {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.canonical())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SolveSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SolveSpec;
+    use crate::solver::{CooperationConstraint, SolveMode};
+
+    #[test]
+    fn standard_is_the_neutral_element() {
+        let spec = SolveSpec::new()
+            .with(SolveMode::Standard)
+            .with(SolveMode::NoCooperation)
+            .with(SolveMode::Standard);
+        assert_eq!(spec.modes(), &[SolveMode::NoCooperation]);
+        assert!(SolveSpec::from(SolveMode::Standard).is_standard());
+        assert_eq!(SolveSpec::new().canonical(), "standard");
+    }
+
+    #[test]
+    fn duplicate_modes_are_kept_once() {
+        let spec: SolveSpec = [
+            SolveMode::no_interdependence(2).unwrap(),
+            SolveMode::RequireCooperation,
+            SolveMode::no_interdependence(2).unwrap(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(spec.canonical(), "no-interdependence+require-cooperation");
+    }
+
+    #[test]
+    fn canonical_round_trips() {
+        for s in [
+            "standard",
+            "no-cooperation",
+            "no-interdependence+no-divergence-3",
+            "require-cooperation+no-sequence-3+no-convergence",
+        ] {
+            let spec: SolveSpec = s.parse().unwrap();
+            assert_eq!(spec.canonical(), s);
+        }
+        let spec: SolveSpec = "no-mutual + no-divergence-3".parse().unwrap();
+        assert_eq!(
+            spec.modes(),
+            &[
+                SolveMode::no_interdependence(2).unwrap(),
+                SolveMode::no_divergent_cooperation(3).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn constraints_follow_the_modes() {
+        let spec: SolveSpec = "forbid-help-0-1+no-mutual+forbid-laser-2+forbid-help-0-1"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.modes(), &[SolveMode::no_interdependence(2).unwrap()]);
+        assert_eq!(
+            spec.constraints(),
+            &[
+                CooperationConstraint::ForbidHelp {
+                    helper: 0,
+                    beneficiary: 1
+                },
+                CooperationConstraint::ForbidLaser(2)
+            ]
+        );
+        assert_eq!(
+            spec.canonical(),
+            "no-interdependence+forbid-help-0-1+forbid-laser-2"
+        );
+        assert!(
+            !SolveSpec::new()
+                .with_constraint(CooperationConstraint::ForbidHelper(0))
+                .is_standard()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_modes() {
+        for s in [
+            "",
+            "no-mutual+",
+            "no-mutual+bogus",
+            "no-sequence-1+standard",
+            "no-mutual+forbid-help-0",
+        ] {
+            assert!(s.parse::<SolveSpec>().is_err(), "{s}");
+        }
+    }
+}
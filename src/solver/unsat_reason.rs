@@ -0,0 +1,172 @@
+use std::fmt::Display;
+
+use crate::{AgentId, Position};
+
+use super::{CooperationConstraint, Literal, SolveMode};
+
+/// One group of clauses of an explained formula (see [`ClauseGenerator::generate_explained`]),
+/// which a selector literal switches on.
+///
+/// When the formula is unsatisfiable, the reasons of the selectors in an assumption core are jointly
+/// responsible: dropping any other group keeps the formula unsatisfiable.
+///
+/// [`ClauseGenerator::generate_explained`]: super::ClauseGenerator::generate_explained
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum UnsatReason {
+    /// The agent must stand on an exit at the horizon.
+    AgentGoal(AgentId),
+    /// The gem on this tile must be collected.
+    Gem(Position),
+    /// The gem on this tile must be collected by `agent`.
+    GemCollector { pos: Position, agent: AgentId },
+    /// At least this many gems must be collected.
+    GemCount(usize),
+    /// Under [`SolveMode::NoCooperation`], `beneficiary` never enters a beam of `helper`.
+    ForbiddenHelp {
+        helper: AgentId,
+        beneficiary: AgentId,
+    },
+    /// Under [`SolveMode::NoSequentialCooperation`], this `(helper, beneficiary)` sequence of help
+    /// events never happens.
+    SequencePattern {
+        length: usize,
+        arcs: Vec<(AgentId, AgentId)>,
+    },
+    /// Under [`SolveMode::NoInterdependence`], this closed trail of help events never happens.
+    InterdependencePattern {
+        order: usize,
+        arcs: Vec<(AgentId, AgentId)>,
+    },
+    /// The restriction or requirement of a mode that is not split any further.
+    Mode(SolveMode),
+    /// A cooperation constraint of the spec.
+    Constraint(CooperationConstraint),
+}
+
+impl Display for UnsatReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn trail(arcs: &[(AgentId, AgentId)]) -> String {
+            arcs.iter()
+                .map(|(helper, beneficiary)| format!("{helper}->{beneficiary}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        match self {
+            UnsatReason::AgentGoal(agent) => write!(f, "agent {agent} must reach an exit"),
+            UnsatReason::Gem(pos) => {
+                write!(f, "the gem at ({}, {}) must be collected", pos.i, pos.j)
+            }
+            UnsatReason::GemCollector { pos, agent } => write!(
+                f,
+                "agent {agent} must collect the gem at ({}, {})",
+                pos.i, pos.j
+            ),
+            UnsatReason::GemCount(k) => write!(f, "at least {k} gems must be collected"),
+            UnsatReason::ForbiddenHelp {
+                helper,
+                beneficiary,
+            } => write!(
+                f,
+                "no-cooperation forbids agent {helper} from helping agent {beneficiary}"
+            ),
+            UnsatReason::SequencePattern { length, arcs } => {
+                write!(
+                    f,
+                    "no-sequence-{length} forbids the help sequence {}",
+                    trail(arcs)
+                )
+            }
+            UnsatReason::InterdependencePattern { order, arcs } => write!(
+                f,
+                "no-interdependence-{order} forbids the closed help trail {}",
+                trail(arcs)
+            ),
+            UnsatReason::Mode(mode) => write!(f, "mode {}", mode.canonical()),
+            UnsatReason::Constraint(constraint) => {
+                write!(f, "constraint {}", constraint.canonical())
+            }
+        }
+    }
+}
+
+/// The selector literals of an explained formula and the reason each of them switches on.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Explanation {
+    selectors: Vec<(Literal, UnsatReason)>,
+}
+
+impl Explanation {
+    pub(crate) fn push(&mut self, selector: Literal, reason: UnsatReason) {
+        self.selectors.push((selector, reason));
+    }
+
+    /// Every selector with its reason, in generation order.
+    pub fn selectors(&self) -> &[(Literal, UnsatReason)] {
+        &self.selectors
+    }
+
+    /// The reason switched on by `selector`, if it is a selector of this explanation.
+    pub fn reason(&self, selector: Literal) -> Option<&UnsatReason> {
+        self.selectors
+            .iter()
+            .find(|(literal, _)| *literal == selector)
+            .map(|(_, reason)| reason)
+    }
+
+    /// The reasons of the selectors in `core`, in generation order. Other literals of the core,
+    /// such as the required-profile flag, are ignored.
+    pub fn reasons(&self, core: &[Literal]) -> Vec<UnsatReason> {
+        self.selectors
+            .iter()
+            .filter(|(selector, _)| core.contains(selector))
+            .map(|(_, reason)| reason.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Explanation, UnsatReason};
+    use crate::solver::{CooperationConstraint, SolveMode};
+
+    #[test]
+    fn reasons_follow_the_selectors_of_the_core() {
+        let mut explanation = Explanation::default();
+        explanation.push(7, UnsatReason::AgentGoal(0));
+        explanation.push(9, UnsatReason::Mode(SolveMode::NoAsymmetricCooperation));
+        explanation.push(
+            12,
+            UnsatReason::Constraint(CooperationConstraint::ForbidHelper(1)),
+        );
+        assert_eq!(
+            explanation.reasons(&[12, 3, 7]),
+            [
+                UnsatReason::AgentGoal(0),
+                UnsatReason::Constraint(CooperationConstraint::ForbidHelper(1))
+            ]
+        );
+        assert_eq!(explanation.reason(9), Some(&explanation.selectors()[1].1));
+        assert_eq!(explanation.reason(3), None);
+    }
+
+    #[test]
+    fn reasons_are_human_readable() {
+        let sequence = UnsatReason::SequencePattern {
+            length: 2,
+            arcs: vec![(0, 1), (1, 2)],
+        };
+        assert_eq!(
+            sequence.to_string(),
+            "no-sequence-2 forbids the help sequence 0->1, 1->2"
+        );
+        assert_eq!(
+            UnsatReason::Constraint(CooperationConstraint::ForbidLaser(0)).to_string(),
+            "constraint forbid-laser-0"
+        );
+        assert_eq!(
+            UnsatReason::GemCount(2).to_string(),
+            "at least 2 gems must be collected"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use crate::{
+    World,
+    solver::{CooperationConstraint, Literal, VarKey, clauses::ClauseEngine},
+};
+
+/// Agents 0 and 1 each own a beam along a row; agent 2 owns nothing.
+const TWO_BEAMS: &str = "
+L0E .  .  .
+S0  S1 S2 .
+L1E .  .  .
+X   X  X  .";
+
+fn engine(t_max: usize) -> ClauseEngine {
+    let world = World::try_from(TWO_BEAMS).unwrap();
+    let mut engine = ClauseEngine::new(&world, t_max);
+    for t in 0..=t_max {
+        engine.generate_movement_clauses(t);
+    }
+    engine
+}
+
+/// The agents and rows of the agent literals in `literals`.
+fn occupants(engine: &ClauseEngine, literals: &[Literal]) -> HashSet<(usize, usize)> {
+    literals
+        .iter()
+        .map(|&lit| match engine.pool.key(lit.abs()) {
+            Some(VarKey::Agent { agent_id, pos, .. }) => (agent_id, pos.i),
+            other => panic!("constraints only use agent literals, got {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn forbidding_constraints_negate_beam_occupancies() {
+    let mut engine = engine(8);
+    let cases = [
+        (
+            CooperationConstraint::ForbidHelp {
+                helper: 0,
+                beneficiary: 1,
+            },
+            HashSet::from([(1, 0)]),
+        ),
+        (
+            CooperationConstraint::ForbidHelper(0),
+            HashSet::from([(1, 0), (2, 0)]),
+        ),
+        (
+            CooperationConstraint::ForbidLaser(1),
+            HashSet::from([(0, 2), (2, 2)]),
+        ),
+    ];
+    for (constraint, expected) in cases {
+        let assumptions: Vec<_> = (0..=8)
+            .flat_map(|t| engine.assume_constraint_at(constraint, t))
+            .collect();
+        assert!(assumptions.iter().all(|&lit| lit < 0), "{constraint:?}");
+        assert_eq!(occupants(&engine, &assumptions), expected, "{constraint:?}");
+    }
+}
+
+#[test]
+fn required_help_is_not_assumed() {
+    let mut engine = engine(8);
+    let constraint = CooperationConstraint::RequireHelp {
+        helper: 0,
+        beneficiary: 1,
+    };
+    assert!(engine.assume_constraint_at(constraint, 4).is_empty());
+}
+
+/// A required help event lists exactly the occupancies that define the help variables.
+#[test]
+fn asserted_required_help_lists_the_occupancies_of_help_events() {
+    let t_max = 8;
+    let mut engine = engine(t_max);
+    let mut help_occupancies = HashSet::new();
+    for t in 0..=t_max {
+        let clauses = engine.generate_help_clauses(t);
+        let Some(help) = engine.pool.get(&VarKey::Help {
+            helper: 0,
+            beneficiary: 1,
+            t,
+        }) else {
+            continue;
+        };
+        // help(0, 1, t) -> OR(occupancies)
+        let definition = clauses
+            .iter()
+            .find(|clause| clause[0] == -help)
+            .expect("every help variable is defined");
+        help_occupancies.extend(definition[1..].iter().copied());
+    }
+    let clauses = engine.generate_constraint_clauses(
+        CooperationConstraint::RequireHelp {
+            helper: 0,
+            beneficiary: 1,
+        },
+        t_max,
+    );
+    assert_eq!(clauses.len(), 1);
+    assert!(!help_occupancies.is_empty());
+    assert_eq!(
+        clauses[0].iter().copied().collect::<HashSet<_>>(),
+        help_occupancies
+    );
+}
+
+#[test]
+fn asserted_forbidding_constraints_are_unit_clauses() {
+    let mut engine = engine(3);
+    let constraint = CooperationConstraint::ForbidHelper(1);
+    let clauses = engine.generate_constraint_clauses(constraint, 3);
+    assert!(!clauses.is_empty());
+    assert!(
+        clauses
+            .iter()
+            .all(|clause| clause.len() == 1 && clause[0] < 0)
+    );
+}
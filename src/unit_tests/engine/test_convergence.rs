@@ -60,3 +60,31 @@ fn outgoing_help_emits_no_convergence_blocker() {
     assert!(engine.generate_no_convergence_clauses(3, 2).is_empty());
     assert_eq!(engine.generate_no_divergence_clauses(3, 2).len(), 1);
 }
+
+/// Requiring convergence gives each pair of incoming summaries a witness that implies both.
+#[test]
+fn required_convergence_witnesses_imply_their_summaries() {
+    let mut engine = convergence_engine(4);
+    engine.pool.help(0, 2, 1);
+    engine.pool.help(1, 2, 3);
+    engine.generate_pairwise_help_clauses(3);
+
+    let from_zero = engine.literal(&summary_key(0, 2, 3)).expect("0 -> 2");
+    let from_one = engine.literal(&summary_key(1, 2, 3)).expect("1 -> 2");
+    let clauses = engine.generate_required_convergence_clauses(3, 2);
+    let required = engine.pool.required_profile(3);
+    let [first, second, goal] = clauses.as_slice() else {
+        panic!("expected one witness and one goal, got {clauses:?}");
+    };
+    let witness = -first[0];
+    assert_eq!(first, &vec![-witness, from_zero]);
+    assert_eq!(second, &vec![-witness, from_one]);
+    assert_eq!(goal, &vec![-required, witness]);
+
+    // Three helpers are impossible among three agents: the required profile is false.
+    let required = engine.pool.required_profile(3);
+    assert_eq!(
+        engine.generate_required_convergence_clauses(3, 3),
+        vec![vec![-required]]
+    );
+}
@@ -129,3 +129,48 @@ fn oversized_interdependence_order_emits_no_clauses() {
         assert!(engine.generate_interdependence_clauses(t, 4).is_empty());
     }
 }
+
+/// Required closed trails keep a progress state for every complete pattern, each supported by its
+/// causes, and the goal requires one of them at the horizon.
+#[test]
+fn required_interdependence_goal_lists_complete_patterns() {
+    // Level 4 requires mutual cooperation.
+    let t_max = 10;
+    let order = 2;
+    let mut engine = ClauseEngine::new(&World::get_level(4).unwrap(), t_max);
+    let mut clauses = Vec::new();
+    for t in 0..=t_max {
+        engine.generate_movement_clauses(t);
+        engine.generate_help_clauses(t);
+        clauses.extend(engine.generate_required_interdependence_clauses(t, order));
+    }
+
+    let patterns = engine.interdependence_patterns(order);
+    assert!(!patterns.is_empty());
+    let finals = patterns
+        .iter()
+        .enumerate()
+        .map(|(pattern_index, pattern)| {
+            engine
+                .literal(&VarKey::InterdependenceProgress {
+                    order,
+                    pattern: pattern_index,
+                    prefix_len: pattern.arcs.len(),
+                    t: t_max,
+                })
+                .expect("complete patterns have a progress state")
+        })
+        .collect::<Vec<_>>();
+    // Every final state has a support clause that starts with its negation.
+    for &state in &finals {
+        assert!(clauses.iter().any(|clause| clause[0] == -state));
+    }
+
+    let required = engine.pool.required_profile(t_max);
+    let mut expected = vec![-required];
+    expected.extend(finals);
+    assert_eq!(
+        engine.generate_required_interdependence_goal(t_max, order),
+        vec![expected]
+    );
+}
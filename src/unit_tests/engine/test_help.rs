@@ -378,3 +378,29 @@ fn number_of_is_helped_clauses_one_laser_two_laser_tiles() {
         horizon: t_max
     }));
 }
+
+/// Requiring cooperation lists every materialized help literal through the horizon, and nothing
+/// when help is impossible.
+#[test]
+fn required_help_lists_every_help_event_up_to_the_horizon() {
+    let world = World::try_from(
+        "
+ @  S0 S1
+L0E  .  .
+ @   X  X
+",
+    )
+    .unwrap();
+    let (mut engine, _) = help_clauses_per_step(&world, 5);
+    let required = engine.pool.required_profile(3);
+    let mut expected = vec![-required];
+    expected.extend((1..=3).map(|t| help_literal(&engine, 0, 1, t)));
+    assert_eq!(engine.generate_required_help_clauses(3), vec![expected]);
+
+    let (mut engine, _) = help_clauses_per_step(&World::try_from("S0 S1\nX X").unwrap(), 3);
+    let required = engine.pool.required_profile(3);
+    assert_eq!(
+        engine.generate_required_help_clauses(3),
+        vec![vec![-required]]
+    );
+}
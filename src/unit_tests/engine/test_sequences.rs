@@ -92,3 +92,50 @@ fn oversized_sequence_length_emits_no_clauses() {
         assert!(engine.generate_sequence_clauses(t, 100).is_empty());
     }
 }
+
+/// Required sequences materialize the final progress states that the forbidding encoding omits,
+/// and the goal requires one of them at the horizon.
+#[test]
+fn required_sequence_clauses_materialize_final_progress_states() {
+    let t_max = 6;
+    let length = 2;
+    let mut engine = sequence_engine(t_max);
+    for t in 0..=t_max {
+        engine.generate_movement_clauses(t);
+        engine.generate_help_clauses(t);
+        engine.generate_required_sequence_clauses(t, length);
+    }
+
+    let finals = (0..engine.sequence_patterns(length).len())
+        .filter_map(|pattern| {
+            engine.pool.get(&VarKey::SequenceProgress {
+                length,
+                pattern,
+                prefix_len: length,
+                t: t_max,
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(!finals.is_empty());
+
+    let goal = engine.generate_required_sequence_goal(t_max, length);
+    let required = engine.pool.required_profile(t_max);
+    let mut expected = vec![-required];
+    expected.extend(finals);
+    assert_eq!(goal, vec![expected]);
+}
+
+/// Without any help literal, no sequence can be completed and the required profile is false.
+#[test]
+fn required_sequence_goal_without_help_forbids_the_profile() {
+    let mut engine = sequence_engine(3);
+    for t in 0..=3 {
+        engine.generate_movement_clauses(t);
+        assert!(engine.generate_required_sequence_clauses(t, 2).is_empty());
+    }
+    let required = engine.pool.required_profile(3);
+    assert_eq!(
+        engine.generate_required_sequence_goal(3, 2),
+        vec![vec![-required]]
+    );
+}
@@ -0,0 +1,155 @@
+use rstest::rstest;
+
+use crate::{
+    Action, Position, World, WorldEvent,
+    solver::{ClauseGenerator, SolveMode, VarKey, sat},
+};
+
+fn pos(i: usize, j: usize) -> Position {
+    Position { i, j }
+}
+
+/// Replay `plan` from the initial state and check that every agent exits without dying.
+fn assert_valid_plan(world: &mut World, plan: &[Vec<Action>]) {
+    world.reset();
+    for joint_action in plan {
+        let events = world.step(joint_action).expect("the plan must be legal");
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, WorldEvent::AgentDied { .. })),
+            "no agent may die while following the plan"
+        );
+    }
+    assert_eq!(world.n_agents_arrived(), world.n_agents());
+}
+
+/// A corridor where agent 0 may wait next to its own beam, followed by private positions.
+const CORRIDOR: &str = "
+    S0 .   . . X
+    @  L0N @ @ @";
+
+/// Waiting before a move to a private position is forbidden: the same wait must happen later.
+#[test]
+fn waits_are_pushed_to_the_end_of_a_private_corridor() {
+    let mut world = World::try_from(CORRIDOR).unwrap();
+    let mut plain = ClauseGenerator::new(&world, 5);
+    let mut symmetric = ClauseGenerator::new(&world, 5).with_symmetry_breaking(true);
+    let (plain_clauses, _) = plain.generate(5, SolveMode::Standard, false).unwrap();
+    let (symmetric_clauses, _) = symmetric.generate(5, SolveMode::Standard, false).unwrap();
+
+    // Wait on (0, 2), next to the beam, then move to the private position (0, 3).
+    let wait_then_move = |generator: &ClauseGenerator| {
+        vec![
+            generator.literal(&VarKey::agent(0, pos(0, 2), 2)).unwrap(),
+            generator.literal(&VarKey::agent(0, pos(0, 2), 3)).unwrap(),
+        ]
+    };
+    assert!(sat::is_sat(&plain_clauses, &wait_then_move(&plain)));
+    assert!(!sat::is_sat(
+        &symmetric_clauses,
+        &wait_then_move(&symmetric)
+    ));
+
+    let model = sat::solve(&symmetric_clauses, &[]).unwrap();
+    assert_valid_plan(&mut world, &symmetric.decode_plan(&model, 5).unwrap());
+}
+
+/// Symmetry breaking only adds clauses over existing agent variables.
+#[test]
+fn symmetry_breaking_adds_no_variable() {
+    let world = World::try_from(CORRIDOR).unwrap();
+    let mut plain = ClauseGenerator::new(&world, 8);
+    let mut symmetric = ClauseGenerator::new(&world, 8).with_symmetry_breaking(true);
+    let (plain_clauses, _) = plain.generate(8, SolveMode::Standard, false).unwrap();
+    let (symmetric_clauses, _) = symmetric.generate(8, SolveMode::Standard, false).unwrap();
+    assert_eq!(plain.n_vars(), symmetric.n_vars());
+    assert!(symmetric_clauses.len() > plain_clauses.len());
+}
+
+/// Positions that another agent can reach, and beam tiles, are never the target of a
+/// symmetry-breaking clause.
+#[test]
+fn shared_positions_and_beams_are_not_private() {
+    let world = World::try_from(
+        "
+        S0 .  . X
+        .  .  . .
+        S1 L0N . X",
+    )
+    .unwrap();
+    let mut engine = crate::solver::clauses::ClauseEngine::new(&world, 6);
+    let clauses = (0..=6)
+        .flat_map(|t| engine.generate_symmetry_breaking_clauses(t))
+        .collect::<Vec<_>>();
+    for clause in clauses {
+        let Some(VarKey::Agent { agent_id, pos, t }) = engine.pool.key(-clause[2]) else {
+            panic!("symmetry-breaking clauses only mention agent variables");
+        };
+        assert_ne!(pos.j, 1, "column 1 holds the beam of laser 0");
+        let other = 1 - agent_id;
+        for tau in t - 2..=t {
+            assert!(
+                !engine
+                    .ctx
+                    .relevant_positions_for_agent(other, tau)
+                    .contains(&pos),
+                "{pos:?} is reachable by agent {other} at {tau}"
+            );
+        }
+    }
+}
+
+/// Check that symmetry breaking never changes whether a horizon is satisfiable, and that the
+/// plans it finds are valid in the world. Stops one satisfiable horizon past the first one, to also
+/// cover a horizon with slack.
+fn assert_equisatisfiable(mut world: World, t_max: usize, mode: SolveMode, collect_gems: bool) {
+    let mut plain = ClauseGenerator::new(&world, t_max);
+    let mut symmetric = ClauseGenerator::new(&world, t_max).with_symmetry_breaking(true);
+    let mut n_sat = 0;
+    for t in plain.solution_lower_bound()..=t_max {
+        let (clauses, assumptions) = plain.generate(t, mode, collect_gems).unwrap();
+        let expected = sat::is_sat(&clauses, &assumptions);
+        let (clauses, assumptions) = symmetric.generate(t, mode, collect_gems).unwrap();
+        let model = sat::solve(&clauses, &assumptions);
+        assert_eq!(
+            model.is_some(),
+            expected,
+            "horizon {t}, mode {mode:?}, collect_gems {collect_gems}"
+        );
+        if let Some(model) = model {
+            assert_valid_plan(&mut world, &symmetric.decode_plan(&model, t).unwrap());
+            n_sat += 1;
+            if n_sat == 2 {
+                break;
+            }
+        }
+    }
+}
+
+#[rstest]
+fn symmetry_breaking_is_equisatisfiable_on_levels(
+    #[values(1, 2, 3, 4, 5, 6)] level: usize,
+    #[values(SolveMode::Standard, SolveMode::NoCooperation)] mode: SolveMode,
+    #[values(false, true)] collect_gems: bool,
+) {
+    assert_equisatisfiable(World::get_level(level).unwrap(), 24, mode, collect_gems);
+}
+
+/// Both agents have private positions, but the second one can only cross the beam when the first
+/// one blocks it.
+#[rstest]
+fn symmetry_breaking_is_equisatisfiable_with_private_regions(
+    #[values(SolveMode::Standard, SolveMode::NoCooperation)] mode: SolveMode,
+    #[values(false, true)] collect_gems: bool,
+) {
+    let world = World::try_from(
+        "
+        S0  . . X X
+        L0E . . . .
+        @   @ @ . @
+        S1  . G . @",
+    )
+    .unwrap();
+    assert_equisatisfiable(world, 14, mode, collect_gems);
+}
@@ -0,0 +1,327 @@
+//! A small CDCL SAT solver used by the tests to check satisfiability of generated formulas.
+//!
+//! It implements unit propagation with two watched literals, first-UIP clause learning and an
+//! activity-based branching heuristic. It is meant for the few thousand variables of the test
+//! worlds, not for speed.
+
+use std::collections::BinaryHeap;
+
+use crate::solver::{Clause, Literal};
+
+/// Return a model (every variable with its sign) if `clauses` are satisfiable under
+/// `assumptions`, or `None` otherwise.
+pub fn solve(clauses: &[Clause], assumptions: &[Literal]) -> Option<Vec<Literal>> {
+    let n_vars = clauses
+        .iter()
+        .flatten()
+        .chain(assumptions)
+        .map(|lit| lit.unsigned_abs() as usize)
+        .max()
+        .unwrap_or(0);
+    let mut solver = Solver::new(n_vars);
+    for clause in clauses {
+        if !solver.add_clause(clause) {
+            return None;
+        }
+    }
+    for &lit in assumptions {
+        if !solver.add_clause(&[lit]) {
+            return None;
+        }
+    }
+    solver.solve()
+}
+
+/// Whether `clauses` are satisfiable under `assumptions`.
+pub fn is_sat(clauses: &[Clause], assumptions: &[Literal]) -> bool {
+    solve(clauses, assumptions).is_some()
+}
+
+/// Return a minimal subset of `assumptions` under which `clauses` are unsatisfiable, or `None` if
+/// they are satisfiable under all of them. Assumptions are dropped one at a time whenever the
+/// formula stays unsatisfiable without them.
+pub fn minimal_core(clauses: &[Clause], assumptions: &[Literal]) -> Option<Vec<Literal>> {
+    if is_sat(clauses, assumptions) {
+        return None;
+    }
+    let mut core = assumptions.to_vec();
+    let mut i = 0;
+    while i < core.len() {
+        let mut candidate = core.clone();
+        candidate.remove(i);
+        if is_sat(clauses, &candidate) {
+            i += 1;
+        } else {
+            core = candidate;
+        }
+    }
+    Some(core)
+}
+
+struct Solver {
+    clauses: Vec<Vec<Literal>>,
+    /// `watches[index(lit)]` = clauses in which `lit` is one of the two watched literals.
+    watches: Vec<Vec<usize>>,
+    /// `1` if the variable is true, `-1` if false, `0` if unassigned.
+    values: Vec<i8>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Literal>,
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    activity: Vec<f64>,
+    increment: f64,
+    /// Max-heap of `(activity bits, var)`, with stale entries skipped when popped.
+    order: BinaryHeap<(u64, usize)>,
+}
+
+fn index(lit: Literal) -> usize {
+    2 * (lit.unsigned_abs() as usize) + usize::from(lit < 0)
+}
+
+fn var(lit: Literal) -> usize {
+    lit.unsigned_abs() as usize
+}
+
+impl Solver {
+    fn new(n_vars: usize) -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2 * n_vars + 2],
+            values: vec![0; n_vars + 1],
+            levels: vec![0; n_vars + 1],
+            reasons: vec![None; n_vars + 1],
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            propagated: 0,
+            activity: vec![0.0; n_vars + 1],
+            increment: 1.0,
+            order: (1..=n_vars).map(|v| (0f64.to_bits(), v)).collect(),
+        }
+    }
+
+    fn value(&self, lit: Literal) -> i8 {
+        let value = self.values[var(lit)];
+        if lit > 0 { value } else { -value }
+    }
+
+    fn level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn assign(&mut self, lit: Literal, reason: Option<usize>) {
+        self.values[var(lit)] = if lit > 0 { 1 } else { -1 };
+        self.levels[var(lit)] = self.level();
+        self.reasons[var(lit)] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Add an input clause at level 0. Returns `false` if the formula became trivially unsat.
+    fn add_clause(&mut self, clause: &[Literal]) -> bool {
+        let mut lits = clause.to_vec();
+        lits.sort_unstable();
+        lits.dedup();
+        if lits
+            .iter()
+            .any(|lit| lits.contains(&-lit) || self.value(*lit) == 1)
+        {
+            return true;
+        }
+        lits.retain(|&lit| self.value(lit) == 0);
+        match lits.len() {
+            0 => false,
+            1 => {
+                self.assign(lits[0], None);
+                self.propagate().is_none()
+            }
+            _ => {
+                self.attach(lits);
+                true
+            }
+        }
+    }
+
+    fn attach(&mut self, lits: Vec<Literal>) -> usize {
+        let id = self.clauses.len();
+        self.watches[index(lits[0])].push(id);
+        self.watches[index(lits[1])].push(id);
+        self.clauses.push(lits);
+        id
+    }
+
+    /// Propagate the pending assignments, returning a conflicting clause if any.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = -self.trail[self.propagated];
+            self.propagated += 1;
+            let watchers = std::mem::take(&mut self.watches[index(falsified)]);
+            let mut kept = Vec::with_capacity(watchers.len());
+            let mut conflict = None;
+            for (position, &id) in watchers.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watchers[position..]);
+                    break;
+                }
+                let clause = &mut self.clauses[id];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.value(first) == 1 {
+                    kept.push(id);
+                    continue;
+                }
+                let clause = &self.clauses[id];
+                if let Some(k) = (2..clause.len()).find(|&k| self.value(clause[k]) != -1) {
+                    let clause = &mut self.clauses[id];
+                    clause.swap(1, k);
+                    let watched = clause[1];
+                    self.watches[index(watched)].push(id);
+                    continue;
+                }
+                kept.push(id);
+                if self.value(first) == -1 {
+                    conflict = Some(id);
+                } else {
+                    self.assign(first, Some(id));
+                }
+            }
+            self.watches[index(falsified)] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump(&mut self, v: usize) {
+        self.activity[v] += self.increment;
+        if self.activity[v] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+            self.order = (1..self.activity.len())
+                .map(|v| (self.activity[v].to_bits(), v))
+                .collect();
+        }
+        self.order.push((self.activity[v].to_bits(), v));
+    }
+
+    /// First-UIP conflict analysis: return the learnt clause (asserting literal first) and the
+    /// level to backtrack to.
+    fn analyze(&mut self, mut conflict: usize) -> (Vec<Literal>, usize) {
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![0];
+        let mut pending = 0;
+        let mut implied: Option<Literal> = None;
+        let mut cursor = self.trail.len();
+        loop {
+            for k in 0..self.clauses[conflict].len() {
+                let lit = self.clauses[conflict][k];
+                if Some(lit) == implied {
+                    continue;
+                }
+                let v = var(lit);
+                if !seen[v] && self.levels[v] > 0 {
+                    seen[v] = true;
+                    self.bump(v);
+                    if self.levels[v] == self.level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            loop {
+                cursor -= 1;
+                if seen[var(self.trail[cursor])] {
+                    break;
+                }
+            }
+            let lit = self.trail[cursor];
+            seen[var(lit)] = false;
+            pending -= 1;
+            implied = Some(lit);
+            if pending == 0 {
+                break;
+            }
+            conflict = self.reasons[var(lit)].expect("implied literals have a reason");
+        }
+        learnt[0] = -implied.expect("a conflict involves the current level");
+        let mut backtrack_level = 0;
+        if learnt.len() > 1 {
+            let max = (1..learnt.len())
+                .max_by_key(|&k| self.levels[var(learnt[k])])
+                .expect("the learnt clause has several literals");
+            learnt.swap(1, max);
+            backtrack_level = self.levels[var(learnt[1])];
+        }
+        (learnt, backtrack_level)
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..) {
+            let v = var(lit);
+            self.values[v] = 0;
+            self.reasons[v] = None;
+            self.order.push((self.activity[v].to_bits(), v));
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn pick_branch_variable(&mut self) -> Option<usize> {
+        while let Some((bits, v)) = self.order.pop() {
+            if self.values[v] == 0 && bits == self.activity[v].to_bits() {
+                return Some(v);
+            }
+        }
+        // Stale heap entries may hide unassigned variables with an outdated activity.
+        (1..self.values.len()).find(|&v| self.values[v] == 0)
+    }
+
+    fn solve(mut self) -> Option<Vec<Literal>> {
+        if self.propagate().is_some() {
+            return None;
+        }
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.level() == 0 {
+                    return None;
+                }
+                let (learnt, backtrack_level) = self.analyze(conflict);
+                self.backtrack(backtrack_level);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let id = self.attach(learnt);
+                    self.assign(asserting, Some(id));
+                }
+                self.increment /= 0.95;
+                continue;
+            }
+            let Some(v) = self.pick_branch_variable() else {
+                return Some(
+                    (1..self.values.len())
+                        .map(|v| {
+                            if self.values[v] > 0 {
+                                v as Literal
+                            } else {
+                                -(v as Literal)
+                            }
+                        })
+                        .collect(),
+                );
+            };
+            self.trail_limits.push(self.trail.len());
+            // Most planning variables are false in a model, so try that polarity first.
+            self.assign(-(v as Literal), None);
+        }
+    }
+}
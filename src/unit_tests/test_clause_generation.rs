@@ -1,12 +1,20 @@
 use std::collections::HashSet;
 
+use crate::Action;
+use crate::ConflictRule;
 use crate::Position;
 use crate::World;
+use crate::WorldEvent;
+use crate::WorldState;
 use crate::solver::Clause;
 use crate::solver::Literal;
 use crate::solver::clauses::ClauseEngine;
 
-use crate::solver::{ClauseGenerator, SolveMode, VarKey};
+use super::super::mode_requirements::{HorizonFamily, ModeRequirements};
+use crate::solver::{
+    AtMostOneEncoding, ClauseGenerator, CooperationConstraint, EncodingConfig, GemObjective,
+    Projection, SolveMode, SolveSpec, UnsatReason, VarKey, errors::SolverError, sat,
+};
 use rstest::rstest;
 use rstest_reuse::{self, apply, template};
 
@@ -26,7 +34,9 @@ fn build(map: &str, t_max: usize) -> ClauseGenerator {
 #[test]
 fn delta_stream_supports_repeated_and_increasing_horizons() {
     let mut generator = build("S0 . . X", 4);
-    let mut stream = generator.start_delta_stream(SolveMode::Standard, false);
+    let mut stream = generator
+        .start_delta_stream(SolveMode::Standard, false)
+        .unwrap();
 
     let (first, first_assumptions) = stream.advance_to(&mut generator, 2);
     assert!(!first.is_empty());
@@ -50,7 +60,9 @@ fn delta_stream_supports_repeated_and_increasing_horizons() {
 #[should_panic(expected = "horizon must not decrease")]
 fn delta_stream_rejects_a_decreasing_horizon() {
     let mut generator = build("S0 . . X", 4);
-    let mut stream = generator.start_delta_stream(SolveMode::Standard, false);
+    let mut stream = generator
+        .start_delta_stream(SolveMode::Standard, false)
+        .unwrap();
     stream.advance_to(&mut generator, 3);
     stream.advance_to(&mut generator, 2);
 }
@@ -71,13 +83,16 @@ fn new_delta_stream_resends_clauses_a_previous_stream_already_sent() {
     let world = World::try_from("L0E .  .  X\nL1E .  .  X\nS0  S1 S2 X").unwrap();
     let mut generator = ClauseGenerator::new(&world, 4);
 
-    let mut first = generator.start_delta_stream(SolveMode::Standard, false);
+    let mut first = generator
+        .start_delta_stream(SolveMode::Standard, false)
+        .unwrap();
     let (first_clauses, _) = first.advance_to(&mut generator, 3);
     assert!(!first_clauses.is_empty());
     drop(first);
 
-    let mut second =
-        generator.start_delta_stream(SolveMode::no_sequential_cooperation(2).unwrap(), false);
+    let mut second = generator
+        .start_delta_stream(SolveMode::no_sequential_cooperation(2).unwrap(), false)
+        .unwrap();
     let (second_clauses, _) = second.advance_to(&mut generator, 3);
     assert!(
         second_clauses.len() >= first_clauses.len(),
@@ -95,11 +110,15 @@ fn new_delta_stream_resends_clauses_a_previous_stream_already_sent() {
 fn different_delta_streams_serve_different_gem_policies() {
     let mut generator = build("S0 G . X", 4);
 
-    let mut without_gems = generator.start_delta_stream(SolveMode::Standard, false);
+    let mut without_gems = generator
+        .start_delta_stream(SolveMode::Standard, false)
+        .unwrap();
     let (_, without_gems_assumptions) = without_gems.advance_to(&mut generator, 3);
     drop(without_gems);
 
-    let mut with_gems = generator.start_delta_stream(SolveMode::Standard, true);
+    let mut with_gems = generator
+        .start_delta_stream(SolveMode::Standard, true)
+        .unwrap();
     let (with_gems_clauses, with_gems_assumptions) = with_gems.advance_to(&mut generator, 3);
 
     assert!(!with_gems_clauses.is_empty());
@@ -113,13 +132,15 @@ fn different_delta_streams_serve_different_gem_policies() {
 #[test]
 fn full_generation_is_unaffected_by_delta_streams() {
     let mut mixed = build("S0 . . X", 4);
-    let full_before_stream = mixed.generate(2, SolveMode::Standard, false);
+    let full_before_stream = mixed.generate(2, SolveMode::Standard, false).unwrap();
 
-    let mut stream = mixed.start_delta_stream(SolveMode::Standard, false);
+    let mut stream = mixed
+        .start_delta_stream(SolveMode::Standard, false)
+        .unwrap();
     let delta = stream.advance_to(&mut mixed, 2);
     assert!(!delta.0.is_empty());
 
-    let repeated_full = mixed.generate(2, SolveMode::Standard, false);
+    let repeated_full = mixed.generate(2, SolveMode::Standard, false).unwrap();
     assert_eq!(full_before_stream, repeated_full);
 }
 
@@ -147,23 +168,28 @@ fn test_position_validity_single_agent() {
     let t_max = 4;
     let world = World::try_from("S0 . X").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, t_max);
-    generator.generate(t_max, SolveMode::Standard, false);
+    generator
+        .generate(t_max, SolveMode::Standard, false)
+        .unwrap();
 
     let start_pos = world.starts()[0];
-    // Pos (0,0) exists at t={0,1,2}
-    for t in 0..=2 {
+    // Staying is useless outside of the exit, so the agent alternates between (0,0) and (0,1).
+    // Pos (0,0) exists at t={0,2}
+    for t in [0, 2] {
         assert!(generator.exists(&VarKey::agent(0, start_pos, t)));
     }
+    assert!(!generator.exists(&VarKey::agent(0, start_pos, 1)));
     // Pos (0, 0) does not exist a t={3,4} (because it is too far from the exit)
     assert!(!generator.exists(&VarKey::agent(0, start_pos, 3)));
     assert!(!generator.exists(&VarKey::agent(0, start_pos, 4)));
-    // Pos (0,1) exists at t={1, 2, 3}
+    // Pos (0,1) exists at t={1, 3}
     assert!(!generator.exists(&VarKey::agent(0, pos(0, 1), 0)));
-    for t in 1..=3 {
+    for t in [1, 3] {
         assert!(generator.exists(&VarKey::agent(0, pos(0, 1), t)));
     }
+    assert!(!generator.exists(&VarKey::agent(0, pos(0, 1), 2)));
     assert!(!generator.exists(&VarKey::agent(0, pos(0, 1), 4)));
-    // Pos (0, 2) exists at t={2, 3, 4}
+    // Pos (0, 2) exists at t={2, 3, 4}: staying on the exit is always possible
     assert!(!generator.exists(&VarKey::agent(0, pos(0, 2), 0)));
     assert!(!generator.exists(&VarKey::agent(0, pos(0, 2), 1)));
     for t in 2..=4 {
@@ -183,7 +209,7 @@ fn possible_positions_multiple_agents() {
     let t_max = 10;
     let mut cg = ClauseGenerator::new(&world, t_max);
 
-    let (clauses, assumptions) = cg.generate(t_max, SolveMode::Standard, false);
+    let (clauses, assumptions) = cg.generate(t_max, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
     assert!(assumptions.is_empty());
     // t=0, only start position
@@ -221,21 +247,21 @@ fn possible_positions_multiple_agents() {
 #[test]
 fn test_at_most_one_position_clause_structure() {
     let mut cg = build("S0 . X", 10);
-    let (clauses, _) = cg.generate(1, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(2, SolveMode::Standard, false).unwrap();
 
     let a00 = cg
-        .literal(&VarKey::agent(0, pos(0, 0), 1))
-        .expect("agent(0,(0,0),1)");
-    let a01 = cg
-        .literal(&VarKey::agent(0, pos(0, 1), 1))
-        .expect("agent(0,(0,1),1)");
+        .literal(&VarKey::agent(0, pos(0, 0), 2))
+        .expect("agent(0,(0,0),2)");
+    let a02 = cg
+        .literal(&VarKey::agent(0, pos(0, 2), 2))
+        .expect("agent(0,(0,2),2)");
 
-    // At-most-one (pairwise): {-a00, -a01} must appear.
+    // At-most-one (pairwise): {-a00, -a02} must appear.
     assert!(
         clauses
             .iter()
-            .any(|c| c.len() == 2 && c.contains(&-a00) && c.contains(&-a01)),
-        "at-most-one clause [-a00, -a01] missing"
+            .any(|c| c.len() == 2 && c.contains(&-a00) && c.contains(&-a02)),
+        "at-most-one clause [-a00, -a02] missing"
     );
 }
 
@@ -244,7 +270,7 @@ fn test_at_most_one_position_clause_structure() {
 #[test]
 fn test_time_wise_adjacency_clause_structure() {
     let mut cg = build("S0 . X", 10);
-    let (clauses, _) = cg.generate(1, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(1, SolveMode::Standard, false).unwrap();
 
     let cur = cg
         .literal(&VarKey::agent(0, pos(0, 1), 1))
@@ -273,7 +299,7 @@ fn test_no_overlap_binary_exclusion_clause() {
     //   exclusive to agent 0: {(0,0)}  → no exclusion clause
     //   exclusive to agent 1: {(0,2), (0,3)}  → no exclusion clause
     let mut cg = build("S0 . S1 X X", 5);
-    let (clauses, _) = cg.generate(1, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(1, SolveMode::Standard, false).unwrap();
 
     // ── shared position (0,1): exclusion clause must exist ──────────────────
     let v0_01 = cg
@@ -320,7 +346,7 @@ fn test_no_following_conflict_encodes_swap_prohibition() {
     // S0 at (0,0), S1 at (0,1).
     // Opt 2: (0,0) is S0's start, so agent 1 cannot be there at t=1 — the variable is pruned.
     let mut cg = build("S0 S1 X X", 5);
-    let (_, _) = cg.generate(2, SolveMode::Standard, false);
+    let (_, _) = cg.generate(2, SolveMode::Standard, false).unwrap();
 
     // Agent 1 cannot be at (0,0) at t=1 (Opt 2 prunes it entirely).
     assert!(
@@ -336,7 +362,7 @@ fn test_no_following_conflict_encodes_swap_prohibition() {
     let a1_t2 = cg
         .literal(&VarKey::agent(1, pos(0, 0), 2))
         .expect("agent 1 at (0,0) t=2");
-    let clauses = cg.generate(2, SolveMode::Standard, false).0;
+    let clauses = cg.generate(2, SolveMode::Standard, false).unwrap().0;
     assert!(
         clauses
             .iter()
@@ -352,7 +378,7 @@ fn test_no_following_conflict_encodes_swap_prohibition() {
 fn test_stays_on_exit_implication_clause() {
     // "S0 . X": exit at (0,2). Agent can reach exit at t=2; at t=3 must stay.
     let mut cg = build("S0 . X", 4);
-    let (clauses, _) = cg.generate(3, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(3, SolveMode::Standard, false).unwrap();
 
     let prev = cg
         .literal(&VarKey::agent(0, pos(0, 2), 2))
@@ -373,7 +399,7 @@ fn test_objective_reaches_exit(level: usize) {
     let world = World::get_level(level).expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 21);
 
-    let obj_clauses = generator.objective(21, false);
+    let obj_clauses = generator.objective(21, false).unwrap();
     assert_eq!(obj_clauses.len(), world.n_agents());
     for clause in obj_clauses {
         assert_eq!(clause.len(), world.n_exits());
@@ -385,7 +411,7 @@ fn test_objective_multiple_agents_multiple_exits() {
     let world = World::try_from("S0 S1 . .\n. . . .\nX X X X").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 10);
 
-    let objective_clauses = generator.objective(10, false);
+    let objective_clauses = generator.objective(10, false).unwrap();
     assert_eq!(objective_clauses.len(), 2, "One objective clause per agent");
     for clause in &objective_clauses {
         assert!(!clause.is_empty());
@@ -519,8 +545,493 @@ fn test_gem_must_be_collected_clause_3gems() {
     );
 }
 
+/// One agent between two gems: one gem takes 3 steps, both take 5.
+const TWO_GEMS_APART: &str = "G S0 G\n. X .";
+/// Agent 0 reaches the gem first and is one step closer to an exit afterwards.
+const SHARED_GEM: &str = "S0 G . S1\nX . . X";
+
+#[rstest]
+#[case(3, 1, true)]
+#[case(3, 2, false)]
+#[case(5, 2, true)]
+#[case(5, 3, false)]
+fn at_least_bounds_the_number_of_gems(#[case] t: usize, #[case] k: usize, #[case] sat: bool) {
+    let mut cg = build(TWO_GEMS_APART, 5);
+    let (clauses, assumptions) = cg
+        .generate(t, SolveMode::Standard, GemObjective::AtLeast(k))
+        .unwrap();
+    let model = sat::solve(&clauses, &assumptions);
+    assert_eq!(model.is_some(), sat);
+    if let Some(model) = model {
+        assert!(cg.count_gems(&model, t) >= k);
+    }
+}
+
+#[test]
+fn at_least_zero_gems_is_no_gem_objective() {
+    let mut cg = build(TWO_GEMS_APART, 5);
+    assert_eq!(
+        cg.objective(2, GemObjective::AtLeast(0)).unwrap(),
+        cg.objective(2, GemObjective::None).unwrap()
+    );
+}
+
+#[test]
+fn subset_requires_only_the_listed_gems() {
+    let mut cg = build(TWO_GEMS_APART, 5);
+    let one = GemObjective::Subset(vec![pos(0, 2)]);
+    let both = GemObjective::Subset(vec![pos(0, 0), pos(0, 2)]);
+    let (clauses, assumptions) = cg.generate(3, SolveMode::Standard, one).unwrap();
+    let model = sat::solve(&clauses, &assumptions).unwrap();
+    let plan = cg.decode_plan(&model, 3).unwrap();
+    assert_eq!(plan[0], [Action::East]);
+    let (clauses, assumptions) = cg.generate(3, SolveMode::Standard, both.clone()).unwrap();
+    assert!(!sat::is_sat(&clauses, &assumptions));
+    let (clauses, assumptions) = cg.generate(5, SolveMode::Standard, both).unwrap();
+    assert!(sat::is_sat(&clauses, &assumptions));
+}
+
+#[rstest]
+#[case(0, true)]
+#[case(1, false)]
+fn by_agent_requires_the_given_collector(#[case] agent: usize, #[case] sat: bool) {
+    let mut cg = build(SHARED_GEM, 3);
+    let objective = GemObjective::ByAgent(vec![(pos(0, 1), agent)]);
+    let (clauses, assumptions) = cg.generate(3, SolveMode::Standard, objective).unwrap();
+    assert_eq!(sat::is_sat(&clauses, &assumptions), sat);
+}
+
+#[test]
+fn delta_streams_keep_their_gem_objective() {
+    let mut cg = build(TWO_GEMS_APART, 5);
+    let mut stream = cg
+        .start_delta_stream(SolveMode::Standard, GemObjective::AtLeast(2))
+        .unwrap();
+    let mut clauses = Vec::new();
+    for t in 3..=5 {
+        let (suffix, assumptions) = stream.advance_to(&mut cg, t);
+        clauses.extend(suffix);
+        assert_eq!(sat::is_sat(&clauses, &assumptions), t == 5, "horizon {t}");
+    }
+}
+
+#[test]
+fn unsat_core_names_the_gem_objective() {
+    let mut cg = build(SHARED_GEM, 3);
+    let objective = GemObjective::ByAgent(vec![(pos(0, 1), 1)]);
+    let (clauses, assumptions, explanation) = cg
+        .generate_explained(3, SolveMode::Standard, objective)
+        .unwrap();
+    let core = sat::minimal_core(&clauses, &assumptions).unwrap();
+    // Positions too far from an exit are pruned, so the gem objectives conflict with the time
+    // left rather than with the goals of the agents.
+    assert_eq!(
+        explanation.reasons(&core),
+        [UnsatReason::GemCollector {
+            pos: pos(0, 1),
+            agent: 1
+        }]
+    );
+
+    let mut cg = build(TWO_GEMS_APART, 3);
+    let (clauses, assumptions, explanation) = cg
+        .generate_explained(3, SolveMode::Standard, GemObjective::AtLeast(2))
+        .unwrap();
+    let core = sat::minimal_core(&clauses, &assumptions).unwrap();
+    assert_eq!(explanation.reasons(&core), [UnsatReason::GemCount(2)]);
+}
+
+#[rstest]
+#[case(GemObjective::Subset(vec![pos(1, 0)]), "there is no gem at (1, 0)")]
+#[case(GemObjective::ByAgent(vec![(pos(0, 0), 1)]), "the world only has 1 agents")]
+fn gem_objectives_must_fit_the_world(#[case] objective: GemObjective, #[case] reason: &str) {
+    let mut cg = build(TWO_GEMS_APART, 3);
+    assert!(cg.check_gem_objective(&GemObjective::AtLeast(5)).is_ok());
+    match cg.check_gem_objective(&objective) {
+        Err(SolverError::InvalidGemObjective { reason: actual }) => assert_eq!(actual, reason),
+        other => panic!("expected an invalid gem objective, got {other:?}"),
+    }
+    let invalid = |result| matches!(result, Err(SolverError::InvalidGemObjective { .. }));
+    assert!(invalid(
+        cg.generate(3, SolveMode::Standard, objective.clone())
+            .map(drop)
+    ));
+    assert!(invalid(
+        cg.generate_explained(3, SolveMode::Standard, objective.clone())
+            .map(drop)
+    ));
+    assert!(invalid(
+        cg.start_delta_stream(SolveMode::Standard, objective.clone())
+            .map(drop)
+    ));
+    assert!(invalid(cg.objective(3, objective).map(drop)));
+}
+
+/// Generators iterate over hash sets, so the formulas only match up to the order of the clauses
+/// and the numbering of the variables.
+#[rstest]
+#[case(SolveMode::Standard, GemObjective::All)]
+#[case(SolveMode::NoCooperation, GemObjective::None)]
+fn from_the_initial_state_is_new(#[case] mode: SolveMode, #[case] gems: GemObjective) {
+    let world = World::get_level(6).unwrap();
+    let mut new = ClauseGenerator::new(&world, 24);
+    let mut from_state = ClauseGenerator::from_state(&world, &world.get_state(), 24).unwrap();
+    assert_eq!(
+        new.solution_lower_bound(),
+        from_state.solution_lower_bound()
+    );
+    for t in [new.solution_lower_bound(), 24] {
+        let (clauses, assumptions) = new.generate(t, mode, gems.clone()).unwrap();
+        let (state_clauses, state_assumptions) =
+            from_state.generate(t, mode, gems.clone()).unwrap();
+        assert_eq!(clauses.len(), state_clauses.len());
+        assert_eq!(assumptions.len(), state_assumptions.len());
+        assert_eq!(
+            sat::is_sat(&clauses, &assumptions),
+            sat::is_sat(&state_clauses, &state_assumptions)
+        );
+    }
+}
+
+/// After collecting the gem on its right, the agent only needs the other one.
+#[test]
+fn from_state_starts_from_the_agents_and_drops_collected_gems() {
+    let mut world = World::try_from(TWO_GEMS_APART).unwrap();
+    world.reset();
+    world.step(&[Action::East]).unwrap();
+    let state = world.get_state();
+    assert_eq!(state.gems_collected, [false, true]);
+    let mut cg = ClauseGenerator::from_state(&world, &state, 5).unwrap();
+    assert_eq!(cg.solution_lower_bound(), 2);
+
+    let (clauses, assumptions) = cg
+        .generate(2, SolveMode::Standard, GemObjective::AtLeast(1))
+        .unwrap();
+    let model = sat::solve(&clauses, &assumptions).unwrap();
+    assert_eq!(cg.count_gems(&model, 2), 1);
+    for actions in cg.decode_plan(&model, 2).unwrap() {
+        world.step(&actions).unwrap();
+    }
+    assert_eq!(world.n_agents_arrived(), 1);
+    for objective in [
+        GemObjective::All,
+        GemObjective::AtLeast(2),
+        GemObjective::Subset(vec![pos(0, 0), pos(0, 2)]),
+    ] {
+        for (t, sat) in [(3, false), (4, true)] {
+            let (clauses, assumptions) = cg
+                .generate(t, SolveMode::Standard, objective.clone())
+                .unwrap();
+            assert_eq!(
+                sat::is_sat(&clauses, &assumptions),
+                sat,
+                "{objective:?} at {t}"
+            );
+        }
+    }
+}
+
+#[test]
+fn from_state_keeps_arrived_agents_on_their_exit() {
+    let world = World::try_from(SHARED_GEM).unwrap();
+    let state = WorldState::new_alive(vec![pos(1, 0), pos(0, 3)], vec![false]);
+    let mut cg = ClauseGenerator::from_state(&world, &state, 5).unwrap();
+    let (clauses, assumptions) = cg
+        .generate(4, SolveMode::Standard, GemObjective::All)
+        .unwrap();
+    assert!(!sat::is_sat(&clauses, &assumptions));
+    let (clauses, assumptions) = cg
+        .generate(5, SolveMode::Standard, GemObjective::All)
+        .unwrap();
+    let model = sat::solve(&clauses, &assumptions).unwrap();
+    let plan = cg.decode_plan(&model, 5).unwrap();
+    assert!(plan.iter().all(|actions| actions[0] == Action::Stay));
+}
+
+#[rstest]
+#[case(WorldState::new_alive(vec![pos(4, 1)], vec![false, false]))]
+#[case(WorldState::new_alive(vec![pos(0, 1)], vec![false]))]
+#[case(WorldState { agents_positions: vec![pos(0, 1)], gems_collected: vec![false, false], agents_alive: vec![false] })]
+fn from_state_rejects_invalid_states(#[case] state: WorldState) {
+    let world = World::try_from(TWO_GEMS_APART).unwrap();
+    assert!(matches!(
+        ClauseGenerator::from_state(&world, &state, 5),
+        Err(SolverError::InvalidState { .. })
+    ));
+}
+
+/// One agent that starts either next to the exit or behind a wall.
+const WALLED_START: &str = r#"
+world_string = "X . @ ."
+[[agents]]
+start_positions = [{ i = 0, j = 1 }, { i = 0, j = 3 }]
+"#;
+
+fn min_horizon(cg: &mut ClauseGenerator, t_max: usize) -> Option<usize> {
+    (0..=t_max).find(|&t| {
+        let (clauses, assumptions) = cg.generate(t, SolveMode::Standard, true).unwrap();
+        sat::is_sat(&clauses, &assumptions)
+    })
+}
+
+#[test]
+fn with_starts_plans_from_each_start_assignment() {
+    let world = World::try_from(WALLED_START).unwrap();
+    let cg = ClauseGenerator::new(&world, 3);
+    let horizons = world
+        .start_assignments()
+        .map(|starts| min_horizon(&mut cg.with_starts(&starts).unwrap(), 3))
+        .collect::<Vec<_>>();
+    assert_eq!(horizons, [Some(1), None]);
+}
+
+#[rstest]
+fn with_starts_matches_a_generator_built_from_these_starts(#[values(2, 4, 6)] level: usize) {
+    let world = World::get_level(level).unwrap();
+    let mut starts = world.starts();
+    starts.reverse();
+    let t_max = 20;
+    let mut shared = ClauseGenerator::new(&world, t_max)
+        .with_starts(&starts)
+        .unwrap();
+    let state = WorldState::new_alive(starts, vec![false; world.n_gems()]);
+    let mut built = ClauseGenerator::from_state(&world, &state, t_max).unwrap();
+    assert_eq!(shared.solution_lower_bound(), built.solution_lower_bound());
+    assert_eq!(
+        min_horizon(&mut shared, t_max),
+        min_horizon(&mut built, t_max)
+    );
+}
+
+#[rstest]
+#[case(vec![pos(0, 1), pos(0, 3)], "expected 1 start positions, got 2")]
+#[case(vec![pos(0, 2)], "(0, 2) is not a walkable tile")]
+#[case(vec![pos(3, 3)], "(3, 3) is not a walkable tile")]
+fn with_starts_rejects_invalid_starts(#[case] starts: Vec<Position>, #[case] reason: &str) {
+    let world = World::try_from(WALLED_START).unwrap();
+    match ClauseGenerator::new(&world, 3).with_starts(&starts) {
+        Err(SolverError::InvalidState { reason: actual }) => assert_eq!(actual, reason),
+        Err(error) => panic!("expected an invalid state, got {error}"),
+        Ok(_) => panic!("expected an invalid state"),
+    }
+}
+
+/// Agent 0 reaches its exit one step earlier if it may follow agent 1.
+const FOLLOW_THE_LEADER: &str = "S0 S1 . X\n@ @ . X";
+
+fn solve_with_rule(map: &str, rule: ConflictRule, t_max: usize) -> Option<Vec<Vec<Action>>> {
+    let mut world = World::try_from(map).unwrap();
+    world.set_conflict_rule(rule);
+    let mut cg = ClauseGenerator::new(&world, t_max);
+    (0..=t_max).find_map(|t| {
+        let (clauses, assumptions) = cg.generate(t, SolveMode::Standard, false).unwrap();
+        let model = sat::solve(&clauses, &assumptions)?;
+        Some(cg.decode_plan(&model, t).unwrap())
+    })
+}
+
+/// Whether every agent arrives when `plan` is played under `rule`.
+fn replays_under(map: &str, rule: ConflictRule, plan: &[Vec<Action>]) -> bool {
+    let mut world = World::try_from(map).unwrap();
+    world.set_conflict_rule(rule);
+    plan.iter().all(|actions| world.step(actions).is_ok())
+        && world.n_agents_arrived() == world.n_agents()
+}
+
+#[rstest]
+#[case(ConflictRule::NoFollowing, 4)]
+#[case(ConflictRule::AllowFollowing, 3)]
+#[case(ConflictRule::Priority, 4)]
+fn the_encoding_follows_the_conflict_rule(#[case] rule: ConflictRule, #[case] horizon: usize) {
+    let plan = solve_with_rule(FOLLOW_THE_LEADER, rule, 6).unwrap();
+    assert_eq!(plan.len(), horizon);
+    assert!(replays_under(FOLLOW_THE_LEADER, rule, &plan));
+    if rule.allows_following() {
+        assert!(!replays_under(
+            FOLLOW_THE_LEADER,
+            ConflictRule::NoFollowing,
+            &plan
+        ));
+    }
+}
+
+/// Under every rule, the shortest plans of the levels are valid in the simulator, and allowing
+/// following never makes them longer.
+#[rstest]
+fn shortest_plans_replay_under_every_conflict_rule(#[values(1, 2, 3, 4, 5)] level: usize) {
+    let map = World::get_level(level).unwrap().world_string();
+    let mut lengths = Vec::new();
+    for rule in [
+        ConflictRule::NoFollowing,
+        ConflictRule::AllowFollowing,
+        ConflictRule::Priority,
+    ] {
+        let plan = solve_with_rule(&map, rule, 20).unwrap();
+        assert!(replays_under(&map, rule, &plan), "{rule:?}");
+        lengths.push(plan.len());
+    }
+    assert!(lengths[1] <= lengths[0]);
+    assert_eq!(lengths[2], lengths[0]);
+}
+
+#[test]
+fn allow_following_forbids_swaps() {
+    let mut world = World::try_from(FOLLOW_THE_LEADER).unwrap();
+    world.set_conflict_rule(ConflictRule::AllowFollowing);
+    let mut cg = ClauseGenerator::new(&world, 4);
+    let (clauses, assumptions) = cg.generate(4, SolveMode::Standard, false).unwrap();
+    let stats = cg.encoding_stats();
+    assert!(stats["no_swap"].clauses > 0);
+    assert!(!stats.contains_key("no_following_conflict"));
+    // Agent 0 at (0, 1) and agent 1 at (0, 0) at step 1 would be a swap.
+    let swapped = [
+        cg.literal(&VarKey::agent(0, pos(0, 1), 1)).unwrap(),
+        cg.literal(&VarKey::agent(1, pos(0, 0), 1)).unwrap(),
+    ];
+    let mut clauses = clauses;
+    clauses.extend(swapped.map(|lit| vec![lit]));
+    assert!(!sat::is_sat(&clauses, &assumptions));
+}
+
+/// Every at-most-one encoding solves the same horizons as the default one, up to the first two
+/// satisfiable horizons.
+#[rstest]
+fn every_encoding_solves_the_same_horizons(
+    #[values(1, 2, 3)] level: usize,
+    #[values(
+        AtMostOneEncoding::Pairwise,
+        AtMostOneEncoding::Sequential,
+        AtMostOneEncoding::Commander,
+        AtMostOneEncoding::Product,
+        AtMostOneEncoding::Binary
+    )]
+    encoding: AtMostOneEncoding,
+) {
+    let world = World::get_level(level).unwrap();
+    let config = EncodingConfig {
+        exactly_one_position: encoding,
+        no_overlap: encoding,
+    };
+    let mut default = ClauseGenerator::new(&world, 20);
+    let mut selected = ClauseGenerator::new(&world, 20).with_encoding(config);
+    let mut n_sat = 0;
+    for t in default.solution_lower_bound()..=20 {
+        let (clauses, assumptions) = default.generate(t, SolveMode::Standard, false).unwrap();
+        let expected = sat::is_sat(&clauses, &assumptions);
+        let (clauses, assumptions) = selected.generate(t, SolveMode::Standard, false).unwrap();
+        let model = sat::solve(&clauses, &assumptions);
+        assert_eq!(model.is_some(), expected, "horizon {t}");
+        if let Some(model) = model {
+            assert!(selected.decode_plan(&model, t).is_ok());
+            n_sat += 1;
+            if n_sat == 2 {
+                break;
+            }
+        }
+    }
+}
+
+/// The stats cover every clause of a standard formula except the goals, and every variable.
+#[test]
+fn encoding_stats_count_the_world_rules() {
+    let world = World::get_level(6).unwrap();
+    let mut cg = ClauseGenerator::new(&world, 24);
+    let (clauses, _) = cg.generate(24, SolveMode::Standard, false).unwrap();
+    let stats = cg.encoding_stats();
+    assert!(stats["exactly_one_position"].clauses > 0 && stats["lasers"].clauses > 0);
+    let n_clauses = stats.values().map(|family| family.clauses).sum::<usize>();
+    assert_eq!(n_clauses + world.n_agents(), clauses.len());
+    let n_vars = stats.values().map(|family| family.variables).sum::<usize>();
+    assert_eq!(n_vars, cg.n_vars());
+}
+
+#[test]
+fn encodings_trade_clauses_for_variables() {
+    let world = World::get_level(6).unwrap();
+    let stats = |encoding| {
+        let config = EncodingConfig {
+            exactly_one_position: encoding,
+            ..EncodingConfig::default()
+        };
+        let mut cg = ClauseGenerator::new(&world, 24).with_encoding(config);
+        cg.generate(24, SolveMode::Standard, false).unwrap();
+        cg.encoding_stats()["exactly_one_position"]
+    };
+    let pairwise = stats(AtMostOneEncoding::Pairwise);
+    let auto = stats(AtMostOneEncoding::Auto);
+    let sequential = stats(AtMostOneEncoding::Sequential);
+    // The automatic selection takes the encoding with fewer clauses for each agent and step.
+    assert!(auto.clauses < pairwise.clauses.min(sequential.clauses));
+    assert!(pairwise.clauses > sequential.clauses);
+    assert!(pairwise.variables < auto.variables && auto.variables < sequential.variables);
+    assert!(stats(AtMostOneEncoding::Binary).clauses < pairwise.clauses);
+}
+
+#[test]
+#[should_panic(expected = "before generating clauses")]
+fn the_encoding_cannot_change_after_generating_clauses() {
+    let mut cg = build("S0 . X", 2);
+    cg.generate(2, SolveMode::Standard, false).unwrap();
+    let _ = cg.with_encoding(EncodingConfig::default());
+}
+
 // ─── beam_activation ─────────────────────────────────────────────────────────
 
+/// The beam of agent 1 cuts the only path of agent 0, and agent 1 cannot reach it to block it.
+const CUT_BY_FOREIGN_BEAM: &str = "S0 @ S1\n. L1W .\n. @ .\nX @ X";
+
+/// The solver agrees with the simulator when the beam is disabled or recoloured after parsing.
+#[rstest]
+#[case::enabled(true, 1, None)]
+#[case::disabled(false, 1, Some(3))]
+#[case::recoloured(true, 0, Some(3))]
+fn lasers_are_encoded_in_their_current_state(
+    #[case] enabled: bool,
+    #[case] colour: usize,
+    #[case] horizon: Option<usize>,
+) {
+    let mut world = World::try_from(CUT_BY_FOREIGN_BEAM).unwrap();
+    let mut source = world.source_at_mut(pos(1, 1)).unwrap();
+    if !enabled {
+        source.disable();
+    }
+    source.set_agent_id(colour);
+    drop(source);
+    let mut cg = ClauseGenerator::new(&world, 6);
+    let solved = (0..=6).find(|&t| {
+        let (clauses, assumptions) = cg.generate(t, SolveMode::Standard, false).unwrap();
+        sat::is_sat(&clauses, &assumptions)
+    });
+    assert_eq!(solved, horizon);
+
+    world.reset();
+    // Once agent 0 dies, it can only stay and the episode stops.
+    for _ in 0..3 {
+        if world.step(&[Action::South, Action::South]).is_err() {
+            break;
+        }
+    }
+    assert_eq!(world.n_agents_arrived() == 2, horizon.is_some());
+}
+
+#[test]
+fn disabled_lasers_allow_no_cooperation() {
+    let mut world = World::try_from(CUT_BY_FOREIGN_BEAM).unwrap();
+    world.source_at_mut(pos(1, 1)).unwrap().disable();
+    let mut cg = ClauseGenerator::new(&world, 6);
+    assert_eq!(
+        cg.effective_mode(SolveMode::NoCooperation),
+        SolveMode::Standard
+    );
+    let (clauses, assumptions) = cg
+        .generate(6, SolveMode::RequireCooperation, false)
+        .unwrap();
+    assert!(!sat::is_sat(&clauses, &assumptions));
+    let spec =
+        SolveSpec::from(SolveMode::Standard).with_constraint(CooperationConstraint::ForbidLaser(0));
+    assert!(cg.check_spec(&spec).is_ok());
+}
+
 /// For the first blockable beam tile, `beam_activation` encodes `active ↔ ¬agent_var`
 /// as two clauses: `[-active, -agent]` and `[active, agent]`.
 #[test]
@@ -529,7 +1040,7 @@ fn test_beam_activation_first_tile_encodes_biconditional() {
     let world = World::try_from(". X\nS0 .\nL0N .").expect("Failed to parse world");
     let laser_id = world.sources().collect::<Vec<_>>()[0].1.laser_id();
     let mut cg = ClauseGenerator::new(&world, 10);
-    let (clauses, _) = cg.generate(2, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(2, SolveMode::Standard, false).unwrap();
 
     // At t=2 agent can reach (1,0): the first (and only) blockable tile.
     let active = cg.literal(&VarKey::laser(laser_id, pos(1, 0), 2));
@@ -559,7 +1070,7 @@ fn test_beam_activation_first_tile_encodes_biconditional() {
 fn test_laser_blocking_same_colour() {
     let world = World::try_from(".   X\nS0  .\nL0N .").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 10);
-    let (clauses, _) = generator.generate(2, SolveMode::Standard, false);
+    let (clauses, _) = generator.generate(2, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
 
     assert_eq!(world.sources().count(), 1);
@@ -581,7 +1092,9 @@ fn test_help_event_clauses_track_blocked_beam_usage() {
     )
     .expect("Failed to parse world");
     let mut cg = ClauseGenerator::new(&world, 4);
-    let (clauses, _) = cg.generate(2, SolveMode::NoAsymmetricCooperation, false);
+    let (clauses, _) = cg
+        .generate(2, SolveMode::NoAsymmetricCooperation, false)
+        .unwrap();
 
     let help = cg
         .literal(&VarKey::Help {
@@ -619,7 +1132,7 @@ fn test_no_step_on_active_laser_binary_clause() {
     let world = World::try_from("L0S . X\n.   S1 X\nS0  . .").expect("Failed to parse world");
     let laser_id = world.sources().next().unwrap().1.laser_id();
     let mut cg = ClauseGenerator::new(&world, 2);
-    let (clauses, _) = cg.generate(1, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(1, SolveMode::Standard, false).unwrap();
 
     let agent_1_at_10 = cg.literal(&VarKey::agent(1, pos(1, 0), 1));
     let laser_at_10 = cg.literal(&VarKey::laser(laser_id, pos(1, 0), 1));
@@ -672,7 +1185,7 @@ fn assert_no_cooperation_does_not_generate_beam_positions(
     // 2) Verify that all agents are assumed not to walk on any foreign colour laser tile
     for agent_id in 0..world.n_agents() {
         for (pos, laser) in world.lasers() {
-            if agent_id == laser.agent_id() {
+            if agent_id == laser.agent_id(world.beams()) {
                 continue;
             }
             for t in 0..=cg.t_max() {
@@ -703,7 +1216,7 @@ fn test_no_cooperation_forbids_non_owner_on_blockable_downstream_beam_tile() {
     .expect("Failed to parse world");
     let t_max = 3;
     let mut cg = ClauseGenerator::new(&world, t_max);
-    let (clauses, assumptions) = cg.generate(t_max, SolveMode::NoCooperation, false);
+    let (clauses, assumptions) = cg.generate(t_max, SolveMode::NoCooperation, false).unwrap();
     assert_no_cooperation_does_not_generate_beam_positions(&cg, &world, clauses, assumptions);
 }
 
@@ -723,8 +1236,8 @@ fn test_no_cooperation_after_standard_generation_forbids_same_downstream_beam_ti
     .expect("Failed to parse world");
     let t_max = 9;
     let mut cg = ClauseGenerator::new(&world, t_max);
-    let _ = cg.generate(t_max, SolveMode::Standard, false);
-    let (clauses, assumptions) = cg.generate(t_max, SolveMode::NoCooperation, false);
+    let _ = cg.generate(t_max, SolveMode::Standard, false).unwrap();
+    let (clauses, assumptions) = cg.generate(t_max, SolveMode::NoCooperation, false).unwrap();
     assert_no_cooperation_does_not_generate_beam_positions(&cg, &world, clauses, assumptions);
 }
 
@@ -737,7 +1250,7 @@ fn test_unblockable_beam_tile_generates_unit_clause() {
     let world = World::try_from("L0E .  .  X\nS0  @  S1 X").expect("Failed to parse world");
     let laser_id = world.sources().next().unwrap().1.laser_id();
     let mut cg = ClauseGenerator::new(&world, 4);
-    let (clauses, _) = cg.generate(2, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(2, SolveMode::Standard, false).unwrap();
 
     // The beam tile (0,2) is downstream and unreachable by agent 0 → constant-active → no var.
     assert!(
@@ -759,7 +1272,7 @@ fn test_unblockable_beam_tile_generates_unit_clause() {
 fn test_laser_blocks_different_colour_agent() {
     let world = World::try_from("L0S . X\n.   S1 X\nS0  . .").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 2);
-    let (clauses, _) = generator.generate(1, SolveMode::Standard, false);
+    let (clauses, _) = generator.generate(1, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
     assert_eq!(world.n_agents(), 2);
     let sources: Vec<_> = world.sources().collect();
@@ -771,7 +1284,7 @@ fn test_laser_blocks_different_colour_agent() {
 fn test_unblockable_constant_active_laser() {
     let world = World::try_from("L0E .  .  X\nS0  @  S1 X").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 4);
-    let (clauses, _) = generator.generate(2, SolveMode::Standard, false);
+    let (clauses, _) = generator.generate(2, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
 
     assert!(!generator.exists(&VarKey::Laser {
@@ -789,7 +1302,7 @@ fn test_two_lasers_stop_at_each_other() {
     // L0E at (0,0) → beam (0,1); L1W at (0,2) → beam (0,1). Neither reaches past the other's source.
     let world = World::try_from("L0E . L1W X X\nS0  . S1  . .").expect("Failed to parse world");
     let mut generator = ClauseGenerator::new(&world, 10);
-    let (clauses, _) = generator.generate(2, SolveMode::Standard, false);
+    let (clauses, _) = generator.generate(2, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
 
     let sources: Vec<_> = world.sources().collect();
@@ -827,7 +1340,7 @@ fn test_multiple_same_colour_same_direction_lasers_get_independent_beams() {
     let world = World::try_from(".  L0S .  L0S .\nS0 .   .  .   S1\nX  .   .  .   X")
         .expect("Failed to parse world");
     let mut cg = ClauseGenerator::new(&world, 10);
-    let (clauses, _) = cg.generate(3, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(3, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
 
     let sources: Vec<_> = world.sources().collect();
@@ -879,7 +1392,7 @@ fn test_multiple_same_colour_same_direction_lasers_get_independent_beams() {
 #[test]
 fn test_opt2_start_tile_pruned_at_t1_only() {
     let mut cg = build("S0 S1 X X", 5);
-    cg.generate(2, SolveMode::Standard, false);
+    cg.generate(2, SolveMode::Standard, false).unwrap();
 
     // At t=1: each agent's other-agent start is pruned.
     assert!(
@@ -924,7 +1437,7 @@ fn test_opt3_first_beam_tile_pruned_for_non_owner() {
     let world = World::try_from("L0S . X\n. S1 X\nS0 . .").expect("Failed to parse world");
     let laser_id = world.sources().next().unwrap().1.laser_id();
     let mut cg = ClauseGenerator::new(&world, 10);
-    cg.generate(3, SolveMode::Standard, false);
+    cg.generate(3, SolveMode::Standard, false).unwrap();
 
     // Non-owner (agent 1) must never have a variable at the first beam tile (1,0).
     for t in 0..=3 {
@@ -962,7 +1475,7 @@ fn test_crossing_lasers_keep_independent_variables() {
     )
     .expect("Failed to parse world");
     let mut cg = ClauseGenerator::new(&world, 20);
-    let (clauses, _) = cg.generate(10, SolveMode::Standard, false);
+    let (clauses, _) = cg.generate(10, SolveMode::Standard, false).unwrap();
     assert!(!clauses.is_empty());
 
     // At each crossing position there must be at least two distinct laser variables
@@ -1014,7 +1527,7 @@ X X . @ .
     let mut cg = ClauseGenerator::new(&world, t_max);
     // Drive the incremental solve exactly like the Python solver: generate at every horizon.
     for t in cg.solution_lower_bound()..=t_max {
-        cg.generate(t, SolveMode::Standard, false);
+        cg.generate(t, SolveMode::Standard, false).unwrap();
     }
     for agent in 0..2 {
         for t in 0..=t_max {
@@ -1052,7 +1565,9 @@ fn help_clauses_include_beneficiary_to_help_implications() {
     )
     .expect("failed to parse world");
     let mut cg = ClauseGenerator::new(&world, 10);
-    let (clauses, _) = cg.generate(10, SolveMode::NoAsymmetricCooperation, false);
+    let (clauses, _) = cg
+        .generate(10, SolveMode::NoAsymmetricCooperation, false)
+        .unwrap();
     let mut produced_any = false;
 
     for clause in clauses {
@@ -1096,7 +1611,9 @@ fn asymmetric_world_generates_forbid_clauses_and_assumptions() {
     .expect("failed to parse world");
     let t_max = 10;
     let mut cg = ClauseGenerator::new(&world, t_max);
-    let (clauses, assumptions) = cg.generate(10, SolveMode::NoAsymmetricCooperation, false);
+    let (clauses, assumptions) = cg
+        .generate(10, SolveMode::NoAsymmetricCooperation, false)
+        .unwrap();
 
     assert!(
         !clauses.is_empty(),
@@ -1148,7 +1665,9 @@ fn no_convergence_mode_generates_pairwise_blocking_clauses() {
     let horizon = 6;
     let mut generator = ClauseGenerator::new(&world, horizon);
 
-    generator.generate(horizon, SolveMode::Standard, false);
+    generator
+        .generate(horizon, SolveMode::Standard, false)
+        .unwrap();
     for helper in 0..world.n_agents() {
         for beneficiary in 0..world.n_agents() {
             assert!(!generator.exists(&VarKey::PairwiseHelp {
@@ -1159,11 +1678,13 @@ fn no_convergence_mode_generates_pairwise_blocking_clauses() {
         }
     }
 
-    let (clauses, assumptions) = generator.generate(
-        horizon,
-        SolveMode::no_convergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (clauses, assumptions) = generator
+        .generate(
+            horizon,
+            SolveMode::no_convergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
     assert!(assumptions.is_empty());
 
     let blockers = clauses
@@ -1222,7 +1743,9 @@ fn no_convergence_generator_reuse_does_not_leak_horizons_or_thresholds() {
     .expect("failed to parse convergence world");
     let mut generator = ClauseGenerator::new(&world, 6);
 
-    generator.generate(6, SolveMode::no_convergent_cooperation(2).unwrap(), false);
+    generator
+        .generate(6, SolveMode::no_convergent_cooperation(2).unwrap(), false)
+        .unwrap();
     let large_pairwise = (0..world.n_agents())
         .flat_map(|helper| {
             (0..world.n_agents()).filter_map(move |beneficiary| {
@@ -1237,8 +1760,9 @@ fn no_convergence_generator_reuse_does_not_leak_horizons_or_thresholds() {
         .collect::<Vec<_>>();
     assert!(!large_pairwise.is_empty());
 
-    let (small_clauses, _) =
-        generator.generate(4, SolveMode::no_convergent_cooperation(2).unwrap(), false);
+    let (small_clauses, _) = generator
+        .generate(4, SolveMode::no_convergent_cooperation(2).unwrap(), false)
+        .unwrap();
     assert!(large_pairwise.iter().all(|large| {
         !small_clauses
             .iter()
@@ -1256,8 +1780,9 @@ fn no_convergence_generator_reuse_does_not_leak_horizons_or_thresholds() {
     }));
 
     let variables_after_two = generator.n_vars();
-    let (threshold_three, _) =
-        generator.generate(4, SolveMode::no_convergent_cooperation(3).unwrap(), false);
+    let (threshold_three, _) = generator
+        .generate(4, SolveMode::no_convergent_cooperation(3).unwrap(), false)
+        .unwrap();
     assert_eq!(generator.n_vars(), variables_after_two);
     assert!(!threshold_three.iter().any(|clause| {
         clause.len() == 3
@@ -1307,7 +1832,9 @@ fn no_divergence_mode_generates_outgoing_blocking_clauses() {
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
 
-    generator.generate(horizon, SolveMode::Standard, false);
+    generator
+        .generate(horizon, SolveMode::Standard, false)
+        .unwrap();
     for helper in 0..world.n_agents() {
         for beneficiary in 0..world.n_agents() {
             assert!(!generator.exists(&VarKey::PairwiseHelp {
@@ -1318,11 +1845,13 @@ fn no_divergence_mode_generates_outgoing_blocking_clauses() {
         }
     }
 
-    let (clauses, assumptions) = generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (clauses, assumptions) = generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
     assert!(assumptions.is_empty());
 
     let blockers = clauses
@@ -1361,11 +1890,13 @@ fn no_divergence_mode_prunes_impossible_help_pairs() {
     let world = divergent_world();
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
+    generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
 
     for helper in 1..world.n_agents() {
         for beneficiary in 0..world.n_agents() {
@@ -1397,16 +1928,20 @@ fn interleaved_convergence_and_divergence_keep_their_orientation() {
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
 
-    let (divergence, _) = generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
-    let (convergence, _) = generator.generate(
-        horizon,
-        SolveMode::no_convergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (divergence, _) = generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
+    let (convergence, _) = generator
+        .generate(
+            horizon,
+            SolveMode::no_convergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
 
     let shared_helpers = |clauses: &[Clause]| {
         clauses
@@ -1437,7 +1972,9 @@ fn no_divergence_generator_reuse_does_not_leak_horizons() {
     let world = divergent_world();
     let mut generator = ClauseGenerator::new(&world, 6);
 
-    generator.generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false);
+    generator
+        .generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false)
+        .unwrap();
     let large_pairwise = (0..world.n_agents())
         .filter_map(|beneficiary| {
             generator.literal(&VarKey::PairwiseHelp {
@@ -1449,8 +1986,9 @@ fn no_divergence_generator_reuse_does_not_leak_horizons() {
         .collect::<Vec<_>>();
     assert!(!large_pairwise.is_empty());
 
-    let (small_clauses, _) =
-        generator.generate(4, SolveMode::no_divergent_cooperation(2).unwrap(), false);
+    let (small_clauses, _) = generator
+        .generate(4, SolveMode::no_divergent_cooperation(2).unwrap(), false)
+        .unwrap();
     assert!(large_pairwise.iter().all(|large| {
         !small_clauses
             .iter()
@@ -1458,11 +1996,13 @@ fn no_divergence_generator_reuse_does_not_leak_horizons() {
             .any(|literal| literal.unsigned_abs() == *large as u32)
     }));
 
-    let (large_again, _) =
-        generator.generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false);
+    let (large_again, _) = generator
+        .generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false)
+        .unwrap();
     let mut fresh_generator = ClauseGenerator::new(&world, 6);
-    let (fresh, _) =
-        fresh_generator.generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false);
+    let (fresh, _) = fresh_generator
+        .generate(6, SolveMode::no_divergent_cooperation(2).unwrap(), false)
+        .unwrap();
     assert_eq!(
         semantic_clauses(&generator, &large_again),
         semantic_clauses(&fresh_generator, &fresh)
@@ -1637,11 +2177,12 @@ fn assert_no_cooperation_variables(generator: &ClauseGenerator) {
 /// @ai-generated
 fn assert_mode_is_shortcut(world: &World, horizon: usize, mode: SolveMode, collect_gems: bool) {
     let mut shortcut = ClauseGenerator::new(world, horizon);
-    let (clauses, assumptions) = shortcut.generate(horizon, mode, collect_gems);
+    let (clauses, assumptions) = shortcut.generate(horizon, mode, collect_gems).unwrap();
 
     let mut reference = ClauseGenerator::new(world, horizon);
-    let (expected, expected_assumptions) =
-        reference.generate(horizon, SolveMode::Standard, collect_gems);
+    let (expected, expected_assumptions) = reference
+        .generate(horizon, SolveMode::Standard, collect_gems)
+        .unwrap();
 
     assert!(
         assumptions.is_empty(),
@@ -1700,9 +2241,12 @@ fn shortcut_preserves_gem_objectives() {
     assert_mode_is_shortcut(&world, horizon, SolveMode::NoFullyCoupledCooperation, true);
 
     let mut generator = ClauseGenerator::new(&world, horizon);
-    let (with_gems, _) = generator.generate(horizon, SolveMode::NoFullyCoupledCooperation, true);
-    let (without_gems, _) =
-        generator.generate(horizon, SolveMode::NoFullyCoupledCooperation, false);
+    let (with_gems, _) = generator
+        .generate(horizon, SolveMode::NoFullyCoupledCooperation, true)
+        .unwrap();
+    let (without_gems, _) = generator
+        .generate(horizon, SolveMode::NoFullyCoupledCooperation, false)
+        .unwrap();
     assert!(
         with_gems.len() > without_gems.len(),
         "the gem clauses must still be appended to a shortcut query"
@@ -1746,11 +2290,13 @@ fn divergence_does_not_shortcut_with_a_single_colour() {
     let world = divergent_world();
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    let (clauses, _) = generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (clauses, _) = generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
 
     assert_has_pairwise_summary(&generator, world.n_agents(), horizon);
     assert!(
@@ -1818,11 +2364,13 @@ fn sequential_cooperation_generates_progress_variables_with_two_colours() {
     let world = mutual_world();
     let horizon = 8;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    generator.generate(
-        horizon,
-        SolveMode::no_sequential_cooperation(2).unwrap(),
-        false,
-    );
+    generator
+        .generate(
+            horizon,
+            SolveMode::no_sequential_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
 
     let keys = allocated_keys(&generator);
     assert!(
@@ -1844,7 +2392,9 @@ fn interdependence_generates_progress_variables_at_the_boundary() {
     let world = mutual_world();
     let horizon = 8;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    generator.generate(horizon, SolveMode::no_interdependence(2).unwrap(), false);
+    generator
+        .generate(horizon, SolveMode::no_interdependence(2).unwrap(), false)
+        .unwrap();
 
     assert!(
         allocated_keys(&generator)
@@ -1862,8 +2412,9 @@ fn fully_coupled_generates_its_blocking_clause_when_every_agent_owns_a_laser() {
     let world = mutual_world();
     let horizon = 8;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    let (clauses, assumptions) =
-        generator.generate(horizon, SolveMode::NoFullyCoupledCooperation, false);
+    let (clauses, assumptions) = generator
+        .generate(horizon, SolveMode::NoFullyCoupledCooperation, false)
+        .unwrap();
     assert!(assumptions.is_empty());
 
     for helper in 0..world.n_agents() {
@@ -1917,7 +2468,9 @@ fn no_cooperation_still_assumes_when_a_beam_is_relevant() {
     .expect("failed to parse world");
     let horizon = 5;
     let mut generator = ClauseGenerator::new(&world, horizon);
-    let (_, assumptions) = generator.generate(horizon, SolveMode::NoCooperation, false);
+    let (_, assumptions) = generator
+        .generate(horizon, SolveMode::NoCooperation, false)
+        .unwrap();
     assert!(
         !assumptions.is_empty(),
         "a feasible no-cooperation query must forbid the foreign beam tiles"
@@ -1933,26 +2486,32 @@ fn shortcut_query_does_not_prefill_cooperation_buffers() {
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
 
-    let (impossible, assumptions) = generator.generate(
-        horizon,
-        SolveMode::no_convergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (impossible, assumptions) = generator
+        .generate(
+            horizon,
+            SolveMode::no_convergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
     assert!(assumptions.is_empty());
     assert_no_cooperation_variables(&generator);
 
     let mut reference = ClauseGenerator::new(&world, horizon);
-    let (standard, _) = reference.generate(horizon, SolveMode::Standard, false);
+    let (standard, _) = reference
+        .generate(horizon, SolveMode::Standard, false)
+        .unwrap();
     assert_eq!(
         semantic_clauses(&generator, &impossible),
         semantic_clauses(&reference, &standard)
     );
 
-    let (feasible, _) = generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
+    let (feasible, _) = generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
     assert_has_pairwise_summary(&generator, world.n_agents(), horizon);
     assert!(
         feasible
@@ -1971,23 +2530,736 @@ fn feasible_query_does_not_leak_into_a_later_shortcut() {
     let horizon = 4;
     let mut generator = ClauseGenerator::new(&world, horizon);
 
-    generator.generate(
-        horizon,
-        SolveMode::no_divergent_cooperation(2).unwrap(),
-        false,
-    );
-    let (shortcut, assumptions) = generator.generate(
-        horizon,
-        SolveMode::no_convergent_cooperation(2).unwrap(),
-        false,
-    );
+    generator
+        .generate(
+            horizon,
+            SolveMode::no_divergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
+    let (shortcut, assumptions) = generator
+        .generate(
+            horizon,
+            SolveMode::no_convergent_cooperation(2).unwrap(),
+            false,
+        )
+        .unwrap();
     assert!(assumptions.is_empty());
 
     let mut reference = ClauseGenerator::new(&world, horizon);
-    let (standard, _) = reference.generate(horizon, SolveMode::Standard, false);
+    let (standard, _) = reference
+        .generate(horizon, SolveMode::Standard, false)
+        .unwrap();
     assert_eq!(
         semantic_clauses(&generator, &shortcut),
         semantic_clauses(&reference, &standard),
         "a shortcut query must not return the cooperation clauses of an earlier mode"
     );
 }
+
+/// Pruning useless stays keeps the shortest horizon of every level: it is unsatisfiable one step
+/// before and satisfiable at the known optimum.
+#[rstest]
+#[case(1, 10)]
+#[case(2, 10)]
+#[case(3, 10)]
+#[case(4, 10)]
+#[case(5, 19)]
+#[case(6, 21)]
+fn useless_stay_pruning_keeps_shortest_horizons(#[case] level: usize, #[case] horizon: usize) {
+    let world = World::get_level(level).unwrap();
+    let mut cg = ClauseGenerator::new(&world, horizon);
+    let (clauses, assumptions) = cg
+        .generate(horizon - 1, SolveMode::Standard, false)
+        .unwrap();
+    assert!(!sat::is_sat(&clauses, &assumptions));
+    let (clauses, assumptions) = cg.generate(horizon, SolveMode::Standard, false).unwrap();
+    assert!(sat::is_sat(&clauses, &assumptions));
+}
+
+/// The agent positions of a model, as assumptions that pin down the same plan in another query.
+fn plan_positions(cg: &ClauseGenerator, model: &[Literal]) -> Vec<Literal> {
+    model
+        .iter()
+        .copied()
+        .filter(|&lit| lit > 0 && matches!(cg.engine.pool.key(lit), Some(VarKey::Agent { .. })))
+        .collect()
+}
+
+/// Solve `required` at `horizon` and check the outcome. A plan found for the required mode must
+/// exhibit the profile, i.e. be rejected by `forbidden`, and a plan without the profile must be
+/// rejected by `required`.
+fn assert_required_profile(
+    world: &str,
+    horizon: usize,
+    required: SolveMode,
+    forbidden: SolveMode,
+    expected: bool,
+) {
+    let mut cg = build(world, horizon);
+    let (clauses, assumptions) = cg.generate(horizon, required, false).unwrap();
+    let model = sat::solve(&clauses, &assumptions);
+    assert_eq!(
+        model.is_some(),
+        expected,
+        "{required:?} at horizon {horizon}"
+    );
+    let (forbidden_clauses, forbidden_assumptions) =
+        cg.generate(horizon, forbidden, false).unwrap();
+    if let Some(model) = model {
+        let mut pinned = forbidden_assumptions.clone();
+        pinned.extend(plan_positions(&cg, &model));
+        assert!(
+            !sat::is_sat(&forbidden_clauses, &pinned),
+            "the {required:?} plan does not exhibit the profile"
+        );
+    }
+    if let Some(model) = sat::solve(&forbidden_clauses, &forbidden_assumptions) {
+        let mut pinned = assumptions;
+        pinned.extend(plan_positions(&cg, &model));
+        assert!(
+            !sat::is_sat(&clauses, &pinned),
+            "the {forbidden:?} plan satisfies {required:?}"
+        );
+    }
+}
+
+const SINGLE_LASER_ASYMMETRIC: &str = "
+ @  S0 S1
+L0E .  .
+ @  X  X";
+
+const CONVERGENT_2_TIGHT: &str = "
+ @  S0  @  @  @  S2
+L0E  .  .  .  .  .
+ @   X  @  @  @  .
+L1E  .  .  .  .  .
+ @  S1  @  @  @  .
+ @   X  @  @  @  X";
+
+const DIVERGENT_2_TIGHT: &str = "
+ @   X   X   X  @
+L0E  .   .   .  .
+ @  S0  S1  S2  @";
+
+#[rstest]
+#[case::cooperation(
+    SINGLE_LASER_ASYMMETRIC,
+    6,
+    SolveMode::RequireCooperation,
+    SolveMode::NoCooperation,
+    true
+)]
+#[case::no_laser(
+    "S0 . S1\n. . .\nX . X",
+    6,
+    SolveMode::RequireCooperation,
+    SolveMode::NoCooperation,
+    false
+)]
+#[case::asymmetric(
+    SINGLE_LASER_ASYMMETRIC,
+    6,
+    SolveMode::RequireAsymmetric,
+    SolveMode::NoAsymmetricCooperation,
+    true
+)]
+#[case::mutual_single_colour(
+    SINGLE_LASER_ASYMMETRIC, 6,
+    SolveMode::require_interdependence(2).unwrap(), SolveMode::no_interdependence(2).unwrap(), false
+)]
+#[case::convergence(
+    CONVERGENT_2_TIGHT, 5,
+    SolveMode::require_convergence(2).unwrap(), SolveMode::no_convergent_cooperation(2).unwrap(), true
+)]
+#[case::convergence_3(
+    CONVERGENT_2_TIGHT, 5,
+    SolveMode::require_convergence(3).unwrap(), SolveMode::no_convergent_cooperation(3).unwrap(), false
+)]
+#[case::divergence(
+    DIVERGENT_2_TIGHT, 2,
+    SolveMode::require_divergence(2).unwrap(), SolveMode::no_divergent_cooperation(2).unwrap(), true
+)]
+#[case::divergence_3(
+    DIVERGENT_2_TIGHT, 2,
+    SolveMode::require_divergence(3).unwrap(), SolveMode::no_divergent_cooperation(3).unwrap(), false
+)]
+fn required_modes_match_their_forbidding_counterparts(
+    #[case] world: &str,
+    #[case] horizon: usize,
+    #[case] required: SolveMode,
+    #[case] forbidden: SolveMode,
+    #[case] expected: bool,
+) {
+    assert_required_profile(world, horizon, required, forbidden, expected);
+}
+
+/// Level 4 needs mutual help and level 6 a sequence of two help events.
+#[rstest]
+#[case::mutual(4, 10, SolveMode::require_interdependence(2).unwrap(), SolveMode::no_interdependence(2).unwrap())]
+#[case::sequence(6, 21, SolveMode::require_sequence(2).unwrap(), SolveMode::no_sequential_cooperation(2).unwrap())]
+fn required_modes_find_the_cooperation_of_levels(
+    #[case] level: usize,
+    #[case] horizon: usize,
+    #[case] required: SolveMode,
+    #[case] forbidden: SolveMode,
+) {
+    let world = World::get_level(level).unwrap().world_string();
+    assert_required_profile(&world, horizon, required, forbidden, true);
+}
+
+/// A required profile is only assumed for the current horizon: requiring help at a short horizon
+/// does not constrain the longer ones of the same delta stream.
+#[test]
+fn required_profile_is_scoped_to_its_horizon_in_a_delta_stream() {
+    let t_max = 8;
+    let mut cg = build(OPTIONAL_HELP, t_max);
+    let first_sat = |cg: &mut ClauseGenerator, mode| {
+        (0..=t_max).find(|&t| {
+            let (clauses, assumptions) = cg.generate(t, mode, false).unwrap();
+            sat::is_sat(&clauses, &assumptions)
+        })
+    };
+    let standard = first_sat(&mut cg, SolveMode::Standard).unwrap();
+    let required = first_sat(&mut cg, SolveMode::RequireCooperation).unwrap();
+    assert!(required > standard);
+
+    let mut stream = cg
+        .start_delta_stream(SolveMode::RequireCooperation, false)
+        .unwrap();
+    let mut clauses = Vec::new();
+    for t in standard..=required {
+        let (delta, assumptions) = stream.advance_to(&mut cg, t);
+        clauses.extend(delta);
+        assert_eq!(
+            sat::is_sat(&clauses, &assumptions),
+            t == required,
+            "horizon {t}"
+        );
+    }
+}
+
+/// Two-agent world where help is possible but not needed: agent 1 may detour through the beam of
+/// agent 0.
+const OPTIONAL_HELP: &str = "
+S0  . . X
+L0E . . .
+S1  . . X";
+
+#[test]
+fn spec_requirements_share_their_families() {
+    let spec: SolveSpec = [
+        SolveMode::no_convergent_cooperation(2).unwrap(),
+        SolveMode::no_divergent_cooperation(3).unwrap(),
+        SolveMode::RequireCooperation,
+    ]
+    .into_iter()
+    .collect();
+    let requirements = ModeRequirements::of_spec(&spec);
+    assert!(requirements.lasers && requirements.help);
+    assert_eq!(
+        requirements.horizon_families,
+        vec![
+            HorizonFamily::PairwiseHelp,
+            HorizonFamily::NoConvergence(2),
+            HorizonFamily::NoDivergence(3),
+            HorizonFamily::RequiredHelp,
+        ]
+    );
+    assert_eq!(requirements.assumptions.len(), 1);
+}
+
+#[test]
+fn spec_requirements_of_single_modes_are_unchanged() {
+    let no_cooperation = ModeRequirements::of_spec(&SolveMode::NoCooperation.into());
+    assert!(!no_cooperation.lasers && !no_cooperation.help);
+    let combined = ModeRequirements::of_spec(
+        &SolveSpec::from(SolveMode::NoCooperation).with(SolveMode::NoAsymmetricCooperation),
+    );
+    assert!(combined.lasers && combined.help);
+    let standard = ModeRequirements::of_spec(&SolveSpec::new());
+    assert!(standard.lasers && !standard.help && standard.assumptions.is_empty());
+}
+
+#[test]
+fn spec_drops_structurally_impossible_modes() {
+    let cg = build(OPTIONAL_HELP, 8);
+    let spec = SolveSpec::from(SolveMode::no_sequential_cooperation(2).unwrap())
+        .with(SolveMode::RequireCooperation);
+    let stream = cg.start_delta_stream(spec, false).unwrap();
+    assert_eq!(stream.spec().modes(), &[SolveMode::RequireCooperation]);
+}
+
+/// Each mode alone is satisfiable, but their conjunction is not.
+#[test]
+fn spec_is_the_conjunction_of_its_modes() {
+    let horizon = 8;
+    let mut cg = build(OPTIONAL_HELP, horizon);
+    for mode in [SolveMode::NoCooperation, SolveMode::RequireCooperation] {
+        let (clauses, assumptions) = cg.generate(horizon, mode, false).unwrap();
+        assert!(sat::is_sat(&clauses, &assumptions), "{mode:?}");
+    }
+    let spec = SolveSpec::from(SolveMode::NoCooperation).with(SolveMode::RequireCooperation);
+    let (clauses, assumptions) = cg.generate(horizon, spec, false).unwrap();
+    assert!(!sat::is_sat(&clauses, &assumptions));
+}
+
+/// A plan of the conjunction satisfies every mode of it, and the delta stream agrees with
+/// `generate` at every horizon.
+#[test]
+fn spec_plans_satisfy_every_mode() {
+    let world = World::get_level(4).unwrap().world_string();
+    let t_max = 12;
+    let modes = [
+        SolveMode::no_convergent_cooperation(2).unwrap(),
+        SolveMode::require_interdependence(2).unwrap(),
+    ];
+    let spec: SolveSpec = modes.into_iter().collect();
+    let mut cg = build(&world, t_max);
+    let mut stream = cg.start_delta_stream(spec.clone(), false).unwrap();
+    let mut streamed = Vec::new();
+    let mut found = false;
+    for t in cg.solution_lower_bound()..=t_max {
+        let (delta, stream_assumptions) = stream.advance_to(&mut cg, t);
+        streamed.extend(delta);
+        let (clauses, assumptions) = cg.generate(t, spec.clone(), false).unwrap();
+        let model = sat::solve(&clauses, &assumptions);
+        assert_eq!(
+            sat::is_sat(&streamed, &stream_assumptions),
+            model.is_some(),
+            "horizon {t}"
+        );
+        let Some(model) = model else { continue };
+        found = true;
+        let positions = plan_positions(&cg, &model);
+        for mode in modes {
+            let (clauses, mut assumptions) = cg.generate(t, mode, false).unwrap();
+            assumptions.extend(&positions);
+            assert!(
+                sat::is_sat(&clauses, &assumptions),
+                "{mode:?} at horizon {t}"
+            );
+        }
+    }
+    assert!(found);
+}
+
+const FORBID_HELP: CooperationConstraint = CooperationConstraint::ForbidHelp {
+    helper: 0,
+    beneficiary: 1,
+};
+const REQUIRE_HELP: CooperationConstraint = CooperationConstraint::RequireHelp {
+    helper: 0,
+    beneficiary: 1,
+};
+
+fn constrained(constraints: &[CooperationConstraint]) -> SolveSpec {
+    constraints
+        .iter()
+        .fold(SolveSpec::new(), |spec, &constraint| {
+            spec.with_constraint(constraint)
+        })
+}
+
+#[rstest]
+#[case::forbid(&[FORBID_HELP], true)]
+#[case::require(&[REQUIRE_HELP], true)]
+#[case::forbid_and_require(&[FORBID_HELP, REQUIRE_HELP], false)]
+#[case::no_laser_for_agent_1(&[CooperationConstraint::RequireHelp { helper: 1, beneficiary: 0 }], false)]
+#[case::forbid_helper(&[REQUIRE_HELP, CooperationConstraint::ForbidHelper(0)], false)]
+#[case::forbid_laser(&[REQUIRE_HELP, CooperationConstraint::ForbidLaser(0)], false)]
+fn assumed_constraints_restrict_the_plans(
+    #[case] constraints: &[CooperationConstraint],
+    #[case] expected: bool,
+) {
+    let horizon = 8;
+    let mut cg = build(OPTIONAL_HELP, horizon);
+    let (clauses, assumptions) = cg
+        .generate(horizon, constrained(constraints), false)
+        .unwrap();
+    assert_eq!(sat::is_sat(&clauses, &assumptions), expected);
+}
+
+/// With a single laser, forbidding help from its owner is the same as forbidding cooperation, and
+/// requiring it is the same as requiring cooperation.
+#[rstest]
+#[case::forbid(FORBID_HELP, SolveMode::NoCooperation)]
+#[case::require(REQUIRE_HELP, SolveMode::RequireCooperation)]
+fn constraints_match_the_global_modes_with_a_single_laser(
+    #[case] constraint: CooperationConstraint,
+    #[case] mode: SolveMode,
+) {
+    let t_max = 8;
+    let mut cg = build(OPTIONAL_HELP, t_max);
+    for t in 0..=t_max {
+        let (clauses, assumptions) = cg.generate(t, constrained(&[constraint]), false).unwrap();
+        let (mode_clauses, mode_assumptions) = cg.generate(t, mode, false).unwrap();
+        assert_eq!(
+            sat::is_sat(&clauses, &assumptions),
+            sat::is_sat(&mode_clauses, &mode_assumptions),
+            "horizon {t}"
+        );
+    }
+}
+
+#[test]
+fn asserted_constraints_restrict_the_plans() {
+    let horizon = 8;
+    let mut cg = build(OPTIONAL_HELP, horizon);
+    let (mut clauses, assumptions) = cg.generate(horizon, SolveMode::Standard, false).unwrap();
+    clauses.extend(cg.constraint_clauses(REQUIRE_HELP, horizon).unwrap());
+    assert!(sat::is_sat(&clauses, &assumptions));
+    clauses.extend(cg.constraint_clauses(FORBID_HELP, horizon).unwrap());
+    assert!(!sat::is_sat(&clauses, &assumptions));
+}
+
+/// `World::step` reports a `Help` event exactly when the model of the solver sets the matching
+/// `VarKey::Help` variable.
+#[rstest]
+fn help_events_match_the_help_variables(#[values(0, 1, 2)] n_help_events: usize) {
+    let horizon = 8;
+    let mut cg = build(OPTIONAL_HELP, horizon);
+    let mode = SolveMode::ExactHelpEvents(n_help_events);
+    let (clauses, assumptions) = cg.generate(horizon, mode, false).unwrap();
+    let model = sat::solve(&clauses, &assumptions).unwrap();
+    let expected: HashSet<(usize, usize, usize)> = model
+        .iter()
+        .filter(|&&literal| literal > 0)
+        .filter_map(|&literal| match cg.engine.pool.key(literal) {
+            Some(VarKey::Help {
+                helper,
+                beneficiary,
+                t,
+            }) if t > 0 => Some((helper, beneficiary, t)),
+            _ => None,
+        })
+        .collect();
+
+    let mut world = World::try_from(OPTIONAL_HELP).unwrap();
+    world.set_help_events(true);
+    world.reset();
+    let mut actual = HashSet::new();
+    for (t, actions) in cg.decode_plan(&model, horizon).unwrap().iter().enumerate() {
+        for event in world.step(actions).unwrap() {
+            if let WorldEvent::Help {
+                helper,
+                beneficiary,
+                ..
+            } = event
+            {
+                actual.insert((helper, beneficiary, t + 1));
+            }
+        }
+    }
+    assert_eq!(actual, expected);
+    assert_eq!(actual.len(), n_help_events);
+}
+
+/// Required help is assumed per horizon, so a delta stream finds the same shortest horizon as the
+/// complete formulas.
+#[test]
+fn required_help_is_scoped_to_its_horizon_in_a_delta_stream() {
+    let t_max = 8;
+    let mut cg = build(OPTIONAL_HELP, t_max);
+    let spec = constrained(&[REQUIRE_HELP]);
+    let mut stream = cg.start_delta_stream(spec.clone(), false).unwrap();
+    let mut streamed = Vec::new();
+    for t in 0..=t_max {
+        let (delta, stream_assumptions) = stream.advance_to(&mut cg, t);
+        streamed.extend(delta);
+        let (clauses, assumptions) = cg.generate(t, spec.clone(), false).unwrap();
+        assert_eq!(
+            sat::is_sat(&streamed, &stream_assumptions),
+            sat::is_sat(&clauses, &assumptions),
+            "horizon {t}"
+        );
+    }
+}
+
+#[rstest]
+#[case::unknown_agent(CooperationConstraint::ForbidHelp { helper: 0, beneficiary: 2 })]
+#[case::self_help(CooperationConstraint::RequireHelp { helper: 1, beneficiary: 1 })]
+#[case::unknown_helper(CooperationConstraint::ForbidHelper(5))]
+#[case::unknown_laser(CooperationConstraint::ForbidLaser(1))]
+fn constraints_must_fit_the_world(#[case] constraint: CooperationConstraint) {
+    let mut cg = build(OPTIONAL_HELP, 4);
+    let spec = constrained(&[constraint]);
+    assert!(matches!(
+        cg.check_spec(&spec),
+        Err(SolverError::InvalidConstraint { .. })
+    ));
+    assert!(cg.constraint_clauses(constraint, 4).is_err());
+    assert!(matches!(
+        cg.generate(4, spec.clone(), false),
+        Err(SolverError::InvalidConstraint { .. })
+    ));
+    assert!(matches!(
+        cg.generate_explained(4, spec.clone(), false),
+        Err(SolverError::InvalidConstraint { .. })
+    ));
+    assert!(matches!(
+        cg.start_delta_stream(spec, false),
+        Err(SolverError::InvalidConstraint { .. })
+    ));
+}
+
+/// Plans found under a help-event bound have that many help events, and bounding them to zero is
+/// the same as forbidding cooperation.
+#[test]
+fn help_event_modes_bound_the_number_of_help_events() {
+    let t_max = 8;
+    let mut cg = build(OPTIONAL_HELP, t_max);
+    for t in 0..=t_max {
+        let (clauses, assumptions) = cg.generate(t, SolveMode::MaxHelpEvents(0), false).unwrap();
+        let (mode_clauses, mode_assumptions) =
+            cg.generate(t, SolveMode::NoCooperation, false).unwrap();
+        assert_eq!(
+            sat::is_sat(&clauses, &assumptions),
+            sat::is_sat(&mode_clauses, &mode_assumptions),
+            "horizon {t}"
+        );
+    }
+    for k in 0..=3 {
+        let (clauses, assumptions) = cg
+            .generate(t_max, SolveMode::ExactHelpEvents(k), false)
+            .unwrap();
+        let model = sat::solve(&clauses, &assumptions).expect("agent 1 may linger in the beam");
+        assert_eq!(cg.count_help_events(&model, t_max), k);
+        let (clauses, assumptions) = cg
+            .generate(t_max, SolveMode::MaxHelpEvents(k), false)
+            .unwrap();
+        let model = sat::solve(&clauses, &assumptions).unwrap();
+        assert!(cg.count_help_events(&model, t_max) <= k);
+    }
+}
+
+/// Minimising help events by tightening `MaxHelpEvents` below the best plan so far finds the
+/// smallest `k` for which `ExactHelpEvents(k)` is satisfiable.
+#[rstest]
+#[case::level_4(4, 10)]
+#[case::level_6(6, 21)]
+fn minimising_help_events_matches_the_exact_counts(#[case] level: usize, #[case] horizon: usize) {
+    let world = World::get_level(level).unwrap().world_string();
+    let mut cg = build(&world, horizon);
+    let (clauses, assumptions) = cg.generate(horizon, SolveMode::Standard, false).unwrap();
+    let model = sat::solve(&clauses, &assumptions).unwrap();
+    let mut best = cg.count_help_events(&model, horizon);
+    while best > 0 {
+        let (clauses, assumptions) = cg
+            .generate(horizon, SolveMode::MaxHelpEvents(best - 1), false)
+            .unwrap();
+        let Some(model) = sat::solve(&clauses, &assumptions) else {
+            break;
+        };
+        let count = cg.count_help_events(&model, horizon);
+        assert!(count < best);
+        best = count;
+    }
+    let exact_minimum = (0..)
+        .find(|&k| {
+            let (clauses, assumptions) = cg
+                .generate(horizon, SolveMode::ExactHelpEvents(k), false)
+                .unwrap();
+            sat::is_sat(&clauses, &assumptions)
+        })
+        .unwrap();
+    assert_eq!(best, exact_minimum);
+    assert!(best > 0, "both levels need cooperation");
+}
+
+// ─── unsat explanations ──────────────────────────────────────────────────────
+
+#[rstest]
+#[case::standard("S0 . X", 2, SolveSpec::new(), false)]
+#[case::gems("S0 G X\n.  . .", 3, SolveSpec::new(), true)]
+#[case::no_cooperation(SINGLE_LASER_ASYMMETRIC, 6, SolveMode::NoCooperation.into(), false)]
+#[case::no_asymmetric(SINGLE_LASER_ASYMMETRIC, 6, SolveMode::NoAsymmetricCooperation.into(), false)]
+#[case::no_sequence(SINGLE_LASER_ASYMMETRIC, 6, SolveMode::no_sequential_cooperation(2).unwrap().into(), false)]
+#[case::no_convergence(CONVERGENT_2_TIGHT, 5, SolveMode::no_convergent_cooperation(2).unwrap().into(), false)]
+#[case::no_divergence(DIVERGENT_2_TIGHT, 2, SolveMode::no_divergent_cooperation(2).unwrap().into(), false)]
+#[case::require(OPTIONAL_HELP, 8, SolveMode::RequireCooperation.into(), false)]
+#[case::max_help_events(OPTIONAL_HELP, 8, SolveMode::MaxHelpEvents(0).into(), false)]
+#[case::constraints(OPTIONAL_HELP, 8, constrained(&[FORBID_HELP, REQUIRE_HELP]), false)]
+fn explained_formulas_are_equisatisfiable(
+    #[case] world: &str,
+    #[case] t_max: usize,
+    #[case] spec: SolveSpec,
+    #[case] collect_gems: bool,
+) {
+    let mut cg = build(world, t_max);
+    for t in 0..=t_max {
+        let (clauses, assumptions) = cg.generate(t, spec.clone(), collect_gems).unwrap();
+        let (explained, selectors, _) = cg
+            .generate_explained(t, spec.clone(), collect_gems)
+            .unwrap();
+        assert_eq!(
+            sat::is_sat(&explained, &selectors),
+            sat::is_sat(&clauses, &assumptions),
+            "horizon {t}"
+        );
+    }
+}
+
+/// Agent 1 cannot cross the beam of agent 0 on its own.
+#[test]
+fn unsat_core_names_the_forbidden_help_pair() {
+    let mut cg = build(SINGLE_LASER_ASYMMETRIC, 6);
+    let (clauses, assumptions, explanation) = cg
+        .generate_explained(6, SolveMode::NoCooperation, false)
+        .unwrap();
+    let core = sat::minimal_core(&clauses, &assumptions).unwrap();
+    assert_eq!(
+        explanation.reasons(&core),
+        [
+            UnsatReason::AgentGoal(1),
+            UnsatReason::ForbiddenHelp {
+                helper: 0,
+                beneficiary: 1
+            }
+        ]
+    );
+}
+
+#[test]
+fn unsat_core_names_the_conflicting_constraints() {
+    let mut cg = build(OPTIONAL_HELP, 8);
+    let spec = constrained(&[FORBID_HELP, REQUIRE_HELP]);
+    let (clauses, assumptions, explanation) = cg.generate_explained(8, spec, false).unwrap();
+    let core = sat::minimal_core(&clauses, &assumptions).unwrap();
+    assert_eq!(
+        explanation.reasons(&core),
+        [
+            UnsatReason::Constraint(FORBID_HELP),
+            UnsatReason::Constraint(REQUIRE_HELP)
+        ]
+    );
+}
+
+/// Level 4 needs mutual help: a core lists the goals of the agents and the closed trails they would
+/// need, and dropping every pattern makes the level solvable again.
+#[test]
+fn unsat_core_names_the_forbidden_patterns() {
+    let world = World::get_level(4).unwrap().world_string();
+    let mut cg = build(&world, 10);
+    let (clauses, assumptions, explanation) = cg
+        .generate_explained(10, SolveMode::no_interdependence(2).unwrap(), false)
+        .unwrap();
+    let core = sat::minimal_core(&clauses, &assumptions).unwrap();
+    let reasons = explanation.reasons(&core);
+    assert!(
+        reasons
+            .iter()
+            .any(|reason| matches!(reason, UnsatReason::InterdependencePattern { order: 2, .. }))
+    );
+    assert!(reasons.iter().all(|reason| matches!(
+        reason,
+        UnsatReason::AgentGoal(_) | UnsatReason::InterdependencePattern { .. }
+    )));
+
+    let goals_only = explanation
+        .selectors()
+        .iter()
+        .filter(|(_, reason)| matches!(reason, UnsatReason::AgentGoal(_)))
+        .map(|&(selector, _)| selector)
+        .collect::<Vec<_>>();
+    assert!(sat::is_sat(&clauses, &goals_only));
+}
+
+// ─── solution enumeration ────────────────────────────────────────────────────
+
+/// Every model of the formula for horizon `t` with a distinct `projection`, at most `cap` of them.
+fn enumerate_solutions(
+    cg: &mut ClauseGenerator,
+    t: usize,
+    spec: impl Into<SolveSpec>,
+    projection: Projection,
+    cap: usize,
+) -> Vec<Vec<Literal>> {
+    let (mut clauses, assumptions) = cg.generate(t, spec, false).unwrap();
+    clauses.extend(cg.projection_clauses(t, projection));
+    let mut models = Vec::new();
+    while models.len() < cap {
+        let Some(model) = sat::solve(&clauses, &assumptions) else {
+            break;
+        };
+        clauses.push(cg.blocking_clause(&model, t, projection));
+        models.push(model);
+    }
+    models
+}
+
+/// The agent goes east then south, or south then east. Waiting is pruned from the encoding when
+/// it is useless, so the plans enumerated are those of the formula.
+#[test]
+fn enumerating_positions_lists_every_plan() {
+    let mut cg = build("S0 .\n. X", 4);
+    let models = enumerate_solutions(&mut cg, 2, SolveMode::Standard, Projection::Positions, 10);
+    let plans = models
+        .iter()
+        .map(|model| cg.decode_plan(model, 2).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(plans.len(), 2);
+    assert_ne!(plans[0], plans[1]);
+
+    let capped = enumerate_solutions(&mut cg, 2, SolveMode::Standard, Projection::Positions, 1);
+    assert_eq!(capped.len(), 1);
+}
+
+/// Agent 1 may cross the beam of agent 0 or not: two pairwise structures, and as many help-edge
+/// structures as there are distinct sets of help events.
+#[test]
+fn enumerating_help_lists_the_cooperation_structures() {
+    let t = 8;
+    let mut cg = build(OPTIONAL_HELP, t);
+    let pairwise = enumerate_solutions(
+        &mut cg,
+        t,
+        SolveMode::Standard,
+        Projection::PairwiseHelp,
+        10,
+    );
+    let pairs = pairwise
+        .iter()
+        .map(|model| {
+            cg.help_edges(model, t)
+                .into_iter()
+                .map(|(helper, beneficiary, _)| (helper, beneficiary))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashSet<_>>();
+    assert_eq!(pairs, HashSet::from([vec![], vec![(0, 1)]]));
+
+    let edges = enumerate_solutions(&mut cg, t, SolveMode::Standard, Projection::HelpEdges, 1000);
+    let structures = edges
+        .iter()
+        .map(|model| cg.help_edges(model, t))
+        .collect::<HashSet<_>>();
+    assert_eq!(structures.len(), edges.len());
+    assert!(structures.len() > 2);
+    assert!(structures.contains(&vec![]));
+}
+
+#[test]
+fn a_projection_without_variables_has_a_single_solution() {
+    let t = 8;
+    let mut cg = build(OPTIONAL_HELP, t);
+    let models = enumerate_solutions(
+        &mut cg,
+        t,
+        SolveMode::NoCooperation,
+        Projection::HelpEdges,
+        10,
+    );
+    assert_eq!(models.len(), 1);
+    let mut cg = build("S0 . . X", 4);
+    let models = enumerate_solutions(
+        &mut cg,
+        4,
+        SolveMode::Standard,
+        Projection::PairwiseHelp,
+        10,
+    );
+    assert_eq!(models.len(), 1);
+}
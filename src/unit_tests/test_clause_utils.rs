@@ -1,4 +1,7 @@
-use super::{equals, implies};
+use rstest::rstest;
+
+use super::{at_most_one, equals, implies, sequential_counter};
+use crate::solver::{AtMostOneEncoding, clauses::VarPool, sat};
 
 #[test]
 fn implies_expands_to_negated_antecedent_or_consequent() {
@@ -13,3 +16,68 @@ fn equals_expands_to_two_implications() {
     assert!(clauses.iter().any(|c| *c == vec![-1, 2]), "missing 1→2");
     assert!(clauses.iter().any(|c| *c == vec![-2, 1]), "missing 2→1");
 }
+
+/// For every assignment of up to five variables, each counter output is forced to whether the
+/// count reaches it.
+#[test]
+fn sequential_counter_outputs_are_equivalent_to_the_count() {
+    for n in 0..=5 {
+        for bound in 1..=4 {
+            let mut pool = VarPool::new();
+            let vars: Vec<_> = (0..n).map(|_| pool.aux()).collect();
+            let outputs: Vec<_> = (0..bound).map(|_| pool.aux()).collect();
+            let clauses = sequential_counter(&vars, &outputs, &mut pool);
+            for assignment in 0..1u32 << n {
+                let fixed: Vec<_> = vars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &var)| if assignment >> i & 1 == 1 { var } else { -var })
+                    .collect();
+                let count = assignment.count_ones() as usize;
+                for (j, &output) in outputs.iter().enumerate() {
+                    let expected = if count > j { output } else { -output };
+                    let mut assumptions = fixed.clone();
+                    assumptions.push(-expected);
+                    assert!(
+                        !sat::is_sat(&clauses, &assumptions),
+                        "n={n}, bound={bound}, assignment={assignment:b}, output {j}"
+                    );
+                }
+                assert!(sat::is_sat(&clauses, &fixed));
+            }
+        }
+    }
+}
+
+/// For every assignment of up to eight variables, the encoding is satisfiable exactly when at most
+/// one of them is true.
+#[rstest]
+fn at_most_one_encodings_allow_exactly_the_assignments_with_one_true_variable(
+    #[values(
+        AtMostOneEncoding::Auto,
+        AtMostOneEncoding::Pairwise,
+        AtMostOneEncoding::Sequential,
+        AtMostOneEncoding::Commander,
+        AtMostOneEncoding::Product,
+        AtMostOneEncoding::Binary
+    )]
+    encoding: AtMostOneEncoding,
+) {
+    for n in 0..=8 {
+        let mut pool = VarPool::new();
+        let vars: Vec<_> = (0..n).map(|_| pool.aux()).collect();
+        let clauses = at_most_one(encoding, &vars, &mut pool);
+        for assignment in 0..1u32 << n {
+            let fixed: Vec<_> = vars
+                .iter()
+                .enumerate()
+                .map(|(i, &var)| if assignment >> i & 1 == 1 { var } else { -var })
+                .collect();
+            assert_eq!(
+                sat::is_sat(&clauses, &fixed),
+                assignment.count_ones() <= 1,
+                "{encoding:?}, n={n}, assignment={assignment:b}"
+            );
+        }
+    }
+}
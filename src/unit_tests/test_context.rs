@@ -503,14 +503,42 @@ fn prev_neighbours_filters_by_previous_relevant_positions() {
             .collect::<HashSet<_>>(),
         HashSet::from([pos(0, 0)])
     );
-    assert_eq!(
-        ctx.prev_neighbours(0, &pos(0, 0), 1)
-            .collect::<HashSet<_>>(),
-        HashSet::from([pos(0, 0)])
-    );
+    // Staying on the start tile is useless: nothing to block, wait for, or avoid.
+    assert!(ctx.prev_neighbours(0, &pos(0, 0), 1).next().is_none());
     assert!(ctx.prev_neighbours(0, &pos(0, 2), 1).next().is_none());
 }
 
+/// Stays are only kept where they can matter: on exits, next to beams, and where another agent
+/// may get in the way.
+#[test]
+fn useless_stays_are_pruned() {
+    let world = World::try_from(
+        "
+        S0 . .   . X
+        @  @ L0N . @
+        S1 . .   . X",
+    )
+    .expect("Failed to parse world");
+    let mut ctx = ConstraintContext::new(&world, 6);
+    ctx.update(6);
+    // Far from the beam, agent 0 must keep moving.
+    assert!(!ctx.can_stay(0, &pos(0, 0)));
+    assert!(!ctx.relevant_positions_for_agent(0, 1).contains(&pos(0, 0)));
+    // Next to the beam or on it, the agent may wait.
+    assert!(ctx.can_stay(0, &pos(0, 1)));
+    assert!(ctx.can_stay(0, &pos(0, 2)));
+    // Exits always allow staying.
+    assert!(ctx.can_stay(0, &pos(0, 4)));
+    // Agent 0 cannot reach the start of the bottom row in time, so it is private to agent 1.
+    assert!(!ctx.can_stay(1, &pos(2, 0)));
+
+    // With enough time, the agents can get in each other's way and must be allowed to wait.
+    let mut ctx = ConstraintContext::new(&world, 12);
+    ctx.update(12);
+    assert!(ctx.can_stay(0, &pos(0, 0)));
+    assert!(ctx.can_stay(1, &pos(2, 0)));
+}
+
 /// Laser source tiles are not walkable: they should not be successors of adjacent walkable tiles,
 /// and they should not have neighbours themselves.
 #[test]
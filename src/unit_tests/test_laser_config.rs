@@ -1,4 +1,4 @@
-use crate::tiles::Direction;
+use crate::{ParseError, tiles::Direction};
 
 use super::LaserConfig;
 
@@ -24,3 +24,24 @@ fn laser_source_from_str() {
     assert_eq!(source.agent_id(), 3);
     assert_eq!(source.laser_id(), 0);
 }
+
+#[test]
+fn laser_source_from_str_rejects_invalid_tokens() {
+    assert!(matches!(
+        LaserConfig::from_str("L0Q", 0),
+        Err(ParseError::InvalidDirection { given, span: None, .. }) if given == "Q"
+    ));
+    assert!(matches!(
+        LaserConfig::from_str("LxE", 0),
+        Err(ParseError::InvalidAgentId { given_agent_id, span: None }) if given_agent_id == "x"
+    ));
+    assert!(matches!(
+        LaserConfig::from_str("L", 0),
+        Err(ParseError::InvalidDirection { .. })
+    ));
+    assert!(matches!(
+        LaserConfig::from_str("LE", 0),
+        Err(ParseError::InvalidAgentId { .. })
+    ));
+    assert!(LaserConfig::from_str("L0é", 0).is_err());
+}
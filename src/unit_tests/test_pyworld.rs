@@ -1,9 +1,31 @@
+use pyo3::{IntoPyObject, Python};
+
 use super::PyWorld;
 
 #[test]
 fn pickle() {
-    let world = PyWorld::level(1).unwrap();
-    let bin = world.__getstate__().unwrap();
-    let mut new_world = PyWorld::new("S0 X".to_string()).unwrap();
-    new_world.__setstate__(bin).unwrap();
+    Python::initialize();
+    Python::attach(|py| {
+        let mut world = PyWorld::level(1).unwrap();
+        world.set_conflict_rule("priority").unwrap();
+        let bin = world.__getstate__().unwrap().into_pyobject(py).unwrap();
+        let mut new_world = PyWorld::new("S0 X".to_string()).unwrap();
+        new_world.__setstate__(&bin).unwrap();
+        assert_eq!(new_world.conflict_rule(), "priority");
+    });
+}
+
+#[test]
+fn pickles_without_the_runtime_settings_get_the_defaults() {
+    Python::initialize();
+    Python::attach(|py| {
+        let mut world = PyWorld::level(1).unwrap();
+        world.set_conflict_rule("priority").unwrap();
+        let (world_string, state, ..) = world.__getstate__().unwrap();
+        let bin = (world_string, state).into_pyobject(py).unwrap();
+        let mut new_world = PyWorld::new("S0 X".to_string()).unwrap();
+        new_world.__setstate__(&bin).unwrap();
+        assert_eq!(new_world.conflict_rule(), "no-following");
+        assert_eq!(new_world.n_agents, world.n_agents);
+    });
 }
@@ -1,3 +1,5 @@
+use image::imageops::crop_imm;
+
 use crate::{Renderer, World, rendering::TILE_SIZE};
 
 #[test]
@@ -25,7 +27,7 @@ fn level_6_pixel_dimensions_include_extra_border() {
 }
 
 #[test]
-fn renderer_falls_back_to_n_sprite_for_agents_above_numbered_range() {
+fn renderer_draws_procedural_sprites_above_numbered_range() {
     let world = World::try_from(
         "L12E .  .  .  .  .  .  .  .  .  .   .   .   X
           S0  S1 S2 S3 S4 S5 S6 S7 S8 S9 S10 S11 S12 .
@@ -34,5 +36,8 @@ fn renderer_falls_back_to_n_sprite_for_agents_above_numbered_range() {
     .unwrap();
     let renderer = Renderer::new(&world);
 
-    renderer.update(&world);
+    let image = renderer.update(&world);
+    assert_eq!(image.dimensions(), (TILE_SIZE * 14 + 1, TILE_SIZE * 3 + 1));
+    let tile = |j: u32| crop_imm(&image, j * TILE_SIZE, TILE_SIZE, TILE_SIZE, TILE_SIZE).to_image();
+    assert_ne!(tile(12), tile(0));
 }
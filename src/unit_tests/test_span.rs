@@ -0,0 +1,92 @@
+use crate::{ParseError, World, core::parsing::parse_v1};
+
+use super::{Span, tokenize};
+
+#[test]
+fn tokenize_reports_character_columns() {
+    let tokens: Vec<_> = tokenize("  S0\tL0E  é X").collect();
+    assert_eq!(tokens, vec![(3, "S0"), (6, "L0E"), (11, "é"), (13, "X")]);
+    assert_eq!(tokenize("   ").count(), 0);
+}
+
+#[test]
+fn span_from_byte_range() {
+    let source = "width = 2\n  unknown = 3\n";
+    let start = source.find("unknown").unwrap();
+    assert_eq!(
+        Span::from_byte_range(source, start..start + 7),
+        Span::new(2, 3, "unknown")
+    );
+    // Ranges that span several lines are truncated to the first one.
+    assert_eq!(
+        Span::from_byte_range(source, 0..source.len()),
+        Span::new(1, 1, "width = 2")
+    );
+}
+
+#[test]
+fn render_underlines_the_token() {
+    let source = "S0 . X\nS1 L0Q X";
+    let rendered = Span::new(2, 4, "L0Q").render(source).unwrap();
+    assert_eq!(
+        rendered,
+        " --> line 2, column 4\n  |\n2 | S1 L0Q X\n  |    ^^^"
+    );
+    assert_eq!(Span::new(3, 1, "X").render(source), None);
+}
+
+#[test]
+fn render_keeps_tabs_aligned() {
+    let rendered = Span::new(1, 4, "Q").render("S0\tQ").unwrap();
+    assert!(rendered.ends_with("1 | S0\tQ\n  |   \t^"));
+}
+
+#[test]
+fn v1_errors_have_spans() {
+    let source = "S0 . X\n\n  S1 L0Q X";
+    let error = parse_v1(source).unwrap_err();
+    assert!(matches!(error, ParseError::InvalidDirection { .. }));
+    assert_eq!(error.span(), Some(&Span::new(3, 6, "L0Q")));
+    assert_eq!(
+        error.report(source),
+        "Invalid direction: 'Q'. Expected one of {N, E, S, W, north, east, south, west}.
+ --> line 3, column 6
+  |
+3 |   S1 L0Q X
+  |      ^^^"
+    );
+
+    let error = parse_v1("S0 Sx X").unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(1, 4, "Sx")));
+    let error = parse_v1("S0 S0 X X").unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(1, 4, "S0")));
+    let error = parse_v1("S0 ? X").unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(1, 4, "?")));
+}
+
+#[test]
+fn errors_display_their_message_and_location() {
+    let error = parse_v1("S0 . X\n\n  S1 L0Q X").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid direction: 'Q'. Expected one of {N, E, S, W, north, east, south, west}.
+ --> line 3, column 6
+  |
+  | L0Q
+  | ^^^"
+    );
+    assert_eq!(
+        ParseError::NoAgents.to_string(),
+        ParseError::NoAgents.message()
+    );
+}
+
+#[test]
+fn level_wide_errors_have_no_span() {
+    let Err(error) = World::try_from("X G") else {
+        panic!("A world without agents should not parse");
+    };
+    assert!(matches!(error, ParseError::NoAgents));
+    assert_eq!(error.span(), None);
+    assert_eq!(error.report("X G"), error.message());
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use image::{Rgb, RgbImage};
+
+use super::{
+    AGENT_FALLBACK, HORIZONTAL_LASER_FALLBACK, LASER_SOURCE_EAST_FALLBACK, agent, horizontal_laser,
+    laser_source_east, laser_source_north, vertical_laser,
+};
+use crate::rendering::procedural::{MAX_PROCEDURAL_SPRITE_ID, rotate_hue};
+
+fn n_distinct<'a>(sprites: impl Iterator<Item = &'a [u8]>) -> usize {
+    sprites.collect::<HashSet<_>>().len()
+}
+
+#[test]
+fn every_agent_id_up_to_the_limit_has_a_unique_agent_sprite() {
+    let ids = 0..=MAX_PROCEDURAL_SPRITE_ID;
+    let expected = ids.clone().count();
+    assert_eq!(
+        n_distinct(ids.map(|id| agent(id).as_raw().as_slice())),
+        expected
+    );
+}
+
+#[test]
+fn every_agent_id_up_to_the_limit_has_unique_laser_sprites() {
+    let ids = 0..=MAX_PROCEDURAL_SPRITE_ID;
+    let expected = ids.clone().count();
+    assert_eq!(
+        n_distinct(
+            ids.clone()
+                .map(|id| horizontal_laser(id).as_raw().as_slice())
+        ),
+        expected
+    );
+    assert_eq!(
+        n_distinct(ids.clone().map(|id| vertical_laser(id).as_raw().as_slice())),
+        expected
+    );
+    assert_eq!(
+        n_distinct(
+            ids.clone()
+                .map(|id| laser_source_east(id).as_raw().as_slice())
+        ),
+        expected
+    );
+    assert_eq!(
+        n_distinct(ids.map(|id| laser_source_north(id).as_raw().as_slice())),
+        expected
+    );
+}
+
+#[test]
+fn generated_sprites_keep_the_base_dimensions() {
+    for id in [12, 42, MAX_PROCEDURAL_SPRITE_ID] {
+        assert_eq!(agent(id).dimensions(), agent(0).dimensions());
+        assert_eq!(
+            vertical_laser(id).dimensions(),
+            vertical_laser(0).dimensions()
+        );
+        assert_eq!(
+            laser_source_north(id).dimensions(),
+            laser_source_north(0).dimensions()
+        );
+    }
+}
+
+#[test]
+fn ids_above_the_limit_use_the_fallback_sprites() {
+    let id = MAX_PROCEDURAL_SPRITE_ID + 1;
+    assert_eq!(agent(id), &*AGENT_FALLBACK);
+    assert_eq!(horizontal_laser(id), &*HORIZONTAL_LASER_FALLBACK);
+    assert_eq!(laser_source_east(id), &*LASER_SOURCE_EAST_FALLBACK);
+}
+
+#[test]
+fn hue_rotation_leaves_grey_pixels_unchanged() {
+    let image = RgbImage::from_fn(3, 1, |x, _| match x {
+        0 => Rgb([0, 0, 0]),
+        1 => Rgb([127, 127, 127]),
+        _ => Rgb([255, 0, 0]),
+    });
+    let rotated = rotate_hue(&image, 120.0);
+    assert_eq!(rotated.get_pixel(0, 0), &Rgb([0, 0, 0]));
+    assert_eq!(rotated.get_pixel(1, 0), &Rgb([127, 127, 127]));
+    assert_eq!(rotated.get_pixel(2, 0), &Rgb([0, 255, 0]));
+}
@@ -0,0 +1,223 @@
+use crate::{
+    ParseError, Position, World,
+    core::{
+        levels,
+        parsing::{LevelMetadata, Span, parse_toml, parse_v1},
+    },
+    tiles::Direction,
+};
+
+use super::{is_text_v2, parse, to_text_v2_string};
+
+const CROSSING: &str = "
+# Two agents that must cross a laser.
+name: Crossing   # the name can be followed by a comment
+author: Jane Doe
+t_max: 20
+---
+S0   S0 . X
+L1E  .  . @  # the source blocks this row
+S1   G  . X
+";
+
+#[test]
+fn parse_header_and_grid() {
+    let config = parse(CROSSING).unwrap();
+    assert_eq!(
+        config.metadata(),
+        &LevelMetadata {
+            name: Some("Crossing".into()),
+            author: Some("Jane Doe".into()),
+            t_max: Some(20),
+        }
+    );
+    assert_eq!((config.width(), config.height()), (4, 3));
+    assert_eq!(
+        config.random_starts(),
+        &vec![
+            vec![Position { i: 0, j: 0 }, Position { i: 0, j: 1 }],
+            vec![Position { i: 2, j: 0 }],
+        ]
+    );
+    assert_eq!(config.gems(), &vec![Position { i: 2, j: 1 }]);
+    let (source_pos, source) = &config.sources()[0];
+    assert_eq!(*source_pos, Position { i: 1, j: 0 });
+    assert_eq!((source.agent_id, source.direction), (1, Direction::East));
+}
+
+#[test]
+fn empty_header() {
+    let config = parse("---\nS0 X").unwrap();
+    assert!(config.metadata().is_empty());
+    assert_eq!(config.n_agents(), 1);
+}
+
+#[test]
+fn v2_is_detected_by_the_separator() {
+    assert!(is_text_v2(CROSSING));
+    assert!(is_text_v2("  ---  # header end\nS0 X"));
+    assert!(!is_text_v2("S0 X"));
+    assert!(!is_text_v2("# --- is commented out\nS0 X"));
+    let world = World::try_from(CROSSING).unwrap();
+    assert_eq!(world.metadata().name.as_deref(), Some("Crossing"));
+}
+
+#[test]
+fn start_tokens_can_be_combined() {
+    let config = parse("---\nG+S0+S1 V+S1 X X").unwrap();
+    assert_eq!(config.gems(), &vec![Position { i: 0, j: 0 }]);
+    assert_eq!(config.voids(), &vec![Position { i: 0, j: 1 }]);
+    assert_eq!(config.random_starts()[0], vec![Position { i: 0, j: 0 }]);
+    assert_eq!(
+        config.random_starts()[1],
+        vec![Position { i: 0, j: 0 }, Position { i: 0, j: 1 }]
+    );
+}
+
+#[test]
+fn tokens_are_strict() {
+    for token in [
+        "Gxyz", "g", "S", "S01", "S+1", "s0", "L0", "L0e", "L0EE", "LE", "L00E", ".+S0", "X+S0",
+        "@+S0", "L0E+S0", "G+V+S0", "G+G", "S0+",
+    ] {
+        match parse(&format!("---\nS2 {token} X X X")) {
+            Err(ParseError::InvalidTile { span }) => assert_eq!(span, Span::new(2, 4, token)),
+            other => panic!("Token {token} should be invalid, got {other:?}"),
+        }
+    }
+    // v1 stays lenient.
+    assert!(parse_v1("S0 Gxyz X").is_ok());
+}
+
+#[test]
+fn comments_do_not_shift_columns() {
+    match parse("---\n# only a comment\nS0 X\n  S0  Q  # trailing comment") {
+        Err(ParseError::InvalidTile { span }) => assert_eq!(span, Span::new(4, 7, "Q")),
+        other => panic!("Expected an invalid tile, got {other:?}"),
+    }
+    match parse("---\nS0 X . # comment\n S0 X\n") {
+        Err(ParseError::InconsistentDimensions { span, .. }) => {
+            assert_eq!(span, Span::new(3, 2, "S0 X"))
+        }
+        other => panic!("Expected inconsistent dimensions, got {other:?}"),
+    }
+}
+
+#[test]
+fn duplicate_start_in_a_combined_token() {
+    assert!(matches!(
+        parse("---\nS0+S0 X"),
+        Err(ParseError::DuplicateStartTile { agent_id: 0, .. })
+    ));
+}
+
+#[test]
+fn header_errors() {
+    assert!(matches!(
+        parse("S0 X"),
+        Err(ParseError::MissingHeaderSeparator)
+    ));
+    let span = |source: &str| parse(source).unwrap_err().span().cloned();
+    assert_eq!(
+        span("  name Crossing # comment\n---\nS0 X"),
+        Some(Span::new(1, 3, "name Crossing"))
+    );
+    assert_eq!(
+        span("# comment\n level : 2\n---\nS0 X"),
+        Some(Span::new(2, 2, "level"))
+    );
+    assert_eq!(
+        span("name: a\nname: b\n---\nS0 X"),
+        Some(Span::new(2, 1, "name"))
+    );
+    for value in ["-1", "twenty", "020", ""] {
+        let source = format!("t_max:  {value}\n---\nS0 X");
+        assert!(matches!(
+            parse(&source),
+            Err(ParseError::InvalidHeaderValue { .. })
+        ));
+        assert_eq!(span(&source), Some(Span::new(1, 9, value)));
+    }
+}
+
+#[test]
+fn world_string_round_trips_v2_levels() {
+    let world = World::try_from(CROSSING).unwrap();
+    let world_string = world.world_string();
+    assert_eq!(
+        world_string,
+        "name: Crossing
+author: Jane Doe
+t_max: 20
+---
+S0  S0 . X
+L1E .  . @
+S1  G  . X"
+    );
+    let reparsed = World::try_from(world_string.as_str()).unwrap();
+    assert_eq!(reparsed.world_string(), world_string);
+    assert_eq!(reparsed.metadata(), world.metadata());
+    assert_eq!(reparsed.possible_starts(), world.possible_starts());
+}
+
+#[test]
+fn multiple_starts_without_metadata_use_text_v2() {
+    let world = World::try_from("---\nS0 S0+S1\nX  X").unwrap();
+    assert_eq!(world.world_string(), "---\nS0 S0+S1\nX  X");
+}
+
+#[test]
+fn toml_worlds_with_overlapping_starts_round_trip() {
+    let config = parse_toml(
+        r#"
+        width = 3
+        height = 2
+        n_agents = 2
+        exits = [{ i = 1, j = 2 }, { i = 0, j = 2 }]
+        gems = [{ i = 0, j = 1 }]
+        starts = [{ row = 0 }]
+        "#,
+    )
+    .unwrap();
+    let world_string = to_text_v2_string(&config).unwrap();
+    assert_eq!(world_string, "---\nS0+S1 G+S0+S1 X\n.     .       X");
+    let world = config.into_world().unwrap();
+    assert_eq!(world.world_string(), world_string);
+    let reparsed = World::try_from(world_string.as_str()).unwrap();
+    assert_eq!(reparsed.possible_starts(), world.possible_starts());
+    assert_eq!(reparsed.gems_positions(), world.gems_positions());
+}
+
+#[test]
+fn unrepresentable_metadata_falls_back_to_toml() {
+    let mut world = World::try_from("S0 X").unwrap();
+    let metadata = LevelMetadata {
+        name: Some("Level #3".into()),
+        author: None,
+        t_max: Some(4),
+    };
+    world.set_metadata(metadata.clone());
+    assert!(to_text_v2_string(&world.get_config()).is_none());
+    let reparsed = World::try_from(world.world_string()).unwrap();
+    assert_eq!(reparsed.metadata(), &metadata);
+    assert_eq!(reparsed.world_string(), world.world_string());
+}
+
+#[test]
+fn v1_levels_keep_their_v1_world_string() {
+    for level in levels::LEVELS {
+        let world = World::try_from(level).unwrap();
+        let world_string = world.world_string();
+        assert!(!is_text_v2(&world_string));
+        assert_eq!(
+            World::try_from(world_string.as_str())
+                .unwrap()
+                .world_string(),
+            world_string
+        );
+        // The same levels can also be written and read back in text v2.
+        let v2 = to_text_v2_string(&world.get_config()).unwrap();
+        let reparsed = World::try_from(v2.as_str()).unwrap();
+        assert_eq!(reparsed.world_string(), world_string);
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{ParseError, Position, World};
 
-use super::parse;
+use super::{PositionsConfig, TomlConfig, parse};
+use crate::core::{
+    levels,
+    parsing::{LevelMetadata, Span, WorldConfig},
+};
+use rstest::rstest;
 
 #[test]
 fn invalid_toml_field() {
@@ -42,9 +47,11 @@ world_string = "S0 X"
         Err(ParseError::InconsistentWorldStringWidth {
             toml_width,
             world_str_width,
+            span,
         }) => {
             assert_eq!(toml_width, 10);
             assert_eq!(world_str_width, 2);
+            assert_eq!(span, Some(Span::new(2, 9, "10")));
         }
         _ => panic!("Should return a ParseError::InconsistentWorldStringWidth"),
     }
@@ -61,9 +68,11 @@ world_string = "S0 X"
         Err(ParseError::InconsistentWorldStringHeight {
             toml_height,
             world_str_height,
+            span,
         }) => {
             assert_eq!(toml_height, 10);
             assert_eq!(world_str_height, 1);
+            assert_eq!(span, Some(Span::new(2, 10, "10")));
         }
         _ => panic!("Should return a ParseError::InconsistentWorldStringWidth"),
     }
@@ -207,3 +216,170 @@ exits = [{ col = 4 }]
         assert_eq!(starts.len(), 9);
     }
 }
+
+#[test]
+fn unknown_toml_key_has_a_span() {
+    let toml_content = "world_string = \"S0 X\"\n  invalid_field = 25\n";
+    let error = parse(toml_content).unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(2, 3, "invalid_field")));
+}
+
+#[test]
+fn world_string_errors_are_located_in_the_file() {
+    let toml_content = "name = \"level\"\nworld_string = \"\"\"\nS0 . X\nS1 L0Q X\n\"\"\"\n";
+    let error = parse(toml_content).unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(4, 4, "L0Q")));
+    assert!(
+        error
+            .report(toml_content)
+            .ends_with("4 | S1 L0Q X\n  |    ^^^")
+    );
+
+    // Escaped newlines do not start a new line of the file.
+    let toml_content = "world_string = \"S0 . X\\nS1 ? X\"\n";
+    let error = parse(toml_content).unwrap_err();
+    assert_eq!(error.span(), Some(&Span::new(1, 28, "?")));
+}
+
+/// The world string errors point at the text of the file that produces the token, whatever the
+/// form of the TOML string.
+#[rstest]
+#[case::basic("world_string = \"S0 . X\\nS1 ? X\"\n", Span::new(1, 28, "?"))]
+#[case::literal("world_string = 'S0 . X ? X'\n", Span::new(1, 24, "?"))]
+#[case::multi_line_basic(
+    "world_string = \"\"\"\nS0 . X\nS1 ? X\n\"\"\"\n",
+    Span::new(3, 4, "?")
+)]
+#[case::multi_line_basic_crlf(
+    "world_string = \"\"\"\r\nS0 . X\r\nS1 ? X\r\n\"\"\"\r\n",
+    Span::new(3, 4, "?")
+)]
+#[case::multi_line_literal("world_string = '''\nS0 . X\nS1 ? X\n'''\n", Span::new(3, 4, "?"))]
+#[case::multi_line_literal_on_the_first_line(
+    "world_string = '''S0 . X\nS1 ? X'''\n",
+    Span::new(2, 4, "?")
+)]
+#[case::escapes(
+    r#"world_string = "S0\t.\u0020X\nS1 L0\u0051 X""#,
+    Span::new(1, 34, r"L0\u0051")
+)]
+#[case::escaped_quote_and_backslash(
+    r#"world_string = "S0 . X\nS1 \"\\ X""#,
+    Span::new(1, 28, r#"\"\\"#)
+)]
+#[case::line_ending_backslash(
+    "world_string = \"\"\"S0 . X\nS1 \\\n    ? X\"\"\"\n",
+    Span::new(3, 5, "?")
+)]
+fn world_string_errors_are_located_in_every_string_form(
+    #[case] toml_content: &str,
+    #[case] expected: Span,
+) {
+    let error = parse(toml_content).unwrap_err();
+    assert_eq!(error.span(), Some(&expected));
+}
+
+#[test]
+fn toml_value_errors_have_a_span() {
+    let toml_content = "width = 3\nheight = 2\nstarts = [{ i = 0, j = 0 }]\nexits = [\n    { i = 1, j = 2 },\n    { i = 2, j = 0 },\n]\n";
+    let error = parse(toml_content).unwrap_err();
+    assert!(matches!(
+        error,
+        ParseError::PositionOutOfBounds { i: 2, j: 0, .. }
+    ));
+    assert_eq!(error.span(), Some(&Span::new(6, 5, "{ i = 2, j = 0 }")));
+
+    let toml_content = "n_agents = 1\nworld_string = \"S0 S1 X X\"\n";
+    let error = parse(toml_content).unwrap_err();
+    assert!(matches!(
+        error,
+        ParseError::InconsistentNumberOfAgents { .. }
+    ));
+    assert_eq!(error.span(), Some(&Span::new(1, 12, "1")));
+}
+
+/// Check that writing `config` in TOML and parsing it back gives the same world.
+fn assert_toml_round_trip(config: &WorldConfig) -> String {
+    let toml_string = TomlConfig::from(config).to_toml_string();
+    let parsed = parse(&toml_string).unwrap();
+    assert_eq!(parsed.metadata(), config.metadata());
+    assert_eq!(parsed.random_starts(), config.random_starts());
+    assert_eq!(parsed.gems(), config.gems());
+    let expected = World::try_from(config.to_string()).unwrap();
+    let actual = parsed.into_world().unwrap();
+    assert_eq!(actual.world_string(), expected.world_string());
+    toml_string
+}
+
+#[test]
+fn compact_positions_partition_the_input() {
+    let (width, height) = (30, 20);
+    let border: Vec<Position> = (0..height)
+        .flat_map(|i| (0..width).map(move |j| Position { i, j }))
+        .filter(|p| p.i == 0 || p.j == 0 || p.i == height - 1 || p.j == width - 1)
+        .collect();
+    let compact = PositionsConfig::compact(&border, width, height);
+    assert_eq!(compact.len(), 4);
+    let mut expanded: Vec<Position> = compact
+        .iter()
+        .flat_map(|c| c.to_positions(width, height).unwrap())
+        .collect();
+    assert_eq!(expanded.len(), border.len(), "Entries must not overlap");
+    expanded.sort_by_key(|p| (p.i, p.j));
+    assert_eq!(expanded, border);
+
+    let column: Vec<Position> = (0..height).map(|i| Position { i, j: 3 }).collect();
+    assert!(matches!(
+        PositionsConfig::compact(&column, width, height).as_slice(),
+        [PositionsConfig::Column { col: 3 }]
+    ));
+}
+
+#[test]
+fn toml_output_of_levels_round_trips() {
+    for level in levels::LEVELS {
+        let config = World::try_from(level).unwrap().get_config();
+        assert_toml_round_trip(&config);
+    }
+}
+
+#[test]
+fn toml_output_of_random_starts_round_trips() {
+    let config = parse(
+        r#"
+        name = "Quotes \" and # are fine"
+        t_max = 12
+        width = 40
+        height = 40
+        n_agents = 3
+        walls = [{ row = 0 }, { row = 39 }, { col = 0 }, { col = 39 }]
+        exits = [{ i_min = 37, i_max = 38, j_min = 36, j_max = 38 }]
+        gems = [{ i = 5, j = 5 }, { i = 2, j = 2 }, { i = 3, j = 7 }]
+        starts = [{ i_min = 1, i_max = 2, j_min = 1, j_max = 38 }]
+
+        [[agents]]
+        starts = [{ i = 10, j = 10 }]
+        "#,
+    )
+    .unwrap();
+    let toml_string = assert_toml_round_trip(&config);
+    assert!(
+        toml_string.lines().count() < 30,
+        "The TOML output should be compact:\n{toml_string}"
+    );
+    assert!(toml_string.contains("starts = [{ i_min = 1, i_max = 2, j_min = 1, j_max = 38 }]"));
+    // Gems are not in reading order and must keep their order.
+    assert!(toml_string.contains("{ i = 5, j = 5 },\n    { i = 2, j = 2 }"));
+}
+
+#[test]
+fn toml_output_of_a_world_with_metadata_round_trips() {
+    let mut world = World::try_from("---\nS0 S0+S1 G+S1\n X   X   L0W").unwrap();
+    world.set_metadata(LevelMetadata {
+        name: Some("Multi\nline".into()),
+        author: Some("Jane".into()),
+        t_max: None,
+    });
+    let toml_string = assert_toml_round_trip(&world.get_config());
+    assert!(toml_string.contains(r#"direction = "West""#));
+}
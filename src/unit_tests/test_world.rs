@@ -1,12 +1,18 @@
 use core::panic;
-use std::vec;
+use std::{
+    collections::{HashMap, HashSet},
+    vec,
+};
+
+use itertools::{Itertools, izip};
 
 use crate::{
-    Action, ParseError, Position, RuntimeWorldError, WorldEvent, agent::Agent, core::WorldState,
-    tiles::Laser,
+    Action, ConflictRule, DistanceOptions, DistanceOrigin, ParseError, Position, RuntimeWorldError,
+    WorldEvent, agent::Agent, core::WorldState, tiles::Laser,
 };
 
 use super::World;
+use rstest::rstest;
 
 fn pos(i: usize, j: usize) -> Position {
     Position { i, j }
@@ -48,7 +54,7 @@ fn test_tile_type() {
         .1;
     assert_eq!(source.agent_id(), 0);
     let laser = get_laser(&world, Position { i: 1, j: 1 });
-    assert_eq!(laser.agent_id(), 0);
+    assert_eq!(laser.agent_id(world.beams()), 0);
     let n_exits_at_1_1 = world
         .exits
         .iter()
@@ -128,9 +134,9 @@ fn test_laser_blocked_on_reset() -> Result<(), RuntimeWorldError> {
     w.reset();
     // All agents should be alive
     assert!(w.agents().iter().all(|a| a.is_alive()));
-    assert!(get_laser(&w, (1, 2).into()).is_on());
-    assert!(get_laser(&w, (2, 2).into()).is_off());
-    assert!(get_laser(&w, (3, 2).into()).is_off());
+    assert!(get_laser(&w, (1, 2).into()).is_on(w.beams()));
+    assert!(get_laser(&w, (2, 2).into()).is_off(w.beams()));
+    assert!(get_laser(&w, (3, 2).into()).is_off(w.beams()));
     Ok(())
 }
 
@@ -149,7 +155,7 @@ fn test_facing_lasers() -> Result<(), RuntimeWorldError> {
     w.step(&[Action::West, Action::West]).unwrap();
     assert!(w.agents().iter().all(Agent::is_alive));
     for (_, laser) in w.lasers() {
-        assert!(laser.is_off());
+        assert!(laser.is_off(w.beams()));
     }
     Ok(())
 }
@@ -266,18 +272,18 @@ fn test_complex_laser_blocking() -> Result<(), RuntimeWorldError> {
     w.reset();
 
     let laser = get_laser(&w, (0, 3).into());
-    assert!(laser.is_on());
+    assert!(laser.is_on(w.beams()));
 
     let state = WorldState::new_alive([(0, 2).into(), (0, 3).into()].into(), [false; 5].into());
     w.set_state(&state).unwrap();
     let laser = get_laser(&w, (0, 3).into());
-    assert!(laser.is_off());
+    assert!(laser.is_off(w.beams()));
     assert!(w.agents().iter().all(Agent::is_alive));
 
     w.step(&[Action::Stay, Action::East]).unwrap();
     assert!(w.agents().iter().all(Agent::is_alive));
     let laser = get_laser(&w, (0, 3).into());
-    assert!(laser.is_off());
+    assert!(laser.is_off(w.beams()));
     Ok(())
 }
 
@@ -418,6 +424,56 @@ fn test_vertex_conflict() {
     assert_eq!(pos[1], (2, 0));
 }
 
+#[rstest]
+#[case(ConflictRule::NoFollowing, [pos(0, 0), pos(2, 0)])]
+#[case(ConflictRule::AllowFollowing, [pos(0, 0), pos(2, 0)])]
+#[case(ConflictRule::Priority, [pos(1, 0), pos(2, 0)])]
+fn vertex_conflicts_follow_the_conflict_rule(
+    #[case] rule: ConflictRule,
+    #[case] expected: [Position; 2],
+) {
+    let mut w = World::try_from("S0 X .\n.  . .\nS1 X .").unwrap();
+    w.set_conflict_rule(rule);
+    w.step(&[Action::South, Action::North]).unwrap();
+    assert_eq!(w.agents_positions(), &expected);
+}
+
+#[rstest]
+#[case(ConflictRule::NoFollowing)]
+#[case(ConflictRule::Priority)]
+fn following_is_not_available_without_the_rule(#[case] rule: ConflictRule) {
+    let mut w = World::try_from("S0 S1 . X X").unwrap();
+    w.set_conflict_rule(rule);
+    assert!(!w.available_actions()[0].contains(&Action::East));
+    assert!(w.step(&[Action::East, Action::East]).is_err());
+}
+
+#[test]
+fn allow_following_moves_agents_in_a_row() {
+    let mut w = World::try_from("S0 S1 . X X").unwrap();
+    w.set_conflict_rule(ConflictRule::AllowFollowing);
+    w.step(&[Action::East, Action::East]).unwrap();
+    assert_eq!(w.agents_positions(), &[pos(0, 1), pos(0, 2)]);
+    // Agent 1 stays, so agent 0 cannot follow it and bounces back.
+    w.step(&[Action::East, Action::Stay]).unwrap();
+    assert_eq!(w.agents_positions(), &[pos(0, 1), pos(0, 2)]);
+}
+
+#[test]
+fn allow_following_bounces_swaps_and_their_followers() {
+    let mut w = World::try_from("S0 S1 S2 X X X").unwrap();
+    w.set_conflict_rule(ConflictRule::AllowFollowing);
+    w.step(&[Action::East, Action::East, Action::West]).unwrap();
+    assert_eq!(w.agents_positions(), &[pos(0, 0), pos(0, 1), pos(0, 2)]);
+}
+
+#[test]
+fn the_conflict_rule_survives_a_clone() {
+    let mut w = World::try_from("S0 S1 . X X").unwrap();
+    w.set_conflict_rule(ConflictRule::AllowFollowing);
+    assert_eq!(w.clone().conflict_rule(), ConflictRule::AllowFollowing);
+}
+
 #[test]
 fn test_reset() {
     let mut w = World::try_from("S0 G X").unwrap();
@@ -772,3 +828,654 @@ fn test_beam_two_sources() {
     let l1_beam = w.beam(l1_first).unwrap();
     assert_eq!(l1_beam.count(), 1);
 }
+
+#[test]
+fn start_assignments_never_share_a_position() {
+    let world = World::try_from(
+        r#"
+world_string = "X . @ . X"
+[[agents]]
+start_positions = [{ i = 0, j = 1 }, { i = 0, j = 3 }]
+[[agents]]
+start_positions = [{ i = 0, j = 1 }, { i = 0, j = 3 }]
+"#,
+    )
+    .unwrap();
+    let assignments = world.start_assignments().collect::<Vec<_>>();
+    assert_eq!(
+        assignments,
+        [vec![pos(0, 1), pos(0, 3)], vec![pos(0, 3), pos(0, 1)]]
+    );
+}
+
+#[test]
+fn the_world_is_deterministic_by_default() {
+    let mut world = World::try_from("S0 . X").unwrap();
+    assert_eq!(world.slip_probability(), 0.0);
+    for _ in 0..10 {
+        world.reset();
+        assert_eq!(world.step(&[Action::East]).unwrap(), vec![]);
+        assert_eq!(world.agents_positions(), &vec![pos(0, 1)]);
+    }
+}
+
+#[rstest]
+#[case(-0.1)]
+#[case(1.5)]
+#[case(f64::NAN)]
+fn invalid_slip_probabilities_are_rejected(#[case] probability: f64) {
+    let mut world = World::try_from("S0 . X").unwrap();
+    assert!(matches!(
+        world.set_slip_probability(probability),
+        Err(RuntimeWorldError::InvalidSlipProbability { .. })
+    ));
+    assert_eq!(world.slip_probability(), 0.0);
+}
+
+#[test]
+fn slips_are_reported_when_they_change_the_action() {
+    let mut world = World::try_from("S0 . X").unwrap();
+    world.set_slip_probability(1.0).unwrap();
+    let mut outcomes = HashSet::new();
+    for _ in 0..50 {
+        world.reset();
+        let events = world.step(&[Action::East]).unwrap();
+        let slipped = events == [WorldEvent::AgentSlipped { agent_id: 0 }];
+        assert!(slipped || events.is_empty());
+        outcomes.insert((slipped, world.agents_positions()[0]));
+    }
+    // The agent draws East or Stay, and only Stay is a slip.
+    assert_eq!(
+        outcomes,
+        HashSet::from([(false, pos(0, 1)), (true, pos(0, 0))])
+    );
+}
+
+#[test]
+fn slips_follow_the_seed_of_the_world() {
+    let run = |seed: u64| {
+        let mut world = World::get_level(3).unwrap();
+        world.set_slip_probability(0.5).unwrap();
+        world.seed(seed);
+        world.reset();
+        let mut trajectory = vec![];
+        for _ in 0..20 {
+            let actions = world.available_actions().iter().map(|a| a[0]).collect_vec();
+            let events = world.step(&actions).unwrap();
+            trajectory.push((events, world.get_state()));
+        }
+        trajectory
+    };
+    assert_eq!(run(42), run(42));
+}
+
+#[test]
+fn transitions_without_slip_are_the_step() {
+    let mut world = World::get_level(4).unwrap();
+    let actions = world.available_actions().iter().map(|a| a[0]).collect_vec();
+    let state = world.get_state();
+    let transitions = world.transitions(&actions).unwrap();
+    assert_eq!(world.get_state(), state);
+    world.step(&actions).unwrap();
+    assert_eq!(transitions, vec![(world.get_state(), 1.0)]);
+}
+
+#[test]
+fn transitions_spread_the_slip_probability() {
+    // Agent 0 can go East, South or stay, agent 1 is on its exit and can only stay.
+    let mut world = World::try_from("S0 . .\n. . X\n. X S1").unwrap();
+    world
+        .set_state(&WorldState::new_alive(vec![pos(0, 0), pos(1, 2)], vec![]))
+        .unwrap();
+    world.set_slip_probability(0.4).unwrap();
+    let state = world.get_state();
+    let transitions = world.transitions(&[Action::East, Action::Stay]).unwrap();
+    assert_eq!(world.get_state(), state);
+
+    let probability_of = |position: Position| {
+        transitions
+            .iter()
+            .find(|(s, _)| s.agents_positions[0] == position)
+            .map(|(_, p)| *p)
+            .unwrap()
+    };
+    // Slipping draws one of the three available actions, the chosen one included.
+    assert_eq!(transitions.len(), 3);
+    assert!((probability_of(pos(0, 1)) - (0.6 + 0.4 / 3.0)).abs() < 1e-9);
+    assert!((probability_of(pos(1, 0)) - 0.4 / 3.0).abs() < 1e-9);
+    assert!((probability_of(pos(0, 0)) - 0.4 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn transitions_match_the_frequencies_of_the_sampled_steps() {
+    let mut world = World::try_from("S0 . .\n. . X\n. X S1").unwrap();
+    let state = WorldState::new_alive(vec![pos(0, 0), pos(1, 2)], vec![]);
+    world.set_state(&state).unwrap();
+    world.set_slip_probability(0.4).unwrap();
+    world.seed(0);
+    let actions = [Action::East, Action::Stay];
+    let transitions = world.transitions(&actions).unwrap();
+    let n_samples = 6000;
+    let mut counts = HashMap::<WorldState, usize>::new();
+    for _ in 0..n_samples {
+        world.set_state(&state).unwrap();
+        world.step(&actions).unwrap();
+        *counts.entry(world.get_state()).or_default() += 1;
+    }
+    assert_eq!(counts.len(), transitions.len());
+    for (next, probability) in transitions {
+        let frequency = counts[&next] as f64 / n_samples as f64;
+        assert!(
+            (frequency - probability).abs() < 0.02,
+            "{next:?}: sampled {frequency}, expected {probability}"
+        );
+    }
+}
+
+#[test]
+fn transitions_cover_the_sampled_steps() {
+    let mut world = World::get_level(6).unwrap();
+    world.set_slip_probability(0.3).unwrap();
+    world.reset();
+    for _ in 0..20 {
+        let actions = world.available_actions().iter().map(|a| a[0]).collect_vec();
+        let transitions = world.transitions(&actions).unwrap();
+        let total: f64 = transitions.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        world.step(&actions).unwrap();
+        let reached = world.get_state();
+        assert!(transitions.iter().any(|(s, _)| *s == reached));
+        if world.agents().iter().any(|a| a.is_dead()) {
+            break;
+        }
+    }
+}
+
+/// Everything that `step` depends on, to compare a restored world with a freshly set one.
+type FullState = (WorldState, Vec<Vec<Action>>, Vec<bool>, Vec<Option<usize>>);
+
+fn full_state(world: &World) -> FullState {
+    let lasers = world
+        .lasers()
+        .iter()
+        .map(|(_, l)| l.is_on(world.beams()))
+        .collect();
+    let occupancy = (0..world.height())
+        .flat_map(|i| (0..world.width()).map(move |j| pos(i, j)))
+        .map(|p| world.at(&p).unwrap().agent())
+        .collect();
+    (
+        world.get_state(),
+        world.available_actions().clone(),
+        lasers,
+        occupancy,
+    )
+}
+
+#[test]
+fn restore_matches_set_state() {
+    let mut world = World::get_level(6).unwrap();
+    world.set_slip_probability(0.3).unwrap();
+    world.reset();
+    let mut snapshots = vec![(world.snapshot(), world.get_state())];
+    for _ in 0..30 {
+        let actions = world.available_actions().iter().map(|a| a[0]).collect_vec();
+        world.step(&actions).unwrap();
+        snapshots.push((world.snapshot(), world.get_state()));
+    }
+    let mut reference = World::get_level(6).unwrap();
+    for (snapshot, state) in snapshots.iter().rev() {
+        world.restore(snapshot).unwrap();
+        reference.set_state(state).unwrap();
+        assert_eq!(full_state(&world), full_state(&reference));
+    }
+}
+
+#[test]
+fn restored_worlds_step_like_the_original() {
+    // Agent 0 blocks the laser for agent 1 until it moves away and collects the gem.
+    let mut world = World::try_from("L0S . .\nS0 G X\nS1 . X").unwrap();
+    let snapshot = world.snapshot();
+    let plan = [[Action::East, Action::East], [Action::East, Action::East]];
+    let run = |world: &mut World| {
+        plan.iter()
+            .map(|actions| (world.step(actions).unwrap(), full_state(world)))
+            .collect_vec()
+    };
+    let first = run(&mut world);
+    world.restore(&snapshot).unwrap();
+    assert_eq!(run(&mut world), first);
+}
+
+#[test]
+fn undo_and_redo_steps() {
+    let mut world = World::try_from("S0 G X").unwrap();
+    assert!(!world.undo());
+    let start = full_state(&world);
+    world.push_snapshot();
+    let events = world.step(&[Action::East]).unwrap();
+    assert_eq!(events, vec![WorldEvent::GemCollected { agent_id: 0 }]);
+    let after = full_state(&world);
+    assert_eq!(world.history_len(), 1);
+
+    assert!(world.undo());
+    assert_eq!(full_state(&world), start);
+    assert_eq!(world.history_len(), 0);
+    assert!(world.redo());
+    assert_eq!(full_state(&world), after);
+    assert!(!world.redo());
+
+    assert!(world.undo());
+    assert_eq!(world.step(&[Action::East]).unwrap(), events);
+    world.push_snapshot();
+    assert!(
+        !world.redo(),
+        "Pushing a snapshot discards what could be redone"
+    );
+}
+
+#[test]
+fn reset_and_set_state_clear_the_history() {
+    let mut world = World::try_from("S0 G X").unwrap();
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    assert!(world.undo());
+    world.reset();
+    assert_eq!(world.history_len(), 0);
+    assert!(!world.undo());
+    assert!(!world.redo());
+
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    assert!(world.undo());
+    let invalid = WorldState {
+        agents_positions: vec![Position { i: 0, j: 3 }],
+        gems_collected: vec![false],
+        agents_alive: vec![true],
+    };
+    assert!(world.set_state(&invalid).is_err());
+    assert_eq!(
+        world.history_len(),
+        1,
+        "A failed set_state keeps the history"
+    );
+    let state = WorldState {
+        agents_positions: vec![Position { i: 0, j: 0 }],
+        gems_collected: vec![false],
+        agents_alive: vec![true],
+    };
+    world.set_state(&state).unwrap();
+    assert_eq!(world.history_len(), 0);
+    assert!(!world.undo());
+    assert!(!world.redo());
+}
+
+#[test]
+fn restore_rejects_snapshots_of_other_worlds() {
+    let snapshot = World::try_from("S0 G X").unwrap().snapshot();
+    let mut world = World::try_from("S0 . X").unwrap();
+    let state = world.get_state();
+    assert!(matches!(
+        world.restore(&snapshot),
+        Err(RuntimeWorldError::InvalidSnapshot { .. })
+    ));
+    assert_eq!(world.get_state(), state);
+}
+
+#[test]
+fn world_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<World>();
+}
+
+#[test]
+fn cloned_worlds_do_not_share_laser_beams() {
+    let mut world = World::try_from("L0S . .\nS0 G X\nS1 . X").unwrap();
+    world.reset();
+    let original = full_state(&world);
+    let mut clone = world.clone();
+    let handle = std::thread::spawn(move || {
+        clone.source_at_mut((0, 0).into()).unwrap().disable();
+        clone.step(&[Action::East, Action::Stay]).unwrap();
+        full_state(&clone)
+    });
+    let stepped = handle.join().unwrap();
+    assert_ne!(stepped, original);
+    assert_eq!(full_state(&world), original);
+    assert!(world.beams().iter().all(|beam| beam.is_enabled()));
+}
+
+#[test]
+fn cloned_worlds_start_without_history() {
+    let mut world = World::try_from("S0 G X").unwrap();
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    world.push_snapshot();
+    world.step(&[Action::East]).unwrap();
+    assert!(world.undo());
+
+    let mut clone = world.clone();
+    assert_eq!(clone.history_len(), 0);
+    assert!(!clone.undo());
+    assert!(!clone.redo());
+    assert_eq!(full_state(&clone), full_state(&world));
+    assert_eq!(world.history_len(), 1);
+    assert!(world.redo());
+}
+
+/// The bitboards that `World` maintains, recomputed from its tiles and beams.
+fn expected_bitboards(world: &World) -> (Vec<Position>, Vec<Position>, Vec<Vec<Position>>) {
+    let occupied = world
+        .tiles()
+        .into_iter()
+        .filter(|(_, tile)| tile.is_occupied())
+        .map(|(pos, _)| pos)
+        .collect();
+    let gems = izip!(world.gems_positions(), world.gems())
+        .filter(|(_, gem)| !gem.is_collected())
+        .map(|(pos, _)| pos)
+        .sorted_by_key(|pos| (pos.i, pos.j))
+        .collect();
+    let beams = (0..world.n_agents())
+        .map(|colour| {
+            world
+                .lasers()
+                .into_iter()
+                .filter(|(_, l)| l.agent_id(world.beams()) == colour && l.is_on(world.beams()))
+                .map(|(pos, _)| pos)
+                .sorted_by_key(|pos| (pos.i, pos.j))
+                .dedup()
+                .collect()
+        })
+        .collect();
+    (occupied, gems, beams)
+}
+
+fn actual_bitboards(world: &World) -> (Vec<Position>, Vec<Position>, Vec<Vec<Position>>) {
+    let bitboards = world.bitboards();
+    let beams = (0..world.n_agents())
+        .map(|colour| match bitboards.beam_coverage(colour) {
+            Some(coverage) => coverage.iter().collect(),
+            None => vec![],
+        })
+        .collect();
+    (
+        bitboards.occupied().iter().collect(),
+        bitboards.gems().iter().collect(),
+        beams,
+    )
+}
+
+#[test]
+fn bitboards_follow_the_steps() {
+    let mut world = World::try_from(
+        "
+        S0  .  G  S1
+        .   .  G  .
+        L1E .  .  .
+        X   .  V  X",
+    )
+    .unwrap();
+    world.reset();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+    for actions in [
+        [Action::South, Action::South],
+        [Action::East, Action::West],
+        [Action::North, Action::Stay],
+        [Action::East, Action::Stay],
+        [Action::Stay, Action::South],
+        [Action::South, Action::East],
+        [Action::South, Action::Stay],
+    ] {
+        world.step(&actions).unwrap();
+        assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+    }
+    assert!(world.agents()[0].is_dead());
+    assert_eq!(world.n_gems_collected(), 2);
+
+    world.source_at_mut(pos(2, 0)).unwrap().disable();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+    world.source_at_mut(pos(2, 0)).unwrap().set_agent_id(0);
+    world.source_at_mut(pos(2, 0)).unwrap().enable();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+
+    world.reset();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+}
+
+#[test]
+fn bitboards_follow_set_state_and_restore() {
+    let mut world = World::try_from("L0S . G\nS0 G X\nS1 . X").unwrap();
+    let snapshot = world.snapshot();
+    world
+        .set_state(&WorldState::new_alive(
+            vec![pos(2, 1), pos(1, 2)],
+            vec![true, false],
+        ))
+        .unwrap();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+    world.restore(&snapshot).unwrap();
+    assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+}
+
+#[test]
+fn bitboards_follow_rollouts_on_the_levels() {
+    for level in 1..=6 {
+        let mut world = World::get_level(level).unwrap();
+        world.reset();
+        let start = world.snapshot();
+        for t in 0..100 {
+            let actions = world
+                .available_actions()
+                .iter()
+                .enumerate()
+                .map(|(agent_id, actions)| actions[(t * 7 + agent_id * 3) % actions.len()])
+                .collect_vec();
+            world.push_snapshot();
+            world.step(&actions).unwrap();
+            assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+            if t % 10 == 9 {
+                world.undo();
+                assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+            }
+            if world.agents().iter().any(Agent::is_dead) {
+                world.restore(&start).unwrap();
+                assert_eq!(actual_bitboards(&world), expected_bitboards(&world));
+            }
+        }
+    }
+}
+
+#[test]
+fn bitboards_of_the_layout() {
+    let mut world = World::try_from("S0 @ X\nL0E . X\nS1 V .").unwrap();
+    let bitboards = world.bitboards();
+    assert!(bitboards.walls().contains(&pos(0, 1)));
+    assert_eq!(bitboards.sources().iter().collect_vec(), [pos(1, 0)]);
+    assert_eq!(bitboards.voids().iter().collect_vec(), [pos(2, 1)]);
+    assert_eq!(
+        bitboards.exits().iter().collect_vec(),
+        [pos(0, 2), pos(1, 2)]
+    );
+
+    world
+        .set_exit_positions(vec![pos(1, 2), pos(2, 2)])
+        .unwrap();
+    assert_eq!(
+        world.bitboards().exits().iter().collect_vec(),
+        [pos(1, 2), pos(2, 2)]
+    );
+}
+
+#[test]
+fn collect_gem_below_a_laser() {
+    let mut world = World::try_from("L0E G S0\nS1 . X\n. . X").unwrap();
+    assert!(matches!(
+        world.collect_gem(pos(1, 1)),
+        Err(RuntimeWorldError::NotAGem { .. })
+    ));
+    world.collect_gem(pos(0, 1)).unwrap();
+    assert_eq!(world.n_gems_collected(), 1);
+    assert_eq!(world.get_state().gems_collected, [true]);
+    assert!(world.bitboards().gems().is_empty());
+}
+
+#[test]
+fn help_events_are_disabled_by_default() {
+    let mut world = World::try_from("L0E . .\n. S0 S1\nX X .").unwrap();
+    world.reset();
+    assert!(!world.help_events());
+    let events = world.step(&[Action::North, Action::North]).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn help_events_follow_the_blocking_of_the_beam() {
+    let mut world = World::try_from("L0E . .\n. S0 S1\nX X .").unwrap();
+    world.set_help_events(true);
+    world.reset();
+    let help = WorldEvent::Help {
+        helper: 0,
+        beneficiary: 1,
+        laser_id: 0,
+    };
+    let events = world.step(&[Action::North, Action::North]).unwrap();
+    assert_eq!(
+        events,
+        [
+            WorldEvent::LaserBlocked {
+                agent_id: 0,
+                laser_id: 0
+            },
+            help.clone()
+        ]
+    );
+    // Help is reported in every state where it occurs.
+    assert_eq!(world.step(&[Action::Stay, Action::Stay]).unwrap(), [help]);
+    assert!(
+        world
+            .step(&[Action::Stay, Action::South])
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        world.step(&[Action::South, Action::Stay]).unwrap(),
+        [WorldEvent::LaserUnblocked {
+            agent_id: 0,
+            laser_id: 0
+        }]
+    );
+}
+
+#[test]
+fn dead_agents_are_not_helped() {
+    let mut world = World::try_from("L0E . .\n. S0 S1\nX X .").unwrap();
+    world.set_help_events(true);
+    world.reset();
+    let events = world.step(&[Action::Stay, Action::North]).unwrap();
+    assert_eq!(events, [WorldEvent::AgentDied { agent_id: 1 }]);
+}
+
+#[test]
+fn distance_map_to_the_exits() {
+    let world = World::try_from("S0 . V\n. @ .\n. . X").unwrap();
+    let map = world
+        .distance_map(DistanceOrigin::Exits, &DistanceOptions::default())
+        .unwrap();
+    let expected = [
+        [Some(4), Some(5), None],
+        [Some(3), None, Some(1)],
+        [Some(2), Some(1), Some(0)],
+    ];
+    for (i, row) in expected.iter().enumerate() {
+        for (j, distance) in row.iter().enumerate() {
+            assert_eq!(map.distance(&pos(i, j)), *distance, "at {:?}", pos(i, j));
+        }
+    }
+    assert_eq!(
+        map.path(&pos(0, 0)).unwrap(),
+        [pos(0, 0), pos(1, 0), pos(2, 0), pos(2, 1), pos(2, 2)]
+    );
+    assert_eq!(map.path(&pos(1, 1)), None);
+
+    let map = world
+        .distance_map(
+            DistanceOrigin::Exits,
+            &DistanceOptions::default().with_ignore_voids(),
+        )
+        .unwrap();
+    assert_eq!(map.distance(&pos(0, 2)), Some(2));
+    assert_eq!(map.distance(&pos(0, 1)), Some(3));
+}
+
+#[test]
+fn distance_map_from_a_position() {
+    let world = World::try_from("S0 . .\nL1E . .\nX X S1").unwrap();
+    let map = world
+        .distance_map(pos(0, 0), &DistanceOptions::default())
+        .unwrap();
+    assert_eq!(map.distance(&pos(2, 1)), Some(3));
+    assert_eq!(
+        map.path(&pos(2, 1)).unwrap(),
+        [pos(0, 0), pos(0, 1), pos(1, 1), pos(2, 1)]
+    );
+    // Sources are not walkable and exits are dead ends.
+    assert_eq!(map.distance(&pos(1, 0)), None);
+    assert_eq!(map.distance(&pos(2, 0)), None);
+    assert_eq!(map.path(&pos(0, 0)).unwrap(), [pos(0, 0)]);
+    assert_eq!(map.distance(&pos(5, 5)), None);
+}
+
+#[test]
+fn distance_map_around_lasers_and_agents() {
+    let world = World::try_from("S0 . .\nL1E . .\nX X S1").unwrap();
+    let map = world
+        .distance_map(
+            pos(0, 0),
+            &DistanceOptions::default().with_lasers_as_walls(1),
+        )
+        .unwrap();
+    assert_eq!(map.distance(&pos(0, 2)), Some(2));
+    assert_eq!(map.distance(&pos(2, 1)), None);
+    // There is no laser of colour 0.
+    let map = world
+        .distance_map(
+            pos(0, 0),
+            &DistanceOptions::default().with_lasers_as_walls(0),
+        )
+        .unwrap();
+    assert_eq!(map.distance(&pos(2, 1)), Some(3));
+
+    let options = DistanceOptions::default().with_agents_as_obstacles();
+    let map = world.distance_map(DistanceOrigin::Exits, &options).unwrap();
+    assert_eq!(map.distance(&pos(2, 2)), None);
+    assert_eq!(map.distance(&pos(1, 2)), Some(2));
+    // The agent at the origin does not block itself.
+    let map = world.distance_map(pos(0, 0), &options).unwrap();
+    assert_eq!(map.distance(&pos(0, 2)), Some(2));
+}
+
+#[rstest]
+#[case::wall(pos(0, 1), DistanceOptions::default())]
+#[case::laser_source(pos(1, 0), DistanceOptions::default())]
+#[case::void(pos(0, 2), DistanceOptions::default())]
+#[case::beam(pos(1, 1), DistanceOptions::default().with_lasers_as_walls(1))]
+fn distance_map_from_a_blocked_tile(#[case] origin: Position, #[case] options: DistanceOptions) {
+    let world = World::try_from("S0 @ V\nL1E . .\nX X S1").unwrap();
+    let map = world.distance_map(origin, &options).unwrap();
+    assert_eq!(map.iter().collect_vec(), [(origin, 0)]);
+    assert_eq!(map.path(&origin).unwrap(), [origin]);
+}
+
+#[test]
+fn distance_map_from_out_of_the_world() {
+    let world = World::try_from("S0 X").unwrap();
+    match world.distance_map(pos(1, 0), &DistanceOptions::default()) {
+        Err(RuntimeWorldError::OutOfWorldPosition { position }) => assert_eq!(position, pos(1, 0)),
+        other => panic!("Expected OutOfWorldPosition, got {other:?}"),
+    }
+}
@@ -0,0 +1,232 @@
+use boon::{Compiler, Schemas};
+use lle::{
+    Action, ParseError, Position, World, WorldEvent, WorldState,
+    solver::{CooperationConstraint, GemObjective, Plan, SolveMode, SolveSpec},
+};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Validate `value` against `definition` of the JSON schema, returning the error messages.
+fn validate(value: &Value, definition: &str) -> Result<(), String> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let index = compiler
+        .compile(
+            &format!("resources/lle_json_schema.json#/definitions/{definition}"),
+            &mut schemas,
+        )
+        .unwrap();
+    schemas.validate(value, index).map_err(|e| format!("{e:#}"))
+}
+
+fn assert_valid<T: Serialize>(value: &T, definition: &str) {
+    let value = serde_json::to_value(value).unwrap();
+    if let Err(e) = validate(&value, definition) {
+        panic!("{value} should be a valid {definition}:\n{e}");
+    }
+}
+
+fn assert_invalid(json: &str, definition: &str) {
+    let value: Value = serde_json::from_str(json).unwrap();
+    assert!(
+        validate(&value, definition).is_err(),
+        "{json} should not be a valid {definition}"
+    );
+}
+
+#[test]
+fn world_config_round_trips_through_json() {
+    let worlds = (1..=6)
+        .map(|level| World::get_level(level).unwrap())
+        .chain([World::try_from("name: Multi\n---\nS0 S0+S1 G+S1\n X   X  L0W").unwrap()]);
+    for world in worlds {
+        let json = world.get_config().to_json();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        if let Err(e) = validate(&value, "WorldConfig") {
+            panic!("{json} should be a valid WorldConfig:\n{e}");
+        }
+        let parsed = World::try_from(json.as_str()).unwrap();
+        assert_eq!(parsed.world_string(), world.world_string());
+        assert_eq!(parsed.metadata(), world.metadata());
+        assert_eq!(parsed.gems_positions(), world.gems_positions());
+    }
+}
+
+#[test]
+fn invalid_json_world_has_a_span() {
+    let json = "{\n  \"width\": 3,\n  \"height\": true\n}";
+    let Err(error) = World::try_from(json) else {
+        panic!("A height must be an integer");
+    };
+    assert!(matches!(error, ParseError::InvalidJson { .. }));
+    assert_eq!(error.span().map(|span| span.line), Some(3));
+
+    let Err(error) = World::try_from("{\"width\": 3, \"unknown\": 1}") else {
+        panic!("Unknown fields are rejected");
+    };
+    assert!(matches!(error, ParseError::InvalidJson { .. }));
+}
+
+#[test]
+fn world_state_round_trips_through_json() {
+    let mut world = World::try_from("S0 G X\nS1 . X").unwrap();
+    world.reset();
+    world.step(&[Action::East, Action::Stay]).unwrap();
+    let state = world.get_state();
+    assert_valid(&state, "WorldState");
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<WorldState>(&json).unwrap(), state);
+
+    assert_invalid(
+        r#"{"agents_positions": [{"i": 0}], "gems_collected": [], "agents_alive": [true]}"#,
+        "WorldState",
+    );
+}
+
+#[test]
+fn world_events_are_tagged_by_type() {
+    let event = WorldEvent::GemCollected { agent_id: 1 };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        serde_json::json!({"type": "GemCollected", "agent_id": 1})
+    );
+    for event in [
+        WorldEvent::AgentExit { agent_id: 0 },
+        WorldEvent::GemCollected { agent_id: 2 },
+        WorldEvent::AgentDied { agent_id: 3 },
+        WorldEvent::AgentSlipped { agent_id: 1 },
+        WorldEvent::Help {
+            helper: 0,
+            beneficiary: 1,
+            laser_id: 2,
+        },
+        WorldEvent::LaserBlocked {
+            agent_id: 0,
+            laser_id: 1,
+        },
+        WorldEvent::LaserUnblocked {
+            agent_id: 0,
+            laser_id: 1,
+        },
+    ] {
+        assert_valid(&event, "WorldEvent");
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<WorldEvent>(&json).unwrap(), event);
+    }
+    assert_invalid(r#"{"type": "AgentJumped", "agent_id": 0}"#, "WorldEvent");
+    assert_invalid(r#"{"type": "Help", "agent_id": 0}"#, "WorldEvent");
+    assert_invalid(
+        r#"{"type": "AgentExit", "agent_id": 0, "laser_id": 1}"#,
+        "WorldEvent",
+    );
+    assert_invalid(r#"{"type": "LaserBlocked", "agent_id": 0}"#, "WorldEvent");
+}
+
+#[test]
+fn actions_are_serialized_by_name() {
+    for action in [
+        Action::North,
+        Action::South,
+        Action::East,
+        Action::West,
+        Action::Stay,
+    ] {
+        assert_valid(&action, "Action");
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), action);
+    }
+    assert_eq!(serde_json::to_string(&Action::North).unwrap(), "\"North\"");
+}
+
+#[test]
+fn plan_round_trips_through_json() {
+    let plan = Plan::new(
+        vec![
+            vec![Action::East, Action::Stay],
+            vec![Action::East, Action::North],
+        ],
+        SolveMode::no_sequential_cooperation(3).unwrap(),
+        true,
+    );
+    assert_valid(&plan, "Plan");
+    let json = plan.to_json();
+    assert!(json.contains("\"no-sequence-3\""));
+    assert_eq!(Plan::from_json(&json).unwrap(), plan);
+
+    // The mode and the gem objective are optional.
+    let plan = Plan::from_json(r#"{"horizon": 1, "actions": [["Stay"]]}"#).unwrap();
+    assert_eq!(plan.mode, SolveSpec::new());
+    assert_eq!(plan.collect_gems, GemObjective::None);
+}
+
+#[test]
+fn plans_keep_their_solve_spec_and_gem_objective() {
+    let actions = vec![vec![Action::East, Action::Stay]];
+    let spec = SolveSpec::from(SolveMode::NoAsymmetricCooperation).with_constraint(
+        CooperationConstraint::ForbidHelp {
+            helper: 0,
+            beneficiary: 1,
+        },
+    );
+    let objectives = [
+        GemObjective::All,
+        GemObjective::AtLeast(2),
+        GemObjective::Subset(vec![Position { i: 0, j: 1 }]),
+        GemObjective::ByAgent(vec![(Position { i: 0, j: 1 }, 1)]),
+    ];
+    for gems in objectives {
+        let plan = Plan::new(actions.clone(), spec.clone(), gems);
+        assert_valid(&plan, "Plan");
+        let json = plan.to_json();
+        assert!(json.contains("\"no-asymmetric+forbid-help-0-1\""));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan);
+    }
+}
+
+#[test]
+fn plans_with_a_collect_gems_flag_are_still_read() {
+    let json = r#"{"horizon": 1, "collect_gems": true, "actions": [["Stay"]]}"#;
+    let value: Value = serde_json::from_str(json).unwrap();
+    assert!(validate(&value, "Plan").is_ok());
+    assert_eq!(
+        Plan::from_json(json).unwrap().collect_gems,
+        GemObjective::All
+    );
+    let json = r#"{"horizon": 1, "collect_gems": false, "actions": [["Stay"]]}"#;
+    assert_eq!(
+        Plan::from_json(json).unwrap().collect_gems,
+        GemObjective::None
+    );
+}
+
+#[test]
+fn invalid_plans_are_rejected() {
+    for json in [
+        r#"{"horizon": 2, "actions": [["Stay"]]}"#,
+        r#"{"horizon": 2, "actions": [["Stay"], ["Stay", "North"]]}"#,
+        r#"{"horizon": 1, "mode": "no-sequence-1", "actions": [["Stay"]]}"#,
+        r#"{"horizon": 1, "mode": "unknown", "actions": [["Stay"]]}"#,
+        r#"{"horizon": 1, "actions": [["Jump"]]}"#,
+        r#"{"horizon": 1, "collect_gems": {"at_most": 2}, "actions": [["Stay"]]}"#,
+        r#"{"horizon": 1, "gems": true, "actions": [["Stay"]]}"#,
+    ] {
+        assert!(Plan::from_json(json).is_err(), "{json} should be rejected");
+    }
+    assert_invalid(r#"{"horizon": 1}"#, "Plan");
+    assert_invalid(r#"{"horizon": 1, "actions": [["Jump"]]}"#, "Plan");
+    assert_invalid(
+        r#"{"horizon": 1, "collect_gems": {"at_most": 2}, "actions": [["Stay"]]}"#,
+        "Plan",
+    );
+    assert_invalid(
+        r#"{"horizon": 1, "gems": true, "actions": [["Stay"]]}"#,
+        "Plan",
+    );
+}
+
+#[test]
+fn world_config_schema_rejects_unknown_fields() {
+    assert_invalid(r#"{"width": 3, "colour": "red"}"#, "WorldConfig");
+    let position = serde_json::to_value(Position { i: 1, j: 2 }).unwrap();
+    assert!(validate(&position, "Position").is_ok());
+}
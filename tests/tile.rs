@@ -1,21 +1,20 @@
-use std::rc::Rc;
-
 use lle::{
     Agent, AgentId, Tile,
     tiles::{Direction, Gem, Laser, LaserBeam, Void},
 };
 
-fn make_laser(agent_id: AgentId, length: usize) -> Laser {
+fn make_laser(agent_id: AgentId, length: usize) -> (Laser, Vec<LaserBeam>) {
     let wrapped = Tile::Floor { agent: None };
-    let beam = Rc::new(LaserBeam::new(length, agent_id, Direction::East, 0));
-    Laser::new(wrapped, beam, 0)
+    let beams = vec![LaserBeam::new(length, agent_id, Direction::East, 0)];
+    (Laser::new(wrapped, 0, 0, Direction::East, 0), beams)
 }
 
 #[test]
 fn test_gem() {
     let mut agent = Agent::new(3);
+    let mut beams = vec![];
     let mut tile = Tile::Gem(Gem::default());
-    tile.reset();
+    tile.reset(&mut beams);
     assert_eq!(tile.agent(), None);
     if let Tile::Gem(g) = &tile {
         assert!(!g.is_collected());
@@ -24,14 +23,14 @@ fn test_gem() {
     assert!(!tile.is_occupied());
 
     // Enter the tile to collect the gem
-    tile.enter(&mut agent);
+    tile.enter(&mut agent, &mut beams);
     if let Tile::Gem(gem) = &tile {
         assert!(gem.is_collected());
     } else {
         panic!();
     }
     assert!(tile.is_occupied());
-    tile.leave();
+    tile.leave(&mut beams);
     assert_eq!(tile.agent(), None);
     if let Tile::Gem(gem) = &tile {
         assert!(gem.is_collected());
@@ -40,7 +39,7 @@ fn test_gem() {
     }
 
     // Reset the tile
-    tile.reset();
+    tile.reset(&mut beams);
     if let Tile::Gem(gem) = &tile {
         assert!(!gem.is_collected());
     } else {
@@ -52,12 +51,12 @@ fn test_gem() {
 
 #[test]
 fn test_laser_basic() {
-    let laser = make_laser(0, 4);
-    assert_eq!(laser.agent_id(), 0);
-    assert!(laser.is_on());
+    let (laser, mut beams) = make_laser(0, 4);
+    assert_eq!(laser.agent_id(&beams), 0);
+    assert!(laser.is_on(&beams));
 
     let mut laser = Tile::Laser(laser);
-    laser.reset();
+    laser.reset(&mut beams);
     assert!(laser.is_walkable());
     assert!(!laser.is_occupied());
 }
@@ -65,20 +64,20 @@ fn test_laser_basic() {
 #[test]
 fn test_laser_agent_survives() {
     let mut agent = Agent::new(0);
-    let laser = make_laser(0, 3);
-    assert!(laser.is_on());
+    let (laser, mut beams) = make_laser(0, 3);
+    assert!(laser.is_on(&beams));
     let mut tile = Tile::Laser(laser);
 
-    tile.pre_enter(&agent).unwrap();
+    tile.pre_enter(&agent, &mut beams).unwrap();
     assert!(!tile.is_occupied());
 
-    tile.enter(&mut agent);
+    tile.enter(&mut agent, &mut beams);
     assert!(agent.is_alive());
 
-    tile.leave();
+    tile.leave(&mut beams);
     assert!(agent.is_alive());
     if let Tile::Laser(laser) = &tile {
-        assert!(laser.is_on());
+        assert!(laser.is_on(&beams));
     }
     assert!(!tile.is_occupied());
 }
@@ -86,12 +85,12 @@ fn test_laser_agent_survives() {
 #[test]
 fn test_laser_agent_dies() {
     let mut agent = Agent::new(0);
-    let mut laser = make_laser(2, 3);
-    laser.pre_enter(&agent).unwrap();
-    assert!(laser.is_on());
+    let (mut laser, mut beams) = make_laser(2, 3);
+    laser.pre_enter(&agent, &mut beams).unwrap();
+    assert!(laser.is_on(&beams));
     assert!(agent.is_alive());
 
-    laser.enter(&mut agent);
+    laser.enter(&mut agent, &mut beams);
     assert!(agent.is_dead());
 }
 
@@ -103,3 +102,26 @@ fn test_void_agent_dies() {
     void.enter(&mut agent);
     assert!(agent.is_dead());
 }
+
+#[test]
+fn test_long_beam() {
+    let mut beam = LaserBeam::new(130, 0, Direction::East, 0);
+    assert_eq!(beam.len(), 130);
+    assert_eq!(beam.on_offsets().count(), 130);
+    beam.turn_off(70);
+    assert!(beam.is_on(69));
+    assert!(beam.is_off(70));
+    assert!(beam.is_off(129));
+    assert_eq!(
+        beam.on_offsets().collect::<Vec<_>>(),
+        (0..70).collect::<Vec<_>>()
+    );
+    beam.turn_off(3);
+    assert_eq!(beam.on_offsets().collect::<Vec<_>>(), vec![0, 1, 2]);
+    beam.turn_on(64);
+    assert_eq!(beam.on_offsets().count(), 3 + 66);
+    beam.disable();
+    assert_eq!(beam.on_offsets().count(), 0);
+    beam.turn_on(0);
+    assert!(beam.is_off(0));
+}
@@ -222,7 +222,7 @@ fn test_force_wrong_state_check_laser_not_blocked() {
     } else {
         panic!("Expected InvalidAgentPosition, got {:?}", res);
     }
-    assert!(w.lasers().iter().all(|(_, l)| l.is_on()));
+    assert!(w.lasers().iter().all(|(_, l)| l.is_on(w.beams())));
     assert!(w.gems().iter().all(|g| !g.is_collected()));
 }
 
@@ -303,7 +303,7 @@ fn test_dead_agent_does_not_block_the_laser() {
     // Check that the laser 1 is not blocked anymore
     for (pos, l) in w.lasers() {
         if pos == (0, 1) {
-            assert!(l.is_on());
+            assert!(l.is_on(w.beams()));
         }
     }
 }
@@ -340,13 +340,11 @@ fn change_laser_id() {
     )
     .unwrap();
     w.reset();
-    assert!(w.lasers().iter().all(|(_, l)| l.agent_id() == 0));
-    {
-        let (_, source) = w.sources().next().unwrap();
-        source.set_agent_id(1);
-        assert_eq!(source.agent_id(), 1);
-    }
-    assert!(w.lasers().iter().all(|(_, l)| l.agent_id() == 1));
+    assert!(w.lasers().iter().all(|(_, l)| l.agent_id(w.beams()) == 0));
+    let (pos, _) = w.sources().next().unwrap();
+    w.source_at_mut(pos).unwrap().set_agent_id(1);
+    assert_eq!(w.source_at(pos).unwrap().agent_id(), 1);
+    assert!(w.lasers().iter().all(|(_, l)| l.agent_id(w.beams()) == 1));
 
     // Kill agent 0 in the laser
     let events = w.step(&[Action::South, Action::Stay]).unwrap();
@@ -370,12 +368,12 @@ fn disable_laser_source() {
     )
     .unwrap();
     w.reset();
-    assert!(w.lasers().iter().all(|(_, l)| l.is_on()));
-    let (_, source) = w.sources().next().unwrap();
-    source.disable();
-    assert!(w.lasers().iter().all(|(_, l)| l.is_off()));
-    source.enable();
-    assert!(w.lasers().iter().all(|(_, l)| l.is_on()));
+    assert!(w.lasers().iter().all(|(_, l)| l.is_on(w.beams())));
+    let (pos, _) = w.sources().next().unwrap();
+    w.source_at_mut(pos).unwrap().disable();
+    assert!(w.lasers().iter().all(|(_, l)| l.is_off(w.beams())));
+    w.source_at_mut(pos).unwrap().enable();
+    assert!(w.lasers().iter().all(|(_, l)| l.is_on(w.beams())));
 }
 
 /// We check that disabling a laser source will turn off the lasers it is connected to,
@@ -394,16 +392,13 @@ fn disable_laser_source_and_block_with_agent() {
             .first()
             .unwrap()
     }
-    let laser = get_laser_at(&w, (0, 1));
-    assert!(laser.is_on());
-    w.sources().next().unwrap().1.disable();
-    assert!(laser.is_off());
+    assert!(get_laser_at(&w, (0, 1)).is_on(w.beams()));
+    w.source_at_mut((0, 0).into()).unwrap().disable();
+    assert!(get_laser_at(&w, (0, 1)).is_off(w.beams()));
     w.step(&[Action::West]).unwrap();
-    let laser = get_laser_at(&w, (0, 2));
-    assert!(laser.is_off());
+    assert!(get_laser_at(&w, (0, 2)).is_off(w.beams()));
     w.step(&[Action::East]).unwrap();
-    let laser = get_laser_at(&w, (0, 1));
-    assert!(laser.is_off());
+    assert!(get_laser_at(&w, (0, 1)).is_off(w.beams()));
 }
 
 #[test]
@@ -447,12 +442,12 @@ fn test_laser_id() {
 fn test_disable_laser_then_reset_does_not_turn_on() {
     let mut w = World::try_from("L0E . S0 X").unwrap();
     w.reset();
-    w.sources().next().unwrap().1.disable();
+    w.source_at_mut((0, 0).into()).unwrap().disable();
     w.reset();
     let laser = w.lasers().iter().find(|(pos, _)| *pos == (0, 1)).unwrap().1;
-    assert!(!laser.is_enabled());
-    assert!(laser.is_disabled());
-    assert!(laser.is_off());
+    assert!(!laser.is_enabled(w.beams()));
+    assert!(laser.is_disabled(w.beams()));
+    assert!(laser.is_off(w.beams()));
 }
 
 #[test]
@@ -467,12 +462,11 @@ fn test_laser_sources_have_different_laser_ids() {
 #[test]
 fn test_compute_world_string() {
     let world_string = "S0  L0S  X ";
-    let world = World::try_from(world_string).unwrap();
+    let mut world = World::try_from(world_string).unwrap();
     let current_string = world.world_string();
     assert_eq!(world_string, current_string);
 
-    let (_, source) = world.sources().next().unwrap();
-    source.set_agent_id(1);
+    world.source_at_mut((0, 1).into()).unwrap().set_agent_id(1);
     let expected = "S0  L1S  X ";
     let res = world.world_string();
     assert_eq!(expected, res);